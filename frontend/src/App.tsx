@@ -7,6 +7,7 @@ import Home from "./pages/Home";
 import About from "./pages/About";
 import Onboarding from "./pages/Onboarding";
 import Login from "./pages/Login";
+import LoginCallback from "./pages/LoginCallback";
 import Monitor from "./pages/Monitor";
 import CreateSchema from "./pages/CreateSchema";
 import Schemas from "./pages/Schemas";
@@ -28,6 +29,7 @@ function AppRoutes() {
       {/* Public auth routes */}
       <Route path="/onboarding" element={<Onboarding />} />
       <Route path="/login" element={<Login />} />
+      <Route path="/login/callback" element={<LoginCallback />} />
 
       {/* Redirect from base path based on admin status */}
       <Route
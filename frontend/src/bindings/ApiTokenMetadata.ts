@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type ApiTokenMetadata = { id: number, name: string, 
+/**
+ * First characters of the token, to help users recognise it
+ */
+prefix: string, scopes: Array<string>, expiresAt: string | null, lastUsedAt: string | null, createdAt: string, };
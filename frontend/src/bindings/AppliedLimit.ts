@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A limit that is in force, and how
+ */
+export type AppliedLimit = { limit: string, 
+/**
+ * e.g. `cgroup memory.max` or `RLIMIT_NOFILE`
+ */
+via: string, };
@@ -0,0 +1,13 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AppliedLimit } from "./AppliedLimit";
+import type { LimitViolations } from "./LimitViolations";
+import type { SkippedLimit } from "./SkippedLimit";
+
+/**
+ * What the current run of a server is actually held to
+ */
+export type AppliedLimits = { 
+/**
+ * The server's own cgroup, when the host delegates a cgroup v2 hierarchy to the panel
+ */
+cgroup: string | null, applied: Array<AppliedLimit>, skipped: Array<SkippedLimit>, violations: LimitViolations, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Kinds of privileged actions recorded in the audit log. Stored as their snake_case name.
+ */
+export type AuditAction = "login" | "login_failed" | "logout" | "sso_login" | "user_create" | "mfa_enable" | "mfa_disable" | "mfa_recovery_codes_regenerate" | "mfa_policy_update" | "api_token_create" | "api_token_revoke" | "schema_create" | "schema_update" | "steam_credential_create" | "steam_credential_update" | "steam_credential_delete" | "steam_cmd_job_start" | "steam_cmd_job_remove" | "steam_guard_submit" | "steam_cmd_bootstrap" | "instance_create" | "instance_update" | "instance_delete" | "instance_mods_update" | "instance_mods_install" | "game_update" | "game_update_policy" | "instance_start" | "instance_stop" | "instance_limits_update" | "instance_file_write" | "instance_file_upload" | "instance_file_rename" | "instance_file_delete" | "install_options_update" | "console_command" | "player_moderation";
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type AuditLogEntry = { id: number, createdAt: string, userId: number | null, username: string | null, apiTokenId: number | null, action: string, targetType: string | null, targetId: string | null, success: boolean, ip: string | null, userAgent: string | null, details: Record<string, any> | null, };
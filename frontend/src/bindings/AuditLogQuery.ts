@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Filters for the audit log query and CSV export. Timestamps are RFC 3339.
+ */
+export type AuditLogQuery = { userId: number | null, action: string | null, targetType: string | null, targetId: string | null, success: boolean | null, from: string | null, to: string | null, page: number | null, perPage: number | null, };
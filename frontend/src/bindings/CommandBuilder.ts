@@ -9,5 +9,6 @@ export type CommandBuilder = {
  * Ordered array of command structure parts
  * Strings can be literals or template variables like {{fieldName}}
  * Template variables are substituted with the corresponding field values at runtime
+ * {{installDir}}, {{savesDir}}, {{logsDir}} and {{backupsDir}} are the instance's directories
  */
 structure: Array<string>, };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ConsoleLogLine } from "./ConsoleLogLine";
+
+/**
+ * Matching lines in chronological order
+ */
+export type ConsoleLogPage = { lines: Array<ConsoleLogLine>, 
+/**
+ * Number of lines matching the filters
+ */
+total: number, offset: number, limit: number, };
@@ -0,0 +1,22 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Filters for reading historical console output. Timestamps are RFC 3339.
+ */
+export type ConsoleLogQuery = { from: string | null, to: string | null, 
+/**
+ * Only lines containing this text, ignoring case
+ */
+search: string | null, 
+/**
+ * Treat `search` as a regular expression
+ */
+regex: boolean | null, 
+/**
+ * Only lines from this SteamCMD session, e.g. `global` or `job-3`
+ */
+source: string | null, 
+/**
+ * Return the newest lines; `offset` then counts back from the end
+ */
+tail: boolean | null, offset: number | null, limit: number | null, };
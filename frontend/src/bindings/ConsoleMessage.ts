@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ConsoleSpan } from "./ConsoleSpan";
+import type { SteamCmdEvent } from "./SteamCmdEvent";
+
+/**
+ * Sent by the server over a console WebSocket
+ */
+export type ConsoleMessage = { "type": "line", seq: number, text: string, 
+/**
+ * Only when the socket was opened with `spans=true`
+ */
+spans: Array<ConsoleSpan> | null, } | { "type": "resync", missed: number, } | { "type": "reset" } | { "type": "event", event: SteamCmdEvent, } | { "type": "error", message: string, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A run of console text sharing one style. Colours are ANSI names such as `red` or
+ * `brightBlue`, or `#rrggbb` for 256-colour and true colour output.
+ */
+export type ConsoleSpan = { text: string, fg: string | null, bg: string | null, bold: boolean, dim: boolean, italic: boolean, underline: boolean, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Returned once on creation; the plain token is never shown again
+ */
+export type CreatedApiToken = { token: string, id: number, name: string, 
+/**
+ * First characters of the token, to help users recognise it
+ */
+prefix: string, scopes: Array<string>, expiresAt: string | null, lastUsedAt: string | null, createdAt: string, };
@@ -1,6 +1,7 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { EnumConfig } from "./EnumConfig";
 import type { NumberConfig } from "./NumberConfig";
+import type { PortConfig } from "./PortConfig";
 import type { StringConfig } from "./StringConfig";
 
 /**
@@ -34,4 +35,8 @@ description: string,
 /**
  * Display name for UI purposes
  */
-displayName: string | null, } & ({ "type": "string" } & StringConfig | { "type": "number" } & NumberConfig | { "type": "boolean" } | { "type": "enum" } & EnumConfig | { "type": "flag" });
+displayName: string | null, 
+/**
+ * Marks a number field as a port the server binds
+ */
+port?: PortConfig | null, } & ({ "type": "string" } & StringConfig | { "type": "number" } & NumberConfig | { "type": "boolean" } | { "type": "enum" } & EnumConfig | { "type": "flag" });
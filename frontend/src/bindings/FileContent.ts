@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A text file opened for editing
+ */
+export type FileContent = { path: string, content: string, size: number, modified: string | null, };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FileKind } from "./FileKind";
+
+/**
+ * A file or directory below the instance root
+ */
+export type FileEntry = { name: string, 
+/**
+ * Relative to the instance root, `/`-separated
+ */
+path: string, kind: FileKind, size: number, modified: string | null, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FileEntry } from "./FileEntry";
+
+export type FileListing = { path: string, 
+/**
+ * Directories first, then by name
+ */
+entries: Array<FileEntry>, };
@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SteamPlatform } from "./SteamPlatform";
+
+/**
+ * How `app_update` installs an instance's server files. The beta password is never returned.
+ */
+export type InstallOptions = { 
+/**
+ * Beta branch to install; the default public branch when omitted
+ */
+branch: string | null, hasBetaPassword: boolean, 
+/**
+ * Verify all installed files on every update
+ */
+validate: boolean, 
+/**
+ * Download files for another operating system than the host's
+ */
+platform: SteamPlatform | null, };
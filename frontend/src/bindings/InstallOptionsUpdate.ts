@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SteamPlatform } from "./SteamPlatform";
+
+export type InstallOptionsUpdate = { branch: string | null, 
+/**
+ * Password of a private beta branch. Omit to keep the stored one, or send an empty
+ * string to remove it.
+ */
+betaPassword: string | null, validate?: boolean, platform: SteamPlatform | null, };
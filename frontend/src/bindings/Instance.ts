@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { InstanceStatus } from "./InstanceStatus";
+import type { ResourceLimits } from "./ResourceLimits";
+
+/**
+ * A game server instance: a schema plus the config values it runs with
+ */
+export type Instance = { id: number, instanceName: string, schemaId: number, config: Record<string, any>, status: InstanceStatus, createdAt: string, updatedAt: string, limits: ResourceLimits, };
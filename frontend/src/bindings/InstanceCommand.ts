@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * The argv an instance would be started with
+ */
+export type InstanceCommand = { command: Array<string>, };
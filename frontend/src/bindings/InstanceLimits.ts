@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AppliedLimits } from "./AppliedLimits";
+import type { ResourceLimits } from "./ResourceLimits";
+
+export type InstanceLimits = { limits: ResourceLimits, 
+/**
+ * `null` while the server is not running
+ */
+applied: AppliedLimits | null, };
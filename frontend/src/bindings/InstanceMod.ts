@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type InstanceMod = { workshopItemId: number, enabled: boolean, loadOrder: number, 
+/**
+ * When the mod was last downloaded and placed into the instance's mod directories
+ */
+installedAt: string | null, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * One entry of a mod list replacement; list position is the load order
+ */
+export type InstanceModEntry = { workshopItemId: number, enabled?: boolean, };
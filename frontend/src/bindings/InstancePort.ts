@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PortProtocol } from "./PortProtocol";
+import type { PortRole } from "./PortRole";
+
+/**
+ * A port the instance's server binds, as its config sets it
+ */
+export type InstancePort = { field: string, role: PortRole, protocol: PortProtocol, port: number, 
+/**
+ * Consecutive ports bound starting at `port`
+ */
+count: number, };
@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { InstanceStatus } from "./InstanceStatus";
+import type { ServerQuery } from "./ServerQuery";
+
+/**
+ * Live state of an instance: its process and, while it runs, what the server reports
+ */
+export type InstanceRuntimeStatus = { status: InstanceStatus, pid: number | null, startedAt: string | null, 
+/**
+ * `null` when the schema declares no query port or the server has not answered yet
+ */
+query: ServerQuery | null, };
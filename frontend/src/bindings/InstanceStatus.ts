@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Lifecycle state of a game server instance, stored in `game_config.status`
+ */
+export type InstanceStatus = "stopped" | "starting" | "running" | "stopping" | "crashed" | "updating";
@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { InstallOptions } from "./InstallOptions";
+import type { UpdatePolicy } from "./UpdatePolicy";
+
+/**
+ * Installed game build of an instance compared with the latest published one
+ */
+export type InstanceUpdateStatus = { instanceId: number, schemaId: number, installedBuildId: number | null, availableBuildId: number | null, updateAvailable: boolean, checkedAt: string | null, 
+/**
+ * When the server files were last updated through the panel
+ */
+updatedAt: string | null, lastError: string | null, policy: UpdatePolicy, installOptions: InstallOptions, };
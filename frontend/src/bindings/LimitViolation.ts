@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LimitViolationKind } from "./LimitViolationKind";
+
+/**
+ * A server running into one of its cgroup limits since the last check
+ */
+export type LimitViolation = { kind: LimitViolationKind, 
+/**
+ * New occurrences since the last check
+ */
+count: number, detectedAt: string, };
@@ -0,0 +1,22 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * How often the running server ran into its cgroup limits
+ */
+export type LimitViolations = { 
+/**
+ * Times memory use reached the limit and had to be reclaimed
+ */
+memoryMax: number, 
+/**
+ * Processes killed for running out of memory
+ */
+oomKills: number, 
+/**
+ * Forks refused for reaching the process limit
+ */
+pidsMax: number, 
+/**
+ * Scheduler periods the server was throttled for using up its CPU quota
+ */
+cpuThrottled: number, };
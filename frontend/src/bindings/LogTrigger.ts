@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TriggerEvent } from "./TriggerEvent";
+
+/**
+ * A regex over the server's console output and the event raised for matching lines. Named
+ * capture groups are passed along with the event.
+ */
+export type LogTrigger = { pattern: string, event: TriggerEvent, 
+/**
+ * Identifies the trigger in notifications; required for custom events
+ */
+name?: string | null, };
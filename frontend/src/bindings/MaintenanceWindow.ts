@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Daily UTC time range, as "HH:MM", in which automatic updates may restart the server.
+ * A range whose end is before its start wraps past midnight.
+ */
+export type MaintenanceWindow = { start: string, end: string, };
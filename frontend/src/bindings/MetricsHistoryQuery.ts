@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Range of the metrics history. Timestamps are RFC 3339 and default to the last hour.
+ */
+export type MetricsHistoryQuery = { from: string | null, to: string | null, 
+/**
+ * Seconds per point, a multiple of 60. Picked from the range when left out.
+ */
+resolution: number | null, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Samples averaged over `resolutionSecs` from `recordedAt` on
+ */
+export type MetricsPoint = { recordedAt: string, resolutionSecs: number, samples: number, cpuPercent: number, cpuPercentMax: number, 
+/**
+ * Peak resident memory
+ */
+memoryBytes: number, threads: number, readBytesPerSec: number, writeBytesPerSec: number, diskBytes: number | null, };
@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Resource usage of a running game server and every process it spawned. CPU is a
+ * percentage of one core, so a server busy on two cores reports 200.
+ */
+export type MetricsSample = { sampledAt: string, cpuPercent: number, 
+/**
+ * Resident memory
+ */
+memoryBytes: number, threads: number, processes: number, 
+/**
+ * Bytes read from and written to disk since the server started
+ */
+readBytes: number, writeBytes: number, readBytesPerSec: number, writeBytesPerSec: number, 
+/**
+ * Size of the instance directory, measured less often than the rest
+ */
+diskBytes: number | null, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Returned by `/login` instead of an [`AuthResponse`] when a second factor is needed
+ */
+export type MfaChallenge = { mfaRequired: boolean, 
+/**
+ * When true the user has not enrolled yet and must do so before completing login
+ */
+enrollmentRequired: boolean, challengeToken: string, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type MfaLogin = { challengeToken: string, 
+/**
+ * A TOTP code or an unused recovery code
+ */
+code: string, };
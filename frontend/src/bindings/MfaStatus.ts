@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type MfaStatus = { enabled: boolean, 
+/**
+ * Whether the admin policy requires MFA for this user's role
+ */
+required: boolean, remainingRecoveryCodes: number, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type ModInstall = { 
+/**
+ * Stored Steam credential to download with; anonymous when omitted
+ */
+credentialId: number | null, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * How installed Workshop mods are placed into an instance's mod directories
+ */
+export type ModInstallMethod = "symlink" | "copy";
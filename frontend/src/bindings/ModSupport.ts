@@ -0,0 +1,27 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ModInstallMethod } from "./ModInstallMethod";
+
+/**
+ * Declares that a game supports Steam Workshop mods and where they go
+ */
+export type ModSupport = { 
+/**
+ * App ID the Workshop items belong to, if different from the server's (often the game client)
+ */
+workshopAppId: number | null, 
+/**
+ * Directories, relative to the instance directory, each installed mod is placed into
+ */
+directories: Array<string>, 
+/**
+ * Symlink or copy mods into the directories (defaults to symlink)
+ */
+installMethod: ModInstallMethod, 
+/**
+ * How a single mod appears in the {{mods}} template variable; `{id}` is the Workshop item ID
+ */
+format: string, 
+/**
+ * Separator between mods in the {{mods}} template variable
+ */
+separator: string, };
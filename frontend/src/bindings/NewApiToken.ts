@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type NewApiToken = { name: string, 
+/**
+ * Scopes such as `game_schema:read`, `steamcmd:write` or `*`
+ */
+scopes: Array<string>, 
+/**
+ * Optional expiry; tokens without one stay valid until revoked
+ */
+expiresAt: string | null, };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type NewSteamCmdJob = { 
+/**
+ * Stored credential to log in with; anonymous when omitted
+ */
+credentialId: number | null, 
+/**
+ * SteamCMD console commands run in order after login, e.g. `app_update 740 validate`
+ */
+commands: Array<string>, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type NewStreamTicket = { 
+/**
+ * The stream route the ticket will be used on, e.g. `/api/steamcmd/stdout`
+ */
+path: string, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type OidcAuthorization = { 
+/**
+ * Identity provider URL to send the browser to
+ */
+authorizationUrl: string, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * What a moderator can do to a player, each backed by a command template in the game schema.
+ * Stored in `player_action.action` as the snake_case name.
+ */
+export type PlayerAction = "kick" | "ban" | "unban" | "whitelist_add" | "whitelist_remove";
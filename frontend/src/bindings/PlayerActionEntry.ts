@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A moderation action, with the command it sent and the moderator who took it
+ */
+export type PlayerActionEntry = { id: number, action: string, playerName: string, steamId: string | null, reason: string | null, command: string, userId: number | null, username: string | null, createdAt: string, };
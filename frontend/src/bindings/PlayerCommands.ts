@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Console commands for moderating players. Actions without a template are unavailable.
+ */
+export type PlayerCommands = { kick: string | null, ban: string | null, unban: string | null, whitelistAdd: string | null, whitelistRemove: string | null, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PlayerAction } from "./PlayerAction";
+
+/**
+ * Kick, ban or whitelist a player through the command template the schema declares
+ */
+export type PlayerModeration = { action: PlayerAction, name: string, steamId: string | null, reason: string | null, };
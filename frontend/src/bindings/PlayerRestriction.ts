@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A player banned or whitelisted through the panel, by their latest such action
+ */
+export type PlayerRestriction = { name: string, steamId: string | null, reason: string | null, username: string | null, since: string, };
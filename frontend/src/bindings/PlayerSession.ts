@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A player's stay on a game server. `leftAt` is `null` while they are still on it.
+ */
+export type PlayerSession = { id: number, name: string, steamId: string | null, joinedAt: string, leftAt: string | null, };
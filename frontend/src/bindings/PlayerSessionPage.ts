@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PlayerSession } from "./PlayerSession";
+
+export type PlayerSessionPage = { sessions: Array<PlayerSession>, total: number, page: number, perPage: number, };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Filters for the player session history of an instance
+ */
+export type PlayerSessionQuery = { 
+/**
+ * Only players still on the server
+ */
+online: boolean | null, 
+/**
+ * Sessions of players whose name contains this text
+ */
+name: string | null, steamId: string | null, page: number | null, perPage: number | null, };
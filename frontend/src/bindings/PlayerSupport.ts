@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PlayerCommands } from "./PlayerCommands";
+
+/**
+ * Console commands that manage a game server's players. Joins and leaves are picked up by
+ * `player_join` and `player_leave` triggers.
+ */
+export type PlayerSupport = { 
+/**
+ * Console command templates using {{name}}, {{steamId}} and {{reason}}
+ */
+commands: PlayerCommands, };
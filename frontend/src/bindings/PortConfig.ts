@@ -0,0 +1,13 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PortProtocol } from "./PortProtocol";
+import type { PortRole } from "./PortRole";
+
+export type PortConfig = { role: PortRole, 
+/**
+ * Defaults to UDP, which most game servers use
+ */
+protocol: PortProtocol, 
+/**
+ * Consecutive ports the server binds starting at the field's value (defaults to 1)
+ */
+count: number, };
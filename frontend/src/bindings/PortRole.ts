@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * What a port of a game server is for
+ */
+export type PortRole = "game" | "query" | "rcon" | "other";
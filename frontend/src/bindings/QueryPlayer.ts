@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Someone on a game server, as reported by A2S_PLAYER
+ */
+export type QueryPlayer = { name: string, score: number, 
+/**
+ * Seconds connected
+ */
+duration: number, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Declares that a game server answers Steam A2S queries, and which of its fields holds the port
+ */
+export type QuerySupport = { 
+/**
+ * Name of the field holding the query port
+ */
+portField: string, };
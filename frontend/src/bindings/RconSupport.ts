@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Declares that a game server accepts Source RCON commands, and which of its fields configure it
+ */
+export type RconSupport = { 
+/**
+ * Name of the field holding the RCON port
+ */
+portField: string, 
+/**
+ * Name of the field holding the RCON password
+ */
+passwordField: string, };
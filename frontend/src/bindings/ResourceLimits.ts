@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Caps on what an instance's server may use. Unset fields are unlimited. CPU quota is a
+ * percentage of one core; weight is the cgroup `cpu.weight`, 100 being the default share.
+ */
+export type ResourceLimits = { memoryMaxMb: number | null, cpuQuotaPercent: number | null, cpuWeight: number | null, maxProcesses: number | null, 
+/**
+ * -20 (highest priority) to 19
+ */
+nice: number | null, maxOpenFiles: number | null, };
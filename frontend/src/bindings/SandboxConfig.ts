@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A sandboxed server sees the host read-only with only its instance directory writable, and
+ * none of the panel's data
+ */
+export type SandboxConfig = { 
+/**
+ * Share the host's network; without it the server cannot accept players
+ */
+network: boolean, 
+/**
+ * Environment variables of the panel the server keeps; all others are cleared
+ */
+keepEnv?: Array<string>, };
@@ -2,6 +2,13 @@
 import type { CommandBuilder } from "./CommandBuilder";
 import type { ConditionalRule } from "./ConditionalRule";
 import type { DynamicField } from "./DynamicField";
+import type { LogTrigger } from "./LogTrigger";
+import type { ModSupport } from "./ModSupport";
+import type { PlayerSupport } from "./PlayerSupport";
+import type { QuerySupport } from "./QuerySupport";
+import type { RconSupport } from "./RconSupport";
+import type { SandboxConfig } from "./SandboxConfig";
+import type { StopSequence } from "./StopSequence";
 
 /**
  * Represents a complete server configuration
@@ -19,6 +26,34 @@ rules: Array<ConditionalRule>,
  * How the server command is constructed from field values
  */
 commandBuilder: CommandBuilder | null, 
+/**
+ * Steam Workshop mod support, for games that have it
+ */
+mods?: ModSupport | null, 
+/**
+ * Source RCON support, for games that take console commands over the network
+ */
+rcon?: RconSupport | null, 
+/**
+ * Steam server query support, for games that report their map and players over A2S
+ */
+query?: QuerySupport | null, 
+/**
+ * Player moderation commands
+ */
+players?: PlayerSupport | null, 
+/**
+ * Patterns over the console output that raise events, such as the server being ready
+ */
+triggers?: Array<LogTrigger>, 
+/**
+ * Graceful stop sequence; without one the server is sent SIGTERM straight away
+ */
+stop?: StopSequence | null, 
+/**
+ * Run the server in a bubblewrap sandbox as the host's SANDBOX_USER
+ */
+sandbox?: SandboxConfig | null, 
 /**
  * Steam App ID for this game
  */
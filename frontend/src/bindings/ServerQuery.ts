@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { QueryPlayer } from "./QueryPlayer";
+
+/**
+ * What a running game server last reported about itself over the Steam server queries.
+ * When a query fails the previous answer is kept with `online` unset and the reason in `error`.
+ */
+export type ServerQuery = { queriedAt: string, online: boolean, name: string, map: string, game: string, version: string, players: number, maxPlayers: number, bots: number, playerList: Array<QueryPlayer>, rules: { [key in string]?: string }, error: string | null, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A configured limit the host could not enforce
+ */
+export type SkippedLimit = { limit: string, reason: string, };
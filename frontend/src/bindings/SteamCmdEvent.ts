@@ -0,0 +1,23 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SteamGuardPrompt } from "./SteamGuardPrompt";
+
+/**
+ * Something SteamCMD reported, recognised from its output. Sent next to the raw lines.
+ */
+export type SteamCmdEvent = { "type": "loggedIn" } | { "type": "loginFailed", reason: string, } | { "type": "steamGuardRequired", prompt: SteamGuardPrompt, } | { "type": "progress", 
+/**
+ * Raw update state flags, e.g. `0x61`
+ */
+code: number, 
+/**
+ * What SteamCMD is doing, e.g. `downloading` or `verifying install`
+ */
+state: string, 
+/**
+ * Percent done
+ */
+progress: number, current: number, total: number, } | { "type": "appInstalled", appId: number, } | { "type": "appUpToDate", appId: number, } | { "type": "itemDownloaded", itemId: number, path: string, } | { "type": "error", message: string, 
+/**
+ * The parenthesised reason at the end of the message, if any
+ */
+reason: string | null, };
@@ -0,0 +1,25 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SteamCmdSessionState } from "./SteamCmdSessionState";
+import type { SteamGuardPrompt } from "./SteamGuardPrompt";
+
+export type SteamCmdSessionInfo = { id: string, 
+/**
+ * Steam account name, `anonymous` for anonymous logins
+ */
+account: string, state: SteamCmdSessionState, steamGuardPrompt: SteamGuardPrompt | null, startedAt: string | null, 
+/**
+ * Set while the process is running
+ */
+pid: number | null, 
+/**
+ * Exit code of the most recent process; `null` if killed by a signal or still on the first run
+ */
+exitCode: number | null, 
+/**
+ * How often the process was respawned after exiting
+ */
+restarts: number, 
+/**
+ * Reason given by SteamCMD when the login failed
+ */
+error: string | null, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Liveness of the global SteamCMD process, reported by `/api/health_detailed`
+ */
+export type SteamCmdStatus = { 
+/**
+ * Whether a SteamCMD binary was found. Without one the server runs degraded.
+ */
+installed: boolean, running: boolean, pid: number | null, uptimeSeconds: number | null, lastExitCode: number | null, restarts: number, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A stored Steam account. The password is never returned.
+ */
+export type SteamCredential = { id: number, username: string, createdAt: string, updatedAt: string, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Which Steam Guard code SteamCMD is asking for
+ */
+export type SteamGuardPrompt = "email" | "two_factor";
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Operating system SteamCMD downloads server files for, overriding the host's
+ */
+export type SteamPlatform = "windows" | "linux" | "macos";
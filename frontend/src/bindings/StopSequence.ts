@@ -0,0 +1,13 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * How to stop a game server without losing data. The commands run first, over RCON when the
+ * schema declares it and on stdin otherwise. The server then gets `timeoutSecs` to exit, or
+ * until a line matches `waitFor`, before it is sent SIGTERM and, `killTimeoutSecs` later,
+ * SIGKILL.
+ */
+export type StopSequence = { commands: Array<string>, 
+/**
+ * Regex over the console output meaning the server has saved and can be terminated
+ */
+waitFor?: string | null, timeoutSecs: number, killTimeoutSecs: number, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type StreamTicket = { 
+/**
+ * Pass as `?ticket=` when opening the stream. Valid once.
+ */
+ticket: string, expiresInSeconds: number, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * What a console line matched by a trigger means
+ */
+export type TriggerEvent = "ready" | "player_join" | "player_leave" | "error" | "custom";
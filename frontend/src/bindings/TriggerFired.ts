@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TriggerEvent } from "./TriggerEvent";
+
+/**
+ * A console line matched by one of the schema's triggers
+ */
+export type TriggerFired = { event: TriggerEvent, name: string | null, 
+/**
+ * The trigger's named capture groups that took part in the match
+ */
+captures: { [key in string]?: string }, line: string, firedAt: string, };
@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { MaintenanceWindow } from "./MaintenanceWindow";
+
+export type UpdatePolicy = { 
+/**
+ * Stop, update and restart the server when a new build is found
+ */
+autoUpdate: boolean, 
+/**
+ * Only update automatically inside this window; any time when omitted
+ */
+maintenanceWindow: MaintenanceWindow | null, 
+/**
+ * Stored Steam credential used for updates; anonymous when omitted
+ */
+credentialId: number | null, };
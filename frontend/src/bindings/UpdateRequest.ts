@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type UpdateRequest = { 
+/**
+ * Stored Steam credential to update with; falls back to the instance's update policy
+ */
+credentialId: number | null, };
@@ -9,6 +9,10 @@ export * from "./EnumConfig";
 export * from "./FieldConstraint";
 export * from "./Login";
 export * from "./Minimum";
+export * from "./MfaChallenge";
+export * from "./MfaEnrollment";
+export * from "./MfaStatus";
+export * from "./RecoveryCodes";
 export * from "./New";
 export * from "./NumberConfig";
 export * from "./ServerConfig";
@@ -16,3 +20,88 @@ export * from "./StaticConfig";
 export * from "./StringConfig";
 export * from "./SchemaMetadata";
 export * from "./GameConfig";
+export * from "./NewStreamTicket";
+export * from "./StreamTicket";
+export * from "./OidcAuthorization";
+export * from "./OidcCallback";
+export * from "./OidcStatus";
+export * from "./AuditAction";
+export * from "./AuditLogEntry";
+export * from "./AuditLogPage";
+export * from "./AuditLogQuery";
+export * from "./NewSteamCredential";
+export * from "./SteamCredentialUpdate";
+export * from "./SteamCredential";
+export * from "./NewSteamCmdJob";
+export * from "./SteamGuardCode";
+export * from "./SteamCmdSessionState";
+export * from "./SteamGuardPrompt";
+export * from "./SteamCmdSessionInfo";
+export * from "./SteamCmdStatus";
+export * from "./ModInstallMethod";
+export * from "./ModSupport";
+export * from "./InstanceStatus";
+export * from "./NewInstance";
+export * from "./InstanceUpdate";
+export * from "./Instance";
+export * from "./InstanceMod";
+export * from "./InstanceModEntry";
+export * from "./ModInstall";
+export * from "./InstanceCommand";
+export * from "./MaintenanceWindow";
+export * from "./UpdatePolicy";
+export * from "./InstanceUpdateStatus";
+export * from "./UpdateRequest";
+export * from "./SteamPlatform";
+export * from "./InstallOptions";
+export * from "./InstallOptionsUpdate";
+export * from "./SteamCmdEvent";
+export * from "./ConsoleSpan";
+export * from "./ConsoleLogQuery";
+export * from "./ConsoleLogLine";
+export * from "./ConsoleLogPage";
+export * from "./ConsoleInput";
+export * from "./ConsoleMessage";
+export * from "./RconCommand";
+export * from "./RconOutput";
+export * from "./RconSupport";
+export * from "./InstanceRuntimeStatus";
+export * from "./QueryPlayer";
+export * from "./QuerySupport";
+export * from "./ServerQuery";
+export * from "./PlayerAction";
+export * from "./PlayerActionEntry";
+export * from "./PlayerCommands";
+export * from "./PlayerModeration";
+export * from "./PlayerRestriction";
+export * from "./PlayerRestrictions";
+export * from "./PlayerSession";
+export * from "./PlayerSessionPage";
+export * from "./PlayerSessionQuery";
+export * from "./PlayerSupport";
+export * from "./LogTrigger";
+export * from "./TriggerEvent";
+export * from "./TriggerFired";
+export * from "./StopSequence";
+export * from "./MetricsHistory";
+export * from "./MetricsHistoryQuery";
+export * from "./MetricsPoint";
+export * from "./MetricsSample";
+export * from "./AppliedLimit";
+export * from "./AppliedLimits";
+export * from "./InstanceLimits";
+export * from "./LimitViolation";
+export * from "./LimitViolationKind";
+export * from "./LimitViolations";
+export * from "./ResourceLimits";
+export * from "./SkippedLimit";
+export * from "./SandboxConfig";
+export * from "./FileContent";
+export * from "./FileEntry";
+export * from "./FileKind";
+export * from "./FileListing";
+export * from "./FileRename";
+export * from "./InstancePort";
+export * from "./PortConfig";
+export * from "./PortProtocol";
+export * from "./PortRole";
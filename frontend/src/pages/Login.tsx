@@ -1,4 +1,4 @@
-import { useState } from "react";
+import { useEffect, useState } from "react";
 import { useNavigate } from "react-router-dom";
 import apiClient from "../lib/api";
 import { useAuth } from "../contexts/AuthContext";
@@ -8,6 +8,19 @@ import TextInput from "../components/TextInput";
 import Button from "../components/Button";
 import ErrorMessage from "../components/ErrorMessage";
 import { result } from "@dbidwell94/ts-utils";
+import type {
+  AuthResponse,
+  MfaChallenge,
+  MfaEnrollment,
+  OidcAuthorization,
+  OidcStatus,
+  RecoveryCodes,
+} from "../bindings";
+
+type LoginStep = "credentials" | "mfa" | "enroll" | "recoveryCodes";
+
+const errorMessageOf = (error: unknown, fallback: string) =>
+  (error as any).response?.data?.error || fallback;
 
 export default function Login() {
   const navigate = useNavigate();
@@ -18,6 +31,40 @@ export default function Login() {
   });
   const [error, setError] = useState<string | null>(null);
   const [isLoading, setIsLoading] = useState(false);
+  const [step, setStep] = useState<LoginStep>("credentials");
+  const [challengeToken, setChallengeToken] = useState("");
+  const [code, setCode] = useState("");
+  const [enrollment, setEnrollment] = useState<MfaEnrollment | null>(null);
+  const [recoveryCodes, setRecoveryCodes] = useState<string[]>([]);
+  const [oidcStatus, setOidcStatus] = useState<OidcStatus | null>(null);
+
+  useEffect(() => {
+    apiClient
+      .get<OidcStatus>("/user/oidc/status")
+      .then((response) => setOidcStatus(response.data))
+      .catch(() => setOidcStatus(null));
+  }, []);
+
+  const handleSsoLogin = async () => {
+    setError(null);
+    setIsLoading(true);
+
+    const authorizeResult = await result.fromPromise(
+      apiClient.get<OidcAuthorization>("/user/oidc/authorize"),
+    );
+
+    if (authorizeResult.isError()) {
+      setError(errorMessageOf(authorizeResult.error, "Single sign-on failed"));
+      setIsLoading(false);
+      return;
+    }
+
+    window.location.assign(authorizeResult.value.data.authorizationUrl);
+  };
+
+  const challengeHeaders = {
+    headers: { Authorization: `Bearer ${challengeToken}` },
+  };
 
   const handleInputChange = (
     e: React.ChangeEvent<HTMLInputElement | HTMLTextAreaElement>,
@@ -56,19 +103,179 @@ export default function Login() {
       }),
     );
 
-    if (loginResult.isOk()) {
-      const { user, accessToken } = loginResult.value.data;
+    if (loginResult.isError()) {
+      setError(errorMessageOf(loginResult.error, "Login failed"));
+      setIsLoading(false);
+      return;
+    }
+
+    const data = loginResult.value.data as AuthResponse | MfaChallenge;
+    if (!("mfaRequired" in data)) {
+      setTokens(data.user, data.accessToken);
+      navigate("/");
+      return;
+    }
+
+    setChallengeToken(data.challengeToken);
+    if (data.enrollmentRequired) {
+      const enrollResult = await result.fromPromise(
+        apiClient.post<MfaEnrollment>(
+          "/user/mfa/enroll",
+          {},
+          { headers: { Authorization: `Bearer ${data.challengeToken}` } },
+        ),
+      );
+      if (enrollResult.isError()) {
+        setError(errorMessageOf(enrollResult.error, "Failed to start enrollment"));
+      } else {
+        setEnrollment(enrollResult.value.data);
+        setStep("enroll");
+      }
+    } else {
+      setStep("mfa");
+    }
+
+    setIsLoading(false);
+  };
+
+  const handleMfaSubmit = async (e: React.FormEvent) => {
+    e.preventDefault();
+    setError(null);
+    setIsLoading(true);
+
+    const mfaResult = await result.fromPromise(
+      apiClient.post<AuthResponse>("/user/login/mfa", {
+        challengeToken,
+        code,
+      }),
+    );
+
+    if (mfaResult.isOk()) {
+      const { user, accessToken } = mfaResult.value.data;
       setTokens(user, accessToken);
       navigate("/");
     } else {
-      const errorMessage =
-        (loginResult.error as any).response?.data?.message || "Login failed";
-      setError(errorMessage);
+      setError(errorMessageOf(mfaResult.error, "Invalid code"));
+    }
+
+    setIsLoading(false);
+  };
+
+  const handleEnrollSubmit = async (e: React.FormEvent) => {
+    e.preventDefault();
+    setError(null);
+    setIsLoading(true);
+
+    const activateResult = await result.fromPromise(
+      apiClient.post<RecoveryCodes>(
+        "/user/mfa/activate",
+        { code },
+        challengeHeaders,
+      ),
+    );
+
+    if (activateResult.isOk()) {
+      setRecoveryCodes(activateResult.value.data.codes);
+      setCode("");
+      setStep("recoveryCodes");
+    } else {
+      setError(errorMessageOf(activateResult.error, "Invalid code"));
     }
 
     setIsLoading(false);
   };
 
+  const codeInput = (
+    <TextInput
+      id="code"
+      name="code"
+      label="Authentication code"
+      value={code}
+      onChange={(e) => setCode(e.target.value)}
+      disabled={isLoading}
+      placeholder="123456"
+    />
+  );
+
+  if (step === "mfa") {
+    return (
+      <PageLayout showFooter>
+        <div className="flex-1 flex items-center justify-center px-4 py-12">
+          <FormCard
+            title="Two-factor authentication"
+            subtitle="Enter the code from your authenticator app or a recovery code"
+          >
+            <form onSubmit={handleMfaSubmit} className="space-y-6">
+              {codeInput}
+              {error && <ErrorMessage message={error} />}
+              <Button type="submit" isLoading={isLoading} loadingText="Verifying...">
+                Verify
+              </Button>
+            </form>
+          </FormCard>
+        </div>
+      </PageLayout>
+    );
+  }
+
+  if (step === "enroll" && enrollment) {
+    return (
+      <PageLayout showFooter>
+        <div className="flex-1 flex items-center justify-center px-4 py-12">
+          <FormCard
+            title="Set up two-factor authentication"
+            subtitle="Your administrator requires two-factor authentication for this account"
+          >
+            <form onSubmit={handleEnrollSubmit} className="space-y-6">
+              <div className="text-sm text-gray-300 space-y-2">
+                <p>Add this account to your authenticator app:</p>
+                <a
+                  href={enrollment.otpauthUri}
+                  className="block break-all text-indigo-400"
+                >
+                  {enrollment.otpauthUri}
+                </a>
+                <p>
+                  Or enter the secret manually:{" "}
+                  <code className="break-all">{enrollment.secret}</code>
+                </p>
+              </div>
+              {codeInput}
+              {error && <ErrorMessage message={error} />}
+              <Button type="submit" isLoading={isLoading} loadingText="Verifying...">
+                Enable
+              </Button>
+            </form>
+          </FormCard>
+        </div>
+      </PageLayout>
+    );
+  }
+
+  if (step === "recoveryCodes") {
+    return (
+      <PageLayout showFooter>
+        <div className="flex-1 flex items-center justify-center px-4 py-12">
+          <FormCard
+            title="Save your recovery codes"
+            subtitle="Each code can be used once if you lose access to your authenticator"
+          >
+            <div className="space-y-6">
+              <ul className="grid grid-cols-2 gap-2 font-mono text-gray-200">
+                {recoveryCodes.map((recoveryCode) => (
+                  <li key={recoveryCode}>{recoveryCode}</li>
+                ))}
+              </ul>
+              <Button type="button" onClick={() => setStep("mfa")}>
+                I have saved these codes
+              </Button>
+            </div>
+          </FormCard>
+        </div>
+      </PageLayout>
+    );
+  }
+
   return (
     <PageLayout showFooter>
       <div className="flex-1 flex items-center justify-center px-4 py-12">
@@ -104,6 +311,17 @@ export default function Login() {
             >
               Sign In
             </Button>
+
+            {oidcStatus?.enabled && (
+              <Button
+                type="button"
+                variant="secondary"
+                onClick={handleSsoLogin}
+                disabled={isLoading}
+              >
+                Sign in with {oidcStatus.displayName ?? "SSO"}
+              </Button>
+            )}
           </form>
         </FormCard>
       </div>
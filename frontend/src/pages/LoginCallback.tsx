@@ -0,0 +1,74 @@
+import { useEffect, useRef, useState } from "react";
+import { useNavigate, useSearchParams } from "react-router-dom";
+import apiClient from "../lib/api";
+import { useAuth } from "../contexts/AuthContext";
+import PageLayout from "../components/PageLayout";
+import FormCard from "../components/FormCard";
+import Button from "../components/Button";
+import ErrorMessage from "../components/ErrorMessage";
+import LoadingSpinner from "../components/LoadingSpinner";
+import { result } from "@dbidwell94/ts-utils";
+import type { AuthResponse } from "../bindings";
+
+// Landing page the identity provider redirects back to after single sign-on
+export default function LoginCallback() {
+  const navigate = useNavigate();
+  const [searchParams] = useSearchParams();
+  const { setTokens } = useAuth();
+  const [error, setError] = useState<string | null>(null);
+  // The state parameter is single use, so guard against double effects in StrictMode
+  const startedRef = useRef(false);
+
+  useEffect(() => {
+    if (startedRef.current) return;
+    startedRef.current = true;
+
+    const code = searchParams.get("code");
+    const state = searchParams.get("state");
+    const providerError = searchParams.get("error_description") ?? searchParams.get("error");
+
+    if (providerError || !code || !state) {
+      setError(providerError ?? "The identity provider did not return a login code");
+      return;
+    }
+
+    const completeLogin = async () => {
+      const callbackResult = await result.fromPromise(
+        apiClient.post<AuthResponse>("/user/oidc/callback", { code, state }),
+      );
+
+      if (callbackResult.isError()) {
+        setError(
+          (callbackResult.error as any).response?.data?.error ||
+            "Single sign-on failed",
+        );
+        return;
+      }
+
+      const { user, accessToken } = callbackResult.value.data;
+      setTokens(user, accessToken);
+      navigate("/", { replace: true });
+    };
+
+    completeLogin();
+  }, [searchParams, setTokens, navigate]);
+
+  if (!error) {
+    return <LoadingSpinner />;
+  }
+
+  return (
+    <PageLayout showFooter>
+      <div className="flex-1 flex items-center justify-center px-4 py-12">
+        <FormCard title="Single sign-on failed">
+          <div className="space-y-6">
+            <ErrorMessage message={error} />
+            <Button type="button" onClick={() => navigate("/login")}>
+              Back to login
+            </Button>
+          </div>
+        </FormCard>
+      </div>
+    </PageLayout>
+  );
+}
@@ -2,45 +2,60 @@ import { useEffect, useState, useRef } from "react";
 import Navbar from "../components/Navbar";
 import TerminalView from "../components/TerminalView";
 import type { LogEntry } from "../components/TerminalView";
-import { useAuth } from "../contexts/AuthContext";
+import { apiClient } from "../lib/api";
+import type { StreamTicket } from "../bindings";
 
 export default function Monitor() {
   const [logs, setLogs] = useState<LogEntry[]>([]);
   const [selectedServer, setSelectedServer] = useState<string>("all");
   const [selectedLevel, setSelectedLevel] = useState<string>("all");
   const logIdRef = useRef(0);
-  const { accessToken } = useAuth();
 
   // Auto-scroll to bottom when new logs arrive
   useEffect(() => {
     // Auto-scroll is now handled by TerminalView component
   }, [logs]);
 
-  // Connect to server-sent events for logs
+  // Connect to server-sent events for logs. EventSource can't send headers, so exchange the
+  // access token for a single-use stream ticket first.
   useEffect(() => {
-    const evtSource = new EventSource(
-      `/api/steamcmd/stdout?token=${accessToken}`,
-    );
-
-    evtSource.onmessage = (evt: MessageEvent<string>) => {
-      const message = evt.data;
-      const newLog: LogEntry = {
-        id: logIdRef.current++,
-        timestamp: new Date().toISOString(),
-        source: "steamcmd",
-        level: "info",
-        message: message,
+    let evtSource: EventSource | null = null;
+    let cancelled = false;
+
+    const connect = async () => {
+      const { data } = await apiClient.post<StreamTicket>(
+        "/user/stream_ticket",
+        { path: "/api/steamcmd/stdout" },
+      );
+      if (cancelled) return;
+
+      evtSource = new EventSource(
+        `/api/steamcmd/stdout?ticket=${encodeURIComponent(data.ticket)}`,
+      );
+
+      evtSource.onmessage = (evt: MessageEvent<string>) => {
+        const message = evt.data;
+        const newLog: LogEntry = {
+          id: logIdRef.current++,
+          timestamp: new Date().toISOString(),
+          source: "steamcmd",
+          level: "info",
+          message: message,
+        };
+        setLogs((prev) => [...prev, newLog].slice(-1000)); // Keep last 1000 logs
       };
-      setLogs((prev) => [...prev, newLog].slice(-1000)); // Keep last 1000 logs
-    };
 
-    evtSource.onerror = () => {
-      console.error("Log stream error");
-      evtSource.close();
+      evtSource.onerror = () => {
+        console.error("Log stream error");
+        evtSource?.close();
+      };
     };
 
+    connect().catch(() => console.error("Failed to open log stream"));
+
     return () => {
-      evtSource.close();
+      cancelled = true;
+      evtSource?.close();
     };
   }, []);
 
@@ -3,6 +3,17 @@ pub use sea_orm_migration::prelude::*;
 mod m20220101_000001_create_table;
 mod m20260116_203718_game_schema;
 mod m20260118_003246_game_config;
+mod m20261018_090000_user_mfa;
+mod m20261018_100000_api_token;
+mod m20261018_110000_user_identity;
+mod m20261018_120000_audit_log;
+mod m20261018_130000_steam_credential;
+mod m20261018_140000_instance_mod;
+mod m20261018_150000_instance_update;
+mod m20261018_160000_install_options;
+mod m20261018_170000_player_session;
+mod m20261018_180000_instance_metric;
+mod m20261018_190000_resource_limits;
 
 pub struct Migrator;
 
@@ -13,6 +24,17 @@ impl MigratorTrait for Migrator {
             Box::new(m20220101_000001_create_table::Migration),
             Box::new(m20260116_203718_game_schema::Migration),
             Box::new(m20260118_003246_game_config::Migration),
+            Box::new(m20261018_090000_user_mfa::Migration),
+            Box::new(m20261018_100000_api_token::Migration),
+            Box::new(m20261018_110000_user_identity::Migration),
+            Box::new(m20261018_120000_audit_log::Migration),
+            Box::new(m20261018_130000_steam_credential::Migration),
+            Box::new(m20261018_140000_instance_mod::Migration),
+            Box::new(m20261018_150000_instance_update::Migration),
+            Box::new(m20261018_160000_install_options::Migration),
+            Box::new(m20261018_170000_player_session::Migration),
+            Box::new(m20261018_180000_instance_metric::Migration),
+            Box::new(m20261018_190000_resource_limits::Migration),
         ]
     }
 }
@@ -73,7 +73,7 @@ impl MigrationTrait for Migration {
 }
 
 #[derive(DeriveIden)]
-enum GameConfig {
+pub enum GameConfig {
     Table,
     Id,
     InstanceName,
@@ -0,0 +1,153 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::m20220101_000001_create_table::User;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLite only supports a single column per ALTER TABLE statement
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(string_null(UserMfa::TotpSecret))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(boolean(UserMfa::TotpEnabled).not_null().default(false))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(big_integer_null(UserMfa::TotpLastStep))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(UserRecoveryCode::Table)
+                    .if_not_exists()
+                    .col(pk_auto(UserRecoveryCode::Id))
+                    .col(integer(UserRecoveryCode::UserId).not_null())
+                    .col(string(UserRecoveryCode::CodeHash).not_null())
+                    .col(timestamp_null(UserRecoveryCode::UsedAt))
+                    .col(
+                        timestamp(UserRecoveryCode::CreatedAt)
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(UserRecoveryCode::Table, UserRecoveryCode::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .index(
+                        Index::create()
+                            .unique()
+                            .col(UserRecoveryCode::UserId)
+                            .col(UserRecoveryCode::CodeHash),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(AppSetting::Table)
+                    .if_not_exists()
+                    .col(string(AppSetting::Key).primary_key())
+                    .col(json(AppSetting::Value).not_null())
+                    .col(
+                        timestamp(AppSetting::UpdatedAt)
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(integer_null(AppSetting::UpdatedBy))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(AppSetting::Table, AppSetting::UpdatedBy)
+                            .to(User::Table, User::Id),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(AppSetting::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(UserRecoveryCode::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        for column in [
+            UserMfa::TotpLastStep,
+            UserMfa::TotpEnabled,
+            UserMfa::TotpSecret,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(User::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// New columns on the existing `user` table
+#[derive(DeriveIden)]
+#[allow(clippy::enum_variant_names)]
+enum UserMfa {
+    TotpSecret,
+    TotpEnabled,
+    TotpLastStep,
+}
+
+#[derive(DeriveIden)]
+enum UserRecoveryCode {
+    Table,
+    Id,
+    UserId,
+    CodeHash,
+    UsedAt,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum AppSetting {
+    Table,
+    Key,
+    Value,
+    UpdatedAt,
+    UpdatedBy,
+}
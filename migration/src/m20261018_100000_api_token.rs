@@ -0,0 +1,67 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::m20220101_000001_create_table::User;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ApiToken::Table)
+                    .if_not_exists()
+                    .col(pk_auto(ApiToken::Id))
+                    .col(integer(ApiToken::UserId).not_null())
+                    .col(string(ApiToken::Name).not_null())
+                    .col(string(ApiToken::TokenHash).unique_key().not_null())
+                    .col(string(ApiToken::TokenPrefix).not_null())
+                    .col(json(ApiToken::Scopes).not_null())
+                    .col(timestamp_null(ApiToken::ExpiresAt))
+                    .col(timestamp_null(ApiToken::LastUsedAt))
+                    .col(timestamp_null(ApiToken::RevokedAt))
+                    .col(
+                        timestamp(ApiToken::CreatedAt)
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ApiToken::Table, ApiToken::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .index(
+                        Index::create()
+                            .unique()
+                            .col(ApiToken::UserId)
+                            .col(ApiToken::Name),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ApiToken::Table).if_exists().to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ApiToken {
+    Table,
+    Id,
+    UserId,
+    Name,
+    TokenHash,
+    TokenPrefix,
+    Scopes,
+    ExpiresAt,
+    LastUsedAt,
+    RevokedAt,
+    CreatedAt,
+}
@@ -0,0 +1,112 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::m20220101_000001_create_table::User;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AuditLog::Table)
+                    .if_not_exists()
+                    .col(pk_auto(AuditLog::Id))
+                    .col(
+                        timestamp(AuditLog::CreatedAt)
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(integer_null(AuditLog::UserId))
+                    // Copied so entries stay readable after the user is deleted or renamed
+                    .col(string_null(AuditLog::Username))
+                    .col(integer_null(AuditLog::ApiTokenId))
+                    .col(string(AuditLog::Action).not_null())
+                    .col(string_null(AuditLog::TargetType))
+                    .col(string_null(AuditLog::TargetId))
+                    .col(boolean(AuditLog::Success).not_null())
+                    .col(string_null(AuditLog::Ip))
+                    .col(string_null(AuditLog::UserAgent))
+                    .col(json_null(AuditLog::Details))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(AuditLog::Table, AuditLog::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        for (name, column) in [
+            ("idx_audit_log_created_at", AuditLog::CreatedAt),
+            ("idx_audit_log_user_id", AuditLog::UserId),
+            ("idx_audit_log_action", AuditLog::Action),
+        ] {
+            manager
+                .create_index(
+                    Index::create()
+                        .if_not_exists()
+                        .name(name)
+                        .table(AuditLog::Table)
+                        .col(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        // Enforce append-only at the database level. The user FK's ON DELETE SET NULL is the
+        // only update allowed, so entries survive their user being deleted.
+        let db = manager.get_connection();
+        db.execute_unprepared(
+            "CREATE TRIGGER IF NOT EXISTS audit_log_no_update BEFORE UPDATE ON audit_log
+             WHEN NOT (OLD.user_id IS NOT NULL AND NEW.user_id IS NULL
+                       AND NEW.id IS OLD.id AND NEW.created_at IS OLD.created_at
+                       AND NEW.username IS OLD.username AND NEW.api_token_id IS OLD.api_token_id
+                       AND NEW.action IS OLD.action AND NEW.target_type IS OLD.target_type
+                       AND NEW.target_id IS OLD.target_id AND NEW.success IS OLD.success
+                       AND NEW.ip IS OLD.ip AND NEW.user_agent IS OLD.user_agent
+                       AND NEW.details IS OLD.details)
+             BEGIN SELECT RAISE(ABORT, 'audit_log is append-only'); END;",
+        )
+        .await?;
+        db.execute_unprepared(
+            "CREATE TRIGGER IF NOT EXISTS audit_log_no_delete BEFORE DELETE ON audit_log
+             BEGIN SELECT RAISE(ABORT, 'audit_log is append-only'); END;",
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        db.execute_unprepared("DROP TRIGGER IF EXISTS audit_log_no_delete;")
+            .await?;
+        db.execute_unprepared("DROP TRIGGER IF EXISTS audit_log_no_update;")
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(AuditLog::Table).if_exists().to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum AuditLog {
+    Table,
+    Id,
+    CreatedAt,
+    UserId,
+    Username,
+    ApiTokenId,
+    Action,
+    TargetType,
+    TargetId,
+    Success,
+    Ip,
+    UserAgent,
+    Details,
+}
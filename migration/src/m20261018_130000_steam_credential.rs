@@ -0,0 +1,53 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(SteamCredential::Table)
+                    .if_not_exists()
+                    .col(pk_auto(SteamCredential::Id))
+                    .col(string(SteamCredential::Username).not_null().unique_key())
+                    .col(text(SteamCredential::PasswordEncrypted).not_null())
+                    .col(
+                        timestamp(SteamCredential::CreatedAt)
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        timestamp(SteamCredential::UpdatedAt)
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(SteamCredential::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+/// Steam accounts used for non-anonymous SteamCMD logins. Passwords are encrypted at rest.
+#[derive(DeriveIden)]
+pub enum SteamCredential {
+    Table,
+    Id,
+    Username,
+    PasswordEncrypted,
+    CreatedAt,
+    UpdatedAt,
+}
@@ -0,0 +1,67 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::m20260118_003246_game_config::GameConfig;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(InstanceMod::Table)
+                    .if_not_exists()
+                    .col(pk_auto(InstanceMod::Id))
+                    .col(integer(InstanceMod::InstanceId).not_null())
+                    .col(big_integer(InstanceMod::WorkshopItemId).not_null())
+                    .col(boolean(InstanceMod::Enabled).not_null().default(true))
+                    .col(integer(InstanceMod::LoadOrder).not_null().default(0))
+                    .col(timestamp_null(InstanceMod::InstalledAt))
+                    .col(
+                        timestamp(InstanceMod::CreatedAt)
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(InstanceMod::Table, InstanceMod::InstanceId)
+                            .to(GameConfig::Table, GameConfig::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .index(
+                        Index::create()
+                            .unique()
+                            .col(InstanceMod::InstanceId)
+                            .col(InstanceMod::WorkshopItemId),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(InstanceMod::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+/// Steam Workshop items installed for a game server instance, in load order
+#[derive(DeriveIden)]
+enum InstanceMod {
+    Table,
+    Id,
+    InstanceId,
+    WorkshopItemId,
+    Enabled,
+    LoadOrder,
+    InstalledAt,
+    CreatedAt,
+}
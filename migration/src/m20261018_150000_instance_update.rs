@@ -0,0 +1,75 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::{
+    m20260118_003246_game_config::GameConfig, m20261018_130000_steam_credential::SteamCredential,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(InstanceUpdate::Table)
+                    .if_not_exists()
+                    .col(integer(InstanceUpdate::InstanceId).primary_key())
+                    .col(big_integer_null(InstanceUpdate::InstalledBuildId))
+                    .col(big_integer_null(InstanceUpdate::AvailableBuildId))
+                    .col(timestamp_null(InstanceUpdate::CheckedAt))
+                    .col(timestamp_null(InstanceUpdate::UpdatedAt))
+                    .col(text_null(InstanceUpdate::LastError))
+                    .col(
+                        boolean(InstanceUpdate::AutoUpdate)
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(string_null(InstanceUpdate::WindowStart))
+                    .col(string_null(InstanceUpdate::WindowEnd))
+                    .col(integer_null(InstanceUpdate::CredentialId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(InstanceUpdate::Table, InstanceUpdate::InstanceId)
+                            .to(GameConfig::Table, GameConfig::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(InstanceUpdate::Table, InstanceUpdate::CredentialId)
+                            .to(SteamCredential::Table, SteamCredential::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(InstanceUpdate::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+/// Installed and latest available game build per instance, plus its auto-update policy
+#[derive(DeriveIden)]
+pub enum InstanceUpdate {
+    Table,
+    InstanceId,
+    InstalledBuildId,
+    AvailableBuildId,
+    CheckedAt,
+    UpdatedAt,
+    LastError,
+    AutoUpdate,
+    WindowStart,
+    WindowEnd,
+    CredentialId,
+}
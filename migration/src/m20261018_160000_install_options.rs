@@ -0,0 +1,62 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::m20261018_150000_instance_update::InstanceUpdate;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLite only supports a single column per ALTER TABLE statement
+        for column in [
+            string_null(InstallOptions::Branch),
+            text_null(InstallOptions::BetaPasswordEncrypted),
+            boolean(InstallOptions::Validate)
+                .not_null()
+                .default(false)
+                .to_owned(),
+            string_null(InstallOptions::Platform),
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(InstanceUpdate::Table)
+                        .add_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [
+            InstallOptions::Platform,
+            InstallOptions::Validate,
+            InstallOptions::BetaPasswordEncrypted,
+            InstallOptions::Branch,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(InstanceUpdate::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// New columns on the existing `instance_update` table. The beta password is encrypted at rest.
+#[derive(DeriveIden)]
+enum InstallOptions {
+    Branch,
+    BetaPasswordEncrypted,
+    Validate,
+    Platform,
+}
@@ -0,0 +1,139 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::m20220101_000001_create_table::User;
+use crate::m20260118_003246_game_config::GameConfig;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(PlayerSession::Table)
+                    .if_not_exists()
+                    .col(pk_auto(PlayerSession::Id))
+                    .col(integer(PlayerSession::InstanceId).not_null())
+                    .col(string(PlayerSession::Name).not_null())
+                    .col(string_null(PlayerSession::SteamId))
+                    .col(timestamp(PlayerSession::JoinedAt).not_null())
+                    .col(timestamp_null(PlayerSession::LeftAt))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(PlayerSession::Table, PlayerSession::InstanceId)
+                            .to(GameConfig::Table, GameConfig::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_player_session_instance_joined")
+                    .table(PlayerSession::Table)
+                    .col(PlayerSession::InstanceId)
+                    .col(PlayerSession::JoinedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(PlayerAction::Table)
+                    .if_not_exists()
+                    .col(pk_auto(PlayerAction::Id))
+                    .col(integer(PlayerAction::InstanceId).not_null())
+                    .col(string(PlayerAction::Action).not_null())
+                    .col(string(PlayerAction::PlayerName).not_null())
+                    .col(string_null(PlayerAction::SteamId))
+                    .col(text_null(PlayerAction::Reason))
+                    .col(text(PlayerAction::Command).not_null())
+                    .col(integer_null(PlayerAction::UserId))
+                    // Copied so actions stay attributed after the moderator is deleted or renamed
+                    .col(string_null(PlayerAction::Username))
+                    .col(
+                        timestamp(PlayerAction::CreatedAt)
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(PlayerAction::Table, PlayerAction::InstanceId)
+                            .to(GameConfig::Table, GameConfig::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(PlayerAction::Table, PlayerAction::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_player_action_instance_id")
+                    .table(PlayerAction::Table)
+                    .col(PlayerAction::InstanceId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(PlayerAction::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(PlayerSession::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+/// A player's stay on a game server, from the console lines announcing their join and leave
+#[derive(DeriveIden)]
+enum PlayerSession {
+    Table,
+    Id,
+    InstanceId,
+    Name,
+    SteamId,
+    JoinedAt,
+    LeftAt,
+}
+
+/// Kicks, bans and whitelist changes moderators made through the panel
+#[derive(DeriveIden)]
+enum PlayerAction {
+    Table,
+    Id,
+    InstanceId,
+    Action,
+    PlayerName,
+    SteamId,
+    Reason,
+    Command,
+    UserId,
+    Username,
+    CreatedAt,
+}
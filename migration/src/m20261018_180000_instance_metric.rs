@@ -0,0 +1,79 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::m20260118_003246_game_config::GameConfig;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(InstanceMetric::Table)
+                    .if_not_exists()
+                    .col(pk_auto(InstanceMetric::Id))
+                    .col(integer(InstanceMetric::InstanceId).not_null())
+                    .col(integer(InstanceMetric::ResolutionSecs).not_null())
+                    .col(timestamp(InstanceMetric::RecordedAt).not_null())
+                    .col(integer(InstanceMetric::Samples).not_null())
+                    .col(double(InstanceMetric::CpuPercent).not_null())
+                    .col(double(InstanceMetric::CpuPercentMax).not_null())
+                    .col(big_integer(InstanceMetric::MemoryBytes).not_null())
+                    .col(integer(InstanceMetric::Threads).not_null())
+                    .col(double(InstanceMetric::ReadBytesPerSec).not_null())
+                    .col(double(InstanceMetric::WriteBytesPerSec).not_null())
+                    .col(big_integer_null(InstanceMetric::DiskBytes))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(InstanceMetric::Table, InstanceMetric::InstanceId)
+                            .to(GameConfig::Table, GameConfig::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_instance_metric_instance_recorded")
+                    .table(InstanceMetric::Table)
+                    .col(InstanceMetric::InstanceId)
+                    .col(InstanceMetric::RecordedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(InstanceMetric::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+/// Resource usage of a game server, averaged per minute and rolled up into hours as it ages
+#[derive(DeriveIden)]
+enum InstanceMetric {
+    Table,
+    Id,
+    InstanceId,
+    ResolutionSecs,
+    RecordedAt,
+    Samples,
+    CpuPercent,
+    CpuPercentMax,
+    MemoryBytes,
+    Threads,
+    ReadBytesPerSec,
+    WriteBytesPerSec,
+    DiskBytes,
+}
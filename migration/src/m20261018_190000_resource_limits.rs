@@ -0,0 +1,65 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::m20260118_003246_game_config::GameConfig;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLite only supports a single column per ALTER TABLE statement
+        for column in [
+            ResourceLimits::MemoryMaxMb,
+            ResourceLimits::CpuQuotaPercent,
+            ResourceLimits::CpuWeight,
+            ResourceLimits::MaxProcesses,
+            ResourceLimits::Nice,
+            ResourceLimits::MaxOpenFiles,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(GameConfig::Table)
+                        .add_column(integer_null(column))
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [
+            ResourceLimits::MaxOpenFiles,
+            ResourceLimits::Nice,
+            ResourceLimits::MaxProcesses,
+            ResourceLimits::CpuWeight,
+            ResourceLimits::CpuQuotaPercent,
+            ResourceLimits::MemoryMaxMb,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(GameConfig::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// New columns on the existing `game_config` table. Unset means unlimited.
+#[derive(DeriveIden)]
+enum ResourceLimits {
+    MemoryMaxMb,
+    CpuQuotaPercent,
+    CpuWeight,
+    MaxProcesses,
+    Nice,
+    MaxOpenFiles,
+}
@@ -0,0 +1,95 @@
+use rand::{distributions::Alphanumeric, thread_rng, Rng};
+use rocket::{http::Method, Request};
+use sha2::{Digest, Sha256};
+
+#[cfg(test)]
+mod tests;
+
+/// Every personal API token starts with this prefix so it can be told apart from a JWT
+pub const TOKEN_PREFIX: &str = "sui_";
+
+/// Number of characters of the token kept in clear text so users can recognise it
+const DISPLAY_PREFIX_LEN: usize = 10;
+
+/// Scope granting every permission the token owner has
+pub const WILDCARD_SCOPE: &str = "*";
+
+pub fn generate() -> String {
+    let random: String = thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(40)
+        .map(char::from)
+        .collect();
+
+    format!("{}{}", TOKEN_PREFIX, random)
+}
+
+pub fn hash(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+pub fn display_prefix(token: &str) -> String {
+    token.chars().take(DISPLAY_PREFIX_LEN).collect()
+}
+
+/// Access level of a scope. `Write` implies `Read`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ScopeAccess {
+    Read,
+    Write,
+}
+
+/// The scope a request needs, derived from the API area (`/api/<area>/...`) and HTTP method
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequiredScope {
+    pub area: String,
+    pub access: ScopeAccess,
+}
+
+impl RequiredScope {
+    pub fn for_request(request: &Request<'_>) -> Self {
+        let mut segments = request.uri().path().segments();
+        let area = match segments.next() {
+            Some("api") => segments.next().unwrap_or_default(),
+            other => other.unwrap_or_default(),
+        };
+        let access = match request.method() {
+            Method::Get | Method::Head | Method::Options => ScopeAccess::Read,
+            _ => ScopeAccess::Write,
+        };
+
+        Self {
+            area: area.to_string(),
+            access,
+        }
+    }
+}
+
+/// Parse a scope of the form `*`, `<area>:read` or `<area>:write` (area may be `*`)
+pub fn parse_scope(scope: &str) -> Option<(&str, ScopeAccess)> {
+    if scope == WILDCARD_SCOPE {
+        return Some((WILDCARD_SCOPE, ScopeAccess::Write));
+    }
+
+    let (area, access) = scope.split_once(':')?;
+    let valid_area = area == WILDCARD_SCOPE
+        || (!area.is_empty() && area.chars().all(|c| c.is_ascii_lowercase() || c == '_'));
+    if !valid_area {
+        return None;
+    }
+
+    match access {
+        "read" => Some((area, ScopeAccess::Read)),
+        "write" => Some((area, ScopeAccess::Write)),
+        _ => None,
+    }
+}
+
+pub fn scopes_allow(scopes: &[String], required: &RequiredScope) -> bool {
+    scopes
+        .iter()
+        .filter_map(|s| parse_scope(s))
+        .any(|(area, access)| {
+            (area == WILDCARD_SCOPE || area == required.area) && access >= required.access
+        })
+}
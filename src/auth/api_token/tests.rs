@@ -0,0 +1,126 @@
+use super::*;
+use rocket::local::blocking::Client;
+
+fn scopes(scopes: &[&str]) -> Vec<String> {
+    scopes.iter().map(|s| s.to_string()).collect()
+}
+
+fn required(area: &str, access: ScopeAccess) -> RequiredScope {
+    RequiredScope {
+        area: area.to_string(),
+        access,
+    }
+}
+
+#[test]
+fn test_parse_scope() {
+    assert_eq!(parse_scope("*"), Some(("*", ScopeAccess::Write)));
+    assert_eq!(parse_scope("*:read"), Some(("*", ScopeAccess::Read)));
+    assert_eq!(
+        parse_scope("game_schema:write"),
+        Some(("game_schema", ScopeAccess::Write))
+    );
+}
+
+#[test]
+fn test_parse_scope_rejects_malformed_scopes() {
+    for scope in [
+        "",
+        "instance",
+        ":read",
+        "instance:",
+        "instance:admin",
+        "Instance:read",
+        "instance:read:write",
+        "inst-ance:read",
+        "instance :read",
+        "**",
+    ] {
+        assert_eq!(parse_scope(scope), None, "{scope:?}");
+    }
+}
+
+#[test]
+fn test_write_implies_read() {
+    let write = scopes(&["instance:write"]);
+    let read = scopes(&["instance:read"]);
+
+    assert!(scopes_allow(
+        &write,
+        &required("instance", ScopeAccess::Read)
+    ));
+    assert!(scopes_allow(
+        &write,
+        &required("instance", ScopeAccess::Write)
+    ));
+    assert!(scopes_allow(
+        &read,
+        &required("instance", ScopeAccess::Read)
+    ));
+    assert!(!scopes_allow(
+        &read,
+        &required("instance", ScopeAccess::Write)
+    ));
+}
+
+#[test]
+fn test_scopes_only_allow_their_own_area() {
+    let instance = scopes(&["instance:write", "not a scope"]);
+
+    assert!(!scopes_allow(
+        &instance,
+        &required("user", ScopeAccess::Read)
+    ));
+    assert!(!scopes_allow(
+        &instance,
+        &required("unknown", ScopeAccess::Read)
+    ));
+    assert!(!scopes_allow(
+        &scopes(&["unknown:write"]),
+        &required("instance", ScopeAccess::Read)
+    ));
+    assert!(!scopes_allow(&[], &required("instance", ScopeAccess::Read)));
+
+    assert!(scopes_allow(
+        &scopes(&["*"]),
+        &required("unknown", ScopeAccess::Write)
+    ));
+    assert!(scopes_allow(
+        &scopes(&["*:read"]),
+        &required("user", ScopeAccess::Read)
+    ));
+    assert!(!scopes_allow(
+        &scopes(&["*:read"]),
+        &required("user", ScopeAccess::Write)
+    ));
+}
+
+#[test]
+fn test_required_scope_maps_methods_to_access() {
+    let client = Client::untracked(rocket::build()).unwrap();
+
+    let scope = |request: rocket::local::blocking::LocalRequest<'_>| {
+        RequiredScope::for_request(request.inner())
+    };
+
+    assert_eq!(
+        scope(client.get("/api/instance/1")),
+        required("instance", ScopeAccess::Read)
+    );
+    assert_eq!(
+        scope(client.head("/api/instance/1")),
+        required("instance", ScopeAccess::Read)
+    );
+    assert_eq!(
+        scope(client.post("/api/instance/1/start")),
+        required("instance", ScopeAccess::Write)
+    );
+    assert_eq!(
+        scope(client.put("/api/game_schema/update/1")),
+        required("game_schema", ScopeAccess::Write)
+    );
+    assert_eq!(
+        scope(client.delete("/api/instance/1")),
+        required("instance", ScopeAccess::Write)
+    );
+}
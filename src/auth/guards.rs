@@ -1,6 +1,8 @@
-use crate::auth::{verify_token, TokenType};
+use crate::auth::{api_token, verify_token, TokenType};
 use crate::dto;
 use crate::models::user::UserRole;
+use crate::service::api_token::ApiTokens;
+use crate::state::stream_ticket::StreamTickets;
 use crate::utils::error_response;
 use rocket::{
     http::Status,
@@ -8,9 +10,10 @@ use rocket::{
     response::Responder,
     Request,
 };
+use sea_orm::DatabaseConnection;
 use thiserror::Error;
 
-#[derive(Error, Debug)]
+#[derive(Error, Debug, Clone)]
 pub enum AuthError {
     #[error("Missing authorization token")]
     MissingToken,
@@ -29,19 +32,120 @@ pub enum AuthError {
 
     #[error("Admin access required")]
     Forbidden,
+
+    #[error("API token does not have the scope required for this request")]
+    InsufficientScope,
+
+    #[error("This action requires an interactive login and cannot be performed with an API token")]
+    SessionRequired,
+
+    #[error("Invalid, expired or already used stream ticket")]
+    InvalidTicket,
+
+    #[error("Access tokens are not accepted in the query string; use a stream ticket instead")]
+    QueryTokenRejected,
+
+    #[error("Too many wrong codes were tried for this account. Try again in a few minutes.")]
+    TooManyMfaAttempts,
 }
 
 impl<'r> Responder<'r, 'static> for AuthError {
     fn respond_to(self, _: &'r Request<'_>) -> rocket::response::Result<'static> {
-        let status = Status::Unauthorized;
+        let status = match self {
+            AuthError::InsufficientScope | AuthError::SessionRequired => Status::Forbidden,
+            AuthError::TooManyMfaAttempts => Status::TooManyRequests,
+            _ => Status::Unauthorized,
+        };
         error_response(self, status)
     }
 }
 
+fn bearer_token<'r>(request: &'r Request<'_>) -> Option<&'r str> {
+    request
+        .headers()
+        .get_one("Authorization")
+        .and_then(|h| h.strip_prefix("Bearer "))
+}
+
+#[derive(Clone)]
 pub struct AccessTokenGuard {
     pub user_id: i32,
     pub username: String,
     pub role: UserRole,
+    /// Set when the request was authenticated with a personal API token instead of a login
+    pub api_token_id: Option<i32>,
+    /// Scopes of that API token, empty for logins
+    pub scopes: Vec<String>,
+}
+
+impl AccessTokenGuard {
+    /// Reject requests made with an API token, for actions that must come from a real login
+    pub fn require_session(&self) -> Result<(), AuthError> {
+        match self.api_token_id {
+            Some(_) => Err(AuthError::SessionRequired),
+            None => Ok(()),
+        }
+    }
+
+    /// Whether the request could also do what `required` needs. Logins may do all their role
+    /// allows, API tokens only what their scopes grant.
+    pub fn allows(&self, required: &api_token::RequiredScope) -> bool {
+        self.api_token_id.is_none() || api_token::scopes_allow(&self.scopes, required)
+    }
+
+    async fn authenticate(request: &Request<'_>) -> Result<Self, (Status, AuthError)> {
+        let query_param = |name: &str| {
+            request
+                .uri()
+                .query()
+                .and_then(|q| q.segments().find(|s| s.0 == name).map(|s| s.1))
+        };
+
+        let Some(token) = bearer_token(request) else {
+            // Streams can't send headers, so they present a single-use ticket instead
+            if let Some(ticket) = query_param("ticket") {
+                let tickets = request
+                    .rocket()
+                    .state::<StreamTickets>()
+                    .ok_or((Status::InternalServerError, AuthError::InvalidTicket))?;
+
+                return tickets
+                    .redeem(ticket, request.uri().path().as_str())
+                    .ok_or((Status::Unauthorized, AuthError::InvalidTicket));
+            }
+
+            // Tokens in URLs end up in proxy logs and browser history
+            if query_param("token").is_some() {
+                return Err((Status::Unauthorized, AuthError::QueryTokenRejected));
+            }
+
+            return Err((Status::Unauthorized, AuthError::MissingToken));
+        };
+
+        if token.starts_with(api_token::TOKEN_PREFIX) {
+            let db = request
+                .rocket()
+                .state::<DatabaseConnection>()
+                .ok_or((Status::InternalServerError, AuthError::InvalidToken))?;
+            let required = api_token::RequiredScope::for_request(request);
+
+            return ApiTokens::new(db.clone())
+                .authenticate(token, &required)
+                .await;
+        }
+
+        match verify_token(token) {
+            Ok(claims) if claims.token_type == TokenType::Access => Ok(AccessTokenGuard {
+                user_id: claims.sub,
+                username: claims.username,
+                role: claims.role,
+                api_token_id: None,
+                scopes: Vec::new(),
+            }),
+            Ok(_) => Err((Status::Unauthorized, AuthError::InvalidTokenType)),
+            Err(_) => Err((Status::Unauthorized, AuthError::InvalidToken)),
+        }
+    }
 }
 
 impl From<AccessTokenGuard> for dto::user::Minimum {
@@ -58,30 +162,14 @@ impl<'r> FromRequest<'r> for AccessTokenGuard {
     type Error = AuthError;
 
     async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
-        let token = request
-            .headers()
-            .get_one("Authorization")
-            .and_then(|h| h.strip_prefix("Bearer "))
-            .or_else(|| {
-                request
-                    .uri()
-                    .query()
-                    .and_then(|q| q.segments().find(|s| s.0 == "token").map(|s| s.1))
-            });
-
-        match token {
-            None => Outcome::Error((Status::Unauthorized, AuthError::MissingToken)),
-            Some(token) => match verify_token(token) {
-                Ok(claims) if claims.token_type == TokenType::Access => {
-                    Outcome::Success(AccessTokenGuard {
-                        user_id: claims.sub,
-                        username: claims.username,
-                        role: claims.role,
-                    })
-                }
-                Ok(_) => Outcome::Error((Status::Unauthorized, AuthError::InvalidTokenType)),
-                Err(_) => Outcome::Error((Status::Unauthorized, AuthError::InvalidToken)),
-            },
+        // Services resolve the session too, so cache the result for the lifetime of the request
+        let result = request
+            .local_cache_async(async { AccessTokenGuard::authenticate(request).await })
+            .await;
+
+        match result {
+            Ok(guard) => Outcome::Success(guard.clone()),
+            Err(e) => Outcome::Error(e.clone()),
         }
     }
 }
@@ -167,3 +255,38 @@ impl<'r> FromRequest<'r> for ModeratorGuard {
         }
     }
 }
+
+/// Accepts either a regular access token or an MFA enrollment challenge, so users who are
+/// forced to enrol at login can set up TOTP before they receive an access token.
+pub struct MfaEnrollmentGuard {
+    pub user_id: i32,
+    pub username: String,
+    pub role: UserRole,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for MfaEnrollmentGuard {
+    type Error = AuthError;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match bearer_token(request) {
+            None => Outcome::Error((Status::Unauthorized, AuthError::MissingToken)),
+            Some(token) => match verify_token(token) {
+                Ok(claims)
+                    if matches!(
+                        claims.token_type,
+                        TokenType::Access | TokenType::MfaEnrollment
+                    ) =>
+                {
+                    Outcome::Success(MfaEnrollmentGuard {
+                        user_id: claims.sub,
+                        username: claims.username,
+                        role: claims.role,
+                    })
+                }
+                Ok(_) => Outcome::Error((Status::Unauthorized, AuthError::InvalidTokenType)),
+                Err(_) => Outcome::Error((Status::Unauthorized, AuthError::InvalidToken)),
+            },
+        }
+    }
+}
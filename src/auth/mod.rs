@@ -4,8 +4,12 @@ use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation}
 use serde::{Deserialize, Serialize};
 use std::sync::OnceLock;
 
+pub mod api_token;
 pub mod guards;
+pub mod oidc;
 pub mod response;
+pub mod secrets;
+pub mod totp;
 
 static JWT_SECRET: OnceLock<String> = OnceLock::new();
 
@@ -35,6 +39,10 @@ pub struct TokenClaims {
 pub enum TokenType {
     Access,
     Refresh,
+    /// Issued after a successful password check when the user must still supply a TOTP code
+    MfaChallenge,
+    /// Issued after a successful password check when policy requires the user to enrol in TOTP
+    MfaEnrollment,
 }
 
 impl TokenClaims {
@@ -65,6 +73,25 @@ impl TokenClaims {
             token_type: TokenType::Refresh,
         }
     }
+
+    pub fn mfa_challenge(
+        user_id: i32,
+        username: String,
+        role: UserRole,
+        token_type: TokenType,
+    ) -> Self {
+        let iat = Utc::now();
+        let exp = iat + Duration::minutes(5);
+
+        Self {
+            sub: user_id,
+            username,
+            role,
+            exp: exp.timestamp(),
+            iat: iat.timestamp(),
+            token_type,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -93,6 +120,21 @@ pub fn generate_tokens(
     })
 }
 
+/// Generate a short-lived token that only allows completing (or enrolling in) MFA for a user
+pub fn generate_mfa_challenge(
+    user_id: i32,
+    username: String,
+    role: UserRole,
+    token_type: TokenType,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    let claims = TokenClaims::mfa_challenge(user_id, username, role, token_type);
+
+    let secret = get_secret();
+    let encoding_key = EncodingKey::from_secret(secret.as_bytes());
+
+    encode(&Header::default(), &claims, &encoding_key)
+}
+
 pub fn verify_token(token: &str) -> Result<TokenClaims, jsonwebtoken::errors::Error> {
     let secret = get_secret();
     let decoding_key = DecodingKey::from_secret(secret.as_bytes());
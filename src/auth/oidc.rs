@@ -0,0 +1,422 @@
+use crate::models::user::UserRole;
+use crate::utils::error_response;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use jsonwebtoken::{jwk::JwkSet, Algorithm, DecodingKey, Validation};
+use rand::{distributions::Alphanumeric, thread_rng, Rng};
+use rocket::{http::Status, response::Responder, Request};
+use serde::Deserialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::sync::RwLock;
+
+#[cfg(test)]
+mod tests;
+
+/// Allowed clock skew when validating ID token timestamps
+const CLOCK_SKEW_SECONDS: u64 = 60;
+
+#[derive(Error, Debug)]
+pub enum OidcError {
+    #[error("Single sign-on is not configured")]
+    Disabled,
+
+    #[error("Invalid OIDC configuration: {0}")]
+    InvalidConfig(String),
+
+    #[error("Failed to reach the identity provider: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("The identity provider's discovery document is invalid: {0}")]
+    InvalidDiscovery(String),
+
+    #[error("Unknown or expired login attempt. Please start the login again.")]
+    InvalidState,
+
+    #[error("The identity provider rejected the authorization code: {0}")]
+    TokenExchange(String),
+
+    #[error("The ID token is invalid: {0}")]
+    InvalidIdToken(String),
+}
+
+impl<'r> Responder<'r, 'static> for OidcError {
+    fn respond_to(self, _: &'r Request<'_>) -> rocket::response::Result<'static> {
+        let status = match self {
+            OidcError::Disabled => Status::NotFound,
+            OidcError::InvalidConfig(_) => Status::InternalServerError,
+            OidcError::Http(_) | OidcError::InvalidDiscovery(_) => Status::BadGateway,
+            OidcError::InvalidState
+            | OidcError::TokenExchange(_)
+            | OidcError::InvalidIdToken(_) => Status::Unauthorized,
+        };
+        error_response(self, status)
+    }
+}
+
+/// Identity provider settings, read from `OIDC_*` environment variables
+#[derive(Debug, Clone)]
+pub struct OidcConfig {
+    /// Exact issuer identifier; discovery is fetched from `<issuer>/.well-known/openid-configuration`
+    pub issuer: String,
+    pub client_id: String,
+    pub client_secret: Option<String>,
+    /// Frontend route the provider redirects back to, e.g. `https://panel.example.com/login/callback`
+    pub redirect_uri: String,
+    pub scopes: Vec<String>,
+    /// Name shown on the login button
+    pub display_name: String,
+    /// ID token claim holding the user's groups or roles
+    pub role_claim: String,
+    pub admin_groups: Vec<String>,
+    pub moderator_groups: Vec<String>,
+    /// Role given to users matching none of the groups. `None` denies them.
+    pub default_role: Option<UserRole>,
+}
+
+impl OidcConfig {
+    /// Returns `Ok(None)` when `OIDC_ISSUER` is not set, i.e. single sign-on is disabled
+    pub fn from_env() -> Result<Option<Self>, OidcError> {
+        let optional = |name: &str| std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+        let required = |name: &str| {
+            optional(name).ok_or_else(|| {
+                OidcError::InvalidConfig(format!("{name} must be set when OIDC_ISSUER is set"))
+            })
+        };
+        let list = |name: &str| {
+            optional(name)
+                .map(|v| {
+                    v.split(',')
+                        .map(|s| s.trim().to_string())
+                        .filter(|s| !s.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        let Some(issuer) = optional("OIDC_ISSUER") else {
+            return Ok(None);
+        };
+
+        let default_role = match optional("OIDC_DEFAULT_ROLE").as_deref() {
+            None => None,
+            Some("admin") => Some(UserRole::Admin),
+            Some("moderator") => Some(UserRole::Moderator),
+            Some(other) => {
+                return Err(OidcError::InvalidConfig(format!(
+                    "OIDC_DEFAULT_ROLE must be 'admin' or 'moderator', got '{other}'"
+                )))
+            }
+        };
+
+        Ok(Some(Self {
+            issuer,
+            client_id: required("OIDC_CLIENT_ID")?,
+            client_secret: optional("OIDC_CLIENT_SECRET"),
+            redirect_uri: required("OIDC_REDIRECT_URI")?,
+            scopes: optional("OIDC_SCOPES")
+                .map(|v| v.split_whitespace().map(str::to_string).collect())
+                .unwrap_or_else(|| vec!["openid".into(), "profile".into(), "email".into()]),
+            display_name: optional("OIDC_DISPLAY_NAME").unwrap_or_else(|| "SSO".to_string()),
+            role_claim: optional("OIDC_ROLE_CLAIM").unwrap_or_else(|| "groups".to_string()),
+            admin_groups: list("OIDC_ADMIN_GROUPS"),
+            moderator_groups: list("OIDC_MODERATOR_GROUPS"),
+            default_role,
+        }))
+    }
+
+    /// Map the configured role claim onto a panel role. Admin wins over Moderator.
+    pub fn map_role(&self, claims: &IdTokenClaims) -> Option<UserRole> {
+        let values: Vec<&str> = match claims.extra.get(&self.role_claim) {
+            Some(Value::Array(values)) => values.iter().filter_map(Value::as_str).collect(),
+            Some(Value::String(value)) => vec![value.as_str()],
+            _ => Vec::new(),
+        };
+        let matches = |groups: &[String]| values.iter().any(|v| groups.iter().any(|g| g == v));
+
+        if matches(&self.admin_groups) {
+            Some(UserRole::Admin)
+        } else if matches(&self.moderator_groups) {
+            Some(UserRole::Moderator)
+        } else {
+            self.default_role
+        }
+    }
+}
+
+/// The validated subset of ID token claims the panel cares about
+#[derive(Debug, Clone, Deserialize)]
+pub struct IdTokenClaims {
+    pub iss: String,
+    pub sub: String,
+    pub nonce: Option<String>,
+    pub preferred_username: Option<String>,
+    pub email: Option<String>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+impl IdTokenClaims {
+    /// Username for a newly provisioned account
+    pub fn username(&self) -> &str {
+        self.preferred_username
+            .as_deref()
+            .or(self.email.as_deref())
+            .unwrap_or(&self.sub)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+    #[serde(default)]
+    code_challenge_methods_supported: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    id_token: Option<String>,
+    error: Option<String>,
+    error_description: Option<String>,
+}
+
+/// Random URL-safe string used for `state`, `nonce` and PKCE verifiers
+pub fn random_token() -> String {
+    thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(64)
+        .map(char::from)
+        .collect()
+}
+
+/// S256 PKCE code challenge for a verifier
+pub fn pkce_challenge(verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+/// Relying-party side of the authorization code flow. Discovery and keys are fetched lazily
+/// and cached; the key set is refreshed when a token is signed with an unknown key id.
+pub struct OidcClient {
+    config: OidcConfig,
+    http: reqwest::Client,
+    metadata: RwLock<Option<ProviderMetadata>>,
+    jwks: RwLock<Option<JwkSet>>,
+}
+
+impl OidcClient {
+    pub fn new(config: OidcConfig) -> Result<Self, OidcError> {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()?;
+
+        Ok(Self {
+            config,
+            http,
+            metadata: RwLock::new(None),
+            jwks: RwLock::new(None),
+        })
+    }
+
+    pub fn config(&self) -> &OidcConfig {
+        &self.config
+    }
+
+    async fn metadata(&self) -> Result<ProviderMetadata, OidcError> {
+        if let Some(metadata) = self.metadata.read().await.as_ref() {
+            return Ok(metadata.clone());
+        }
+
+        let url = format!(
+            "{}/.well-known/openid-configuration",
+            self.config.issuer.trim_end_matches('/')
+        );
+        let metadata: ProviderMetadata = self
+            .http
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        if metadata.issuer != self.config.issuer {
+            return Err(OidcError::InvalidDiscovery(format!(
+                "issuer '{}' does not match the configured issuer",
+                metadata.issuer
+            )));
+        }
+        if !metadata.code_challenge_methods_supported.is_empty()
+            && !metadata
+                .code_challenge_methods_supported
+                .iter()
+                .any(|m| m == "S256")
+        {
+            return Err(OidcError::InvalidDiscovery(
+                "provider does not support S256 PKCE".to_string(),
+            ));
+        }
+
+        *self.metadata.write().await = Some(metadata.clone());
+        Ok(metadata)
+    }
+
+    async fn fetch_jwks(&self, jwks_uri: &str) -> Result<JwkSet, OidcError> {
+        let jwks: JwkSet = self
+            .http
+            .get(jwks_uri)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        *self.jwks.write().await = Some(jwks.clone());
+        Ok(jwks)
+    }
+
+    /// Build the URL the browser is sent to in order to log in at the provider
+    pub async fn authorization_url(
+        &self,
+        state: &str,
+        nonce: &str,
+        code_challenge: &str,
+    ) -> Result<String, OidcError> {
+        let metadata = self.metadata().await?;
+        let mut url = url::Url::parse(&metadata.authorization_endpoint)
+            .map_err(|e| OidcError::InvalidDiscovery(e.to_string()))?;
+
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &self.config.client_id)
+            .append_pair("redirect_uri", &self.config.redirect_uri)
+            .append_pair("scope", &self.config.scopes.join(" "))
+            .append_pair("state", state)
+            .append_pair("nonce", nonce)
+            .append_pair("code_challenge", code_challenge)
+            .append_pair("code_challenge_method", "S256");
+
+        Ok(url.into())
+    }
+
+    /// Redeem an authorization code and return the validated ID token claims
+    pub async fn exchange_code(
+        &self,
+        code: &str,
+        code_verifier: &str,
+        nonce: &str,
+    ) -> Result<IdTokenClaims, OidcError> {
+        let metadata = self.metadata().await?;
+
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", self.config.redirect_uri.as_str()),
+            ("code_verifier", code_verifier),
+        ];
+        let mut request = self.http.post(&metadata.token_endpoint);
+        match &self.config.client_secret {
+            Some(secret) => {
+                request = request.basic_auth(&self.config.client_id, Some(secret));
+            }
+            None => form.push(("client_id", self.config.client_id.as_str())),
+        }
+
+        let response = request.form(&form).send().await?;
+        let status = response.status();
+        let body: TokenResponse = response.json().await.map_err(|_| {
+            OidcError::TokenExchange(format!("unexpected response with status {status}"))
+        })?;
+
+        if !status.is_success() || body.error.is_some() {
+            return Err(OidcError::TokenExchange(
+                body.error_description
+                    .or(body.error)
+                    .unwrap_or_else(|| status.to_string()),
+            ));
+        }
+
+        let id_token = body
+            .id_token
+            .ok_or_else(|| OidcError::TokenExchange("response has no id_token".to_string()))?;
+
+        self.validate_id_token(&id_token, &metadata, nonce).await
+    }
+
+    async fn validate_id_token(
+        &self,
+        id_token: &str,
+        metadata: &ProviderMetadata,
+        nonce: &str,
+    ) -> Result<IdTokenClaims, OidcError> {
+        let invalid = |e: jsonwebtoken::errors::Error| OidcError::InvalidIdToken(e.to_string());
+
+        let header = jsonwebtoken::decode_header(id_token).map_err(invalid)?;
+        // Symmetric algorithms would let anyone holding the client secret mint tokens
+        if matches!(
+            header.alg,
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+        ) {
+            return Err(OidcError::InvalidIdToken(format!(
+                "unsupported signing algorithm {:?}",
+                header.alg
+            )));
+        }
+
+        let cached = self.jwks.read().await.clone();
+        let jwks = match cached {
+            Some(jwks) => jwks,
+            None => self.fetch_jwks(&metadata.jwks_uri).await?,
+        };
+        let jwk = match header.kid.as_deref() {
+            Some(kid) => match jwks.find(kid) {
+                Some(jwk) => jwk.clone(),
+                // The provider may have rotated its keys since we last fetched them
+                None => self
+                    .fetch_jwks(&metadata.jwks_uri)
+                    .await?
+                    .find(kid)
+                    .cloned()
+                    .ok_or_else(|| {
+                        OidcError::InvalidIdToken(format!("unknown signing key '{kid}'"))
+                    })?,
+            },
+            None => match jwks.keys.as_slice() {
+                [jwk] => jwk.clone(),
+                _ => {
+                    return Err(OidcError::InvalidIdToken(
+                        "token has no key id and the provider has several keys".to_string(),
+                    ))
+                }
+            },
+        };
+
+        let key = DecodingKey::from_jwk(&jwk).map_err(invalid)?;
+        let mut validation = Validation::new(header.alg);
+        validation.leeway = CLOCK_SKEW_SECONDS;
+        validation.set_audience(&[&self.config.client_id]);
+        validation.set_issuer(&[&self.config.issuer]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+
+        let claims = jsonwebtoken::decode::<IdTokenClaims>(id_token, &key, &validation)
+            .map_err(invalid)?
+            .claims;
+
+        if claims.nonce.as_deref() != Some(nonce) {
+            return Err(OidcError::InvalidIdToken("nonce mismatch".to_string()));
+        }
+        if let Some(azp) = claims.extra.get("azp").and_then(Value::as_str) {
+            if azp != self.config.client_id {
+                return Err(OidcError::InvalidIdToken(format!(
+                    "token was issued to '{azp}'"
+                )));
+            }
+        }
+
+        Ok(claims)
+    }
+}
@@ -0,0 +1,392 @@
+use super::*;
+use crate::state::oidc::OidcLogins;
+use jsonwebtoken::{EncodingKey, Header};
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+const CLIENT_ID: &str = "server-ui";
+const KEY_ID: &str = "test-key";
+const GOOD_CODE: &str = "good-code";
+/// Modulus of `test_key.pem`, base64url encoded
+const KEY_MODULUS: &str = "1Z0LhaR2otY9LqA-vMGQy3Fk47Pr92O-0QQXKt9s7WgS1ZkFm8TnyDyU7CBM6zmILkFQx7E9XqNhuJmj21aZChN_TpyIBJw1BAbWNs-bXHrzcH0V3cy94XwrG-b2csuYWZsAhTerQZtifAIasfPbaUxnZQT-8DVW8Pmxxf87caxQGHVZ091qG4IEjVX2Ztrqpv52wTDdnxzRieHboN-nlWFBius7yoM6VhIBkaf0WckbcHK5blcl92YXjbt33_CSwYyjUtCtjPEXTY92ZDVjXEBWRt5OqLhSAbLUN7dLX8iLPxO6t_0ItoD3RAFLX3Zn95Jc8htjh81NEZNdf21kaQ";
+
+/// What the mock identity provider puts into (or how it mangles) the next ID token
+#[derive(Default)]
+struct MockState {
+    code_challenge: Option<String>,
+    nonce: Option<String>,
+    overrides: serde_json::Map<String, Value>,
+    discovery_issuer: Option<String>,
+}
+
+/// Minimal OpenID provider serving discovery, JWKS and a PKCE-checking token endpoint
+struct MockIdp {
+    issuer: String,
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockIdp {
+    async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let issuer = format!("http://{}", listener.local_addr().unwrap());
+        let state = Arc::new(Mutex::new(MockState::default()));
+
+        let (server_issuer, server_state) = (issuer.clone(), state.clone());
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let (issuer, state) = (server_issuer.clone(), server_state.clone());
+                tokio::spawn(async move { handle(stream, &issuer, &state).await });
+            }
+        });
+
+        Self { issuer, state }
+    }
+
+    fn config(&self) -> OidcConfig {
+        OidcConfig {
+            issuer: self.issuer.clone(),
+            client_id: CLIENT_ID.to_string(),
+            client_secret: None,
+            redirect_uri: "http://localhost:5173/login/callback".to_string(),
+            scopes: vec!["openid".to_string(), "profile".to_string()],
+            display_name: "Mock".to_string(),
+            role_claim: "groups".to_string(),
+            admin_groups: vec!["panel-admins".to_string()],
+            moderator_groups: vec!["panel-mods".to_string()],
+            default_role: None,
+        }
+    }
+
+    fn client(&self) -> OidcClient {
+        OidcClient::new(self.config()).unwrap()
+    }
+
+    fn set(&self, f: impl FnOnce(&mut MockState)) {
+        f(&mut self.state.lock().unwrap());
+    }
+}
+
+async fn handle(mut stream: TcpStream, issuer: &str, state: &Mutex<MockState>) {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let (head, body) = loop {
+        let n = stream.read(&mut chunk).await.unwrap();
+        buf.extend_from_slice(&chunk[..n]);
+        let text = String::from_utf8_lossy(&buf).to_string();
+        if let Some((head, body)) = text.split_once("\r\n\r\n") {
+            let length = head
+                .lines()
+                .find_map(|l| {
+                    let (name, value) = l.split_once(':')?;
+                    name.eq_ignore_ascii_case("content-length")
+                        .then(|| value.trim().parse::<usize>().ok())?
+                })
+                .unwrap_or(0);
+            if body.len() >= length || n == 0 {
+                break (head.to_string(), body.to_string());
+            }
+        }
+    };
+    let path = head.split_whitespace().nth(1).unwrap_or_default();
+
+    let (status, response) = match path {
+        "/.well-known/openid-configuration" => {
+            let discovery_issuer = state.lock().unwrap().discovery_issuer.clone();
+            (
+                "200 OK",
+                json!({
+                    "issuer": discovery_issuer.as_deref().unwrap_or(issuer),
+                    "authorization_endpoint": format!("{issuer}/authorize"),
+                    "token_endpoint": format!("{issuer}/token"),
+                    "jwks_uri": format!("{issuer}/jwks"),
+                    "code_challenge_methods_supported": ["S256"],
+                }),
+            )
+        }
+        "/jwks" => (
+            "200 OK",
+            json!({ "keys": [{
+                "kty": "RSA",
+                "kid": KEY_ID,
+                "use": "sig",
+                "alg": "RS256",
+                "n": KEY_MODULUS,
+                "e": "AQAB",
+            }]}),
+        ),
+        "/token" => token_response(issuer, state, &body),
+        _ => ("404 Not Found", json!({})),
+    };
+
+    let response = response.to_string();
+    let reply = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{response}",
+        response.len()
+    );
+    _ = stream.write_all(reply.as_bytes()).await;
+}
+
+fn token_response(issuer: &str, state: &Mutex<MockState>, body: &str) -> (&'static str, Value) {
+    let form: HashMap<String, String> = url::form_urlencoded::parse(body.as_bytes())
+        .into_owned()
+        .collect();
+    let state = state.lock().unwrap();
+
+    let verifier_matches = form
+        .get("code_verifier")
+        .is_some_and(|v| Some(pkce_challenge(v)) == state.code_challenge);
+    if form.get("grant_type").map(String::as_str) != Some("authorization_code")
+        || form.get("code").map(String::as_str) != Some(GOOD_CODE)
+        || form.get("client_id").map(String::as_str) != Some(CLIENT_ID)
+        || !verifier_matches
+    {
+        return (
+            "400 Bad Request",
+            json!({ "error": "invalid_grant", "error_description": "PKCE verification failed" }),
+        );
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    let mut claims = json!({
+        "iss": issuer,
+        "sub": "user-123",
+        "aud": CLIENT_ID,
+        "iat": now,
+        "exp": now + 300,
+        "nonce": state.nonce,
+        "preferred_username": "alice",
+        "groups": ["panel-admins"],
+    });
+    claims
+        .as_object_mut()
+        .unwrap()
+        .extend(state.overrides.clone());
+
+    let mut header = Header::new(Algorithm::RS256);
+    header.kid = Some(KEY_ID.to_string());
+    let key = EncodingKey::from_rsa_pem(include_bytes!("test_key.pem")).unwrap();
+    let id_token = jsonwebtoken::encode(&header, &claims, &key).unwrap();
+
+    (
+        "200 OK",
+        json!({ "access_token": "opaque", "token_type": "Bearer", "id_token": id_token }),
+    )
+}
+
+/// Run the authorization code flow against the mock provider with the given verifier
+async fn login_with_verifier(idp: &MockIdp, verifier: &str) -> Result<IdTokenClaims, OidcError> {
+    let client = idp.client();
+    let nonce = random_token();
+    let real_verifier = random_token();
+
+    client
+        .authorization_url(&random_token(), &nonce, &pkce_challenge(&real_verifier))
+        .await?;
+    idp.set(|s| {
+        s.code_challenge = Some(pkce_challenge(&real_verifier));
+        s.nonce.get_or_insert(nonce.clone());
+    });
+
+    let verifier = if verifier.is_empty() {
+        real_verifier.as_str()
+    } else {
+        verifier
+    };
+    client.exchange_code(GOOD_CODE, verifier, &nonce).await
+}
+
+async fn login(idp: &MockIdp) -> Result<IdTokenClaims, OidcError> {
+    login_with_verifier(idp, "").await
+}
+
+fn query_param(url: &str, name: &str) -> Option<String> {
+    url::Url::parse(url)
+        .unwrap()
+        .query_pairs()
+        .find(|(k, _)| k == name)
+        .map(|(_, v)| v.into_owned())
+}
+
+#[test]
+fn test_pkce_challenge_matches_rfc_7636_example() {
+    assert_eq!(
+        pkce_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+        "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+    );
+}
+
+#[tokio::test]
+async fn test_authorization_url_uses_discovered_endpoint_and_pkce() {
+    let idp = MockIdp::start().await;
+    let url = idp
+        .client()
+        .authorization_url("the-state", "the-nonce", "the-challenge")
+        .await
+        .unwrap();
+
+    assert!(url.starts_with(&format!("{}/authorize?", idp.issuer)));
+    assert_eq!(query_param(&url, "response_type").as_deref(), Some("code"));
+    assert_eq!(query_param(&url, "client_id").as_deref(), Some(CLIENT_ID));
+    assert_eq!(
+        query_param(&url, "scope").as_deref(),
+        Some("openid profile")
+    );
+    assert_eq!(query_param(&url, "state").as_deref(), Some("the-state"));
+    assert_eq!(query_param(&url, "nonce").as_deref(), Some("the-nonce"));
+    assert_eq!(
+        query_param(&url, "code_challenge").as_deref(),
+        Some("the-challenge")
+    );
+    assert_eq!(
+        query_param(&url, "code_challenge_method").as_deref(),
+        Some("S256")
+    );
+}
+
+#[tokio::test]
+async fn test_code_exchange_returns_validated_claims() {
+    let idp = MockIdp::start().await;
+    let claims = login(&idp).await.unwrap();
+
+    assert_eq!(claims.iss, idp.issuer);
+    assert_eq!(claims.sub, "user-123");
+    assert_eq!(claims.username(), "alice");
+    assert_eq!(idp.config().map_role(&claims), Some(UserRole::Admin));
+}
+
+#[tokio::test]
+async fn test_code_exchange_rejects_wrong_pkce_verifier() {
+    let idp = MockIdp::start().await;
+    let result = login_with_verifier(&idp, "not-the-verifier").await;
+
+    assert!(matches!(result, Err(OidcError::TokenExchange(_))));
+}
+
+#[tokio::test]
+async fn test_id_token_with_wrong_nonce_is_rejected() {
+    let idp = MockIdp::start().await;
+    idp.set(|s| s.nonce = Some("replayed-nonce".to_string()));
+
+    assert!(matches!(
+        login(&idp).await,
+        Err(OidcError::InvalidIdToken(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_id_token_for_another_client_is_rejected() {
+    let idp = MockIdp::start().await;
+    idp.set(|s| {
+        s.overrides.insert("aud".into(), json!("another-client"));
+    });
+
+    assert!(matches!(
+        login(&idp).await,
+        Err(OidcError::InvalidIdToken(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_expired_id_token_is_rejected() {
+    let idp = MockIdp::start().await;
+    let expired = chrono::Utc::now().timestamp() - 3600;
+    idp.set(|s| {
+        s.overrides.insert("exp".into(), json!(expired));
+    });
+
+    assert!(matches!(
+        login(&idp).await,
+        Err(OidcError::InvalidIdToken(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_id_token_from_another_issuer_is_rejected() {
+    let idp = MockIdp::start().await;
+    idp.set(|s| {
+        s.overrides
+            .insert("iss".into(), json!("https://evil.example.com"));
+    });
+
+    assert!(matches!(
+        login(&idp).await,
+        Err(OidcError::InvalidIdToken(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_discovery_with_mismatched_issuer_is_rejected() {
+    let idp = MockIdp::start().await;
+    idp.set(|s| s.discovery_issuer = Some("https://elsewhere.example.com".to_string()));
+
+    let result = idp.client().authorization_url("s", "n", "c").await;
+    assert!(matches!(result, Err(OidcError::InvalidDiscovery(_))));
+}
+
+#[tokio::test]
+async fn test_login_state_is_single_use() {
+    let idp = MockIdp::start().await;
+    let logins = OidcLogins::new(Some(idp.client()));
+
+    let url = logins.begin().await.unwrap();
+    let state = query_param(&url, "state").unwrap();
+    idp.set(|s| {
+        s.code_challenge = query_param(&url, "code_challenge");
+        s.nonce = query_param(&url, "nonce");
+    });
+
+    assert!(logins.complete(&state, GOOD_CODE).await.is_ok());
+    assert!(matches!(
+        logins.complete(&state, GOOD_CODE).await,
+        Err(OidcError::InvalidState)
+    ));
+}
+
+#[tokio::test]
+async fn test_disabled_sso_reports_disabled() {
+    let logins = OidcLogins::new(None);
+
+    assert!(matches!(logins.begin().await, Err(OidcError::Disabled)));
+}
+
+#[test]
+fn test_map_role_prefers_admin_and_falls_back_to_default() {
+    let claims = |groups: Value| IdTokenClaims {
+        iss: "https://idp.example.com".to_string(),
+        sub: "sub".to_string(),
+        nonce: None,
+        preferred_username: None,
+        email: None,
+        extra: HashMap::from([("groups".to_string(), groups)]),
+    };
+    let mut config = OidcConfig {
+        issuer: "https://idp.example.com".to_string(),
+        client_id: CLIENT_ID.to_string(),
+        client_secret: None,
+        redirect_uri: String::new(),
+        scopes: vec![],
+        display_name: String::new(),
+        role_claim: "groups".to_string(),
+        admin_groups: vec!["admins".to_string()],
+        moderator_groups: vec!["mods".to_string()],
+        default_role: None,
+    };
+
+    assert_eq!(
+        config.map_role(&claims(json!(["mods", "admins"]))),
+        Some(UserRole::Admin)
+    );
+    assert_eq!(
+        config.map_role(&claims(json!("mods"))),
+        Some(UserRole::Moderator)
+    );
+    assert_eq!(config.map_role(&claims(json!(["players"]))), None);
+
+    config.default_role = Some(UserRole::Moderator);
+    assert_eq!(
+        config.map_role(&claims(json!(["players"]))),
+        Some(UserRole::Moderator)
+    );
+}
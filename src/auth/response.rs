@@ -65,3 +65,20 @@ impl<'r> Responder<'r, 'static> for AuthResponse {
             .ok()
     }
 }
+
+/// Result of a password login: either a full session or a challenge for a second factor
+pub enum LoginResponse {
+    Authenticated(AuthResponse),
+    MfaChallenge(crate::dto::user::MfaChallenge),
+}
+
+impl<'r> Responder<'r, 'static> for LoginResponse {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'static> {
+        match self {
+            LoginResponse::Authenticated(auth) => auth.respond_to(request),
+            LoginResponse::MfaChallenge(challenge) => {
+                rocket::serde::json::Json(challenge).respond_to(request)
+            }
+        }
+    }
+}
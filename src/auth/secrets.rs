@@ -0,0 +1,119 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chacha20poly1305::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    XChaCha20Poly1305, XNonce,
+};
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+
+#[cfg(test)]
+mod tests;
+
+/// Environment variable holding a base64 encoded 32-byte key. When unset, a key file is
+/// generated in the data directory on first start.
+const KEY_ENV: &str = "SECRETS_KEY";
+const KEY_FILE: &str = "secrets.key";
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 24;
+
+#[derive(Error, Debug)]
+pub enum SecretsError {
+    #[error("The secrets key must be {KEY_LEN} bytes of base64")]
+    InvalidKey,
+
+    #[error("Failed to read or create the secrets key file {0}: {1}")]
+    KeyFile(PathBuf, std::io::Error),
+
+    #[error("Failed to encrypt secret")]
+    Encrypt,
+
+    #[error("Failed to decrypt secret. Was the secrets key changed?")]
+    Decrypt,
+}
+
+/// Symmetric encryption for secrets stored in the database, such as Steam passwords.
+/// Values are stored as base64 of `nonce || ciphertext`.
+#[derive(Clone)]
+pub struct SecretBox {
+    cipher: XChaCha20Poly1305,
+}
+
+impl SecretBox {
+    pub fn new(key: &[u8]) -> Result<Self, SecretsError> {
+        let cipher =
+            XChaCha20Poly1305::new_from_slice(key).map_err(|_| SecretsError::InvalidKey)?;
+        Ok(Self { cipher })
+    }
+
+    /// Load the key from `SECRETS_KEY`, falling back to (and creating) `<data_dir>/secrets.key`
+    pub fn load_or_create(data_dir: &Path) -> Result<Self, SecretsError> {
+        if let Ok(encoded) = std::env::var(KEY_ENV) {
+            let key = STANDARD
+                .decode(encoded.trim())
+                .map_err(|_| SecretsError::InvalidKey)?;
+            return Self::new(&key);
+        }
+
+        Self::from_key_file(data_dir.join(KEY_FILE))
+    }
+
+    /// Load the key from `path`, generating it first when the file does not exist yet
+    fn from_key_file(path: PathBuf) -> Result<Self, SecretsError> {
+        match std::fs::read(&path) {
+            Ok(key) => Self::new(&key),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                let key = XChaCha20Poly1305::generate_key(&mut OsRng);
+                write_key_file(&path, &key).map_err(|e| SecretsError::KeyFile(path, e))?;
+                Self::new(&key)
+            }
+            Err(e) => Err(SecretsError::KeyFile(path, e)),
+        }
+    }
+
+    pub fn encrypt(&self, plaintext: &str) -> Result<String, SecretsError> {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|_| SecretsError::Encrypt)?;
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+        Ok(STANDARD.encode(sealed))
+    }
+
+    pub fn decrypt(&self, sealed: &str) -> Result<String, SecretsError> {
+        let sealed = STANDARD.decode(sealed).map_err(|_| SecretsError::Decrypt)?;
+        if sealed.len() < NONCE_LEN {
+            return Err(SecretsError::Decrypt);
+        }
+
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let plaintext = self
+            .cipher
+            .decrypt(XNonce::from_slice(nonce), ciphertext)
+            .map_err(|_| SecretsError::Decrypt)?;
+        String::from_utf8(plaintext).map_err(|_| SecretsError::Decrypt)
+    }
+}
+
+fn write_key_file(path: &Path, key: &[u8]) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = options.open(path)?;
+    file.write_all(key)?;
+    file.sync_all()
+}
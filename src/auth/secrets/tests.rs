@@ -0,0 +1,88 @@
+use super::*;
+use crate::utils::TempDir;
+
+#[test]
+fn test_encrypt_round_trip() {
+    let secrets = SecretBox::new(&[1; KEY_LEN]).unwrap();
+
+    let sealed = secrets.encrypt("hunter2").unwrap();
+    assert_ne!(sealed, "hunter2");
+    assert_eq!(secrets.decrypt(&sealed).unwrap(), "hunter2");
+    // A fresh nonce every time
+    assert_ne!(secrets.encrypt("hunter2").unwrap(), sealed);
+}
+
+#[test]
+fn test_decrypt_rejects_tampered_ciphertext() {
+    let secrets = SecretBox::new(&[1; KEY_LEN]).unwrap();
+    let mut sealed = STANDARD
+        .decode(secrets.encrypt("hunter2").unwrap())
+        .unwrap();
+    *sealed.last_mut().unwrap() ^= 1;
+
+    assert!(matches!(
+        secrets.decrypt(&STANDARD.encode(&sealed)),
+        Err(SecretsError::Decrypt)
+    ));
+    assert!(matches!(
+        secrets.decrypt(&STANDARD.encode(&sealed[..NONCE_LEN - 1])),
+        Err(SecretsError::Decrypt)
+    ));
+    assert!(matches!(
+        secrets.decrypt("not base64!"),
+        Err(SecretsError::Decrypt)
+    ));
+}
+
+#[test]
+fn test_decrypt_rejects_another_key() {
+    let sealed = SecretBox::new(&[1; KEY_LEN])
+        .unwrap()
+        .encrypt("hunter2")
+        .unwrap();
+    let other = SecretBox::new(&[2; KEY_LEN]).unwrap();
+
+    assert!(matches!(other.decrypt(&sealed), Err(SecretsError::Decrypt)));
+}
+
+#[test]
+fn test_key_must_be_32_bytes() {
+    assert!(matches!(
+        SecretBox::new(&[1; 16]),
+        Err(SecretsError::InvalidKey)
+    ));
+}
+
+#[test]
+fn test_key_file_is_created_private_and_reused() {
+    let dir = TempDir::new();
+    let path = dir.0.join("nested").join(KEY_FILE);
+
+    let sealed = SecretBox::from_key_file(path.clone())
+        .unwrap()
+        .encrypt("hunter2")
+        .unwrap();
+
+    assert_eq!(std::fs::read(&path).unwrap().len(), KEY_LEN);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    let reloaded = SecretBox::from_key_file(path).unwrap();
+    assert_eq!(reloaded.decrypt(&sealed).unwrap(), "hunter2");
+}
+
+#[test]
+fn test_key_file_of_the_wrong_length_is_rejected() {
+    let dir = TempDir::new();
+    let path = dir.0.join(KEY_FILE);
+    std::fs::write(&path, b"too short").unwrap();
+
+    assert!(matches!(
+        SecretBox::from_key_file(path),
+        Err(SecretsError::InvalidKey)
+    ));
+}
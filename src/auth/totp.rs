@@ -0,0 +1,71 @@
+use rand::{distributions::Alphanumeric, thread_rng, Rng};
+use sha2::{Digest, Sha256};
+use totp_rs::{Builder, Secret, Totp, TotpError};
+
+/// Issuer shown in authenticator apps next to the account name
+const ISSUER: &str = "ServerUI";
+
+/// Number of recovery codes generated whenever a user (re)enrols
+pub const RECOVERY_CODE_COUNT: usize = 10;
+
+/// Generate a new random TOTP secret, encoded as base32
+pub fn generate_secret() -> String {
+    Secret::generate().to_base32()
+}
+
+fn build(secret: &str, account_name: &str) -> Result<Totp, TotpError> {
+    let secret = Secret::try_from_base32(secret).map_err(|_| TotpError::SecretNotSet)?;
+
+    Builder::new()
+        .with_secret(secret)
+        .with_issuer(Some(ISSUER))
+        .with_account_name(account_name.replace(':', "_"))
+        .build()
+}
+
+/// Build the `otpauth://` URI used to enrol the secret in an authenticator app
+pub fn otpauth_uri(secret: &str, account_name: &str) -> Result<String, TotpError> {
+    build(secret, account_name)?.to_url()
+}
+
+/// Check a TOTP code against the secret.
+///
+/// Returns the matched time step when the code is valid and was issued after `last_step`,
+/// so a code can never be accepted twice.
+pub fn verify(secret: &str, code: &str, last_step: Option<i64>) -> Option<i64> {
+    let totp = build(secret, "").ok()?;
+    let step = totp.check_current(code.trim()).map(|s| s as i64)?;
+
+    match last_step {
+        Some(last) if step <= last => None,
+        _ => Some(step),
+    }
+}
+
+/// Generate a fresh set of human-friendly recovery codes (e.g. `k3j9d-x8a2q`)
+pub fn generate_recovery_codes() -> Vec<String> {
+    let mut rng = thread_rng();
+
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let raw: String = (&mut rng)
+                .sample_iter(&Alphanumeric)
+                .take(10)
+                .map(|c| (c as char).to_ascii_lowercase())
+                .collect();
+            format!("{}-{}", &raw[..5], &raw[5..])
+        })
+        .collect()
+}
+
+/// Hash a recovery code for storage. Codes are random and high-entropy, so a fast hash is
+/// sufficient and lets codes be looked up directly.
+pub fn hash_recovery_code(code: &str) -> String {
+    let normalized: String = code
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+
+    format!("{:x}", Sha256::digest(normalized.as_bytes()))
+}
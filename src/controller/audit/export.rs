@@ -0,0 +1,39 @@
+use crate::{auth::guards::AdminGuard, controller, dto, service};
+use rocket::{
+    get,
+    http::{ContentType, Header},
+    response::{self, Responder, Response},
+    Request,
+};
+use std::io::Cursor;
+
+/// CSV body served as a file download
+pub struct CsvExport(String);
+
+impl<'r> Responder<'r, 'static> for CsvExport {
+    fn respond_to(self, _: &'r Request<'_>) -> response::Result<'static> {
+        let filename = format!(
+            "audit_log_{}.csv",
+            chrono::Utc::now().format("%Y%m%dT%H%M%SZ")
+        );
+
+        Response::build()
+            .header(ContentType::CSV)
+            .header(Header::new(
+                "Content-Disposition",
+                format!("attachment; filename=\"{filename}\""),
+            ))
+            .sized_body(self.0.len(), Cursor::new(self.0))
+            .ok()
+    }
+}
+
+#[get("/export.csv?<query..>")]
+pub async fn export_csv(
+    _admin: AdminGuard,
+    query: dto::audit::AuditLogQuery,
+    audit_service: service::audit::Audit,
+) -> Result<CsvExport, controller::Error> {
+    let csv = audit_service.export_csv(&query).await?;
+    Ok(CsvExport(csv))
+}
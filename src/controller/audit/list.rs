@@ -0,0 +1,12 @@
+use crate::{auth::guards::AdminGuard, controller, dto, service};
+use rocket::{get, serde::json::Json};
+
+#[get("/?<query..>")]
+pub async fn list(
+    _admin: AdminGuard,
+    query: dto::audit::AuditLogQuery,
+    audit_service: service::audit::Audit,
+) -> Result<Json<dto::audit::AuditLogPage>, controller::Error> {
+    let page = audit_service.query(&query).await?;
+    Ok(Json(page))
+}
@@ -0,0 +1,10 @@
+mod export;
+mod list;
+
+use rocket::{routes, Route};
+
+const BASE_PATH: &str = "/api/audit";
+
+pub fn get_all_routes() -> Vec<(&'static str, Vec<Route>)> {
+    vec![(BASE_PATH, routes![list::list, export::export_csv])]
+}
@@ -0,0 +1,116 @@
+use crate::{
+    dto::{
+        console::{ConsoleInput, ConsoleMessage},
+        steamcmd::SteamCmdEvent,
+    },
+    state::{
+        console::{ConsoleFollower, Followed},
+        vt,
+    },
+};
+use rocket::{
+    futures::{SinkExt, StreamExt},
+    Shutdown,
+};
+use rocket_ws::{Channel, Message, WebSocket};
+use tokio::sync::broadcast::{self, error::RecvError};
+
+/// Where a console socket sends the commands its client types
+#[rocket::async_trait]
+pub trait ConsoleInputHandler: Send + Sync {
+    async fn send(&self, command: &str) -> Result<(), String>;
+}
+
+/// How a console socket is wired up
+pub struct ConsoleSocket {
+    pub follower: ConsoleFollower,
+    /// Typed SteamCMD events, sent between the lines
+    pub events: Option<broadcast::Receiver<SteamCmdEvent>>,
+    /// `None` for clients that may only watch
+    pub input: Option<Box<dyn ConsoleInputHandler>>,
+    /// Attach coloured spans to every line
+    pub spans: bool,
+}
+
+fn line_message(followed: Followed, spans: bool) -> ConsoleMessage {
+    match followed {
+        Followed::Line(line) => ConsoleMessage::Line {
+            seq: line.seq,
+            spans: spans.then(|| vt::spans(&line.text)),
+            text: vt::plain(&line.text),
+        },
+        Followed::Resync { missed } => ConsoleMessage::Resync { missed },
+        Followed::Reset => ConsoleMessage::Reset,
+    }
+}
+
+async fn next_event(
+    events: &mut Option<broadcast::Receiver<SteamCmdEvent>>,
+) -> Result<SteamCmdEvent, RecvError> {
+    match events {
+        Some(events) => events.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+async fn handle_input(input: Option<&dyn ConsoleInputHandler>, text: &str) -> Result<(), String> {
+    let input = input.ok_or("This console is read-only for you")?;
+    let ConsoleInput { command } = serde_json::from_str(text)
+        .map_err(|_| "Expected a message of the form {\"command\": \"...\"}".to_string())?;
+
+    input.send(&command).await
+}
+
+/// Stream a console to a WebSocket as JSON [`ConsoleMessage`]s and pass on the commands the
+/// client sends as [`ConsoleInput`]. Falling behind the output resyncs from the history
+/// instead of dropping the connection.
+pub fn socket(ws: WebSocket, console: ConsoleSocket, mut shutdown: Shutdown) -> Channel<'static> {
+    let ConsoleSocket {
+        mut follower,
+        mut events,
+        input,
+        spans,
+    } = console;
+
+    ws.channel(move |mut stream| {
+        Box::pin(async move {
+            loop {
+                let message = tokio::select! {
+                    _ = &mut shutdown => break,
+                    followed = follower.recv() => match followed {
+                        Some(followed) => line_message(followed, spans),
+                        None => break,
+                    },
+                    event = next_event(&mut events) => match event {
+                        Ok(event) => ConsoleMessage::Event { event },
+                        // Lagging behind only loses events; the lines still have everything
+                        Err(RecvError::Lagged(_)) => continue,
+                        Err(RecvError::Closed) => {
+                            events = None;
+                            continue;
+                        }
+                    },
+                    incoming = stream.next() => match incoming {
+                        Some(Ok(Message::Text(text))) => {
+                            match handle_input(input.as_deref(), &text).await {
+                                Ok(()) => continue,
+                                Err(message) => ConsoleMessage::Error { message },
+                            }
+                        }
+                        Some(Ok(Message::Close(_))) | None => break,
+                        // Pings are answered by the library
+                        Some(Ok(_)) => continue,
+                        Some(Err(e)) => return Err(e),
+                    },
+                };
+
+                let Ok(json) = serde_json::to_string(&message) else {
+                    continue;
+                };
+                stream.send(Message::Text(json)).await?;
+            }
+
+            Ok(())
+        })
+    })
+}
@@ -1,5 +1,7 @@
 use crate::controller;
+use crate::models::audit::AuditAction;
 use crate::schema::server_config::ServerConfig;
+use crate::service::audit::AuditEvent;
 use crate::{auth::guards::AccessTokenGuard, service};
 use rocket::response::status::Created;
 use rocket::{post, serde::json::Json};
@@ -9,12 +11,25 @@ pub async fn create(
     _auth_user: AccessTokenGuard,
     game_schema_service: service::game_schema::GameSchema,
     data: Json<ServerConfig>,
+    audit: service::audit::Audit,
 ) -> Result<Created<Json<crate::dto::game_schema::SchemaMetadata>>, controller::Error> {
     let new_schema = data.into_inner();
     let schema_id = game_schema_service.insert_schema(new_schema).await?;
 
     let metadata = game_schema_service.get_metadata_by_id(schema_id).await?;
 
+    audit
+        .record(
+            AuditEvent::new(AuditAction::SchemaCreate)
+                .target("game_schema", schema_id)
+                .details(serde_json::json!({
+                    "name": metadata.name,
+                    "schemaVersion": metadata.schema_version,
+                    "steamAppId": metadata.steam_app_id,
+                })),
+        )
+        .await;
+
     let created = Created::new(format!("/api/game_schema/metadata/{}", schema_id));
 
     Ok(created.body(Json(metadata)))
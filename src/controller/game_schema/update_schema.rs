@@ -1,4 +1,9 @@
-use crate::{auth::guards::AccessTokenGuard, schema, service};
+use crate::{
+    auth::guards::AccessTokenGuard,
+    models::audit::AuditAction,
+    schema,
+    service::{self, audit::AuditEvent},
+};
 use rocket::{put, serde::json::Json};
 
 #[put("/update/<id>", data = "<data>")]
@@ -6,9 +11,17 @@ pub async fn update_schema(
     id: i32,
     data: Json<schema::server_config::ServerConfig>,
     schema_service: service::game_schema::GameSchema,
+    audit: service::audit::Audit,
     _auth_guard: AccessTokenGuard,
 ) -> Result<(), crate::controller::Error> {
+    let before = schema_service.get_schema_json_by_id(id).await?;
+    let event = AuditEvent::new(AuditAction::SchemaUpdate)
+        .target("game_schema", id)
+        .diff(&before, &data.0);
+
     schema_service.update_schema(id, data.0).await?;
 
+    audit.record(event).await;
+
     Ok(())
 }
@@ -1,14 +1,18 @@
+use crate::dto::steamcmd::SteamCmdStatus;
 use crate::service::user::{User, UserError};
+use crate::state::steamcmd::SteamCMD;
 use rocket::serde::json::Json;
-use rocket::{get, routes, Route};
-use serde::{Deserialize, Serialize};
+use rocket::{get, routes, Route, State};
+use serde::Serialize;
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize)]
 #[serde(crate = "rocket::serde")]
 pub struct HealthResponse {
     pub status: String,
     pub message: String,
     pub errors: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub steamcmd: Option<SteamCmdStatus>,
 }
 
 #[get("/health")]
@@ -17,25 +21,42 @@ fn health() -> Json<HealthResponse> {
         status: "healthy".to_string(),
         message: "Server is running!".to_string(),
         errors: vec![],
+        steamcmd: None,
     })
 }
 
 #[get("/health_detailed")]
-fn health_detailed(user_service: Result<User, UserError>) -> Json<HealthResponse> {
+fn health_detailed(
+    user_service: Result<User, UserError>,
+    steamcmd: &State<SteamCMD>,
+) -> Json<HealthResponse> {
     let mut errors = Vec::<String>::new();
 
     if let Err(e) = user_service {
         errors.push(e.to_string());
     }
 
+    let steamcmd = steamcmd.status();
+    if steamcmd.installed && !steamcmd.running {
+        errors.push("steamcmd is not running".to_string());
+    }
+
     Json(HealthResponse {
-        status: if errors.is_empty() {
+        status: if !errors.is_empty() {
+            "error".to_string()
+        } else if !steamcmd.installed {
+            "degraded".to_string()
+        } else {
             "healthy".to_string()
+        },
+        message: if steamcmd.installed {
+            "Server is running!".to_string()
         } else {
-            "error".to_string()
+            "Server is running without SteamCMD. An admin can install it from the panel."
+                .to_string()
         },
-        message: "Server is running!".to_string(),
         errors,
+        steamcmd: Some(steamcmd),
     })
 }
 
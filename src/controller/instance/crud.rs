@@ -0,0 +1,119 @@
+use crate::{
+    auth::guards::{AccessTokenGuard, AdminGuard, ModeratorGuard},
+    controller, dto,
+    models::audit::AuditAction,
+    service::{self, audit::AuditEvent},
+    state::{instance::InstanceManager, metrics::InstanceMetrics, server_query::ServerQueries},
+};
+use rocket::{delete, get, post, put, response::status::Created, serde::json::Json, State};
+
+#[get("/list")]
+pub async fn list(
+    _auth_guard: AccessTokenGuard,
+    instance_service: service::instance::Instance,
+) -> Result<Json<Vec<dto::instance::Instance>>, controller::Error> {
+    Ok(Json(instance_service.list().await?))
+}
+
+#[get("/<id>")]
+pub async fn by_id(
+    id: i32,
+    _auth_guard: AccessTokenGuard,
+    instance_service: service::instance::Instance,
+) -> Result<Json<dto::instance::Instance>, controller::Error> {
+    Ok(Json(instance_service.get(id).await?))
+}
+
+#[post("/create", data = "<data>")]
+pub async fn create(
+    _admin: AdminGuard,
+    data: Json<dto::instance::NewInstance>,
+    instance_service: service::instance::Instance,
+    audit: service::audit::Audit,
+) -> Result<Created<Json<dto::instance::Instance>>, controller::Error> {
+    let instance = instance_service.create(data.into_inner()).await?;
+
+    audit
+        .record(
+            AuditEvent::new(AuditAction::InstanceCreate)
+                .target("instance", instance.id)
+                .details(serde_json::json!({
+                    "instanceName": instance.instance_name,
+                    "schemaId": instance.schema_id,
+                })),
+        )
+        .await;
+
+    Ok(Created::new(format!("/api/instance/{}", instance.id)).body(Json(instance)))
+}
+
+#[put("/<id>", data = "<data>")]
+pub async fn update(
+    id: i32,
+    _moderator: ModeratorGuard,
+    data: Json<dto::instance::InstanceUpdate>,
+    instance_service: service::instance::Instance,
+    audit: service::audit::Audit,
+) -> Result<Json<dto::instance::Instance>, controller::Error> {
+    let before = instance_service.find_redacted(id).await?;
+    let instance = instance_service.update(id, data.into_inner()).await?;
+    let after = instance_service.find_redacted(id).await?;
+
+    audit
+        .record(
+            AuditEvent::new(AuditAction::InstanceUpdate)
+                .target("instance", id)
+                .diff(&before, &after),
+        )
+        .await;
+
+    Ok(Json(instance))
+}
+
+/// Stop the server if it is running and remove the instance
+#[delete("/<id>")]
+pub async fn delete(
+    id: i32,
+    _admin: AdminGuard,
+    manager: &State<InstanceManager>,
+    queries: &State<ServerQueries>,
+    metrics: &State<InstanceMetrics>,
+    instance_service: service::instance::Instance,
+    audit: service::audit::Audit,
+) -> Result<(), controller::Error> {
+    if manager.is_running(id).await {
+        instance_service.stop(id, manager).await?;
+    }
+    instance_service.delete(id).await?;
+    manager.forget(id).await;
+    queries.forget(id);
+    metrics.forget(id);
+
+    audit
+        .record(AuditEvent::new(AuditAction::InstanceDelete).target("instance", id))
+        .await;
+
+    Ok(())
+}
+
+/// Preview the command line the instance would be started with
+#[get("/<id>/command")]
+pub async fn command(
+    id: i32,
+    _auth_guard: AccessTokenGuard,
+    instance_service: service::instance::Instance,
+) -> Result<Json<dto::instance::InstanceCommand>, controller::Error> {
+    Ok(Json(dto::instance::InstanceCommand {
+        command: instance_service.command_preview(id).await?,
+    }))
+}
+
+/// The ports the instance's server binds, as its config sets them
+#[get("/<id>/ports")]
+pub async fn ports(
+    id: i32,
+    _auth_guard: AccessTokenGuard,
+    instance_service: service::instance::Instance,
+) -> Result<Json<Vec<dto::instance::InstancePort>>, controller::Error> {
+    Ok(Json(instance_service.ports(id).await?))
+}
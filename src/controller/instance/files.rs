@@ -0,0 +1,232 @@
+use crate::{
+    auth::guards::{AdminGuard, ModeratorGuard},
+    controller, dto,
+    models::audit::AuditAction,
+    service::{
+        self,
+        audit::AuditEvent,
+        files::{FileLimits, Files, FilesError, InstanceLayout},
+    },
+};
+use rocket::{
+    delete, get,
+    http::Header,
+    post, put,
+    response::{self, Responder},
+    serde::json::Json,
+    Data, Request, State,
+};
+
+/// A file served as a download under its own name
+pub struct Download {
+    file: rocket::fs::NamedFile,
+    name: String,
+}
+
+impl<'r> Responder<'r, 'static> for Download {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'static> {
+        let name = self.name.replace(['"', '\\'], "_");
+        let mut response = self.file.respond_to(req)?;
+        response.set_header(Header::new(
+            "Content-Disposition",
+            format!("attachment; filename=\"{name}\""),
+        ));
+        Ok(response)
+    }
+}
+
+/// The file API of an existing instance
+async fn files(
+    id: i32,
+    instance_service: &service::instance::Instance,
+    limits: &FileLimits,
+) -> Result<Files, controller::Error> {
+    instance_service.find_by_id(id).await?;
+    Ok(Files::new(InstanceLayout::new(id), *limits))
+}
+
+/// List a directory of the instance, the root with its layout directories by default
+#[get("/<id>/files?<path>")]
+pub async fn list(
+    id: i32,
+    path: Option<&str>,
+    _moderator: ModeratorGuard,
+    limits: &State<FileLimits>,
+    instance_service: service::instance::Instance,
+) -> Result<Json<dto::files::FileListing>, controller::Error> {
+    let files = files(id, &instance_service, limits).await?;
+    Ok(Json(files.list(path.unwrap_or_default()).await?))
+}
+
+/// Open a text file for editing
+#[get("/<id>/files/content?<path>")]
+pub async fn read(
+    id: i32,
+    path: &str,
+    _moderator: ModeratorGuard,
+    limits: &State<FileLimits>,
+    instance_service: service::instance::Instance,
+) -> Result<Json<dto::files::FileContent>, controller::Error> {
+    let files = files(id, &instance_service, limits).await?;
+    Ok(Json(files.read(path).await?))
+}
+
+/// Replace or create a text file with the request body
+#[put("/<id>/files/content?<path>", data = "<content>")]
+pub async fn write(
+    id: i32,
+    path: &str,
+    content: Data<'_>,
+    _admin: AdminGuard,
+    limits: &State<FileLimits>,
+    instance_service: service::instance::Instance,
+    audit: service::audit::Audit,
+) -> Result<Json<dto::files::FileEntry>, controller::Error> {
+    let files = files(id, &instance_service, limits).await?;
+    let content = content
+        .open(files.edit_limit())
+        .into_string()
+        .await
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::InvalidData => FilesError::NotText(path.to_string()),
+            _ => e.into(),
+        })?;
+    if !content.is_complete() {
+        return Err(FilesError::TooLargeToEdit(limits.edit_kib).into());
+    }
+    let entry = files.write(path, &content).await?;
+
+    audit
+        .record(
+            AuditEvent::new(AuditAction::InstanceFileWrite)
+                .target("instance", id)
+                .details(serde_json::json!({ "path": entry.path, "size": entry.size })),
+        )
+        .await;
+
+    Ok(Json(entry))
+}
+
+/// Store the request body as a file, refusing to replace one unless `overwrite` is set
+#[post("/<id>/files/upload?<path>&<overwrite>", data = "<data>")]
+#[allow(clippy::too_many_arguments)]
+pub async fn upload(
+    id: i32,
+    path: &str,
+    overwrite: Option<bool>,
+    data: Data<'_>,
+    _admin: AdminGuard,
+    limits: &State<FileLimits>,
+    instance_service: service::instance::Instance,
+    audit: service::audit::Audit,
+) -> Result<Json<dto::files::FileEntry>, controller::Error> {
+    let files = files(id, &instance_service, limits).await?;
+    let entry = files
+        .upload(path, data, overwrite.unwrap_or_default())
+        .await?;
+
+    audit
+        .record(
+            AuditEvent::new(AuditAction::InstanceFileUpload)
+                .target("instance", id)
+                .details(serde_json::json!({ "path": entry.path, "size": entry.size })),
+        )
+        .await;
+
+    Ok(Json(entry))
+}
+
+#[get("/<id>/files/download?<path>")]
+pub async fn download(
+    id: i32,
+    path: &str,
+    _moderator: ModeratorGuard,
+    limits: &State<FileLimits>,
+    instance_service: service::instance::Instance,
+) -> Result<Download, controller::Error> {
+    let files = files(id, &instance_service, limits).await?;
+    let file = files.download(path).await?;
+    let name = file
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    Ok(Download {
+        file: rocket::fs::NamedFile::open(&file)
+            .await
+            .map_err(FilesError::from)?,
+        name,
+    })
+}
+
+#[post("/<id>/files/directory?<path>")]
+pub async fn create_dir(
+    id: i32,
+    path: &str,
+    _admin: AdminGuard,
+    limits: &State<FileLimits>,
+    instance_service: service::instance::Instance,
+    audit: service::audit::Audit,
+) -> Result<Json<dto::files::FileEntry>, controller::Error> {
+    let files = files(id, &instance_service, limits).await?;
+    let entry = files.create_dir(path).await?;
+
+    audit
+        .record(
+            AuditEvent::new(AuditAction::InstanceFileWrite)
+                .target("instance", id)
+                .details(serde_json::json!({ "path": entry.path, "directory": true })),
+        )
+        .await;
+
+    Ok(Json(entry))
+}
+
+#[post("/<id>/files/rename", data = "<data>")]
+pub async fn rename(
+    id: i32,
+    data: Json<dto::files::FileRename>,
+    _admin: AdminGuard,
+    limits: &State<FileLimits>,
+    instance_service: service::instance::Instance,
+    audit: service::audit::Audit,
+) -> Result<Json<dto::files::FileEntry>, controller::Error> {
+    let files = files(id, &instance_service, limits).await?;
+    let entry = files.rename(&data.from, &data.to).await?;
+
+    audit
+        .record(
+            AuditEvent::new(AuditAction::InstanceFileRename)
+                .target("instance", id)
+                .details(serde_json::json!({ "from": data.from, "to": entry.path })),
+        )
+        .await;
+
+    Ok(Json(entry))
+}
+
+/// Delete a file or an empty directory; `recursive` also deletes directories with content
+#[delete("/<id>/files?<path>&<recursive>")]
+pub async fn delete(
+    id: i32,
+    path: &str,
+    recursive: Option<bool>,
+    _admin: AdminGuard,
+    limits: &State<FileLimits>,
+    instance_service: service::instance::Instance,
+    audit: service::audit::Audit,
+) -> Result<(), controller::Error> {
+    let files = files(id, &instance_service, limits).await?;
+    let recursive = recursive.unwrap_or_default();
+    files.delete(path, recursive).await?;
+
+    audit
+        .record(
+            AuditEvent::new(AuditAction::InstanceFileDelete)
+                .target("instance", id)
+                .details(serde_json::json!({ "path": path, "recursive": recursive })),
+        )
+        .await;
+
+    Ok(())
+}
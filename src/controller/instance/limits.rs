@@ -0,0 +1,48 @@
+use crate::{
+    auth::guards::{AccessTokenGuard, AdminGuard},
+    controller, dto,
+    models::audit::AuditAction,
+    service::{self, audit::AuditEvent},
+    state::instance::InstanceManager,
+};
+use rocket::{get, put, serde::json::Json, State};
+
+/// The configured limits and, while the server runs, how each of them is enforced
+#[get("/<id>/limits")]
+pub async fn limits(
+    id: i32,
+    _auth_guard: AccessTokenGuard,
+    manager: &State<InstanceManager>,
+    instance_service: service::instance::Instance,
+) -> Result<Json<dto::instance::InstanceLimits>, controller::Error> {
+    let instance = instance_service.find_by_id(id).await?;
+    let process = manager.process(id).await.filter(|p| p.is_running());
+
+    Ok(Json(dto::instance::InstanceLimits {
+        limits: (&instance).into(),
+        applied: process.map(|p| p.limits()),
+    }))
+}
+
+/// Replace the instance's limits. A running server keeps its current ones until restarted.
+#[put("/<id>/limits", data = "<data>")]
+pub async fn update(
+    id: i32,
+    _admin: AdminGuard,
+    data: Json<dto::instance::ResourceLimits>,
+    instance_service: service::instance::Instance,
+    audit: service::audit::Audit,
+) -> Result<Json<dto::instance::ResourceLimits>, controller::Error> {
+    let before: dto::instance::ResourceLimits = (&instance_service.find_by_id(id).await?).into();
+    let limits = instance_service.set_limits(id, data.into_inner()).await?;
+
+    audit
+        .record(
+            AuditEvent::new(AuditAction::InstanceLimitsUpdate)
+                .target("instance", id)
+                .diff(&before, &limits),
+        )
+        .await;
+
+    Ok(Json(limits))
+}
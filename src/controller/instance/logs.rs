@@ -0,0 +1,19 @@
+use crate::{
+    auth::guards::AccessTokenGuard,
+    controller, dto, service,
+    state::console_log::{ConsoleLogs, LogStream},
+};
+use rocket::{get, serde::json::Json, State};
+
+/// Page through or search the server's console output, including past runs and days
+#[get("/<id>/logs?<query..>")]
+pub async fn logs(
+    id: i32,
+    _auth_guard: AccessTokenGuard,
+    query: dto::console_log::ConsoleLogQuery,
+    logs: &State<ConsoleLogs>,
+    instance_service: service::instance::Instance,
+) -> Result<Json<dto::console_log::ConsoleLogPage>, controller::Error> {
+    instance_service.find_by_id(id).await?;
+    Ok(Json(logs.query(LogStream::Instance(id), query).await?))
+}
@@ -0,0 +1,73 @@
+use crate::{
+    auth::guards::AccessTokenGuard,
+    controller, dto,
+    state::{instance::InstanceManager, metrics::InstanceMetrics},
+};
+use rocket::{
+    get,
+    response::stream::{Event, EventStream},
+    serde::json::Json,
+    Shutdown, State,
+};
+use tokio::sync::broadcast::error::RecvError;
+
+/// The latest resource usage of the instance's server, `null` while it is not running
+#[get("/<id>/metrics")]
+pub async fn current(
+    id: i32,
+    _auth_guard: AccessTokenGuard,
+    manager: &State<InstanceManager>,
+    metrics: &State<InstanceMetrics>,
+    instance_service: crate::service::instance::Instance,
+) -> Result<Json<Option<dto::metrics::MetricsSample>>, controller::Error> {
+    instance_service.find_by_id(id).await?;
+    let running = manager.is_running(id).await;
+
+    Ok(Json(metrics.get(id).filter(|_| running)))
+}
+
+#[get("/<id>/metrics/history?<query..>")]
+pub async fn history(
+    id: i32,
+    query: dto::metrics::MetricsHistoryQuery,
+    _auth_guard: AccessTokenGuard,
+    metrics: &State<InstanceMetrics>,
+    instance_service: crate::service::instance::Instance,
+) -> Result<Json<dto::metrics::MetricsHistory>, controller::Error> {
+    instance_service.find_by_id(id).await?;
+
+    Ok(Json(metrics.history(id, &query).await?))
+}
+
+/// Stream a `sample` event with every new sample, starting with the latest one
+#[get("/<id>/metrics/events")]
+pub async fn events(
+    id: i32,
+    _auth_guard: AccessTokenGuard,
+    metrics: &State<InstanceMetrics>,
+    instance_service: crate::service::instance::Instance,
+    mut shutdown: Shutdown,
+) -> Result<EventStream![], controller::Error> {
+    instance_service.find_by_id(id).await?;
+    let mut updates = metrics.subscribe();
+    let current = metrics.get(id);
+
+    Ok(EventStream! {
+        if let Some(sample) = current {
+            yield Event::json(&sample).event("sample");
+        }
+        loop {
+            tokio::select! {
+                _ = &mut shutdown => break,
+                update = updates.recv() => match update {
+                    Ok((update_id, sample)) if update_id == id => {
+                        yield Event::json(&sample).event("sample")
+                    }
+                    // The next sample is only seconds away
+                    Ok(_) | Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => break,
+                },
+            }
+        }
+    })
+}
@@ -0,0 +1,66 @@
+mod crud;
+mod files;
+mod limits;
+mod logs;
+mod metrics;
+mod mods;
+mod players;
+mod process;
+mod rcon;
+mod status;
+mod update;
+
+use rocket::{routes, Route};
+
+const BASE_PATH: &str = "/api/instance";
+
+pub fn get_all_routes() -> Vec<(&'static str, Vec<Route>)> {
+    vec![(
+        BASE_PATH,
+        routes![
+            crud::list,
+            crud::by_id,
+            crud::create,
+            crud::update,
+            crud::delete,
+            crud::command,
+            crud::ports,
+            mods::list,
+            mods::add,
+            mods::replace,
+            mods::remove,
+            mods::install,
+            process::start,
+            process::stop,
+            process::stdout,
+            process::console,
+            rcon::execute,
+            players::sessions,
+            players::actions,
+            players::restrictions,
+            players::moderate,
+            status::status,
+            status::status_events,
+            metrics::current,
+            metrics::history,
+            metrics::events,
+            limits::limits,
+            limits::update,
+            files::list,
+            files::read,
+            files::write,
+            files::upload,
+            files::download,
+            files::create_dir,
+            files::rename,
+            files::delete,
+            logs::logs,
+            update::list,
+            update::status,
+            update::policy,
+            update::options,
+            update::check,
+            update::update,
+        ],
+    )]
+}
@@ -0,0 +1,123 @@
+use crate::{
+    auth::guards::{AccessTokenGuard, ModeratorGuard},
+    controller, dto,
+    models::audit::AuditAction,
+    service::{self, audit::AuditEvent},
+    state::steamcmd::{SteamCMD, SteamLogin},
+};
+use rocket::{delete, get, post, put, response::status::Created, serde::json::Json, State};
+
+#[get("/<id>/mods")]
+pub async fn list(
+    id: i32,
+    _auth_guard: AccessTokenGuard,
+    workshop: service::workshop::Workshop,
+) -> Result<Json<Vec<dto::instance::InstanceMod>>, controller::Error> {
+    Ok(Json(workshop.list(id).await?))
+}
+
+/// Add a Workshop item to the end of the instance's load order
+#[post("/<id>/mods", data = "<data>")]
+pub async fn add(
+    id: i32,
+    _moderator: ModeratorGuard,
+    data: Json<dto::instance::InstanceModEntry>,
+    workshop: service::workshop::Workshop,
+    audit: service::audit::Audit,
+) -> Result<Created<Json<dto::instance::InstanceMod>>, controller::Error> {
+    let added = workshop.add(id, data.into_inner()).await?;
+
+    audit
+        .record(
+            AuditEvent::new(AuditAction::InstanceModsUpdate)
+                .target("instance", id)
+                .details(serde_json::json!({ "added": added.workshop_item_id })),
+        )
+        .await;
+
+    Ok(Created::new(format!("/api/instance/{id}/mods")).body(Json(added)))
+}
+
+/// Replace the mod list; the order given is the load order
+#[put("/<id>/mods", data = "<data>")]
+pub async fn replace(
+    id: i32,
+    _moderator: ModeratorGuard,
+    data: Json<Vec<dto::instance::InstanceModEntry>>,
+    workshop: service::workshop::Workshop,
+    audit: service::audit::Audit,
+) -> Result<Json<Vec<dto::instance::InstanceMod>>, controller::Error> {
+    let item_ids = |mods: &[dto::instance::InstanceMod]| {
+        mods.iter()
+            .map(|m| (m.workshop_item_id, m.enabled))
+            .collect::<Vec<_>>()
+    };
+    let before = item_ids(&workshop.list(id).await?);
+    let mods = workshop.replace(id, data.into_inner()).await?;
+
+    audit
+        .record(
+            AuditEvent::new(AuditAction::InstanceModsUpdate)
+                .target("instance", id)
+                .details(serde_json::json!({ "before": before, "after": item_ids(&mods) })),
+        )
+        .await;
+
+    Ok(Json(mods))
+}
+
+#[delete("/<id>/mods/<item_id>")]
+pub async fn remove(
+    id: i32,
+    item_id: i64,
+    _moderator: ModeratorGuard,
+    workshop: service::workshop::Workshop,
+    audit: service::audit::Audit,
+) -> Result<(), controller::Error> {
+    workshop.remove(id, item_id).await?;
+
+    audit
+        .record(
+            AuditEvent::new(AuditAction::InstanceModsUpdate)
+                .target("instance", id)
+                .details(serde_json::json!({ "removed": item_id })),
+        )
+        .await;
+
+    Ok(())
+}
+
+/// Download the enabled mods with a SteamCMD job and place them into the instance.
+/// Follow progress through the returned session's stdout stream.
+#[post("/<id>/install_mods", data = "<data>")]
+pub async fn install(
+    id: i32,
+    _moderator: ModeratorGuard,
+    data: Option<Json<dto::instance::ModInstall>>,
+    steamcmd: &State<SteamCMD>,
+    workshop: service::workshop::Workshop,
+    credential_service: service::steam_credential::SteamCredentials,
+    audit: service::audit::Audit,
+) -> Result<Json<dto::steamcmd::SteamCmdSessionInfo>, controller::Error> {
+    let options = data.map(|d| d.into_inner()).unwrap_or_default();
+    let login = match options.credential_id {
+        Some(credential_id) => credential_service.login(credential_id).await?,
+        None => SteamLogin::Anonymous,
+    };
+
+    let session = workshop.install(id, steamcmd, login).await?;
+    let info = session.info();
+
+    audit
+        .record(
+            AuditEvent::new(AuditAction::InstanceModsInstall)
+                .target("instance", id)
+                .details(serde_json::json!({
+                    "session": info.id,
+                    "credentialId": options.credential_id,
+                })),
+        )
+        .await;
+
+    Ok(Json(info))
+}
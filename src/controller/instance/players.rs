@@ -0,0 +1,66 @@
+use crate::{
+    auth::guards::{AccessTokenGuard, ModeratorGuard},
+    controller, dto,
+    models::audit::AuditAction,
+    service::{self, audit::AuditEvent},
+    state::instance::InstanceManager,
+};
+use rocket::{get, post, response::status::Created, serde::json::Json, State};
+
+/// Who played on the server and when, newest first. Pass `online=true` for the players on it now.
+#[get("/<id>/players?<query..>")]
+pub async fn sessions(
+    id: i32,
+    query: dto::player::PlayerSessionQuery,
+    _auth_guard: AccessTokenGuard,
+    players: service::player::Players,
+) -> Result<Json<dto::player::PlayerSessionPage>, controller::Error> {
+    Ok(Json(players.sessions(id, &query).await?))
+}
+
+#[get("/<id>/players/actions")]
+pub async fn actions(
+    id: i32,
+    _moderator: ModeratorGuard,
+    players: service::player::Players,
+) -> Result<Json<Vec<dto::player::PlayerActionEntry>>, controller::Error> {
+    Ok(Json(players.actions(id).await?))
+}
+
+#[get("/<id>/players/restrictions")]
+pub async fn restrictions(
+    id: i32,
+    _moderator: ModeratorGuard,
+    players: service::player::Players,
+) -> Result<Json<dto::player::PlayerRestrictions>, controller::Error> {
+    Ok(Json(players.restrictions(id).await?))
+}
+
+/// Kick, ban, unban or whitelist a player with the command the game schema declares for it
+#[post("/<id>/players/actions", data = "<data>")]
+pub async fn moderate(
+    id: i32,
+    moderator: ModeratorGuard,
+    data: Json<dto::player::PlayerModeration>,
+    manager: &State<InstanceManager>,
+    players: service::player::Players,
+    audit: service::audit::Audit,
+) -> Result<Created<Json<dto::player::PlayerActionEntry>>, controller::Error> {
+    let (action, via) = players.moderate(id, &data, &moderator, manager).await?;
+
+    audit
+        .record(
+            AuditEvent::new(AuditAction::PlayerModeration)
+                .target("instance", id)
+                .details(serde_json::json!({
+                    "action": action.action,
+                    "name": action.player_name,
+                    "steamId": action.steam_id,
+                    "reason": action.reason,
+                    "via": via,
+                })),
+        )
+        .await;
+
+    Ok(Created::new(format!("/api/instance/{id}/players/actions")).body(Json(action)))
+}
@@ -0,0 +1,175 @@
+use crate::{
+    auth::guards::{AccessTokenGuard, ModeratorGuard},
+    controller::{
+        self,
+        console::{ConsoleInputHandler, ConsoleSocket},
+    },
+    dto,
+    models::{audit::AuditAction, user::UserRole},
+    service::{self, audit::AuditEvent, instance::InstanceError},
+    state::{
+        console::Followed,
+        instance::{InstanceManager, InstanceProcessError},
+        vt,
+    },
+};
+use rocket::{
+    get, post,
+    response::stream::{Event, EventStream},
+    serde::json::Json,
+    Shutdown, State,
+};
+use rocket_ws::{Channel, WebSocket};
+
+#[post("/<id>/start")]
+pub async fn start(
+    id: i32,
+    _moderator: ModeratorGuard,
+    manager: &State<InstanceManager>,
+    instance_service: service::instance::Instance,
+    audit: service::audit::Audit,
+) -> Result<Json<dto::instance::Instance>, controller::Error> {
+    let process = instance_service.start(id, manager).await?;
+
+    audit
+        .record(
+            AuditEvent::new(AuditAction::InstanceStart)
+                .target("instance", id)
+                .details(serde_json::json!({ "pid": process.pid() })),
+        )
+        .await;
+
+    Ok(Json(instance_service.get(id).await?))
+}
+
+#[post("/<id>/stop")]
+pub async fn stop(
+    id: i32,
+    _moderator: ModeratorGuard,
+    manager: &State<InstanceManager>,
+    instance_service: service::instance::Instance,
+    audit: service::audit::Audit,
+) -> Result<Json<dto::instance::Instance>, controller::Error> {
+    instance_service.find_by_id(id).await?;
+    instance_service.stop(id, manager).await?;
+
+    audit
+        .record(AuditEvent::new(AuditAction::InstanceStop).target("instance", id))
+        .await;
+
+    Ok(Json(instance_service.get(id).await?))
+}
+
+/// Render a console line for the stream: plain text, or styled spans as JSON with `spans`
+fn console_event(line: &str, spans: bool) -> Event {
+    match spans {
+        true => Event::json(&vt::spans(line)),
+        // Keep the line break like the SteamCMD streams do
+        false => Event::data(format!("{}\n", vt::plain(line))),
+    }
+}
+
+/// Replay the server's recent output, then follow it live. Pass `spans=true` to receive each
+/// line as coloured spans instead of plain text. The stream carries on across restarts of the
+/// server; lines dropped before a slow client could read them are announced with a `resync`
+/// event.
+#[get("/<id>/stdout?<spans>")]
+pub async fn stdout(
+    id: i32,
+    spans: Option<bool>,
+    manager: &State<InstanceManager>,
+    mut shutdown: Shutdown,
+    _auth_guard: AccessTokenGuard,
+) -> Result<EventStream![], controller::Error> {
+    let mut lines = manager
+        .follow(id, None)
+        .ok_or(InstanceError::Process(InstanceProcessError::NotRunning(id)))?;
+    let spans = spans.unwrap_or(false);
+
+    Ok(EventStream! {
+        loop {
+            tokio::select! {
+                _ = &mut shutdown => break,
+                followed = lines.recv() => match followed {
+                    Some(Followed::Line(line)) => yield console_event(&line.text, spans),
+                    Some(Followed::Resync { missed }) => {
+                        yield Event::json(&serde_json::json!({ "missed": missed })).event("resync")
+                    }
+                    Some(Followed::Reset) => continue,
+                    None => break,
+                },
+            }
+        }
+    })
+}
+
+/// Commands typed into an instance's console. They go over RCON when the schema declares it
+/// and to the server's stdin otherwise.
+struct InstanceInput {
+    id: i32,
+    manager: InstanceManager,
+    rcon: service::rcon::Rcon,
+    audit: service::audit::Audit,
+}
+
+#[rocket::async_trait]
+impl ConsoleInputHandler for InstanceInput {
+    async fn send(&self, command: &str) -> Result<(), String> {
+        let via = self
+            .rcon
+            .send(self.id, command, &self.manager)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        self.audit
+            .record(
+                AuditEvent::new(AuditAction::ConsoleCommand)
+                    .target("instance", self.id)
+                    .details(serde_json::json!({ "command": command.trim(), "via": via })),
+            )
+            .await;
+
+        Ok(())
+    }
+}
+
+/// The server's console over a WebSocket: output as JSON messages, and for moderators, input
+/// sent over RCON or written to the server's stdin. Pass `after` with the `seq` of the last
+/// line seen to resume without replaying the whole history.
+#[get("/<id>/console?<after>&<spans>")]
+#[allow(clippy::too_many_arguments)]
+pub async fn console(
+    ws: WebSocket,
+    id: i32,
+    after: Option<u64>,
+    spans: Option<bool>,
+    manager: &State<InstanceManager>,
+    auth: AccessTokenGuard,
+    rcon: service::rcon::Rcon,
+    audit: service::audit::Audit,
+    shutdown: Shutdown,
+) -> Result<Channel<'static>, controller::Error> {
+    let follower = manager
+        .follow(id, after)
+        .ok_or(InstanceError::Process(InstanceProcessError::NotRunning(id)))?;
+
+    // Same bar as starting and stopping. API tokens only ever pass a read scope check for the
+    // upgrade request, so they stay read-only.
+    let can_type =
+        matches!(auth.role, UserRole::Admin | UserRole::Moderator) && auth.api_token_id.is_none();
+    let console = ConsoleSocket {
+        follower,
+        events: None,
+        input: can_type.then(|| {
+            Box::new(InstanceInput {
+                id,
+                manager: manager.inner().clone(),
+                rcon,
+                audit,
+            }) as Box<dyn ConsoleInputHandler>
+        }),
+        spans: spans.unwrap_or(false),
+    };
+
+    Ok(controller::console::socket(ws, console, shutdown))
+}
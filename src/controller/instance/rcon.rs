@@ -0,0 +1,31 @@
+use crate::{
+    auth::guards::ModeratorGuard,
+    controller, dto,
+    models::audit::AuditAction,
+    service::{self, audit::AuditEvent},
+    state::instance::InstanceManager,
+};
+use rocket::{post, serde::json::Json, State};
+
+/// Run a console command on the server over RCON, for games whose schema declares it
+#[post("/<id>/rcon", data = "<data>")]
+pub async fn execute(
+    id: i32,
+    _moderator: ModeratorGuard,
+    data: Json<dto::instance::RconCommand>,
+    manager: &State<InstanceManager>,
+    rcon: service::rcon::Rcon,
+    audit: service::audit::Audit,
+) -> Result<Json<dto::instance::RconOutput>, controller::Error> {
+    let output = rcon.execute(id, &data.command, manager).await?;
+
+    audit
+        .record(
+            AuditEvent::new(AuditAction::ConsoleCommand)
+                .target("instance", id)
+                .details(serde_json::json!({ "command": data.command.trim(), "via": "rcon" })),
+        )
+        .await;
+
+    Ok(Json(dto::instance::RconOutput { output }))
+}
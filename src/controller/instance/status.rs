@@ -0,0 +1,91 @@
+use crate::{
+    auth::guards::AccessTokenGuard,
+    controller, dto,
+    state::{instance::InstanceManager, server_query::ServerQueries},
+};
+use rocket::{
+    get,
+    response::stream::{Event, EventStream},
+    serde::json::Json,
+    Shutdown, State,
+};
+use tokio::sync::broadcast::error::RecvError;
+
+/// The instance's process and what the server last reported over the Steam server queries
+#[get("/<id>/status")]
+pub async fn status(
+    id: i32,
+    _auth_guard: AccessTokenGuard,
+    manager: &State<InstanceManager>,
+    queries: &State<ServerQueries>,
+    instance_service: crate::service::instance::Instance,
+) -> Result<Json<dto::instance::InstanceRuntimeStatus>, controller::Error> {
+    let instance = instance_service.find_by_id(id).await?;
+    let process = manager.process(id).await.filter(|p| p.is_running());
+
+    Ok(Json(dto::instance::InstanceRuntimeStatus {
+        status: instance.status.into(),
+        pid: process.as_ref().and_then(|p| p.pid()),
+        started_at: process.as_ref().map(|p| p.started_at()),
+        query: process.and_then(|_| queries.get(id)),
+    }))
+}
+
+/// Stream a `query` event with every new answer of the server, starting with the current one,
+/// a `trigger` event whenever its output matches one of the schema's triggers and a `limit`
+/// event whenever it runs into one of its resource limits
+#[get("/<id>/status/events")]
+pub async fn status_events(
+    id: i32,
+    _auth_guard: AccessTokenGuard,
+    manager: &State<InstanceManager>,
+    queries: &State<ServerQueries>,
+    instance_service: crate::service::instance::Instance,
+    mut shutdown: Shutdown,
+) -> Result<EventStream![], controller::Error> {
+    instance_service.find_by_id(id).await?;
+    let mut updates = queries.subscribe();
+    let mut triggers = manager.subscribe_triggers();
+    let mut violations = manager.subscribe_violations();
+    let current = queries.get(id);
+    let queries = queries.inner().clone();
+
+    Ok(EventStream! {
+        if let Some(query) = current {
+            yield Event::json(&query).event("query");
+        }
+        loop {
+            tokio::select! {
+                _ = &mut shutdown => break,
+                update = updates.recv() => match update {
+                    Ok((update_id, query)) if update_id == id => {
+                        yield Event::json(&query).event("query")
+                    }
+                    Ok(_) => continue,
+                    // Only the latest answer matters, so catch up with that
+                    Err(RecvError::Lagged(_)) => {
+                        if let Some(query) = queries.get(id) {
+                            yield Event::json(&query).event("query");
+                        }
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+                fired = triggers.recv() => match fired {
+                    Ok((fired_id, fired)) if fired_id == id => {
+                        yield Event::json(&fired).event("trigger")
+                    }
+                    // Unlike query answers, missed triggers cannot be caught up with
+                    Ok(_) | Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => break,
+                },
+                violation = violations.recv() => match violation {
+                    Ok((violation_id, violation)) if violation_id == id => {
+                        yield Event::json(&violation).event("limit")
+                    }
+                    Ok(_) | Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => break,
+                },
+            }
+        }
+    })
+}
@@ -0,0 +1,145 @@
+use crate::{
+    auth::guards::{AccessTokenGuard, ModeratorGuard},
+    controller, dto,
+    models::audit::AuditAction,
+    service::{self, audit::AuditEvent},
+    state::{
+        instance::InstanceManager,
+        steamcmd::{SteamCMD, SteamLogin},
+    },
+};
+use rocket::{get, post, put, serde::json::Json, State};
+
+/// Installed and available builds of every instance, as of the last check
+#[get("/updates")]
+pub async fn list(
+    _auth_guard: AccessTokenGuard,
+    updates: service::update::Updates,
+) -> Result<Json<Vec<dto::instance::InstanceUpdateStatus>>, controller::Error> {
+    Ok(Json(updates.list().await?))
+}
+
+#[get("/<id>/update")]
+pub async fn status(
+    id: i32,
+    _auth_guard: AccessTokenGuard,
+    updates: service::update::Updates,
+) -> Result<Json<dto::instance::InstanceUpdateStatus>, controller::Error> {
+    Ok(Json(updates.status(id).await?))
+}
+
+#[put("/<id>/update/policy", data = "<data>")]
+pub async fn policy(
+    id: i32,
+    moderator: ModeratorGuard,
+    data: Json<dto::instance::UpdatePolicy>,
+    updates: service::update::Updates,
+    audit: service::audit::Audit,
+) -> Result<Json<dto::instance::InstanceUpdateStatus>, controller::Error> {
+    let before = updates.status(id).await?.policy;
+    service::update::ensure_may_choose_credential(
+        moderator.role,
+        before.credential_id,
+        data.credential_id,
+    )?;
+    let status = updates.set_policy(id, data.into_inner()).await?;
+
+    audit
+        .record(
+            AuditEvent::new(AuditAction::GameUpdatePolicy)
+                .target("instance", id)
+                .diff(&before, &status.policy),
+        )
+        .await;
+
+    Ok(Json(status))
+}
+
+/// Change the branch, validation and platform used by future updates
+#[put("/<id>/update/options", data = "<data>")]
+pub async fn options(
+    id: i32,
+    _moderator: ModeratorGuard,
+    data: Json<dto::instance::InstallOptionsUpdate>,
+    updates: service::update::Updates,
+    audit: service::audit::Audit,
+) -> Result<Json<dto::instance::InstanceUpdateStatus>, controller::Error> {
+    let before = updates.status(id).await?.install_options;
+    let password_changed = data.beta_password.is_some();
+    let status = updates.set_install_options(id, data.into_inner()).await?;
+
+    // The password itself is never logged, only that it was replaced
+    audit
+        .record(
+            AuditEvent::new(AuditAction::InstallOptionsUpdate)
+                .target("instance", id)
+                .diff(
+                    &serde_json::json!({ "options": before, "betaPasswordChanged": false }),
+                    &serde_json::json!({
+                        "options": status.install_options,
+                        "betaPasswordChanged": password_changed,
+                    }),
+                ),
+        )
+        .await;
+
+    Ok(Json(status))
+}
+
+/// Look up the latest published build now instead of waiting for the next scheduled check
+#[post("/<id>/update/check")]
+pub async fn check(
+    id: i32,
+    _moderator: ModeratorGuard,
+    steamcmd: &State<SteamCMD>,
+    updates: service::update::Updates,
+) -> Result<Json<dto::instance::InstanceUpdateStatus>, controller::Error> {
+    let mut statuses = updates.check(steamcmd, Some(id)).await?;
+    Ok(Json(statuses.remove(0)))
+}
+
+/// Stop, update and restart the server. Follow progress through the returned session's
+/// stdout stream.
+#[post("/<id>/update", data = "<data>")]
+#[allow(clippy::too_many_arguments)]
+pub async fn update(
+    id: i32,
+    moderator: ModeratorGuard,
+    data: Option<Json<dto::instance::UpdateRequest>>,
+    steamcmd: &State<SteamCMD>,
+    manager: &State<InstanceManager>,
+    updates: service::update::Updates,
+    credential_service: service::steam_credential::SteamCredentials,
+    audit: service::audit::Audit,
+) -> Result<Json<dto::steamcmd::SteamCmdSessionInfo>, controller::Error> {
+    let request = data.map(|d| d.into_inner()).unwrap_or_default();
+    let status = updates.status(id).await?;
+    service::update::ensure_may_choose_credential(
+        moderator.role,
+        status.policy.credential_id,
+        request.credential_id,
+    )?;
+    let credential_id = request.credential_id.or(status.policy.credential_id);
+    let login = match credential_id {
+        Some(credential_id) => credential_service.login(credential_id).await?,
+        None => SteamLogin::Anonymous,
+    };
+
+    let session = updates.update(id, steamcmd, manager, login).await?;
+    let info = session.info();
+
+    audit
+        .record(
+            AuditEvent::new(AuditAction::GameUpdate)
+                .target("instance", id)
+                .details(serde_json::json!({
+                    "session": info.id,
+                    "credentialId": credential_id,
+                    "from": status.installed_build_id,
+                    "to": status.available_build_id,
+                })),
+        )
+        .await;
+
+    Ok(Json(info))
+}
@@ -1,5 +1,8 @@
+mod audit;
+mod console;
 mod game_schema;
 mod health;
+mod instance;
 mod steamcmd;
 mod user;
 
@@ -14,6 +17,8 @@ pub fn get_all_routes() -> Vec<(&'static str, Vec<Route>)> {
     routes.extend(user::get_all_routes());
     routes.extend(steamcmd::get_all_routes());
     routes.extend(game_schema::get_all_routes());
+    routes.extend(instance::get_all_routes());
+    routes.extend(audit::get_all_routes());
 
     routes
 }
@@ -31,6 +36,51 @@ pub enum Error {
 
     #[error(transparent)]
     AuthError(#[from] crate::auth::guards::AuthError),
+
+    #[error(transparent)]
+    Mfa(#[from] crate::service::mfa::MfaError),
+
+    #[error(transparent)]
+    ApiToken(#[from] crate::service::api_token::ApiTokenError),
+
+    #[error(transparent)]
+    Audit(#[from] crate::service::audit::AuditError),
+
+    #[error(transparent)]
+    Sso(#[from] crate::service::sso::SsoError),
+
+    #[error(transparent)]
+    StreamTicket(#[from] crate::state::stream_ticket::StreamTicketError),
+
+    #[error(transparent)]
+    SteamCredential(#[from] crate::service::steam_credential::SteamCredentialError),
+
+    #[error(transparent)]
+    SteamCmdBootstrap(#[from] crate::state::steamcmd::bootstrap::BootstrapError),
+
+    #[error(transparent)]
+    Instance(#[from] crate::service::instance::InstanceError),
+
+    #[error(transparent)]
+    Workshop(#[from] crate::service::workshop::WorkshopError),
+
+    #[error(transparent)]
+    Update(#[from] crate::service::update::UpdateError),
+
+    #[error(transparent)]
+    ConsoleLog(#[from] crate::state::console_log::ConsoleLogError),
+
+    #[error(transparent)]
+    Rcon(#[from] crate::service::rcon::RconError),
+
+    #[error(transparent)]
+    Player(#[from] crate::service::player::PlayerError),
+
+    #[error(transparent)]
+    Metrics(#[from] crate::state::metrics::MetricsError),
+
+    #[error(transparent)]
+    Files(#[from] crate::service::files::FilesError),
 }
 
 impl<'r> Responder<'r, 'static> for Error {
@@ -40,6 +90,21 @@ impl<'r> Responder<'r, 'static> for Error {
             Error::SteamCMD(e) => e.respond_to(req),
             Error::GameSchema(e) => e.respond_to(req),
             Error::AuthError(e) => e.respond_to(req),
+            Error::Mfa(e) => e.respond_to(req),
+            Error::ApiToken(e) => e.respond_to(req),
+            Error::Audit(e) => e.respond_to(req),
+            Error::Sso(e) => e.respond_to(req),
+            Error::StreamTicket(e) => e.respond_to(req),
+            Error::SteamCredential(e) => e.respond_to(req),
+            Error::SteamCmdBootstrap(e) => e.respond_to(req),
+            Error::Instance(e) => e.respond_to(req),
+            Error::Workshop(e) => e.respond_to(req),
+            Error::Update(e) => e.respond_to(req),
+            Error::ConsoleLog(e) => e.respond_to(req),
+            Error::Rcon(e) => e.respond_to(req),
+            Error::Player(e) => e.respond_to(req),
+            Error::Metrics(e) => e.respond_to(req),
+            Error::Files(e) => e.respond_to(req),
         }
     }
 }
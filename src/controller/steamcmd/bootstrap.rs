@@ -0,0 +1,34 @@
+use crate::{
+    auth::guards::AdminGuard,
+    controller, dto,
+    models::audit::AuditAction,
+    service::{self, audit::AuditEvent},
+    state::steamcmd::{bootstrap::BootstrapConfig, SteamCMD},
+};
+use rocket::{post, serde::json::Json, State};
+
+/// Download SteamCMD into the data directory and start it, for servers running without one
+#[post("/bootstrap")]
+pub async fn bootstrap(
+    _admin: AdminGuard,
+    steamcmd: &State<SteamCMD>,
+    config: &State<BootstrapConfig>,
+    audit: service::audit::Audit,
+) -> Result<Json<dto::steamcmd::SteamCmdStatus>, controller::Error> {
+    let result = steamcmd.bootstrap(config).await;
+
+    let mut event = AuditEvent::new(AuditAction::SteamCmdBootstrap);
+    event = match &result {
+        Ok(path) => event.details(serde_json::json!({
+            "url": config.url,
+            "path": path.display().to_string(),
+        })),
+        Err(e) => event
+            .failed()
+            .details(serde_json::json!({ "url": config.url, "error": e.to_string() })),
+    };
+    audit.record(event).await;
+
+    result?;
+    Ok(Json(steamcmd.status()))
+}
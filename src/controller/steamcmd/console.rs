@@ -0,0 +1,100 @@
+use crate::{
+    auth::guards::AccessTokenGuard,
+    controller::{
+        self,
+        console::{ConsoleInputHandler, ConsoleSocket},
+    },
+    models::{audit::AuditAction, user::UserRole},
+    service::audit::{Audit, AuditEvent},
+    state::steamcmd::{SteamCMD, SteamCmdSession},
+};
+use rocket::{get, Shutdown, State};
+use rocket_ws::{Channel, WebSocket};
+use std::sync::{Arc, Weak};
+
+/// Commands typed into a session's console. Weak so that removing a job still ends its socket.
+struct SessionInput {
+    session: Weak<SteamCmdSession>,
+    audit: Audit,
+}
+
+#[rocket::async_trait]
+impl ConsoleInputHandler for SessionInput {
+    async fn send(&self, command: &str) -> Result<(), String> {
+        let session = self
+            .session
+            .upgrade()
+            .ok_or_else(|| "The SteamCMD session has been removed".to_string())?;
+        session
+            .send_command(command)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        self.audit
+            .record(
+                AuditEvent::new(AuditAction::ConsoleCommand)
+                    .target("steamcmd_session", session.id())
+                    .details(serde_json::json!({ "command": command.trim() })),
+            )
+            .await;
+
+        Ok(())
+    }
+}
+
+fn session_socket(
+    ws: WebSocket,
+    session: Arc<SteamCmdSession>,
+    after: Option<u64>,
+    auth: AccessTokenGuard,
+    audit: Audit,
+    shutdown: Shutdown,
+) -> Channel<'static> {
+    // Same bar as the other session controls. API tokens only ever pass a read scope check
+    // for the upgrade request, so they stay read-only.
+    let can_type = auth.role == UserRole::Admin && auth.api_token_id.is_none();
+    let console = ConsoleSocket {
+        follower: session.follow(after),
+        events: Some(session.subscribe_events()),
+        input: can_type.then(|| {
+            Box::new(SessionInput {
+                session: Arc::downgrade(&session),
+                audit,
+            }) as Box<dyn ConsoleInputHandler>
+        }),
+        spans: false,
+    };
+
+    controller::console::socket(ws, console, shutdown)
+}
+
+/// The global session's console over a WebSocket. Pass `after` with the `seq` of the last
+/// line seen to resume without replaying the whole history.
+#[get("/console?<after>")]
+pub async fn console(
+    ws: WebSocket,
+    after: Option<u64>,
+    steamcmd: &State<SteamCMD>,
+    auth: AccessTokenGuard,
+    audit: Audit,
+    shutdown: Shutdown,
+) -> Result<Channel<'static>, controller::Error> {
+    let session = steamcmd
+        .session(crate::state::steamcmd::GLOBAL_SESSION_ID)
+        .await?;
+    Ok(session_socket(ws, session, after, auth, audit, shutdown))
+}
+
+#[get("/sessions/<id>/console?<after>")]
+pub async fn session_console(
+    ws: WebSocket,
+    id: &str,
+    after: Option<u64>,
+    steamcmd: &State<SteamCMD>,
+    auth: AccessTokenGuard,
+    audit: Audit,
+    shutdown: Shutdown,
+) -> Result<Channel<'static>, controller::Error> {
+    let session = steamcmd.session(id).await?;
+    Ok(session_socket(ws, session, after, auth, audit, shutdown))
+}
@@ -0,0 +1,74 @@
+use crate::{
+    auth::guards::AdminGuard,
+    controller, dto,
+    models::audit::AuditAction,
+    service::{self, audit::AuditEvent},
+};
+use rocket::{delete, get, post, put, response::status::Created, serde::json::Json};
+
+#[get("/credentials")]
+pub async fn list(
+    _admin: AdminGuard,
+    credential_service: service::steam_credential::SteamCredentials,
+) -> Result<Json<Vec<dto::steamcmd::SteamCredential>>, controller::Error> {
+    Ok(Json(credential_service.list().await?))
+}
+
+#[post("/credentials", data = "<data>")]
+pub async fn create(
+    _admin: AdminGuard,
+    data: Json<dto::steamcmd::NewSteamCredential>,
+    credential_service: service::steam_credential::SteamCredentials,
+    audit: service::audit::Audit,
+) -> Result<Created<Json<dto::steamcmd::SteamCredential>>, controller::Error> {
+    let credential = credential_service.create(data.into_inner()).await?;
+
+    audit
+        .record(
+            AuditEvent::new(AuditAction::SteamCredentialCreate)
+                .target("steam_credential", credential.id)
+                .details(serde_json::json!({ "username": credential.username })),
+        )
+        .await;
+
+    Ok(Created::new("/api/steamcmd/credentials").body(Json(credential)))
+}
+
+#[put("/credentials/<id>", data = "<data>")]
+pub async fn update(
+    id: i32,
+    _admin: AdminGuard,
+    data: Json<dto::steamcmd::SteamCredentialUpdate>,
+    credential_service: service::steam_credential::SteamCredentials,
+    audit: service::audit::Audit,
+) -> Result<Json<dto::steamcmd::SteamCredential>, controller::Error> {
+    let credential = credential_service
+        .update_password(id, data.into_inner())
+        .await?;
+
+    audit
+        .record(
+            AuditEvent::new(AuditAction::SteamCredentialUpdate)
+                .target("steam_credential", credential.id)
+                .details(serde_json::json!({ "username": credential.username })),
+        )
+        .await;
+
+    Ok(Json(credential))
+}
+
+#[delete("/credentials/<id>")]
+pub async fn delete(
+    id: i32,
+    _admin: AdminGuard,
+    credential_service: service::steam_credential::SteamCredentials,
+    audit: service::audit::Audit,
+) -> Result<(), controller::Error> {
+    credential_service.delete(id).await?;
+
+    audit
+        .record(AuditEvent::new(AuditAction::SteamCredentialDelete).target("steam_credential", id))
+        .await;
+
+    Ok(())
+}
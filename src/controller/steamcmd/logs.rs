@@ -0,0 +1,16 @@
+use crate::{
+    auth::guards::AccessTokenGuard,
+    controller, dto,
+    state::console_log::{ConsoleLogs, LogStream},
+};
+use rocket::{get, serde::json::Json, State};
+
+/// Page through or search the output of all SteamCMD sessions, including past days
+#[get("/logs?<query..>")]
+pub async fn logs(
+    _auth_guard: AccessTokenGuard,
+    query: dto::console_log::ConsoleLogQuery,
+    logs: &State<ConsoleLogs>,
+) -> Result<Json<dto::console_log::ConsoleLogPage>, controller::Error> {
+    Ok(Json(logs.query(LogStream::SteamCmd, query).await?))
+}
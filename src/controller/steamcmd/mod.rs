@@ -1,3 +1,8 @@
+mod bootstrap;
+mod console;
+mod credentials;
+mod logs;
+mod sessions;
 mod stdout;
 
 use rocket::{routes, Route};
@@ -5,5 +10,24 @@ use rocket::{routes, Route};
 const BASE_PATH: &str = "/api/steamcmd";
 
 pub fn get_all_routes() -> Vec<(&'static str, Vec<Route>)> {
-    vec![(BASE_PATH, routes![stdout::stdout])]
+    vec![(
+        BASE_PATH,
+        routes![
+            stdout::stdout,
+            stdout::session_stdout,
+            console::console,
+            console::session_console,
+            logs::logs,
+            sessions::list,
+            sessions::by_id,
+            sessions::create,
+            sessions::steam_guard,
+            sessions::remove,
+            credentials::list,
+            credentials::create,
+            credentials::update,
+            credentials::delete,
+            bootstrap::bootstrap,
+        ],
+    )]
 }
@@ -0,0 +1,96 @@
+use crate::{
+    auth::guards::{AccessTokenGuard, AdminGuard},
+    controller, dto,
+    models::audit::AuditAction,
+    service::{self, audit::AuditEvent},
+    state::steamcmd::{SteamCMD, SteamLogin},
+};
+use rocket::{delete, get, post, response::status::Created, serde::json::Json, State};
+
+#[get("/sessions")]
+pub async fn list(
+    steamcmd: &State<SteamCMD>,
+    _auth_guard: AccessTokenGuard,
+) -> Json<Vec<dto::steamcmd::SteamCmdSessionInfo>> {
+    Json(steamcmd.sessions().await)
+}
+
+#[get("/sessions/<id>")]
+pub async fn by_id(
+    id: &str,
+    steamcmd: &State<SteamCMD>,
+    _auth_guard: AccessTokenGuard,
+) -> Result<Json<dto::steamcmd::SteamCmdSessionInfo>, controller::Error> {
+    Ok(Json(steamcmd.session(id).await?.info()))
+}
+
+/// Start a dedicated SteamCMD process for a job, logged in with a stored credential or anonymously
+#[post("/sessions", data = "<data>")]
+pub async fn create(
+    _admin: AdminGuard,
+    data: Json<dto::steamcmd::NewSteamCmdJob>,
+    steamcmd: &State<SteamCMD>,
+    credential_service: service::steam_credential::SteamCredentials,
+    audit: service::audit::Audit,
+) -> Result<Created<Json<dto::steamcmd::SteamCmdSessionInfo>>, controller::Error> {
+    let job = data.into_inner();
+    let login = match job.credential_id {
+        Some(id) => credential_service.login(id).await?,
+        None => SteamLogin::Anonymous,
+    };
+
+    let session = steamcmd
+        .spawn_job(login, None, job.commands.clone())
+        .await?;
+    let info = session.info();
+
+    audit
+        .record(
+            AuditEvent::new(AuditAction::SteamCmdJobStart)
+                .target("steamcmd_session", &info.id)
+                .details(serde_json::json!({
+                    "account": info.account,
+                    "credentialId": job.credential_id,
+                    "commands": job.commands,
+                })),
+        )
+        .await;
+
+    Ok(Created::new(format!("/api/steamcmd/sessions/{}", info.id)).body(Json(info)))
+}
+
+/// Answer SteamCMD's Steam Guard prompt for a session
+#[post("/sessions/<id>/steam_guard", data = "<data>")]
+pub async fn steam_guard(
+    id: &str,
+    _admin: AdminGuard,
+    data: Json<dto::steamcmd::SteamGuardCode>,
+    steamcmd: &State<SteamCMD>,
+    audit: service::audit::Audit,
+) -> Result<Json<dto::steamcmd::SteamCmdSessionInfo>, controller::Error> {
+    let session = steamcmd.session(id).await?;
+    session.submit_steam_guard_code(&data.code).await?;
+
+    audit
+        .record(AuditEvent::new(AuditAction::SteamGuardSubmit).target("steamcmd_session", id))
+        .await;
+
+    Ok(Json(session.info()))
+}
+
+/// Kill a job session and drop its output
+#[delete("/sessions/<id>")]
+pub async fn remove(
+    id: &str,
+    _admin: AdminGuard,
+    steamcmd: &State<SteamCMD>,
+    audit: service::audit::Audit,
+) -> Result<(), controller::Error> {
+    steamcmd.remove_job(id).await?;
+
+    audit
+        .record(AuditEvent::new(AuditAction::SteamCmdJobRemove).target("steamcmd_session", id))
+        .await;
+
+    Ok(())
+}
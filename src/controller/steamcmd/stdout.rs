@@ -1,42 +1,74 @@
-use crate::{auth::guards::AccessTokenGuard, state::steamcmd::SteamCMD};
+use crate::{
+    auth::guards::AccessTokenGuard,
+    controller,
+    state::{
+        console::Followed,
+        steamcmd::{SteamCMD, SteamCmdSession},
+    },
+};
 use rocket::{
     get,
     response::stream::{Event, EventStream},
     Shutdown, State,
 };
+use tokio::sync::broadcast::error::RecvError;
 
-#[get("/stdout")]
-pub async fn stdout(
-    steamcmd: &State<SteamCMD>,
-    mut shutdown: Shutdown,
-    _auth_guard: AccessTokenGuard,
-) -> EventStream![] {
-    let mut rx = steamcmd.subscribe();
-    let last_lines = steamcmd.get_last_lines().await;
+/// Replay a session's history, then follow its live output. Recognised events such as
+/// download progress are sent as JSON `steamcmd` events between the raw lines. A client that
+/// falls behind catches up from the history; lines that were dropped from it before they could
+/// be sent are announced with a `resync` event.
+async fn follow(session: &SteamCmdSession, mut shutdown: Shutdown) -> EventStream![] {
+    let mut lines = session.follow(None);
+    let mut events = session.subscribe_events();
 
     EventStream! {
-        // Send all cached lines from history
-        for line in last_lines {
-            yield Event::data(line);
-        }
-
         loop {
             tokio::select! {
                 _ = &mut shutdown => {
                     break;
                 },
-                msg = rx.recv() => {
-                    match msg {
-                        Ok(msg) => {
-                            yield Event::data(msg)
-                        },
-                        Err(e) => {
-                            println!("Error receiving stdout message: {}", e);
-                            break
-                        },
+                followed = lines.recv() => {
+                    match followed {
+                        Some(Followed::Line(line)) => yield Event::data(line.text),
+                        Some(Followed::Resync { missed }) => {
+                            yield Event::json(&serde_json::json!({ "missed": missed })).event("resync")
+                        }
+                        Some(Followed::Reset) => continue,
+                        None => break,
+                    }
+                }
+                event = events.recv() => {
+                    match event {
+                        Ok(event) => yield Event::json(&event).event("steamcmd"),
+                        // Lagging behind only loses events; the raw lines still have everything
+                        Err(RecvError::Lagged(_)) => continue,
+                        Err(RecvError::Closed) => break,
                     }
                 }
             }
         }
     }
 }
+
+#[get("/stdout")]
+pub async fn stdout(
+    steamcmd: &State<SteamCMD>,
+    shutdown: Shutdown,
+    _auth_guard: AccessTokenGuard,
+) -> Result<EventStream![], controller::Error> {
+    let session = steamcmd
+        .session(crate::state::steamcmd::GLOBAL_SESSION_ID)
+        .await?;
+    Ok(follow(&session, shutdown).await)
+}
+
+#[get("/sessions/<id>/stdout")]
+pub async fn session_stdout(
+    id: &str,
+    steamcmd: &State<SteamCMD>,
+    shutdown: Shutdown,
+    _auth_guard: AccessTokenGuard,
+) -> Result<EventStream![], controller::Error> {
+    let session = steamcmd.session(id).await?;
+    Ok(follow(&session, shutdown).await)
+}
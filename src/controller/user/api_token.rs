@@ -0,0 +1,66 @@
+use crate::{
+    auth::guards::AccessTokenGuard,
+    controller, dto,
+    models::audit::AuditAction,
+    service::{self, audit::AuditEvent},
+};
+use rocket::{delete, get, post, response::status::Created, serde::json::Json};
+
+#[get("/tokens")]
+pub async fn list(
+    auth_guard: AccessTokenGuard,
+    token_service: service::api_token::ApiTokens,
+) -> Result<Json<Vec<dto::api_token::ApiTokenMetadata>>, controller::Error> {
+    auth_guard.require_session()?;
+
+    let tokens = token_service.list(auth_guard.user_id).await?;
+    Ok(Json(tokens))
+}
+
+#[post("/tokens", data = "<data>")]
+pub async fn create(
+    auth_guard: AccessTokenGuard,
+    data: Json<dto::api_token::NewApiToken>,
+    token_service: service::api_token::ApiTokens,
+    audit: service::audit::Audit,
+) -> Result<Created<Json<dto::api_token::CreatedApiToken>>, controller::Error> {
+    auth_guard.require_session()?;
+
+    let token = token_service
+        .create(auth_guard.user_id, data.into_inner())
+        .await?;
+
+    audit
+        .record(
+            AuditEvent::new(AuditAction::ApiTokenCreate)
+                .target("api_token", token.metadata.id)
+                .details(serde_json::json!({
+                    "name": token.metadata.name,
+                    "scopes": token.metadata.scopes,
+                    "expiresAt": token.metadata.expires_at,
+                })),
+        )
+        .await;
+
+    Ok(Created::new("/api/user/tokens").body(Json(token)))
+}
+
+#[delete("/tokens/<id>")]
+pub async fn revoke(
+    id: i32,
+    auth_guard: AccessTokenGuard,
+    token_service: service::api_token::ApiTokens,
+    audit: service::audit::Audit,
+) -> Result<(), controller::Error> {
+    auth_guard.require_session()?;
+
+    token_service
+        .revoke(auth_guard.user_id, auth_guard.role, id)
+        .await?;
+
+    audit
+        .record(AuditEvent::new(AuditAction::ApiTokenRevoke).target("api_token", id))
+        .await;
+
+    Ok(())
+}
@@ -1,4 +1,9 @@
-use crate::{auth, dto::user, models::user::UserRole, service};
+use crate::{
+    auth,
+    dto::user,
+    models::{audit::AuditAction, user::UserRole},
+    service::{self, audit::AuditEvent},
+};
 use rocket::post;
 
 #[post("/create", data = "<new_user>")]
@@ -6,6 +11,7 @@ pub async fn create_user(
     _admin: auth::guards::AdminGuard,
     new_user: rocket::serde::json::Json<user::NewWithRole>,
     user_service: service::user::User,
+    audit: service::audit::Audit,
 ) -> Result<auth::response::AuthResponse, crate::controller::Error> {
     let created_id = user_service.new_user_with_role(new_user.0).await?;
 
@@ -16,6 +22,14 @@ pub async fn create_user(
     let role =
         UserRole::try_from(user_model.role).map_err(|_| service::user::UserError::HashError)?;
 
+    audit
+        .record(
+            AuditEvent::new(AuditAction::UserCreate)
+                .target("user", user_model.id)
+                .details(serde_json::json!({ "username": user_model.name, "role": role })),
+        )
+        .await;
+
     let token_pair = auth::generate_tokens(user_model.id, user_model.name.clone(), role)
         .map_err(|_| service::user::UserError::HashError)?;
 
@@ -31,6 +45,7 @@ pub async fn create_user(
 pub async fn onboarding(
     new_user: rocket::serde::json::Json<user::New>,
     user_service: service::user::User,
+    audit: service::audit::Audit,
 ) -> Result<auth::response::AuthResponse, crate::controller::Error> {
     // Check if any admin accounts exist
     if user_service.has_admin().await? {
@@ -57,6 +72,16 @@ pub async fn onboarding(
     let token_pair = auth::generate_tokens(user_model.id, user_model.name.clone(), role)
         .map_err(|_| service::user::UserError::HashError)?;
 
+    audit
+        .record_for(
+            Some(user_model.id),
+            &user_model.name,
+            AuditEvent::new(AuditAction::UserCreate)
+                .target("user", user_model.id)
+                .details(serde_json::json!({ "username": user_model.name, "role": role, "onboarding": true })),
+        )
+        .await;
+
     Ok(auth::response::AuthResponse::new(
         token_pair.access_token,
         user_model.id,
@@ -1,22 +1,140 @@
-use crate::{auth, dto::user, models::user::UserRole, service};
-use rocket::post;
+use crate::{
+    auth::{self, TokenType},
+    dto::user,
+    models::{audit::AuditAction, user::UserRole},
+    service::{self, audit::AuditEvent},
+    state::mfa_attempts::MfaAttempts,
+};
+use rocket::{post, serde::json::Json, State};
 
 #[post("/login", data = "<credentials>")]
 pub async fn login(
-    credentials: rocket::serde::json::Json<user::Login>,
+    credentials: Json<user::Login>,
     user_service: service::user::User,
-) -> Result<auth::response::AuthResponse, crate::controller::Error> {
-    let user_model = user_service.find_by_username(&credentials.username).await?;
+    mfa_service: service::mfa::Mfa,
+    audit: service::audit::Audit,
+) -> Result<auth::response::LoginResponse, crate::controller::Error> {
+    let user_model = match user_service.find_by_username(&credentials.username).await {
+        Ok(user_model) => user_model,
+        Err(e) => {
+            audit
+                .record_for(
+                    None,
+                    &credentials.username,
+                    AuditEvent::new(AuditAction::LoginFailed)
+                        .failed()
+                        .details(serde_json::json!({ "reason": "unknown_user" })),
+                )
+                .await;
+            return Err(e.into());
+        }
+    };
 
     // Verify password
-    user_service.verify_password(&credentials.password, &user_model.password_hash)?;
+    if let Err(e) = user_service.verify_password(&credentials.password, &user_model.password_hash) {
+        audit
+            .record_for(
+                Some(user_model.id),
+                &user_model.name,
+                AuditEvent::new(AuditAction::LoginFailed)
+                    .failed()
+                    .details(serde_json::json!({ "reason": "invalid_password" })),
+            )
+            .await;
+        return Err(e.into());
+    }
+
+    let role =
+        UserRole::try_from(user_model.role).map_err(|_| service::user::UserError::HashError)?;
+
+    // Users with TOTP enabled (or required by policy) only get a challenge at this point
+    if let Some(token_type) = mfa_service.login_challenge(&user_model, role).await? {
+        let enrollment_required = token_type == TokenType::MfaEnrollment;
+        let challenge_token =
+            auth::generate_mfa_challenge(user_model.id, user_model.name, role, token_type)
+                .map_err(|_| service::user::UserError::HashError)?;
+
+        return Ok(auth::response::LoginResponse::MfaChallenge(
+            user::MfaChallenge {
+                mfa_required: true,
+                enrollment_required,
+                challenge_token,
+            },
+        ));
+    }
 
     // Generate tokens
-    let role = UserRole::try_from(user_model.role)
+    let token_pair = auth::generate_tokens(user_model.id, user_model.name.clone(), role)
         .map_err(|_| service::user::UserError::HashError)?;
+
+    audit
+        .record_for(
+            Some(user_model.id),
+            &user_model.name,
+            AuditEvent::new(AuditAction::Login),
+        )
+        .await;
+
+    Ok(auth::response::LoginResponse::Authenticated(
+        auth::response::AuthResponse::new(
+            token_pair.access_token,
+            user_model.id,
+            user_model.name,
+            token_pair.refresh_token,
+        ),
+    ))
+}
+
+#[post("/login/mfa", data = "<data>")]
+pub async fn login_mfa(
+    data: Json<user::MfaLogin>,
+    attempts: &State<MfaAttempts>,
+    user_service: service::user::User,
+    mfa_service: service::mfa::Mfa,
+    audit: service::audit::Audit,
+) -> Result<auth::response::AuthResponse, crate::controller::Error> {
+    let claims = auth::verify_token(&data.challenge_token)
+        .ok()
+        .filter(|claims| {
+            matches!(
+                claims.token_type,
+                TokenType::MfaChallenge | TokenType::MfaEnrollment
+            )
+        })
+        .ok_or(auth::guards::AuthError::InvalidToken)?;
+    if !attempts.begin(claims.sub) {
+        return Err(auth::guards::AuthError::TooManyMfaAttempts.into());
+    }
+
+    if let Err(e) = mfa_service.verify_code(claims.sub, &data.code).await {
+        audit
+            .record_for(
+                Some(claims.sub),
+                &claims.username,
+                AuditEvent::new(AuditAction::LoginFailed)
+                    .failed()
+                    .details(serde_json::json!({ "reason": "invalid_mfa_code" })),
+            )
+            .await;
+        return Err(e.into());
+    }
+    attempts.succeeded(claims.sub);
+
+    let user_model = user_service.find_by_id(claims.sub).await?;
+    let role =
+        UserRole::try_from(user_model.role).map_err(|_| service::user::UserError::HashError)?;
+
     let token_pair = auth::generate_tokens(user_model.id, user_model.name.clone(), role)
         .map_err(|_| service::user::UserError::HashError)?;
 
+    audit
+        .record_for(
+            Some(user_model.id),
+            &user_model.name,
+            AuditEvent::new(AuditAction::Login).details(serde_json::json!({ "mfa": true })),
+        )
+        .await;
+
     Ok(auth::response::AuthResponse::new(
         token_pair.access_token,
         user_model.id,
@@ -35,8 +153,9 @@ pub async fn refresh_token(
         .await
         .map_err(|_| auth::guards::AuthError::InvalidToken)?;
 
-    let token_pair = auth::generate_tokens(user_model.id, user_model.name.clone(), token_guard.role)
-        .map_err(|_| auth::guards::AuthError::InvalidToken)?;
+    let token_pair =
+        auth::generate_tokens(user_model.id, user_model.name.clone(), token_guard.role)
+            .map_err(|_| auth::guards::AuthError::InvalidToken)?;
 
     Ok(auth::response::AuthResponse::new(
         token_pair.access_token,
@@ -1,13 +1,25 @@
+use crate::{
+    controller,
+    models::audit::AuditAction,
+    service::{self, audit::AuditEvent},
+};
 use rocket::{
     http::{Cookie, CookieJar},
     post,
 };
 
 #[post("/logout")]
-pub fn logout(cookies: &CookieJar) {
+pub async fn logout(
+    cookies: &CookieJar<'_>,
+    audit: service::audit::Audit,
+) -> Result<(), controller::Error> {
     // Create an expired cookie to delete the refresh token
     let mut cookie = Cookie::new("refreshToken", "");
     cookie.set_path("/");
 
     cookies.remove(cookie);
+
+    audit.record(AuditEvent::new(AuditAction::Logout)).await;
+
+    Ok(())
 }
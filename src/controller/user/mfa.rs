@@ -0,0 +1,119 @@
+use crate::{
+    auth::guards::{AccessTokenGuard, AdminGuard, MfaEnrollmentGuard},
+    controller, dto,
+    models::audit::AuditAction,
+    service::{self, audit::AuditEvent},
+};
+use rocket::{get, post, put, serde::json::Json};
+
+#[get("/mfa/status")]
+pub async fn status(
+    auth_guard: AccessTokenGuard,
+    mfa_service: service::mfa::Mfa,
+) -> Result<Json<dto::user::MfaStatus>, controller::Error> {
+    let status = mfa_service
+        .status(auth_guard.user_id, auth_guard.role)
+        .await?;
+    Ok(Json(status))
+}
+
+#[post("/mfa/enroll")]
+pub async fn enroll(
+    auth_guard: MfaEnrollmentGuard,
+    mfa_service: service::mfa::Mfa,
+) -> Result<Json<dto::user::MfaEnrollment>, controller::Error> {
+    let enrollment = mfa_service.begin_enrollment(auth_guard.user_id).await?;
+    Ok(Json(enrollment))
+}
+
+#[post("/mfa/activate", data = "<data>")]
+pub async fn activate(
+    auth_guard: MfaEnrollmentGuard,
+    data: Json<dto::user::MfaCode>,
+    mfa_service: service::mfa::Mfa,
+    audit: service::audit::Audit,
+) -> Result<Json<dto::user::RecoveryCodes>, controller::Error> {
+    let codes = mfa_service.activate(auth_guard.user_id, &data.code).await?;
+
+    // Enrollment may happen with a challenge token during login, before there is a session
+    audit
+        .record_for(
+            Some(auth_guard.user_id),
+            &auth_guard.username,
+            AuditEvent::new(AuditAction::MfaEnable).target("user", auth_guard.user_id),
+        )
+        .await;
+
+    Ok(Json(codes))
+}
+
+#[post("/mfa/recovery_codes", data = "<data>")]
+pub async fn regenerate_recovery_codes(
+    auth_guard: AccessTokenGuard,
+    data: Json<dto::user::MfaCode>,
+    mfa_service: service::mfa::Mfa,
+    audit: service::audit::Audit,
+) -> Result<Json<dto::user::RecoveryCodes>, controller::Error> {
+    auth_guard.require_session()?;
+
+    let codes = mfa_service
+        .regenerate_recovery_codes(auth_guard.user_id, &data.code)
+        .await?;
+
+    audit
+        .record(
+            AuditEvent::new(AuditAction::MfaRecoveryCodesRegenerate)
+                .target("user", auth_guard.user_id),
+        )
+        .await;
+
+    Ok(Json(codes))
+}
+
+#[post("/mfa/disable", data = "<data>")]
+pub async fn disable(
+    auth_guard: AccessTokenGuard,
+    data: Json<dto::user::MfaCode>,
+    mfa_service: service::mfa::Mfa,
+    audit: service::audit::Audit,
+) -> Result<(), controller::Error> {
+    auth_guard.require_session()?;
+
+    mfa_service
+        .disable(auth_guard.user_id, auth_guard.role, &data.code)
+        .await?;
+
+    audit
+        .record(AuditEvent::new(AuditAction::MfaDisable).target("user", auth_guard.user_id))
+        .await;
+
+    Ok(())
+}
+
+#[get("/mfa/policy")]
+pub async fn get_policy(
+    _admin: AdminGuard,
+    mfa_service: service::mfa::Mfa,
+) -> Result<Json<dto::user::MfaPolicy>, controller::Error> {
+    let policy = mfa_service.admin_policy().await?;
+    Ok(Json(policy))
+}
+
+#[put("/mfa/policy", data = "<data>")]
+pub async fn set_policy(
+    admin: AdminGuard,
+    data: Json<dto::user::MfaPolicy>,
+    mfa_service: service::mfa::Mfa,
+    audit: service::audit::Audit,
+) -> Result<(), controller::Error> {
+    let before = mfa_service.admin_policy().await?;
+    let event = AuditEvent::new(AuditAction::MfaPolicyUpdate).diff(&before, &data.0);
+
+    mfa_service
+        .set_admin_policy(data.into_inner(), admin.user_id)
+        .await?;
+
+    audit.record(event).await;
+
+    Ok(())
+}
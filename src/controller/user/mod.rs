@@ -1,8 +1,12 @@
+mod api_token;
 mod by_id;
 mod create;
 mod has_admin;
 mod login;
 mod logout;
+mod mfa;
+mod oidc;
+mod stream_ticket;
 mod whoami;
 
 use rocket::{routes, Route};
@@ -17,10 +21,25 @@ pub fn get_all_routes() -> Vec<(&'static str, Vec<Route>)> {
             create::create_user,
             create::onboarding,
             login::login,
+            login::login_mfa,
             login::refresh_token,
             whoami::whoami,
             by_id::get_user_by_id,
-            logout::logout
+            logout::logout,
+            mfa::status,
+            mfa::enroll,
+            mfa::activate,
+            mfa::regenerate_recovery_codes,
+            mfa::disable,
+            mfa::get_policy,
+            mfa::set_policy,
+            api_token::list,
+            api_token::create,
+            api_token::revoke,
+            stream_ticket::stream_ticket,
+            oidc::status,
+            oidc::authorize,
+            oidc::callback
         ],
     )]
 }
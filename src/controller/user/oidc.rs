@@ -0,0 +1,91 @@
+use crate::{
+    auth, controller, dto,
+    models::audit::AuditAction,
+    service::{self, audit::AuditEvent, sso::SsoError},
+    state::oidc::OidcLogins,
+};
+use rocket::{get, post, serde::json::Json, State};
+
+#[get("/oidc/status")]
+pub fn status(logins: &State<OidcLogins>) -> Json<dto::user::OidcStatus> {
+    let client = logins.client().ok();
+
+    Json(dto::user::OidcStatus {
+        enabled: client.is_some(),
+        display_name: client.map(|c| c.config().display_name.clone()),
+    })
+}
+
+#[get("/oidc/authorize")]
+pub async fn authorize(
+    logins: &State<OidcLogins>,
+) -> Result<Json<dto::user::OidcAuthorization>, controller::Error> {
+    let authorization_url = logins.begin().await.map_err(SsoError::from)?;
+
+    Ok(Json(dto::user::OidcAuthorization { authorization_url }))
+}
+
+/// Complete a login after the provider redirects back with `code` and `state`.
+/// Second factors are left to the identity provider, so no MFA challenge is issued here.
+#[post("/oidc/callback", data = "<data>")]
+pub async fn callback(
+    data: Json<dto::user::OidcCallback>,
+    logins: &State<OidcLogins>,
+    sso_service: service::sso::Sso,
+    audit: service::audit::Audit,
+) -> Result<auth::response::AuthResponse, controller::Error> {
+    let claims = logins
+        .complete(&data.state, &data.code)
+        .await
+        .map_err(SsoError::from)?;
+    let identity =
+        serde_json::json!({ "method": "sso", "issuer": claims.iss, "subject": claims.sub });
+
+    let provisioned = match logins
+        .client()
+        .map_err(SsoError::from)?
+        .config()
+        .map_role(&claims)
+    {
+        Some(role) => sso_service
+            .provision(&claims, role)
+            .await
+            .map(|u| (u, role)),
+        None => Err(SsoError::RoleNotMapped),
+    };
+    let (user_model, role) = match provisioned {
+        Ok(provisioned) => provisioned,
+        Err(e) => {
+            let mut details = identity;
+            details["reason"] = e.to_string().into();
+            audit
+                .record_for(
+                    None,
+                    claims.username(),
+                    AuditEvent::new(AuditAction::LoginFailed)
+                        .failed()
+                        .details(details),
+                )
+                .await;
+            return Err(e.into());
+        }
+    };
+
+    audit
+        .record_for(
+            Some(user_model.id),
+            &user_model.name,
+            AuditEvent::new(AuditAction::SsoLogin).details(identity),
+        )
+        .await;
+
+    let token_pair = auth::generate_tokens(user_model.id, user_model.name.clone(), role)
+        .map_err(|_| service::user::UserError::HashError)?;
+
+    Ok(auth::response::AuthResponse::new(
+        token_pair.access_token,
+        user_model.id,
+        user_model.name,
+        token_pair.refresh_token,
+    ))
+}
@@ -0,0 +1,19 @@
+use crate::{auth::guards::AccessTokenGuard, controller, dto, state::stream_ticket::StreamTickets};
+use rocket::{post, serde::json::Json, State};
+
+#[post("/stream_ticket", data = "<data>")]
+pub fn stream_ticket(
+    auth_guard: AccessTokenGuard,
+    data: Json<dto::stream_ticket::NewStreamTicket>,
+    tickets: &State<StreamTickets>,
+) -> Result<Json<dto::stream_ticket::StreamTicket>, controller::Error> {
+    // Tickets exist for browsers; API token clients can send the Authorization header directly
+    auth_guard.require_session()?;
+
+    let (ticket, ttl) = tickets.issue(auth_guard, &data.path)?;
+
+    Ok(Json(dto::stream_ticket::StreamTicket {
+        ticket,
+        expires_in_seconds: ttl.as_secs(),
+    }))
+}
@@ -0,0 +1,56 @@
+use crate::entity::api_token::Model;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+#[derive(Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct NewApiToken {
+    pub name: String,
+    /// Scopes such as `game_schema:read`, `steamcmd:write` or `*`
+    pub scopes: Vec<String>,
+    /// Optional expiry; tokens without one stay valid until revoked
+    #[ts(type = "string | null")]
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ApiTokenMetadata {
+    pub id: i32,
+    pub name: String,
+    /// First characters of the token, to help users recognise it
+    pub prefix: String,
+    pub scopes: Vec<String>,
+    #[ts(type = "string | null")]
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[ts(type = "string | null")]
+    pub last_used_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[ts(type = "string")]
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<Model> for ApiTokenMetadata {
+    fn from(model: Model) -> Self {
+        ApiTokenMetadata {
+            id: model.id,
+            name: model.name,
+            prefix: model.token_prefix,
+            scopes: serde_json::from_value(model.scopes).unwrap_or_default(),
+            expires_at: model.expires_at,
+            last_used_at: model.last_used_at,
+            created_at: model.created_at,
+        }
+    }
+}
+
+/// Returned once on creation; the plain token is never shown again
+#[derive(Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct CreatedApiToken {
+    pub token: String,
+    #[serde(flatten)]
+    pub metadata: ApiTokenMetadata,
+}
@@ -0,0 +1,80 @@
+use crate::entity::audit_log::Model;
+use rocket::FromForm;
+use serde::Serialize;
+use ts_rs::TS;
+
+/// Filters for the audit log query and CSV export. Timestamps are RFC 3339.
+#[derive(FromForm, TS, Default)]
+#[ts(export)]
+pub struct AuditLogQuery {
+    #[field(name = "userId")]
+    #[ts(rename = "userId")]
+    pub user_id: Option<i32>,
+    pub action: Option<String>,
+    #[field(name = "targetType")]
+    #[ts(rename = "targetType")]
+    pub target_type: Option<String>,
+    #[field(name = "targetId")]
+    #[ts(rename = "targetId")]
+    pub target_id: Option<String>,
+    pub success: Option<bool>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    #[ts(type = "number | null")]
+    pub page: Option<u64>,
+    #[field(name = "perPage")]
+    #[ts(rename = "perPage", type = "number | null")]
+    pub per_page: Option<u64>,
+}
+
+#[derive(Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct AuditLogEntry {
+    pub id: i32,
+    #[ts(type = "string")]
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub user_id: Option<i32>,
+    pub username: Option<String>,
+    pub api_token_id: Option<i32>,
+    pub action: String,
+    pub target_type: Option<String>,
+    pub target_id: Option<String>,
+    pub success: bool,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    #[ts(type = "Record<string, any> | null")]
+    pub details: Option<serde_json::Value>,
+}
+
+impl From<Model> for AuditLogEntry {
+    fn from(model: Model) -> Self {
+        Self {
+            id: model.id,
+            created_at: model.created_at,
+            user_id: model.user_id,
+            username: model.username,
+            api_token_id: model.api_token_id,
+            action: model.action,
+            target_type: model.target_type,
+            target_id: model.target_id,
+            success: model.success,
+            ip: model.ip,
+            user_agent: model.user_agent,
+            details: model.details,
+        }
+    }
+}
+
+#[derive(Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct AuditLogPage {
+    pub entries: Vec<AuditLogEntry>,
+    #[ts(type = "number")]
+    pub total: u64,
+    #[ts(type = "number")]
+    pub page: u64,
+    #[ts(type = "number")]
+    pub per_page: u64,
+}
@@ -0,0 +1,37 @@
+use crate::dto::{instance::ConsoleSpan, steamcmd::SteamCmdEvent};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// Sent by the server over a console WebSocket
+#[derive(Serialize, TS, Debug)]
+#[serde(tag = "type", rename_all = "camelCase")]
+#[ts(export)]
+pub enum ConsoleMessage {
+    /// A line of output. Reconnect with `?after=<seq>` to pick up after it.
+    Line {
+        #[ts(type = "number")]
+        seq: u64,
+        text: String,
+        /// Only when the socket was opened with `spans=true`
+        spans: Option<Vec<ConsoleSpan>>,
+    },
+    /// `missed` lines before the next one were dropped from the history and cannot be sent
+    Resync {
+        #[ts(type = "number")]
+        missed: u64,
+    },
+    /// The cursor passed as `after` is from before a restart of the panel; clear the console,
+    /// the whole history follows
+    Reset,
+    /// Something SteamCMD reported, see [`SteamCmdEvent`]
+    Event { event: SteamCmdEvent },
+    /// A command sent by the client was rejected
+    Error { message: String },
+}
+
+/// Sent by the client to type a line into the console
+#[derive(Deserialize, TS)]
+#[ts(export)]
+pub struct ConsoleInput {
+    pub command: String,
+}
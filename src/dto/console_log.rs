@@ -0,0 +1,48 @@
+use rocket::FromForm;
+use serde::Serialize;
+use ts_rs::TS;
+
+/// Filters for reading historical console output. Timestamps are RFC 3339.
+#[derive(FromForm, TS, Default)]
+#[ts(export)]
+pub struct ConsoleLogQuery {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    /// Only lines containing this text, ignoring case
+    pub search: Option<String>,
+    /// Treat `search` as a regular expression
+    pub regex: Option<bool>,
+    /// Only lines from this SteamCMD session, e.g. `global` or `job-3`
+    pub source: Option<String>,
+    /// Return the newest lines; `offset` then counts back from the end
+    pub tail: Option<bool>,
+    #[ts(type = "number | null")]
+    pub offset: Option<u64>,
+    #[ts(type = "number | null")]
+    pub limit: Option<u64>,
+}
+
+#[derive(Serialize, TS, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ConsoleLogLine {
+    #[ts(type = "string")]
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub source: Option<String>,
+    pub text: String,
+}
+
+/// Matching lines in chronological order
+#[derive(Serialize, TS, Debug)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ConsoleLogPage {
+    pub lines: Vec<ConsoleLogLine>,
+    /// Number of lines matching the filters
+    #[ts(type = "number")]
+    pub total: u64,
+    #[ts(type = "number")]
+    pub offset: u64,
+    #[ts(type = "number")]
+    pub limit: u64,
+}
@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum FileKind {
+    File,
+    Directory,
+    Symlink,
+}
+
+/// A file or directory below the instance root
+#[derive(Serialize, Debug, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct FileEntry {
+    pub name: String,
+    /// Relative to the instance root, `/`-separated
+    pub path: String,
+    pub kind: FileKind,
+    #[ts(type = "number")]
+    pub size: u64,
+    #[ts(type = "string | null")]
+    pub modified: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct FileListing {
+    pub path: String,
+    /// Directories first, then by name
+    pub entries: Vec<FileEntry>,
+}
+
+/// A text file opened for editing
+#[derive(Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct FileContent {
+    pub path: String,
+    pub content: String,
+    #[ts(type = "number")]
+    pub size: u64,
+    #[ts(type = "string | null")]
+    pub modified: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct FileRename {
+    pub from: String,
+    pub to: String,
+}
@@ -0,0 +1,402 @@
+use crate::entity::{game_config, instance_mod};
+use crate::models::instance::{InstanceStatus, SteamPlatform};
+use crate::schema;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+#[derive(Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct NewInstance {
+    pub instance_name: String,
+    pub schema_id: i32,
+    #[ts(type = "Record<string, any>")]
+    pub config: schema::GameConfig,
+}
+
+#[derive(Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct InstanceUpdate {
+    pub instance_name: String,
+    #[ts(type = "Record<string, any>")]
+    pub config: schema::GameConfig,
+}
+
+/// A game server instance: a schema plus the config values it runs with
+#[derive(Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct Instance {
+    pub id: i32,
+    pub instance_name: String,
+    pub schema_id: i32,
+    #[ts(type = "Record<string, any>")]
+    pub config: schema::GameConfig,
+    pub status: InstanceStatus,
+    #[ts(type = "string")]
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    #[ts(type = "string")]
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+    pub limits: ResourceLimits,
+}
+
+impl From<game_config::Model> for Instance {
+    fn from(model: game_config::Model) -> Self {
+        Instance {
+            limits: ResourceLimits::from(&model),
+            id: model.id,
+            instance_name: model.instance_name,
+            schema_id: model.schema_id,
+            config: serde_json::from_value(model.config_json).unwrap_or_default(),
+            status: InstanceStatus::from(model.status),
+            created_at: model.created_at,
+            updated_at: model.updated_at,
+        }
+    }
+}
+
+#[derive(Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct InstanceMod {
+    #[ts(type = "number")]
+    pub workshop_item_id: i64,
+    pub enabled: bool,
+    pub load_order: i32,
+    /// When the mod was last downloaded and placed into the instance's mod directories
+    #[ts(type = "string | null")]
+    pub installed_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl From<instance_mod::Model> for InstanceMod {
+    fn from(model: instance_mod::Model) -> Self {
+        InstanceMod {
+            workshop_item_id: model.workshop_item_id,
+            enabled: model.enabled,
+            load_order: model.load_order,
+            installed_at: model.installed_at,
+        }
+    }
+}
+
+/// One entry of a mod list replacement; list position is the load order
+#[derive(Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct InstanceModEntry {
+    #[ts(type = "number")]
+    pub workshop_item_id: i64,
+    #[serde(default = "default_enabled")]
+    #[ts(as = "Option<bool>", optional)]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+#[derive(Deserialize, Default, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ModInstall {
+    /// Stored Steam credential to download with; anonymous when omitted
+    pub credential_id: Option<i32>,
+}
+
+/// The argv an instance would be started with
+#[derive(Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct InstanceCommand {
+    pub command: Vec<String>,
+}
+
+/// Daily UTC time range, as "HH:MM", in which automatic updates may restart the server.
+/// A range whose end is before its start wraps past midnight.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct MaintenanceWindow {
+    pub start: String,
+    pub end: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct UpdatePolicy {
+    /// Stop, update and restart the server when a new build is found
+    pub auto_update: bool,
+    /// Only update automatically inside this window; any time when omitted
+    pub maintenance_window: Option<MaintenanceWindow>,
+    /// Stored Steam credential used for updates; anonymous when omitted
+    pub credential_id: Option<i32>,
+}
+
+/// Installed game build of an instance compared with the latest published one
+#[derive(Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct InstanceUpdateStatus {
+    pub instance_id: i32,
+    pub schema_id: i32,
+    #[ts(type = "number | null")]
+    pub installed_build_id: Option<i64>,
+    #[ts(type = "number | null")]
+    pub available_build_id: Option<i64>,
+    pub update_available: bool,
+    #[ts(type = "string | null")]
+    pub checked_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// When the server files were last updated through the panel
+    #[ts(type = "string | null")]
+    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub last_error: Option<String>,
+    pub policy: UpdatePolicy,
+    pub install_options: InstallOptions,
+}
+
+#[derive(Deserialize, Default, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct UpdateRequest {
+    /// Stored Steam credential to update with; falls back to the instance's update policy
+    pub credential_id: Option<i32>,
+}
+
+/// How `app_update` installs an instance's server files. The beta password is never returned.
+#[derive(Serialize, Clone, Debug, Default, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct InstallOptions {
+    /// Beta branch to install; the default public branch when omitted
+    pub branch: Option<String>,
+    pub has_beta_password: bool,
+    /// Verify all installed files on every update
+    pub validate: bool,
+    /// Download files for another operating system than the host's
+    pub platform: Option<SteamPlatform>,
+}
+
+#[derive(Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct InstallOptionsUpdate {
+    pub branch: Option<String>,
+    /// Password of a private beta branch. Omit to keep the stored one, or send an empty
+    /// string to remove it.
+    pub beta_password: Option<String>,
+    #[serde(default)]
+    #[ts(as = "Option<bool>", optional)]
+    pub validate: bool,
+    pub platform: Option<SteamPlatform>,
+}
+
+/// A run of console text sharing one style. Colours are ANSI names such as `red` or
+/// `brightBlue`, or `#rrggbb` for 256-colour and true colour output.
+#[derive(Serialize, Clone, Debug, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ConsoleSpan {
+    pub text: String,
+    pub fg: Option<String>,
+    pub bg: Option<String>,
+    pub bold: bool,
+    pub dim: bool,
+    pub italic: bool,
+    pub underline: bool,
+}
+
+#[derive(Deserialize, TS)]
+#[ts(export)]
+pub struct RconCommand {
+    pub command: String,
+}
+
+#[derive(Serialize, TS)]
+#[ts(export)]
+pub struct RconOutput {
+    pub output: String,
+}
+
+/// Someone on a game server, as reported by A2S_PLAYER
+#[derive(Serialize, Clone, Debug, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct QueryPlayer {
+    pub name: String,
+    pub score: i32,
+    /// Seconds connected
+    pub duration: f32,
+}
+
+/// What a running game server last reported about itself over the Steam server queries.
+/// When a query fails the previous answer is kept with `online` unset and the reason in `error`.
+#[derive(Serialize, Clone, Debug, Default, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ServerQuery {
+    #[ts(type = "string")]
+    pub queried_at: chrono::DateTime<chrono::Utc>,
+    pub online: bool,
+    pub name: String,
+    pub map: String,
+    pub game: String,
+    pub version: String,
+    pub players: u8,
+    pub max_players: u8,
+    pub bots: u8,
+    pub player_list: Vec<QueryPlayer>,
+    pub rules: std::collections::BTreeMap<String, String>,
+    pub error: Option<String>,
+}
+
+/// Live state of an instance: its process and, while it runs, what the server reports
+#[derive(Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct InstanceRuntimeStatus {
+    pub status: InstanceStatus,
+    pub pid: Option<u32>,
+    #[ts(type = "string | null")]
+    pub started_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// `null` when the schema declares no query port or the server has not answered yet
+    pub query: Option<ServerQuery>,
+}
+
+/// A console line matched by one of the schema's triggers
+#[derive(Serialize, Clone, Debug, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct TriggerFired {
+    pub event: schema::server_config::TriggerEvent,
+    pub name: Option<String>,
+    /// The trigger's named capture groups that took part in the match
+    pub captures: std::collections::BTreeMap<String, String>,
+    pub line: String,
+    #[ts(type = "string")]
+    pub fired_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Caps on what an instance's server may use. Unset fields are unlimited. CPU quota is a
+/// percentage of one core; weight is the cgroup `cpu.weight`, 100 being the default share.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ResourceLimits {
+    pub memory_max_mb: Option<u32>,
+    pub cpu_quota_percent: Option<u32>,
+    pub cpu_weight: Option<u32>,
+    pub max_processes: Option<u32>,
+    /// -20 (highest priority) to 19
+    pub nice: Option<i32>,
+    pub max_open_files: Option<u32>,
+}
+
+impl From<&game_config::Model> for ResourceLimits {
+    fn from(model: &game_config::Model) -> Self {
+        let unsigned = |value: Option<i32>| value.map(|v| v.max(0) as u32);
+        ResourceLimits {
+            memory_max_mb: unsigned(model.memory_max_mb),
+            cpu_quota_percent: unsigned(model.cpu_quota_percent),
+            cpu_weight: unsigned(model.cpu_weight),
+            max_processes: unsigned(model.max_processes),
+            nice: model.nice,
+            max_open_files: unsigned(model.max_open_files),
+        }
+    }
+}
+
+/// A limit that is in force, and how
+#[derive(Serialize, Clone, Debug, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct AppliedLimit {
+    pub limit: String,
+    /// e.g. `cgroup memory.max` or `RLIMIT_NOFILE`
+    pub via: String,
+}
+
+/// A configured limit the host could not enforce
+#[derive(Serialize, Clone, Debug, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct SkippedLimit {
+    pub limit: String,
+    pub reason: String,
+}
+
+/// How often the running server ran into its cgroup limits
+#[derive(Serialize, Clone, Copy, Debug, Default, PartialEq, Eq, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct LimitViolations {
+    /// Times memory use reached the limit and had to be reclaimed
+    #[ts(type = "number")]
+    pub memory_max: u64,
+    /// Processes killed for running out of memory
+    #[ts(type = "number")]
+    pub oom_kills: u64,
+    /// Forks refused for reaching the process limit
+    #[ts(type = "number")]
+    pub pids_max: u64,
+    /// Scheduler periods the server was throttled for using up its CPU quota
+    #[ts(type = "number")]
+    pub cpu_throttled: u64,
+}
+
+/// What the current run of a server is actually held to
+#[derive(Serialize, Clone, Debug, Default, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct AppliedLimits {
+    /// The server's own cgroup, when the host delegates a cgroup v2 hierarchy to the panel
+    pub cgroup: Option<String>,
+    pub applied: Vec<AppliedLimit>,
+    pub skipped: Vec<SkippedLimit>,
+    pub violations: LimitViolations,
+}
+
+#[derive(Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct InstanceLimits {
+    pub limits: ResourceLimits,
+    /// `null` while the server is not running
+    pub applied: Option<AppliedLimits>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum LimitViolationKind {
+    MemoryMax,
+    OomKill,
+    PidsMax,
+}
+
+/// A server running into one of its cgroup limits since the last check
+#[derive(Serialize, Clone, Debug, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct LimitViolation {
+    pub kind: LimitViolationKind,
+    /// New occurrences since the last check
+    #[ts(type = "number")]
+    pub count: u64,
+    #[ts(type = "string")]
+    pub detected_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// A port the instance's server binds, as its config sets it
+#[derive(Serialize, Clone, Debug, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct InstancePort {
+    pub field: String,
+    pub role: schema::server_config::PortRole,
+    pub protocol: schema::server_config::PortProtocol,
+    pub port: u16,
+    /// Consecutive ports bound starting at `port`
+    pub count: u16,
+}
@@ -0,0 +1,90 @@
+use crate::entity::instance_metric;
+use rocket::FromForm;
+use serde::Serialize;
+use ts_rs::TS;
+
+/// Resource usage of a running game server and every process it spawned. CPU is a
+/// percentage of one core, so a server busy on two cores reports 200.
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct MetricsSample {
+    #[ts(type = "string")]
+    pub sampled_at: chrono::DateTime<chrono::Utc>,
+    pub cpu_percent: f64,
+    /// Resident memory
+    #[ts(type = "number")]
+    pub memory_bytes: u64,
+    #[ts(type = "number")]
+    pub threads: u64,
+    #[ts(type = "number")]
+    pub processes: u64,
+    /// Bytes read from and written to disk since the server started
+    #[ts(type = "number")]
+    pub read_bytes: u64,
+    #[ts(type = "number")]
+    pub write_bytes: u64,
+    pub read_bytes_per_sec: f64,
+    pub write_bytes_per_sec: f64,
+    /// Size of the instance directory, measured less often than the rest
+    #[ts(type = "number | null")]
+    pub disk_bytes: Option<u64>,
+}
+
+/// Range of the metrics history. Timestamps are RFC 3339 and default to the last hour.
+#[derive(FromForm, TS, Default)]
+#[ts(export)]
+pub struct MetricsHistoryQuery {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    /// Seconds per point, a multiple of 60. Picked from the range when left out.
+    #[ts(type = "number | null")]
+    pub resolution: Option<u32>,
+}
+
+/// Samples averaged over `resolutionSecs` from `recordedAt` on
+#[derive(Debug, Clone, PartialEq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct MetricsPoint {
+    #[ts(type = "string")]
+    pub recorded_at: chrono::DateTime<chrono::Utc>,
+    pub resolution_secs: u32,
+    pub samples: u32,
+    pub cpu_percent: f64,
+    pub cpu_percent_max: f64,
+    /// Peak resident memory
+    #[ts(type = "number")]
+    pub memory_bytes: u64,
+    #[ts(type = "number")]
+    pub threads: u64,
+    pub read_bytes_per_sec: f64,
+    pub write_bytes_per_sec: f64,
+    #[ts(type = "number | null")]
+    pub disk_bytes: Option<u64>,
+}
+
+impl From<instance_metric::Model> for MetricsPoint {
+    fn from(model: instance_metric::Model) -> Self {
+        Self {
+            recorded_at: model.recorded_at,
+            resolution_secs: model.resolution_secs.max(0) as u32,
+            samples: model.samples.max(0) as u32,
+            cpu_percent: model.cpu_percent,
+            cpu_percent_max: model.cpu_percent_max,
+            memory_bytes: model.memory_bytes.max(0) as u64,
+            threads: model.threads.max(0) as u64,
+            read_bytes_per_sec: model.read_bytes_per_sec,
+            write_bytes_per_sec: model.write_bytes_per_sec,
+            disk_bytes: model.disk_bytes.map(|bytes| bytes.max(0) as u64),
+        }
+    }
+}
+
+#[derive(Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct MetricsHistory {
+    pub resolution_secs: u32,
+    pub points: Vec<MetricsPoint>,
+}
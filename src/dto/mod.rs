@@ -1,2 +1,12 @@
+pub mod api_token;
+pub mod audit;
+pub mod console;
+pub mod console_log;
+pub mod files;
 pub mod game_schema;
+pub mod instance;
+pub mod metrics;
+pub mod player;
+pub mod steamcmd;
+pub mod stream_ticket;
 pub mod user;
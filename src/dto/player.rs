@@ -0,0 +1,127 @@
+use crate::entity::{player_action, player_session};
+use crate::models::player::PlayerAction;
+use rocket::FromForm;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// Filters for the player session history of an instance
+#[derive(FromForm, TS, Default)]
+#[ts(export)]
+pub struct PlayerSessionQuery {
+    /// Only players still on the server
+    pub online: Option<bool>,
+    /// Sessions of players whose name contains this text
+    pub name: Option<String>,
+    #[field(name = "steamId")]
+    #[ts(rename = "steamId")]
+    pub steam_id: Option<String>,
+    #[ts(type = "number | null")]
+    pub page: Option<u64>,
+    #[field(name = "perPage")]
+    #[ts(rename = "perPage", type = "number | null")]
+    pub per_page: Option<u64>,
+}
+
+/// A player's stay on a game server. `leftAt` is `null` while they are still on it.
+#[derive(Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct PlayerSession {
+    pub id: i32,
+    pub name: String,
+    pub steam_id: Option<String>,
+    #[ts(type = "string")]
+    pub joined_at: chrono::DateTime<chrono::Utc>,
+    #[ts(type = "string | null")]
+    pub left_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl From<player_session::Model> for PlayerSession {
+    fn from(model: player_session::Model) -> Self {
+        Self {
+            id: model.id,
+            name: model.name,
+            steam_id: model.steam_id,
+            joined_at: model.joined_at,
+            left_at: model.left_at,
+        }
+    }
+}
+
+#[derive(Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct PlayerSessionPage {
+    pub sessions: Vec<PlayerSession>,
+    #[ts(type = "number")]
+    pub total: u64,
+    #[ts(type = "number")]
+    pub page: u64,
+    #[ts(type = "number")]
+    pub per_page: u64,
+}
+
+/// Kick, ban or whitelist a player through the command template the schema declares
+#[derive(Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct PlayerModeration {
+    pub action: PlayerAction,
+    pub name: String,
+    pub steam_id: Option<String>,
+    pub reason: Option<String>,
+}
+
+/// A moderation action, with the command it sent and the moderator who took it
+#[derive(Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct PlayerActionEntry {
+    pub id: i32,
+    pub action: String,
+    pub player_name: String,
+    pub steam_id: Option<String>,
+    pub reason: Option<String>,
+    pub command: String,
+    pub user_id: Option<i32>,
+    pub username: Option<String>,
+    #[ts(type = "string")]
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<player_action::Model> for PlayerActionEntry {
+    fn from(model: player_action::Model) -> Self {
+        Self {
+            id: model.id,
+            action: model.action,
+            player_name: model.player_name,
+            steam_id: model.steam_id,
+            reason: model.reason,
+            command: model.command,
+            user_id: model.user_id,
+            username: model.username,
+            created_at: model.created_at,
+        }
+    }
+}
+
+/// A player banned or whitelisted through the panel, by their latest such action
+#[derive(Serialize, Clone, Debug, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct PlayerRestriction {
+    pub name: String,
+    pub steam_id: Option<String>,
+    pub reason: Option<String>,
+    pub username: Option<String>,
+    #[ts(type = "string")]
+    pub since: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Serialize, Debug, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct PlayerRestrictions {
+    pub banned: Vec<PlayerRestriction>,
+    pub whitelisted: Vec<PlayerRestriction>,
+}
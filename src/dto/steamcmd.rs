@@ -0,0 +1,167 @@
+use crate::entity::steam_credential::Model;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+#[derive(Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct NewSteamCredential {
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct SteamCredentialUpdate {
+    pub password: String,
+}
+
+/// A stored Steam account. The password is never returned.
+#[derive(Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct SteamCredential {
+    pub id: i32,
+    pub username: String,
+    #[ts(type = "string")]
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    #[ts(type = "string")]
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<Model> for SteamCredential {
+    fn from(model: Model) -> Self {
+        SteamCredential {
+            id: model.id,
+            username: model.username,
+            created_at: model.created_at,
+            updated_at: model.updated_at,
+        }
+    }
+}
+
+#[derive(Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct NewSteamCmdJob {
+    /// Stored credential to log in with; anonymous when omitted
+    pub credential_id: Option<i32>,
+    /// SteamCMD console commands run in order after login, e.g. `app_update 740 validate`
+    pub commands: Vec<String>,
+}
+
+#[derive(Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct SteamGuardCode {
+    pub code: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum SteamCmdSessionState {
+    /// Process started, login not yet confirmed
+    Starting,
+    /// Waiting for a Steam Guard code to be submitted
+    SteamGuardRequired,
+    /// Logged in; jobs are running their commands
+    Ready,
+    LoginFailed,
+    Exited,
+}
+
+/// Which Steam Guard code SteamCMD is asking for
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum SteamGuardPrompt {
+    /// Code mailed to the account's address
+    Email,
+    /// Code from the Steam mobile authenticator
+    TwoFactor,
+}
+
+#[derive(Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct SteamCmdSessionInfo {
+    pub id: String,
+    /// Steam account name, `anonymous` for anonymous logins
+    pub account: String,
+    pub state: SteamCmdSessionState,
+    pub steam_guard_prompt: Option<SteamGuardPrompt>,
+    #[ts(type = "string | null")]
+    pub started_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Set while the process is running
+    pub pid: Option<u32>,
+    /// Exit code of the most recent process; `null` if killed by a signal or still on the first run
+    pub exit_code: Option<i32>,
+    /// How often the process was respawned after exiting
+    pub restarts: u32,
+    /// Reason given by SteamCMD when the login failed
+    pub error: Option<String>,
+}
+
+/// Liveness of the global SteamCMD process, reported by `/api/health_detailed`
+#[derive(Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct SteamCmdStatus {
+    /// Whether a SteamCMD binary was found. Without one the server runs degraded.
+    pub installed: bool,
+    pub running: bool,
+    pub pid: Option<u32>,
+    #[ts(type = "number | null")]
+    pub uptime_seconds: Option<u64>,
+    pub last_exit_code: Option<i32>,
+    pub restarts: u32,
+}
+
+/// Something SteamCMD reported, recognised from its output. Sent next to the raw lines.
+#[derive(Serialize, Debug, Clone, PartialEq, TS)]
+#[serde(tag = "type", rename_all = "camelCase")]
+#[ts(export)]
+pub enum SteamCmdEvent {
+    LoggedIn,
+    LoginFailed {
+        reason: String,
+    },
+    SteamGuardRequired {
+        prompt: SteamGuardPrompt,
+    },
+    /// `Update state (0x61) downloading, progress: 42.13 (1234 / 2930)`
+    Progress {
+        /// Raw update state flags, e.g. `0x61`
+        code: u32,
+        /// What SteamCMD is doing, e.g. `downloading` or `verifying install`
+        state: String,
+        /// Percent done
+        progress: f64,
+        #[ts(type = "number")]
+        current: u64,
+        #[ts(type = "number")]
+        total: u64,
+    },
+    AppInstalled {
+        #[serde(rename = "appId")]
+        app_id: i32,
+    },
+    AppUpToDate {
+        #[serde(rename = "appId")]
+        app_id: i32,
+    },
+    ItemDownloaded {
+        #[serde(rename = "itemId")]
+        #[ts(type = "number")]
+        item_id: i64,
+        path: String,
+    },
+    /// An `ERROR!` line such as `Failed to install app '740' (No subscription)`
+    Error {
+        message: String,
+        /// The parenthesised reason at the end of the message, if any
+        reason: Option<String>,
+    },
+}
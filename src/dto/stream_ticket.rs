@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+#[derive(Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct NewStreamTicket {
+    /// The stream route the ticket will be used on, e.g. `/api/steamcmd/stdout`
+    pub path: String,
+}
+
+#[derive(Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct StreamTicket {
+    /// Pass as `?ticket=` when opening the stream. Valid once.
+    pub ticket: String,
+    #[ts(type = "number")]
+    pub expires_in_seconds: u64,
+}
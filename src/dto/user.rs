@@ -94,3 +94,88 @@ pub struct AuthResponse {
     pub user: Minimum,
     pub access_token: String,
 }
+
+/// Returned by `/login` instead of an [`AuthResponse`] when a second factor is needed
+#[derive(serde::Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct MfaChallenge {
+    pub mfa_required: bool,
+    /// When true the user has not enrolled yet and must do so before completing login
+    pub enrollment_required: bool,
+    pub challenge_token: String,
+}
+
+#[derive(serde::Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct MfaLogin {
+    pub challenge_token: String,
+    /// A TOTP code or an unused recovery code
+    pub code: String,
+}
+
+#[derive(serde::Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct MfaCode {
+    pub code: String,
+}
+
+#[derive(serde::Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct MfaEnrollment {
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+#[derive(serde::Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct RecoveryCodes {
+    pub codes: Vec<String>,
+}
+
+#[derive(serde::Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct MfaStatus {
+    pub enabled: bool,
+    /// Whether the admin policy requires MFA for this user's role
+    pub required: bool,
+    #[ts(type = "number")]
+    pub remaining_recovery_codes: u64,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct MfaPolicy {
+    pub require_for_admin: bool,
+}
+
+#[derive(serde::Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct OidcStatus {
+    pub enabled: bool,
+    /// Label for the single sign-on button
+    pub display_name: Option<String>,
+}
+
+#[derive(serde::Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct OidcAuthorization {
+    /// Identity provider URL to send the browser to
+    pub authorization_url: String,
+}
+
+#[derive(serde::Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct OidcCallback {
+    pub code: String,
+    pub state: String,
+}
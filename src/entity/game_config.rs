@@ -18,6 +18,12 @@ pub struct Model {
     pub created_by: i32,
     pub updated_at: DateTimeUtc,
     pub updated_by: i32,
+    pub memory_max_mb: Option<i32>,
+    pub cpu_quota_percent: Option<i32>,
+    pub cpu_weight: Option<i32>,
+    pub max_processes: Option<i32>,
+    pub nice: Option<i32>,
+    pub max_open_files: Option<i32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -30,6 +36,16 @@ pub enum Relation {
         on_delete = "Restrict"
     )]
     GameSchema,
+    #[sea_orm(has_many = "super::instance_metric::Entity")]
+    InstanceMetric,
+    #[sea_orm(has_many = "super::instance_mod::Entity")]
+    InstanceMod,
+    #[sea_orm(has_one = "super::instance_update::Entity")]
+    InstanceUpdate,
+    #[sea_orm(has_many = "super::player_action::Entity")]
+    PlayerAction,
+    #[sea_orm(has_many = "super::player_session::Entity")]
+    PlayerSession,
     #[sea_orm(
         belongs_to = "super::user::Entity",
         from = "Column::UpdatedBy",
@@ -54,4 +70,34 @@ impl Related<super::game_schema::Entity> for Entity {
     }
 }
 
+impl Related<super::instance_metric::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::InstanceMetric.def()
+    }
+}
+
+impl Related<super::instance_mod::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::InstanceMod.def()
+    }
+}
+
+impl Related<super::instance_update::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::InstanceUpdate.def()
+    }
+}
+
+impl Related<super::player_action::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PlayerAction.def()
+    }
+}
+
+impl Related<super::player_session::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PlayerSession.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -2,6 +2,17 @@
 
 pub mod prelude;
 
+pub mod api_token;
+pub mod app_setting;
+pub mod audit_log;
 pub mod game_config;
 pub mod game_schema;
+pub mod instance_metric;
+pub mod instance_mod;
+pub mod instance_update;
+pub mod player_action;
+pub mod player_session;
+pub mod steam_credential;
 pub mod user;
+pub mod user_identity;
+pub mod user_recovery_code;
@@ -1,5 +1,16 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.19
 
+pub use super::api_token::Entity as ApiToken;
+pub use super::app_setting::Entity as AppSetting;
+pub use super::audit_log::Entity as AuditLog;
 pub use super::game_config::Entity as GameConfig;
 pub use super::game_schema::Entity as GameSchema;
+pub use super::instance_metric::Entity as InstanceMetric;
+pub use super::instance_mod::Entity as InstanceMod;
+pub use super::instance_update::Entity as InstanceUpdate;
+pub use super::player_action::Entity as PlayerAction;
+pub use super::player_session::Entity as PlayerSession;
+pub use super::steam_credential::Entity as SteamCredential;
 pub use super::user::Entity as User;
+pub use super::user_identity::Entity as UserIdentity;
+pub use super::user_recovery_code::Entity as UserRecoveryCode;
@@ -16,6 +16,9 @@ pub struct Model {
     pub created_by: Option<i32>,
     pub updated_at: DateTimeUtc,
     pub updated_by: Option<i32>,
+    pub totp_secret: Option<String>,
+    pub totp_enabled: bool,
+    pub totp_last_step: Option<i64>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -36,6 +39,46 @@ pub enum Relation {
         on_delete = "NoAction"
     )]
     SelfRef1,
+    #[sea_orm(has_many = "super::api_token::Entity")]
+    ApiToken,
+    #[sea_orm(has_many = "super::audit_log::Entity")]
+    AuditLog,
+    #[sea_orm(has_many = "super::player_action::Entity")]
+    PlayerAction,
+    #[sea_orm(has_many = "super::user_identity::Entity")]
+    UserIdentity,
+    #[sea_orm(has_many = "super::user_recovery_code::Entity")]
+    UserRecoveryCode,
+}
+
+impl Related<super::api_token::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ApiToken.def()
+    }
+}
+
+impl Related<super::audit_log::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::AuditLog.def()
+    }
+}
+
+impl Related<super::player_action::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PlayerAction.def()
+    }
+}
+
+impl Related<super::user_identity::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::UserIdentity.def()
+    }
+}
+
+impl Related<super::user_recovery_code::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::UserRecoveryCode.def()
+    }
 }
 
 impl ActiveModelBehavior for ActiveModel {}
@@ -10,6 +10,7 @@ pub mod state;
 pub mod utils;
 
 use dotenvy::dotenv;
+use rocket::fairing::AdHoc;
 use rocket::http::Method;
 use rocket::{get, routes};
 use rocket_ext::cors::Cors;
@@ -90,6 +91,9 @@ fn dev_mode_fallback(_path: std::path::PathBuf) -> rocket::serde::json::Json<ser
     }))
 }
 
+/// How long SteamCMD gets to quit when the panel shuts down
+const STEAMCMD_QUIT_TIMEOUT_SECS: u64 = 5;
+
 #[rocket::main]
 async fn main() -> anyhow::Result<()> {
     dotenv().ok();
@@ -103,10 +107,91 @@ async fn main() -> anyhow::Result<()> {
         std::env::var("DATABASE_URL").unwrap_or_else(|_| "sqlite://data/server_ui.db".to_string());
 
     let db = db::init(&database_url).await?;
-    let mut steamcmd = state::steamcmd::SteamCMD::create(None)?;
-    steamcmd.init().await?;
+    let oidc_client = auth::oidc::OidcConfig::from_env()?
+        .map(auth::oidc::OidcClient::new)
+        .transpose()?;
+
+    let data_dir = utils::data_dir();
+    let secrets = auth::secrets::SecretBox::load_or_create(&data_dir)?;
+
+    let logs = state::console_log::ConsoleLogs::start(
+        data_dir.join("logs"),
+        state::console_log::Retention::from_env(),
+    );
+
+    // Before SteamCMD starts, so the panel's cgroup holds no other processes yet
+    let cgroups = state::limits::Cgroups::detect();
+    match &cgroups {
+        Ok(cgroups) => eprintln!("Instance cgroups live under {}", cgroups.base().display()),
+        Err(e) => eprintln!("Memory and CPU limits are unavailable: {e}"),
+    }
+
+    let steamcmd = state::steamcmd::SteamCMD::create(None, &data_dir).with_logs(logs.clone());
+    if let Err(e) = steamcmd.init().await {
+        eprintln!("SteamCMD unavailable, running in degraded mode: {e}");
+    }
+
+    // Sandboxed servers must not see the panel's data, database or working directory
+    let sandbox = state::sandbox::Sandbox::detect(
+        [data_dir.clone()]
+            .into_iter()
+            .chain(
+                state::sandbox::sqlite_file(&database_url)
+                    .and_then(|db| db.parent().map(|dir| dir.to_path_buf())),
+            )
+            .chain(std::env::current_dir().ok()),
+    )
+    .await;
+    if let Err(e) = &sandbox {
+        eprintln!("Sandboxed game servers cannot start: {e}");
+    }
 
-    let mut rocket = rocket::build().manage(db).manage(steamcmd);
+    let instances = state::instance::InstanceManager::new(db.clone())
+        .with_logs(logs.clone())
+        .with_cgroups(cgroups)
+        .with_sandbox(sandbox);
+    instances.reset_statuses().await?;
+    service::player::Players::new(db.clone())
+        .close_sessions(None)
+        .await?;
+    service::update::Updates::new(db.clone(), secrets.clone())
+        .spawn_scheduler(steamcmd.clone(), instances.clone());
+
+    let queries = state::server_query::ServerQueries::new(db.clone());
+    queries.clone().spawn_poller(instances.clone());
+
+    let metrics = state::metrics::InstanceMetrics::new(db.clone());
+    metrics.clone().spawn_sampler(instances.clone());
+
+    let mut rocket = rocket::build()
+        .manage(db)
+        .manage(steamcmd)
+        .manage(instances)
+        .manage(queries)
+        .manage(metrics)
+        .manage(logs)
+        .manage(secrets)
+        .manage(state::steamcmd::bootstrap::BootstrapConfig::from_env())
+        .manage(service::files::FileLimits::from_env())
+        .manage(state::stream_ticket::StreamTickets::default())
+        .manage(state::mfa_attempts::MfaAttempts::default())
+        .manage(state::oidc::OidcLogins::new(oidc_client))
+        .attach(AdHoc::on_shutdown("Stop game servers", |rocket| {
+            Box::pin(async move {
+                let (Some(db), Some(instances), Some(steamcmd)) = (
+                    rocket.state::<sea_orm::DatabaseConnection>(),
+                    rocket.state::<state::instance::InstanceManager>(),
+                    rocket.state::<state::steamcmd::SteamCMD>(),
+                ) else {
+                    return;
+                };
+                let instance_service = service::instance::Instance::new(db.clone(), None);
+                tokio::join!(
+                    instance_service.stop_all(instances),
+                    steamcmd.shutdown(std::time::Duration::from_secs(STEAMCMD_QUIT_TIMEOUT_SECS)),
+                );
+            })
+        }));
 
     // Mount all API routes with their respective base paths
     for (base_path, routes) in controller::get_all_routes() {
@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// Kinds of privileged actions recorded in the audit log. Stored as their snake_case name.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum AuditAction {
+    Login,
+    LoginFailed,
+    Logout,
+    SsoLogin,
+    UserCreate,
+    MfaEnable,
+    MfaDisable,
+    MfaRecoveryCodesRegenerate,
+    MfaPolicyUpdate,
+    ApiTokenCreate,
+    ApiTokenRevoke,
+    SchemaCreate,
+    SchemaUpdate,
+    SteamCredentialCreate,
+    SteamCredentialUpdate,
+    SteamCredentialDelete,
+    SteamCmdJobStart,
+    SteamCmdJobRemove,
+    SteamGuardSubmit,
+    SteamCmdBootstrap,
+    InstanceCreate,
+    InstanceUpdate,
+    InstanceDelete,
+    InstanceModsUpdate,
+    InstanceModsInstall,
+    GameUpdate,
+    GameUpdatePolicy,
+    InstanceStart,
+    InstanceStop,
+    InstanceLimitsUpdate,
+    InstanceFileWrite,
+    InstanceFileUpload,
+    InstanceFileRename,
+    InstanceFileDelete,
+    InstallOptionsUpdate,
+    ConsoleCommand,
+    PlayerModeration,
+}
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::Login => "login",
+            AuditAction::LoginFailed => "login_failed",
+            AuditAction::Logout => "logout",
+            AuditAction::SsoLogin => "sso_login",
+            AuditAction::UserCreate => "user_create",
+            AuditAction::MfaEnable => "mfa_enable",
+            AuditAction::MfaDisable => "mfa_disable",
+            AuditAction::MfaRecoveryCodesRegenerate => "mfa_recovery_codes_regenerate",
+            AuditAction::MfaPolicyUpdate => "mfa_policy_update",
+            AuditAction::ApiTokenCreate => "api_token_create",
+            AuditAction::ApiTokenRevoke => "api_token_revoke",
+            AuditAction::SchemaCreate => "schema_create",
+            AuditAction::SchemaUpdate => "schema_update",
+            AuditAction::SteamCredentialCreate => "steam_credential_create",
+            AuditAction::SteamCredentialUpdate => "steam_credential_update",
+            AuditAction::SteamCredentialDelete => "steam_credential_delete",
+            AuditAction::SteamCmdJobStart => "steam_cmd_job_start",
+            AuditAction::SteamCmdJobRemove => "steam_cmd_job_remove",
+            AuditAction::SteamGuardSubmit => "steam_guard_submit",
+            AuditAction::SteamCmdBootstrap => "steam_cmd_bootstrap",
+            AuditAction::InstanceCreate => "instance_create",
+            AuditAction::InstanceUpdate => "instance_update",
+            AuditAction::InstanceDelete => "instance_delete",
+            AuditAction::InstanceModsUpdate => "instance_mods_update",
+            AuditAction::InstanceModsInstall => "instance_mods_install",
+            AuditAction::GameUpdate => "game_update",
+            AuditAction::GameUpdatePolicy => "game_update_policy",
+            AuditAction::InstanceStart => "instance_start",
+            AuditAction::InstanceStop => "instance_stop",
+            AuditAction::InstanceLimitsUpdate => "instance_limits_update",
+            AuditAction::InstanceFileWrite => "instance_file_write",
+            AuditAction::InstanceFileUpload => "instance_file_upload",
+            AuditAction::InstanceFileRename => "instance_file_rename",
+            AuditAction::InstanceFileDelete => "instance_file_delete",
+            AuditAction::InstallOptionsUpdate => "install_options_update",
+            AuditAction::ConsoleCommand => "console_command",
+            AuditAction::PlayerModeration => "player_moderation",
+        }
+    }
+}
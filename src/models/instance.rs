@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// Lifecycle state of a game server instance, stored in `game_config.status`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export)]
+pub enum InstanceStatus {
+    Stopped = 0,
+    Starting = 1,
+    Running = 2,
+    Stopping = 3,
+    Crashed = 4,
+    Updating = 5,
+}
+
+impl From<i32> for InstanceStatus {
+    /// Unknown values are treated as stopped
+    fn from(value: i32) -> Self {
+        match value {
+            1 => InstanceStatus::Starting,
+            2 => InstanceStatus::Running,
+            3 => InstanceStatus::Stopping,
+            4 => InstanceStatus::Crashed,
+            5 => InstanceStatus::Updating,
+            _ => InstanceStatus::Stopped,
+        }
+    }
+}
+
+/// Operating system SteamCMD downloads server files for, overriding the host's
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export)]
+pub enum SteamPlatform {
+    Windows,
+    Linux,
+    Macos,
+}
+
+impl SteamPlatform {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SteamPlatform::Windows => "windows",
+            SteamPlatform::Linux => "linux",
+            SteamPlatform::Macos => "macos",
+        }
+    }
+
+    /// Parse a value stored in `instance_update.platform`
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "windows" => Some(SteamPlatform::Windows),
+            "linux" => Some(SteamPlatform::Linux),
+            "macos" => Some(SteamPlatform::Macos),
+            _ => None,
+        }
+    }
+}
@@ -1 +1,4 @@
+pub mod audit;
+pub mod instance;
+pub mod player;
 pub mod user;
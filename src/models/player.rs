@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// What a moderator can do to a player, each backed by a command template in the game schema.
+/// Stored in `player_action.action` as the snake_case name.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum PlayerAction {
+    Kick,
+    Ban,
+    Unban,
+    WhitelistAdd,
+    WhitelistRemove,
+}
+
+impl PlayerAction {
+    pub const ALL: [PlayerAction; 5] = [
+        PlayerAction::Kick,
+        PlayerAction::Ban,
+        PlayerAction::Unban,
+        PlayerAction::WhitelistAdd,
+        PlayerAction::WhitelistRemove,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PlayerAction::Kick => "kick",
+            PlayerAction::Ban => "ban",
+            PlayerAction::Unban => "unban",
+            PlayerAction::WhitelistAdd => "whitelist_add",
+            PlayerAction::WhitelistRemove => "whitelist_remove",
+        }
+    }
+
+    /// Parse a value stored in `player_action.action`
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "kick" => Some(PlayerAction::Kick),
+            "ban" => Some(PlayerAction::Ban),
+            "unban" => Some(PlayerAction::Unban),
+            "whitelist_add" => Some(PlayerAction::WhitelistAdd),
+            "whitelist_remove" => Some(PlayerAction::WhitelistRemove),
+            _ => None,
+        }
+    }
+}
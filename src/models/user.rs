@@ -45,10 +45,10 @@ impl TryGetable for UserRole {
     ) -> Result<Self, sea_orm::TryGetError> {
         let value: i32 = res.try_get_by(index)?;
         UserRole::try_from(value).map_err(|_| {
-            sea_orm::TryGetError::DbErr(sea_orm::DbErr::Custom(String::from(format!(
+            sea_orm::TryGetError::DbErr(sea_orm::DbErr::Custom(format!(
                 "Invalid UserRole value: {}",
                 value
-            ))))
+            )))
         })
     }
 }
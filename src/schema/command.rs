@@ -0,0 +1,214 @@
+use crate::schema::{
+    server_config::{ArgumentType, CommandBuilder, DynamicField, ServerConfig},
+    GameConfig,
+};
+use serde_json::Value;
+use thiserror::Error;
+
+#[cfg(test)]
+mod tests;
+
+/// Template variable replaced with the instance's enabled Workshop mods, in load order
+pub const MODS_VARIABLE: &str = "mods";
+
+/// Template variables replaced with the directories of the instance's layout
+pub const LAYOUT_VARIABLES: [&str; 4] = ["installDir", "savesDir", "logsDir", "backupsDir"];
+
+/// Variables available to the player command templates of a schema
+pub const PLAYER_VARIABLES: &[&str] = &["name", "steamId", "reason"];
+
+#[derive(Error, Debug, PartialEq)]
+pub enum CommandError {
+    #[error("Command builder references unknown field '{0}'")]
+    UnknownVariable(String),
+
+    #[error("Unterminated template variable in '{0}'")]
+    Unterminated(String),
+}
+
+enum Segment<'a> {
+    Literal(&'a str),
+    Variable(&'a str),
+}
+
+fn parse(part: &str) -> Result<Vec<Segment<'_>>, CommandError> {
+    let mut segments = Vec::new();
+    let mut rest = part;
+
+    while let Some(start) = rest.find("{{") {
+        if start > 0 {
+            segments.push(Segment::Literal(&rest[..start]));
+        }
+        let after = &rest[start + 2..];
+        let end = after
+            .find("}}")
+            .ok_or_else(|| CommandError::Unterminated(part.to_string()))?;
+        segments.push(Segment::Variable(after[..end].trim()));
+        rest = &after[end + 2..];
+    }
+    if !rest.is_empty() {
+        segments.push(Segment::Literal(rest));
+    }
+
+    Ok(segments)
+}
+
+impl CommandBuilder {
+    /// Names of all {{variables}} used in the structure
+    pub fn variables(&self) -> Vec<&str> {
+        self.structure
+            .iter()
+            .filter_map(|part| parse(part).ok())
+            .flatten()
+            .filter_map(|segment| match segment {
+                Segment::Variable(name) => Some(name),
+                Segment::Literal(_) => None,
+            })
+            .collect()
+    }
+}
+
+/// Names of all {{variables}} used in a console command template
+pub fn template_variables(template: &str) -> Result<Vec<&str>, CommandError> {
+    Ok(parse(template)?
+        .into_iter()
+        .filter_map(|segment| match segment {
+            Segment::Variable(name) => Some(name),
+            Segment::Literal(_) => None,
+        })
+        .collect())
+}
+
+/// Fill in a console command template. Variables missing from `values` render empty.
+pub fn render_template(template: &str, values: &[(&str, &str)]) -> Result<String, CommandError> {
+    let mut rendered = String::new();
+    for segment in parse(template)? {
+        match segment {
+            Segment::Literal(text) => rendered.push_str(text),
+            Segment::Variable(name) => {
+                let value = values.iter().find(|(key, _)| *key == name);
+                rendered.push_str(value.map(|(_, value)| *value).unwrap_or_default());
+            }
+        }
+    }
+
+    Ok(rendered.trim().to_string())
+}
+
+/// The configured value of a field as text, falling back to its default
+pub fn config_value(field: &DynamicField, config: &GameConfig) -> Option<String> {
+    let value = match config.get(&field.name) {
+        Some(Value::Null) | None => Value::String(field.default.clone()?),
+        Some(value) => value.clone(),
+    };
+
+    let text = match value {
+        Value::String(s) => s,
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        other => other.to_string(),
+    };
+
+    (!text.is_empty()).then_some(text)
+}
+
+/// The configured value of a field as it goes on the command line
+fn field_value(field: &DynamicField, config: &GameConfig) -> Option<String> {
+    let text = config_value(field, config);
+
+    if matches!(field.arg_type, ArgumentType::Flag) {
+        return (text.as_deref() == Some("true")).then(|| field.flag.clone());
+    }
+
+    text
+}
+
+/// The {{mods}} value: each Workshop item formatted and joined per the schema's mod support
+fn mods_value(schema: &ServerConfig, mods: &[i64]) -> Option<String> {
+    if mods.is_empty() {
+        return None;
+    }
+
+    let (format, separator) = schema
+        .mods
+        .as_ref()
+        .map(|m| (m.format.as_str(), m.separator.as_str()))
+        .unwrap_or(("{id}", ","));
+
+    Some(
+        mods.iter()
+            .map(|id| format.replace("{id}", &id.to_string()))
+            .collect::<Vec<_>>()
+            .join(separator),
+    )
+}
+
+/// Without a command builder: the executable followed by every field that has a value
+fn default_command(schema: &ServerConfig, config: &GameConfig) -> Vec<String> {
+    let mut command = vec![schema.static_config.executable_name.clone()];
+
+    for field in &schema.args {
+        let Some(value) = field_value(field, config) else {
+            continue;
+        };
+        match field.arg_type {
+            ArgumentType::Flag => command.push(value),
+            _ if field.use_equals => command.push(format!("{}={}", field.flag, value)),
+            _ => {
+                command.push(field.flag.clone());
+                command.push(value);
+            }
+        }
+    }
+
+    command
+}
+
+/// Build the server's argv from an instance's config and its enabled mods (in load order).
+/// Parts made up of a single variable with no value are left out entirely.
+pub fn render_command(
+    schema: &ServerConfig,
+    config: &GameConfig,
+    mods: &[i64],
+    dirs: &[(&str, String)],
+) -> Result<Vec<String>, CommandError> {
+    let Some(builder) = &schema.command_builder else {
+        return Ok(default_command(schema, config));
+    };
+
+    let mut command = Vec::with_capacity(builder.structure.len());
+    for part in &builder.structure {
+        let segments = parse(part)?;
+        let lone_variable = matches!(segments.as_slice(), [Segment::Variable(_)]);
+
+        let mut rendered = String::new();
+        for segment in segments {
+            match segment {
+                Segment::Literal(text) => rendered.push_str(text),
+                Segment::Variable(name) => {
+                    let value = if name == MODS_VARIABLE {
+                        mods_value(schema, mods)
+                    } else if LAYOUT_VARIABLES.contains(&name) {
+                        dirs.iter()
+                            .find(|(dir, _)| *dir == name)
+                            .map(|(_, path)| path.clone())
+                    } else {
+                        let field = schema
+                            .args
+                            .iter()
+                            .find(|f| f.name == name)
+                            .ok_or_else(|| CommandError::UnknownVariable(name.to_string()))?;
+                        field_value(field, config)
+                    };
+                    rendered.push_str(value.as_deref().unwrap_or_default());
+                }
+            }
+        }
+
+        if !(lone_variable && rendered.is_empty()) {
+            command.push(rendered);
+        }
+    }
+
+    Ok(command)
+}
@@ -0,0 +1,213 @@
+use super::*;
+use serde_json::json;
+
+fn schema(value: serde_json::Value) -> ServerConfig {
+    serde_json::from_value(value).unwrap()
+}
+
+fn config(value: serde_json::Value) -> GameConfig {
+    serde_json::from_value(value).unwrap()
+}
+
+fn test_schema(structure: Option<Vec<&str>>) -> ServerConfig {
+    let mut value = json!({
+        "steamAppId": 376030,
+        "executableName": "ShooterGameServer",
+        "displayName": "ARK",
+        "args": [
+            { "name": "map", "flag": "--map", "type": "string", "default": "TheIsland", "description": "Map" },
+            { "name": "maxPlayers", "flag": "--max-players", "useEquals": true, "type": "number", "description": "Players" },
+            { "name": "pve", "flag": "--pve", "type": "flag", "description": "PvE" }
+        ],
+        "mods": {
+            "workshopAppId": 346110,
+            "directories": ["ShooterGame/Content/Mods"],
+            "format": "{id}",
+            "separator": ","
+        }
+    });
+    if let Some(structure) = structure {
+        value["commandBuilder"] = json!({ "structure": structure });
+    }
+    schema(value)
+}
+
+#[test]
+fn test_default_command_uses_flags_and_defaults() {
+    let schema = test_schema(None);
+    let command = render_command(
+        &schema,
+        &config(json!({ "maxPlayers": 10, "pve": true })),
+        &[],
+        &[],
+    );
+
+    assert_eq!(
+        command.unwrap(),
+        vec![
+            "ShooterGameServer",
+            "--map",
+            "TheIsland",
+            "--max-players=10",
+            "--pve"
+        ]
+    );
+}
+
+#[test]
+fn test_builder_substitutes_fields_and_mods() {
+    let schema = test_schema(Some(vec![
+        "./ShooterGameServer",
+        "{{map}}?listen?MaxPlayers={{maxPlayers}}",
+        "-automanagedmods",
+        "?GameModIds={{mods}}",
+    ]));
+    let command = render_command(
+        &schema,
+        &config(json!({ "maxPlayers": 70 })),
+        &[731604991, 889745138],
+        &[],
+    );
+
+    assert_eq!(
+        command.unwrap(),
+        vec![
+            "./ShooterGameServer",
+            "TheIsland?listen?MaxPlayers=70",
+            "-automanagedmods",
+            "?GameModIds=731604991,889745138"
+        ]
+    );
+}
+
+#[test]
+fn test_builder_drops_lone_empty_variables() {
+    let schema = test_schema(Some(vec![
+        "./server",
+        "{{pve}}",
+        "{{mods}}",
+        "{{maxPlayers}}",
+    ]));
+    let command = render_command(&schema, &config(json!({ "pve": false })), &[], &[]);
+
+    assert_eq!(command.unwrap(), vec!["./server"]);
+}
+
+#[test]
+fn test_builder_renders_flag_fields_as_their_flag() {
+    let schema = test_schema(Some(vec!["./server", "{{pve}}"]));
+    let command = render_command(&schema, &config(json!({ "pve": true })), &[], &[]);
+
+    assert_eq!(command.unwrap(), vec!["./server", "--pve"]);
+}
+
+#[test]
+fn test_mods_use_schema_format_and_separator() {
+    let mut schema = test_schema(Some(vec!["-mod={{mods}}"]));
+    let mods = schema.mods.as_mut().unwrap();
+    mods.format = "@{id}".to_string();
+    mods.separator = ";".to_string();
+
+    let command = render_command(&schema, &GameConfig::new(), &[1, 2, 3], &[]);
+
+    assert_eq!(command.unwrap(), vec!["-mod=@1;@2;@3"]);
+}
+
+#[test]
+fn test_unknown_variable_is_an_error() {
+    let schema = test_schema(Some(vec!["{{nope}}"]));
+
+    assert_eq!(
+        render_command(&schema, &GameConfig::new(), &[], &[]),
+        Err(CommandError::UnknownVariable("nope".to_string()))
+    );
+    assert!(schema.validate().is_err());
+}
+
+#[test]
+fn test_builder_substitutes_layout_directories() {
+    let schema = test_schema(Some(vec!["./server", "-saves={{savesDir}}", "{{logsDir}}"]));
+    let dirs = [("savesDir", "/data/instances/1/saves".to_string())];
+
+    let command = render_command(&schema, &GameConfig::new(), &[], &dirs);
+
+    assert_eq!(
+        command.unwrap(),
+        vec!["./server", "-saves=/data/instances/1/saves"]
+    );
+    assert!(schema.validate().is_ok());
+}
+
+#[test]
+fn test_unterminated_variable_is_an_error() {
+    let schema = test_schema(Some(vec!["{{map"]));
+
+    assert_eq!(
+        render_command(&schema, &GameConfig::new(), &[], &[]),
+        Err(CommandError::Unterminated("{{map".to_string()))
+    );
+}
+
+#[test]
+fn test_mod_directories_must_stay_inside_instance() {
+    let mut schema = test_schema(None);
+    assert!(schema.validate().is_ok());
+
+    for dir in ["../escape", "/etc", "Mods/../../escape", ""] {
+        schema.mods.as_mut().unwrap().directories = vec![dir.to_string()];
+        assert!(schema.validate().is_err(), "{dir} should be rejected");
+    }
+}
+
+#[test]
+fn test_stop_sequence_defaults_and_validation() {
+    let mut schema = test_schema(None);
+    schema.stop = serde_json::from_value(json!({ "commands": ["save", "quit"] })).unwrap();
+    let stop = schema.stop.as_ref().unwrap();
+    assert_eq!((stop.timeout_secs, stop.kill_timeout_secs), (30, 10));
+    assert!(schema.validate().is_ok());
+
+    schema.stop = serde_json::from_value(json!({
+        "commands": ["save\nquit"],
+        "waitFor": "Saved ("
+    }))
+    .unwrap();
+    let errors = schema.validate().unwrap_err();
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0], "Stop command 'save\nquit' is not a single line");
+    assert!(errors[1].starts_with("Stop waitFor pattern is invalid"));
+}
+
+#[test]
+fn test_sandbox_keep_env_must_be_variable_names() {
+    let mut schema = test_schema(None);
+    schema.sandbox = serde_json::from_value(json!({ "keepEnv": ["STEAM_RUNTIME"] })).unwrap();
+    assert!(schema.validate().is_ok());
+
+    schema.sandbox = serde_json::from_value(json!({ "keepEnv": ["A=B", ""] })).unwrap();
+    assert_eq!(schema.validate().unwrap_err().len(), 2);
+}
+
+#[test]
+fn test_only_number_fields_can_be_ports() {
+    let mut schema = test_schema(None);
+    schema.args[1].port = serde_json::from_value(json!({ "role": "game" })).unwrap();
+    assert!(schema.validate().is_ok());
+
+    schema.args[0].port = schema.args[1].port;
+    assert_eq!(schema.validate().unwrap_err().len(), 1);
+}
+
+#[test]
+fn test_redact_secrets_hides_the_rcon_password() {
+    let mut schema = test_schema(None);
+    schema.rcon =
+        serde_json::from_value(json!({ "portField": "maxPlayers", "passwordField": "map" }))
+            .unwrap();
+    let mut config = config(json!({ "map": "hunter2", "pve": true }));
+
+    schema.redact_secrets(&mut config);
+
+    assert_eq!(config["map"], json!(crate::schema::REDACTED));
+    assert_eq!(config["pve"], json!(true));
+}
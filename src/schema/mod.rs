@@ -1,3 +1,4 @@
+pub mod command;
 pub mod server_config;
 pub mod validate_config;
 
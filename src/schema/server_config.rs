@@ -1,6 +1,10 @@
+use crate::models::player::PlayerAction;
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
+/// Stands in for a secret config value
+pub const REDACTED: &str = "********";
+
 /// Represents the type of an argument that a game server supports
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
 #[serde(tag = "type", rename_all = "lowercase")]
@@ -88,6 +92,56 @@ pub struct DynamicField {
     /// Display name for UI purposes
     #[serde(skip_serializing_if = "Option::is_none")]
     pub display_name: Option<String>,
+
+    /// Marks a number field as a port the server binds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<PortConfig>,
+}
+
+/// What a port of a game server is for
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum PortRole {
+    Game,
+    Query,
+    Rcon,
+    Other,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum PortProtocol {
+    Tcp,
+    #[default]
+    Udp,
+    /// Both TCP and UDP
+    Both,
+}
+
+impl PortProtocol {
+    /// Whether the two protocols share a port number space
+    pub fn overlaps(self, other: PortProtocol) -> bool {
+        self == other || self == PortProtocol::Both || other == PortProtocol::Both
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct PortConfig {
+    pub role: PortRole,
+    /// Defaults to UDP, which most game servers use
+    #[serde(default)]
+    pub protocol: PortProtocol,
+    /// Consecutive ports the server binds starting at the field's value (defaults to 1)
+    #[serde(default = "default_port_count")]
+    pub count: u16,
+}
+
+fn default_port_count() -> u16 {
+    1
 }
 
 impl DynamicField {
@@ -98,6 +152,14 @@ impl DynamicField {
                 return Err("Enum values cannot be empty".to_string());
             }
         }
+        if let Some(port) = &self.port {
+            if !matches!(self.arg_type, ArgumentType::Number(_)) {
+                return Err("Only number fields can be ports".to_string());
+            }
+            if port.count == 0 {
+                return Err("A port count must be at least 1".to_string());
+            }
+        }
         Ok(())
     }
 }
@@ -233,9 +295,198 @@ pub struct CommandBuilder {
     /// Ordered array of command structure parts
     /// Strings can be literals or template variables like {{fieldName}}
     /// Template variables are substituted with the corresponding field values at runtime
+    /// {{installDir}}, {{savesDir}}, {{logsDir}} and {{backupsDir}} are the instance's directories
     pub structure: Vec<String>,
 }
 
+/// How installed Workshop mods are placed into an instance's mod directories
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export)]
+pub enum ModInstallMethod {
+    /// Symlink to the shared Workshop download (default)
+    #[default]
+    Symlink,
+    /// Copy the files, for games that do not follow symlinks
+    Copy,
+}
+
+/// Declares that a game supports Steam Workshop mods and where they go
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ModSupport {
+    /// App ID the Workshop items belong to, if different from the server's (often the game client)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workshop_app_id: Option<i32>,
+
+    /// Directories, relative to the instance directory, each installed mod is placed into
+    pub directories: Vec<String>,
+
+    /// Symlink or copy mods into the directories (defaults to symlink)
+    #[serde(default)]
+    pub install_method: ModInstallMethod,
+
+    /// How a single mod appears in the {{mods}} template variable; `{id}` is the Workshop item ID
+    #[serde(default = "default_mod_format")]
+    pub format: String,
+
+    /// Separator between mods in the {{mods}} template variable
+    #[serde(default = "default_mod_separator")]
+    pub separator: String,
+}
+
+fn default_mod_format() -> String {
+    "{id}".to_string()
+}
+
+fn default_mod_separator() -> String {
+    ",".to_string()
+}
+
+/// Declares that a game server accepts Source RCON commands, and which of its fields configure it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct RconSupport {
+    /// Name of the field holding the RCON port
+    pub port_field: String,
+
+    /// Name of the field holding the RCON password
+    pub password_field: String,
+}
+
+/// Declares that a game server answers Steam A2S queries, and which of its fields holds the port
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct QuerySupport {
+    /// Name of the field holding the query port
+    pub port_field: String,
+}
+
+/// Console commands that manage a game server's players. Joins and leaves are picked up by
+/// `player_join` and `player_leave` triggers.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct PlayerSupport {
+    /// Console command templates using {{name}}, {{steamId}} and {{reason}}
+    #[serde(default)]
+    pub commands: PlayerCommands,
+}
+
+/// What a console line matched by a trigger means
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum TriggerEvent {
+    /// The server finished starting; it is Starting until then
+    Ready,
+    /// A player joined; the `name` group captures their name, the optional `steamId` group
+    /// their Steam ID
+    PlayerJoin,
+    /// A player left, with the same groups as `player_join`
+    PlayerLeave,
+    Error,
+    Custom,
+}
+
+/// A regex over the server's console output and the event raised for matching lines. Named
+/// capture groups are passed along with the event.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct LogTrigger {
+    pub pattern: String,
+    pub event: TriggerEvent,
+    /// Identifies the trigger in notifications; required for custom events
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+/// How to stop a game server without losing data. The commands run first, over RCON when the
+/// schema declares it and on stdin otherwise. The server then gets `timeoutSecs` to exit, or
+/// until a line matches `waitFor`, before it is sent SIGTERM and, `killTimeoutSecs` later,
+/// SIGKILL.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct StopSequence {
+    #[serde(default)]
+    pub commands: Vec<String>,
+    /// Regex over the console output meaning the server has saved and can be terminated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wait_for: Option<String>,
+    #[serde(default = "default_stop_timeout")]
+    #[ts(type = "number")]
+    pub timeout_secs: u64,
+    #[serde(default = "default_kill_timeout")]
+    #[ts(type = "number")]
+    pub kill_timeout_secs: u64,
+}
+
+fn default_stop_timeout() -> u64 {
+    30
+}
+
+fn default_kill_timeout() -> u64 {
+    10
+}
+
+impl Default for StopSequence {
+    fn default() -> Self {
+        Self {
+            commands: Vec::new(),
+            wait_for: None,
+            timeout_secs: default_stop_timeout(),
+            kill_timeout_secs: default_kill_timeout(),
+        }
+    }
+}
+
+/// A sandboxed server sees the host read-only with only its instance directory writable, and
+/// none of the panel's data
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct SandboxConfig {
+    /// Share the host's network; without it the server cannot accept players
+    #[serde(default = "default_sandbox_network")]
+    pub network: bool,
+    /// Environment variables of the panel the server keeps; all others are cleared
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keep_env: Vec<String>,
+}
+
+fn default_sandbox_network() -> bool {
+    true
+}
+
+/// Console commands for moderating players. Actions without a template are unavailable.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct PlayerCommands {
+    pub kick: Option<String>,
+    pub ban: Option<String>,
+    pub unban: Option<String>,
+    pub whitelist_add: Option<String>,
+    pub whitelist_remove: Option<String>,
+}
+
+impl PlayerCommands {
+    pub fn template(&self, action: PlayerAction) -> Option<&str> {
+        match action {
+            PlayerAction::Kick => self.kick.as_deref(),
+            PlayerAction::Ban => self.ban.as_deref(),
+            PlayerAction::Unban => self.unban.as_deref(),
+            PlayerAction::WhitelistAdd => self.whitelist_add.as_deref(),
+            PlayerAction::WhitelistRemove => self.whitelist_remove.as_deref(),
+        }
+    }
+}
+
 /// Represents a complete server configuration
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[serde(rename_all = "camelCase")]
@@ -256,6 +507,34 @@ pub struct ServerConfig {
     /// How the server command is constructed from field values
     #[serde(skip_serializing_if = "Option::is_none")]
     pub command_builder: Option<CommandBuilder>,
+
+    /// Steam Workshop mod support, for games that have it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mods: Option<ModSupport>,
+
+    /// Source RCON support, for games that take console commands over the network
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rcon: Option<RconSupport>,
+
+    /// Steam server query support, for games that report their map and players over A2S
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<QuerySupport>,
+
+    /// Player moderation commands
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub players: Option<PlayerSupport>,
+
+    /// Patterns over the console output that raise events, such as the server being ready
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub triggers: Vec<LogTrigger>,
+
+    /// Graceful stop sequence; without one the server is sent SIGTERM straight away
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop: Option<StopSequence>,
+
+    /// Run the server in a bubblewrap sandbox as the host's SANDBOX_USER
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sandbox: Option<SandboxConfig>,
 }
 
 /// Static configuration for a server
@@ -281,6 +560,18 @@ fn default_schema_version() -> String {
     "1.0.0".to_string()
 }
 
+/// A non-empty relative path that cannot climb out of the directory it is joined to
+pub fn is_relative_subpath(path: &str) -> bool {
+    let path = std::path::Path::new(path);
+    !path.as_os_str().is_empty()
+        && path.components().all(|c| {
+            matches!(
+                c,
+                std::path::Component::Normal(_) | std::path::Component::CurDir
+            )
+        })
+}
+
 impl ServerConfig {
     /// Creates a new server config with static config and empty dynamic fields
     pub fn new(steam_app_id: i32, executable_name: String, display_name: String) -> Self {
@@ -294,6 +585,13 @@ impl ServerConfig {
             args: Vec::new(),
             rules: Vec::new(),
             command_builder: None,
+            mods: None,
+            rcon: None,
+            query: None,
+            players: None,
+            triggers: Vec::new(),
+            stop: None,
+            sandbox: None,
         }
     }
 
@@ -305,6 +603,51 @@ impl ServerConfig {
         self
     }
 
+    /// Replace the RCON password in `config` with [`REDACTED`], for users who may not use RCON
+    /// and for the audit log
+    pub fn redact_secrets(&self, config: &mut super::GameConfig) {
+        let Some(rcon) = &self.rcon else {
+            return;
+        };
+        if let Some(value) = config.get_mut(&rcon.password_field) {
+            if !value.is_null() {
+                *value = serde_json::Value::String(REDACTED.to_string());
+            }
+        }
+    }
+
+    /// The fields holding ports the server binds: those marked as ports, and the RCON and query
+    /// port fields, which are TCP and UDP unless marked otherwise
+    pub fn ports(&self) -> Vec<(&DynamicField, PortConfig)> {
+        let implicit = [
+            self.rcon
+                .as_ref()
+                .map(|rcon| (&rcon.port_field, PortRole::Rcon, PortProtocol::Tcp)),
+            self.query
+                .as_ref()
+                .map(|query| (&query.port_field, PortRole::Query, PortProtocol::Udp)),
+        ];
+
+        self.args
+            .iter()
+            .filter_map(|field| {
+                let port = field.port.or_else(|| {
+                    implicit
+                        .iter()
+                        .flatten()
+                        .find_map(|(name, role, protocol)| {
+                            (*name == &field.name).then_some(PortConfig {
+                                role: *role,
+                                protocol: *protocol,
+                                count: 1,
+                            })
+                        })
+                })?;
+                Some((field, port))
+            })
+            .collect()
+    }
+
     /// Validates the entire config
     pub fn validate(&self) -> Result<(), Vec<String>> {
         let mut errors = Vec::new();
@@ -323,6 +666,117 @@ impl ServerConfig {
             }
         }
 
+        if let Some(builder) = &self.command_builder {
+            for variable in builder.variables() {
+                if variable != crate::schema::command::MODS_VARIABLE
+                    && !crate::schema::command::LAYOUT_VARIABLES.contains(&variable)
+                    && !self.args.iter().any(|f| f.name == variable)
+                {
+                    errors.push(format!(
+                        "Command builder references unknown field '{}'",
+                        variable
+                    ));
+                }
+            }
+        }
+
+        if let Some(mods) = &self.mods {
+            for dir in &mods.directories {
+                if !is_relative_subpath(dir) {
+                    errors.push(format!(
+                        "Mod directory '{}' must be a relative path inside the instance directory",
+                        dir
+                    ));
+                }
+            }
+            if !mods.format.contains("{id}") {
+                errors.push("Mod format must contain {id}".to_string());
+            }
+        }
+
+        if let Some(rcon) = &self.rcon {
+            for (purpose, name) in [
+                ("port", &rcon.port_field),
+                ("password", &rcon.password_field),
+            ] {
+                if !self.args.iter().any(|f| &f.name == name) {
+                    errors.push(format!("RCON {} field '{}' is not a field", purpose, name));
+                }
+            }
+        }
+
+        if let Some(query) = &self.query {
+            if !self.args.iter().any(|f| f.name == query.port_field) {
+                errors.push(format!(
+                    "Query port field '{}' is not a field",
+                    query.port_field
+                ));
+            }
+        }
+
+        if let Some(players) = &self.players {
+            for action in PlayerAction::ALL {
+                let Some(template) = players.commands.template(action) else {
+                    continue;
+                };
+                match crate::schema::command::template_variables(template) {
+                    Ok(variables) => {
+                        for variable in variables {
+                            if !crate::schema::command::PLAYER_VARIABLES.contains(&variable) {
+                                errors.push(format!(
+                                    "Player {} command uses unknown variable '{}'",
+                                    action.as_str(),
+                                    variable
+                                ));
+                            }
+                        }
+                    }
+                    Err(e) => errors.push(format!("Player {} command: {}", action.as_str(), e)),
+                }
+            }
+        }
+
+        for (index, trigger) in self.triggers.iter().enumerate() {
+            let label = trigger
+                .name
+                .clone()
+                .unwrap_or_else(|| format!("#{}", index + 1));
+            match regex::Regex::new(&trigger.pattern) {
+                Ok(regex) => {
+                    let needs_name = matches!(
+                        trigger.event,
+                        TriggerEvent::PlayerJoin | TriggerEvent::PlayerLeave
+                    );
+                    if needs_name && !regex.capture_names().any(|n| n == Some("name")) {
+                        errors.push(format!("Trigger {} needs a 'name' group", label));
+                    }
+                }
+                Err(e) => errors.push(format!("Trigger {} pattern is invalid: {}", label, e)),
+            }
+            if trigger.event == TriggerEvent::Custom && trigger.name.is_none() {
+                errors.push(format!("Custom trigger {} needs a name", label));
+            }
+        }
+
+        if let Some(stop) = &self.stop {
+            for command in &stop.commands {
+                if command.trim().is_empty() || command.chars().any(char::is_control) {
+                    errors.push(format!("Stop command '{}' is not a single line", command));
+                }
+            }
+            if let Some(Err(e)) = stop.wait_for.as_deref().map(regex::Regex::new) {
+                errors.push(format!("Stop waitFor pattern is invalid: {}", e));
+            }
+        }
+
+        if let Some(sandbox) = &self.sandbox {
+            for name in &sandbox.keep_env {
+                if name.is_empty() || name.contains(['=', '\0']) {
+                    errors.push(format!("Sandbox keepEnv '{}' is not a variable name", name));
+                }
+            }
+        }
+
         if errors.is_empty() {
             Ok(())
         } else {
@@ -40,14 +40,14 @@ pub fn validate_config(
 
     // First pass: Validate each field in the schema
     for field in &schema.args {
-        if let Err(field_errors) = validate_field(field, &config) {
+        if let Err(field_errors) = validate_field(field, config) {
             errors.extend(field_errors);
         }
     }
 
     // Second pass: Evaluate rules and apply constraints
     for rule in &schema.rules {
-        if let Err(rule_errors) = apply_rule_constraints(rule, &schema.args, &config) {
+        if let Err(rule_errors) = apply_rule_constraints(rule, &schema.args, config) {
             errors.extend(rule_errors);
         }
     }
@@ -337,18 +337,16 @@ fn apply_rule_constraints(
                     // No validation needed for optional constraint
                 }
                 schema::FieldConstraint::RestrictEnum { values, .. } => {
-                    if let Some(field_value) = config.get(&target_field.name) {
-                        if let Value::String(s) = field_value {
-                            if !values.contains(s) {
-                                errors.push(SchemaValidationError::InvalidFieldValue(
-                                    target_field.name.clone(),
-                                    format!(
-                                        "Value '{}' not allowed. Restricted to: {}",
-                                        s,
-                                        values.join(", ")
-                                    ),
-                                ));
-                            }
+                    if let Some(Value::String(s)) = config.get(&target_field.name) {
+                        if !values.contains(s) {
+                            errors.push(SchemaValidationError::InvalidFieldValue(
+                                target_field.name.clone(),
+                                format!(
+                                    "Value '{}' not allowed. Restricted to: {}",
+                                    s,
+                                    values.join(", ")
+                                ),
+                            ));
                         }
                     }
                 }
@@ -379,41 +377,39 @@ fn apply_rule_constraints(
                     min_length,
                     max_length,
                 } => {
-                    if let Some(field_value) = config.get(&target_field.name) {
-                        if let Value::String(s) = field_value {
-                            if let Some(pat) = pattern {
-                                match Regex::new(pat) {
-                                    Ok(regex) => {
-                                        if !regex.is_match(s) {
-                                            errors.push(SchemaValidationError::InvalidFieldValue(
-                                                target_field.name.clone(),
-                                                format!("Does not match required pattern: {}", pat),
-                                            ));
-                                        }
-                                    }
-                                    Err(_) => {
-                                        errors.push(SchemaValidationError::GeneralError(format!(
-                                            "Invalid regex pattern in rule constraint: {}",
-                                            pat
-                                        )));
+                    if let Some(Value::String(s)) = config.get(&target_field.name) {
+                        if let Some(pat) = pattern {
+                            match Regex::new(pat) {
+                                Ok(regex) => {
+                                    if !regex.is_match(s) {
+                                        errors.push(SchemaValidationError::InvalidFieldValue(
+                                            target_field.name.clone(),
+                                            format!("Does not match required pattern: {}", pat),
+                                        ));
                                     }
                                 }
-                            }
-                            if let Some(min_len) = min_length {
-                                if s.len() < *min_len {
-                                    errors.push(SchemaValidationError::InvalidFieldValue(
-                                        target_field.name.clone(),
-                                        format!("Length must be at least {}", min_len),
-                                    ));
+                                Err(_) => {
+                                    errors.push(SchemaValidationError::GeneralError(format!(
+                                        "Invalid regex pattern in rule constraint: {}",
+                                        pat
+                                    )));
                                 }
                             }
-                            if let Some(max_len) = max_length {
-                                if s.len() > *max_len {
-                                    errors.push(SchemaValidationError::InvalidFieldValue(
-                                        target_field.name.clone(),
-                                        format!("Length must not exceed {}", max_len),
-                                    ));
-                                }
+                        }
+                        if let Some(min_len) = min_length {
+                            if s.len() < *min_len {
+                                errors.push(SchemaValidationError::InvalidFieldValue(
+                                    target_field.name.clone(),
+                                    format!("Length must be at least {}", min_len),
+                                ));
+                            }
+                        }
+                        if let Some(max_len) = max_length {
+                            if s.len() > *max_len {
+                                errors.push(SchemaValidationError::InvalidFieldValue(
+                                    target_field.name.clone(),
+                                    format!("Length must not exceed {}", max_len),
+                                ));
                             }
                         }
                     }
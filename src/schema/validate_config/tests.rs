@@ -23,6 +23,13 @@ fn create_test_schema() -> ServerConfig {
         args: vec![],
         rules: vec![],
         command_builder: None,
+        mods: None,
+        rcon: None,
+        query: None,
+        players: None,
+        triggers: Vec::new(),
+        stop: None,
+        sandbox: None,
     }
 }
 
@@ -63,6 +70,7 @@ fn test_required_string_field_missing() {
         required: true,
         description: "Server name".to_string(),
         display_name: None,
+        port: None,
     });
 
     let config_value = json!({});
@@ -94,6 +102,7 @@ fn test_required_string_field_provided() {
         required: true,
         description: "Server name".to_string(),
         display_name: None,
+        port: None,
     });
 
     let config_value = json!({
@@ -121,6 +130,7 @@ fn test_string_field_pattern_validation_pass() {
         required: false,
         description: "Port".to_string(),
         display_name: None,
+        port: None,
     });
 
     let config_value = json!({
@@ -148,6 +158,7 @@ fn test_string_field_pattern_validation_fail() {
         required: false,
         description: "Port".to_string(),
         display_name: None,
+        port: None,
     });
 
     let config_value = json!({
@@ -184,6 +195,7 @@ fn test_string_field_min_length() {
         required: false,
         description: "Name".to_string(),
         display_name: None,
+        port: None,
     });
 
     let config_value = json!({
@@ -220,6 +232,7 @@ fn test_string_field_max_length() {
         required: false,
         description: "Name".to_string(),
         display_name: None,
+        port: None,
     });
 
     let config_value = json!({
@@ -246,6 +259,7 @@ fn test_number_field_parse_string() {
         required: false,
         description: "Max players".to_string(),
         display_name: None,
+        port: None,
     });
 
     let config_value = json!({
@@ -272,6 +286,7 @@ fn test_number_field_min_constraint() {
         required: false,
         description: "Max players".to_string(),
         display_name: None,
+        port: None,
     });
 
     let config_value = json!({
@@ -306,6 +321,7 @@ fn test_number_field_max_constraint() {
         required: false,
         description: "Max players".to_string(),
         display_name: None,
+        port: None,
     });
 
     let config_value = json!({
@@ -332,6 +348,7 @@ fn test_number_field_invalid_string() {
         required: false,
         description: "Max players".to_string(),
         display_name: None,
+        port: None,
     });
 
     let config_value = json!({
@@ -365,6 +382,7 @@ fn test_enum_field_valid() {
         required: false,
         description: "Difficulty".to_string(),
         display_name: None,
+        port: None,
     });
 
     let config_value = json!({
@@ -391,6 +409,7 @@ fn test_enum_field_invalid() {
         required: false,
         description: "Difficulty".to_string(),
         display_name: None,
+        port: None,
     });
 
     let config_value = json!({
@@ -422,6 +441,7 @@ fn test_boolean_field_valid() {
         required: false,
         description: "Enable PvP".to_string(),
         display_name: None,
+        port: None,
     });
 
     let config_value = json!({
@@ -448,6 +468,7 @@ fn test_condition_equals_true() {
         required: false,
         description: "Difficulty".to_string(),
         display_name: None,
+        port: None,
     });
 
     schema.args.push(DynamicField {
@@ -462,6 +483,7 @@ fn test_condition_equals_true() {
         required: false,
         description: "Loot level".to_string(),
         display_name: None,
+        port: None,
     });
 
     schema.rules.push(ConditionalRule {
@@ -502,6 +524,7 @@ fn test_condition_equals_constraint_violated() {
         required: false,
         description: "Difficulty".to_string(),
         display_name: None,
+        port: None,
     });
 
     schema.args.push(DynamicField {
@@ -516,6 +539,7 @@ fn test_condition_equals_constraint_violated() {
         required: false,
         description: "Loot level".to_string(),
         display_name: None,
+        port: None,
     });
 
     schema.rules.push(ConditionalRule {
@@ -564,6 +588,7 @@ fn test_condition_not_met_no_constraint() {
         required: false,
         description: "Difficulty".to_string(),
         display_name: None,
+        port: None,
     });
 
     schema.args.push(DynamicField {
@@ -578,6 +603,7 @@ fn test_condition_not_met_no_constraint() {
         required: false,
         description: "Loot level".to_string(),
         display_name: None,
+        port: None,
     });
 
     schema.rules.push(ConditionalRule {
@@ -618,6 +644,7 @@ fn test_condition_greater_than() {
         required: false,
         description: "Max players".to_string(),
         display_name: None,
+        port: None,
     });
 
     schema.args.push(DynamicField {
@@ -632,6 +659,7 @@ fn test_condition_greater_than() {
         required: false,
         description: "Server RAM".to_string(),
         display_name: None,
+        port: None,
     });
 
     schema.rules.push(ConditionalRule {
@@ -672,6 +700,7 @@ fn test_condition_less_than() {
         required: false,
         description: "Player count".to_string(),
         display_name: None,
+        port: None,
     });
 
     schema.args.push(DynamicField {
@@ -686,6 +715,7 @@ fn test_condition_less_than() {
         required: false,
         description: "Mode".to_string(),
         display_name: None,
+        port: None,
     });
 
     schema.rules.push(ConditionalRule {
@@ -727,6 +757,7 @@ fn test_condition_contains() {
         required: false,
         description: "Server name".to_string(),
         display_name: None,
+        port: None,
     });
 
     schema.args.push(DynamicField {
@@ -738,6 +769,7 @@ fn test_condition_contains() {
         required: false,
         description: "PvP enabled".to_string(),
         display_name: None,
+        port: None,
     });
 
     schema.rules.push(ConditionalRule {
@@ -779,6 +811,7 @@ fn test_condition_in_operator() {
         required: false,
         description: "Game type".to_string(),
         display_name: None,
+        port: None,
     });
 
     schema.args.push(DynamicField {
@@ -790,6 +823,7 @@ fn test_condition_in_operator() {
         required: false,
         description: "Lock difficulty".to_string(),
         display_name: None,
+        port: None,
     });
 
     schema.rules.push(ConditionalRule {
@@ -828,6 +862,7 @@ fn test_condition_matches_regex() {
         required: false,
         description: "Port".to_string(),
         display_name: None,
+        port: None,
     });
 
     schema.args.push(DynamicField {
@@ -839,6 +874,7 @@ fn test_condition_matches_regex() {
         required: false,
         description: "Require auth".to_string(),
         display_name: None,
+        port: None,
     });
 
     schema.rules.push(ConditionalRule {
@@ -877,6 +913,7 @@ fn test_multiple_validation_errors_collected() {
         required: true,
         description: "Server name".to_string(),
         display_name: None,
+        port: None,
     });
 
     schema.args.push(DynamicField {
@@ -891,6 +928,7 @@ fn test_multiple_validation_errors_collected() {
         required: true,
         description: "Max players".to_string(),
         display_name: None,
+        port: None,
     });
 
     schema.args.push(DynamicField {
@@ -906,6 +944,7 @@ fn test_multiple_validation_errors_collected() {
         required: true,
         description: "Port".to_string(),
         display_name: None,
+        port: None,
     });
 
     let config_value = json!({
@@ -936,6 +975,7 @@ fn test_optional_field_with_no_value() {
         required: false,
         description: "Optional field".to_string(),
         display_name: None,
+        port: None,
     });
 
     let config_value = json!({});
@@ -961,6 +1001,7 @@ fn test_field_with_default_value() {
         required: false,
         description: "Difficulty".to_string(),
         display_name: None,
+        port: None,
     });
 
     let config_value = json!({});
@@ -985,6 +1026,7 @@ fn test_restrict_number_constraint() {
         required: false,
         description: "Player count".to_string(),
         display_name: None,
+        port: None,
     });
 
     schema.args.push(DynamicField {
@@ -999,6 +1041,7 @@ fn test_restrict_number_constraint() {
         required: false,
         description: "Server RAM".to_string(),
         display_name: None,
+        port: None,
     });
 
     schema.rules.push(ConditionalRule {
@@ -1040,6 +1083,7 @@ fn test_restrict_string_constraint() {
         required: false,
         description: "Mode".to_string(),
         display_name: None,
+        port: None,
     });
 
     schema.args.push(DynamicField {
@@ -1055,6 +1099,7 @@ fn test_restrict_string_constraint() {
         required: false,
         description: "Server name".to_string(),
         display_name: None,
+        port: None,
     });
 
     schema.rules.push(ConditionalRule {
@@ -1097,6 +1142,7 @@ fn test_invalid_regex_in_pattern() {
         required: false,
         description: "Port".to_string(),
         display_name: None,
+        port: None,
     });
 
     let config_value = json!({
@@ -1129,6 +1175,7 @@ fn test_null_value_for_optional_field() {
         required: false,
         description: "Optional".to_string(),
         display_name: None,
+        port: None,
     });
 
     let config_value = json!({
@@ -1155,6 +1202,7 @@ fn test_condition_not_equals() {
         required: false,
         description: "Difficulty".to_string(),
         display_name: None,
+        port: None,
     });
 
     schema.args.push(DynamicField {
@@ -1170,6 +1218,7 @@ fn test_condition_not_equals() {
         required: false,
         description: "Warning level".to_string(),
         display_name: None,
+        port: None,
     });
 
     schema.rules.push(ConditionalRule {
@@ -0,0 +1,219 @@
+use crate::auth::api_token::{self, RequiredScope};
+use crate::auth::guards::{AccessTokenGuard, AuthError};
+use crate::dto;
+use crate::entity;
+use crate::models::user::UserRole;
+use crate::utils::error_response;
+use rocket::{
+    http::Status,
+    request::{FromRequest, Outcome},
+    response::Responder,
+};
+use sea_orm::prelude::*;
+use sea_orm::sea_query::Expr;
+use sea_orm::ActiveValue::Set;
+use sea_orm::{Condition, QueryOrder};
+use thiserror::Error;
+
+/// How stale `last_used_at` may get before a request refreshes it
+const LAST_USED_RESOLUTION: chrono::Duration = chrono::Duration::minutes(1);
+
+#[derive(Error, Debug)]
+pub enum ApiTokenError {
+    #[error("database connection not found")]
+    DbNotFound,
+
+    #[error("A database error occurred. Please review the logs for more details.")]
+    DbError(#[from] sea_orm::DbErr),
+
+    #[error("API token with id {0} not found")]
+    NotFound(i32),
+
+    #[error("An API token named '{0}' already exists")]
+    DuplicateName(String),
+
+    #[error("Invalid scope '{0}'. Expected '*', '<area>:read' or '<area>:write'")]
+    InvalidScope(String),
+
+    #[error("An API token needs a name and at least one scope")]
+    MissingFields,
+
+    #[error("Expiry must be in the future")]
+    InvalidExpiry,
+}
+
+impl<'r> Responder<'r, 'static> for ApiTokenError {
+    fn respond_to(self, _: &'r rocket::Request<'_>) -> rocket::response::Result<'static> {
+        let status = match self {
+            ApiTokenError::DbNotFound | ApiTokenError::DbError(_) => Status::InternalServerError,
+            ApiTokenError::NotFound(_) => Status::NotFound,
+            ApiTokenError::DuplicateName(_) => Status::Conflict,
+            ApiTokenError::InvalidScope(_)
+            | ApiTokenError::MissingFields
+            | ApiTokenError::InvalidExpiry => Status::UnprocessableEntity,
+        };
+        error_response(self, status)
+    }
+}
+
+pub struct ApiTokens {
+    db: DatabaseConnection,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for ApiTokens {
+    type Error = ApiTokenError;
+
+    async fn from_request(request: &'r rocket::Request<'_>) -> Outcome<Self, Self::Error> {
+        match request.rocket().state::<DatabaseConnection>() {
+            Some(db) => Outcome::Success(ApiTokens::new(db.clone())),
+            None => Outcome::Error((Status::InternalServerError, ApiTokenError::DbNotFound)),
+        }
+    }
+}
+
+impl ApiTokens {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    pub async fn create(
+        &self,
+        user_id: i32,
+        new_token: dto::api_token::NewApiToken,
+    ) -> Result<dto::api_token::CreatedApiToken, ApiTokenError> {
+        let name = new_token.name.trim().to_string();
+        if name.is_empty() || new_token.scopes.is_empty() {
+            return Err(ApiTokenError::MissingFields);
+        }
+        if let Some(scope) = new_token
+            .scopes
+            .iter()
+            .find(|s| api_token::parse_scope(s).is_none())
+        {
+            return Err(ApiTokenError::InvalidScope(scope.clone()));
+        }
+        if new_token
+            .expires_at
+            .is_some_and(|expires_at| expires_at <= chrono::Utc::now())
+        {
+            return Err(ApiTokenError::InvalidExpiry);
+        }
+
+        let existing = entity::api_token::Entity::find()
+            .filter(entity::api_token::Column::UserId.eq(user_id))
+            .filter(entity::api_token::Column::Name.eq(&name))
+            .count(&self.db)
+            .await?;
+        if existing > 0 {
+            return Err(ApiTokenError::DuplicateName(name));
+        }
+
+        let token = api_token::generate();
+        let active_model = entity::api_token::ActiveModel {
+            user_id: Set(user_id),
+            name: Set(name),
+            token_hash: Set(api_token::hash(&token)),
+            token_prefix: Set(api_token::display_prefix(&token)),
+            scopes: Set(serde_json::json!(new_token.scopes)),
+            expires_at: Set(new_token.expires_at),
+            created_at: Set(chrono::Utc::now()),
+            ..Default::default()
+        };
+        let model = active_model.insert(&self.db).await?;
+
+        Ok(dto::api_token::CreatedApiToken {
+            token,
+            metadata: model.into(),
+        })
+    }
+
+    pub async fn list(
+        &self,
+        user_id: i32,
+    ) -> Result<Vec<dto::api_token::ApiTokenMetadata>, ApiTokenError> {
+        let tokens = entity::api_token::Entity::find()
+            .filter(entity::api_token::Column::UserId.eq(user_id))
+            .filter(entity::api_token::Column::RevokedAt.is_null())
+            .order_by_asc(entity::api_token::Column::CreatedAt)
+            .all(&self.db)
+            .await?;
+
+        Ok(tokens.into_iter().map(Into::into).collect())
+    }
+
+    /// Revoke a token. Admins may revoke any user's token, everybody else only their own.
+    pub async fn revoke(&self, user_id: i32, role: UserRole, id: i32) -> Result<(), ApiTokenError> {
+        let mut condition = Condition::all()
+            .add(entity::api_token::Column::Id.eq(id))
+            .add(entity::api_token::Column::RevokedAt.is_null());
+        if role != UserRole::Admin {
+            condition = condition.add(entity::api_token::Column::UserId.eq(user_id));
+        }
+
+        let res = entity::api_token::Entity::update_many()
+            .col_expr(
+                entity::api_token::Column::RevokedAt,
+                Expr::value(chrono::Utc::now()),
+            )
+            .filter(condition)
+            .exec(&self.db)
+            .await?;
+
+        match res.rows_affected {
+            0 => Err(ApiTokenError::NotFound(id)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Resolve a presented API token into the session it grants, checking expiry and scopes
+    pub async fn authenticate(
+        &self,
+        token: &str,
+        required: &RequiredScope,
+    ) -> Result<AccessTokenGuard, (Status, AuthError)> {
+        let invalid = (Status::Unauthorized, AuthError::InvalidToken);
+        let now = chrono::Utc::now();
+
+        let (token_model, user) = entity::api_token::Entity::find()
+            .filter(entity::api_token::Column::TokenHash.eq(api_token::hash(token)))
+            .filter(entity::api_token::Column::RevokedAt.is_null())
+            .find_also_related(entity::user::Entity)
+            .one(&self.db)
+            .await
+            .map_err(|_| invalid.clone())?
+            .ok_or(invalid.clone())?;
+        let user = user.filter(|u| u.active).ok_or(invalid.clone())?;
+
+        if token_model.expires_at.is_some_and(|exp| exp <= now) {
+            return Err(invalid);
+        }
+
+        let scopes: Vec<String> =
+            serde_json::from_value(token_model.scopes.clone()).map_err(|_| invalid.clone())?;
+        if !api_token::scopes_allow(&scopes, required) {
+            return Err((Status::Forbidden, AuthError::InsufficientScope));
+        }
+
+        let role = UserRole::try_from(user.role).map_err(|_| invalid)?;
+
+        if token_model
+            .last_used_at
+            .is_none_or(|last| now - last > LAST_USED_RESOLUTION)
+        {
+            _ = entity::api_token::Entity::update_many()
+                .col_expr(entity::api_token::Column::LastUsedAt, Expr::value(now))
+                .filter(entity::api_token::Column::Id.eq(token_model.id))
+                .exec(&self.db)
+                .await;
+        }
+
+        Ok(AccessTokenGuard {
+            user_id: user.id,
+            username: user.name,
+            role,
+            api_token_id: Some(token_model.id),
+            scopes,
+        })
+    }
+}
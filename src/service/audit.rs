@@ -0,0 +1,339 @@
+use crate::auth::guards::AccessTokenGuard;
+use crate::dto;
+use crate::entity;
+use crate::models::audit::AuditAction;
+use crate::utils::error_response;
+use rocket::{
+    http::Status,
+    request::{FromRequest, Outcome},
+    response::Responder,
+};
+use sea_orm::prelude::*;
+use sea_orm::ActiveValue::Set;
+use sea_orm::{QueryOrder, QuerySelect, Select};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+#[cfg(test)]
+mod tests;
+
+const DEFAULT_PER_PAGE: u64 = 50;
+const MAX_PER_PAGE: u64 = 500;
+
+#[derive(Error, Debug)]
+pub enum AuditError {
+    #[error("database connection not found")]
+    DbNotFound,
+
+    #[error("A database error occurred. Please review the logs for more details.")]
+    DbError(#[from] sea_orm::DbErr),
+
+    #[error("Invalid '{0}' timestamp. Expected RFC 3339, e.g. 2026-01-31T12:00:00Z")]
+    InvalidTimestamp(&'static str),
+}
+
+impl<'r> Responder<'r, 'static> for AuditError {
+    fn respond_to(self, _: &'r rocket::Request<'_>) -> rocket::response::Result<'static> {
+        let status = match self {
+            AuditError::DbNotFound | AuditError::DbError(_) => Status::InternalServerError,
+            AuditError::InvalidTimestamp(_) => Status::UnprocessableEntity,
+        };
+        error_response(self, status)
+    }
+}
+
+/// A single audit log entry, before the request context is attached
+pub struct AuditEvent {
+    action: AuditAction,
+    target_type: Option<&'static str>,
+    target_id: Option<String>,
+    success: bool,
+    details: Option<Value>,
+}
+
+impl AuditEvent {
+    pub fn new(action: AuditAction) -> Self {
+        Self {
+            action,
+            target_type: None,
+            target_id: None,
+            success: true,
+            details: None,
+        }
+    }
+
+    pub fn target(mut self, target_type: &'static str, target_id: impl ToString) -> Self {
+        self.target_type = Some(target_type);
+        self.target_id = Some(target_id.to_string());
+        self
+    }
+
+    pub fn failed(mut self) -> Self {
+        self.success = false;
+        self
+    }
+
+    pub fn details(mut self, details: Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+
+    /// Record the fields that changed between two serialized states as `{"changes": {...}}`
+    pub fn diff<B: Serialize, A: Serialize>(self, before: &B, after: &A) -> Self {
+        match (serde_json::to_value(before), serde_json::to_value(after)) {
+            (Ok(before), Ok(after)) => {
+                let changes = json_diff(&before, &after);
+                self.details(serde_json::json!({ "changes": changes }))
+            }
+            (Err(e), _) | (_, Err(e)) => {
+                eprintln!("Failed to serialize audit details: {e}");
+                self
+            }
+        }
+    }
+}
+
+/// Flatten the differences between two JSON documents into
+/// `{"path.to.field": {"before": .., "after": ..}}`. Arrays are compared as a whole.
+pub fn json_diff(before: &Value, after: &Value) -> Map<String, Value> {
+    fn walk(path: &str, before: &Value, after: &Value, changes: &mut Map<String, Value>) {
+        match (before, after) {
+            (Value::Object(b), Value::Object(a)) => {
+                let mut keys: Vec<&String> = b.keys().chain(a.keys()).collect();
+                keys.sort();
+                keys.dedup();
+                for key in keys {
+                    let child = if path.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{path}.{key}")
+                    };
+                    walk(
+                        &child,
+                        b.get(key).unwrap_or(&Value::Null),
+                        a.get(key).unwrap_or(&Value::Null),
+                        changes,
+                    );
+                }
+            }
+            _ if before != after => {
+                changes.insert(
+                    path.to_string(),
+                    serde_json::json!({ "before": before, "after": after }),
+                );
+            }
+            _ => {}
+        }
+    }
+
+    let mut changes = Map::new();
+    walk("", before, after, &mut changes);
+    changes
+}
+
+/// Records privileged actions together with who performed them and from where
+pub struct Audit {
+    db: DatabaseConnection,
+    actor: Option<AccessTokenGuard>,
+    ip: Option<String>,
+    user_agent: Option<String>,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Audit {
+    type Error = AuditError;
+
+    async fn from_request(request: &'r rocket::Request<'_>) -> Outcome<Self, Self::Error> {
+        let actor = AccessTokenGuard::from_request(request).await.succeeded();
+
+        match request.rocket().state::<DatabaseConnection>() {
+            Some(db) => Outcome::Success(Audit {
+                db: db.clone(),
+                actor,
+                ip: request.client_ip().map(|ip| ip.to_string()),
+                user_agent: request.headers().get_one("User-Agent").map(str::to_string),
+            }),
+            None => Outcome::Error((Status::InternalServerError, AuditError::DbNotFound)),
+        }
+    }
+}
+
+impl Audit {
+    /// An audit log outside of a request, for actions the server takes on its own
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self {
+            db,
+            actor: None,
+            ip: None,
+            user_agent: None,
+        }
+    }
+
+    /// Record an action performed by the authenticated user of this request. Actions are
+    /// recorded once they took effect, so a failure to record one is logged rather than
+    /// turned into an error for a change that did happen.
+    pub async fn record(&self, event: AuditEvent) {
+        let (user_id, username, api_token_id) = match &self.actor {
+            Some(actor) => (
+                Some(actor.user_id),
+                Some(actor.username.clone()),
+                actor.api_token_id,
+            ),
+            None => (None, None, None),
+        };
+
+        self.insert(user_id, username, api_token_id, event).await
+    }
+
+    /// Record an action for a user who is not (yet) authenticated, e.g. a login attempt
+    pub async fn record_for(&self, user_id: Option<i32>, username: &str, event: AuditEvent) {
+        self.insert(user_id, Some(username.to_string()), None, event)
+            .await
+    }
+
+    async fn insert(
+        &self,
+        user_id: Option<i32>,
+        username: Option<String>,
+        api_token_id: Option<i32>,
+        event: AuditEvent,
+    ) {
+        let action = event.action;
+        let active_model = entity::audit_log::ActiveModel {
+            created_at: Set(chrono::Utc::now()),
+            user_id: Set(user_id),
+            username: Set(username),
+            api_token_id: Set(api_token_id),
+            action: Set(event.action.as_str().to_string()),
+            target_type: Set(event.target_type.map(str::to_string)),
+            target_id: Set(event.target_id),
+            success: Set(event.success),
+            ip: Set(self.ip.clone()),
+            user_agent: Set(self.user_agent.clone()),
+            details: Set(event.details),
+            ..Default::default()
+        };
+        if let Err(e) = active_model.insert(&self.db).await {
+            eprintln!("Failed to record audit event '{}': {e}", action.as_str());
+        }
+    }
+
+    fn filtered(
+        query: &dto::audit::AuditLogQuery,
+    ) -> Result<Select<entity::audit_log::Entity>, AuditError> {
+        let parse = |value: &Option<String>, name: &'static str| {
+            value
+                .as_deref()
+                .map(|v| {
+                    chrono::DateTime::parse_from_rfc3339(v)
+                        .map(|t| t.with_timezone(&chrono::Utc))
+                        .map_err(|_| AuditError::InvalidTimestamp(name))
+                })
+                .transpose()
+        };
+
+        let mut select = entity::audit_log::Entity::find();
+        if let Some(user_id) = query.user_id {
+            select = select.filter(entity::audit_log::Column::UserId.eq(user_id));
+        }
+        if let Some(action) = &query.action {
+            select = select.filter(entity::audit_log::Column::Action.eq(action));
+        }
+        if let Some(target_type) = &query.target_type {
+            select = select.filter(entity::audit_log::Column::TargetType.eq(target_type));
+        }
+        if let Some(target_id) = &query.target_id {
+            select = select.filter(entity::audit_log::Column::TargetId.eq(target_id));
+        }
+        if let Some(success) = query.success {
+            select = select.filter(entity::audit_log::Column::Success.eq(success));
+        }
+        if let Some(from) = parse(&query.from, "from")? {
+            select = select.filter(entity::audit_log::Column::CreatedAt.gte(from));
+        }
+        if let Some(to) = parse(&query.to, "to")? {
+            select = select.filter(entity::audit_log::Column::CreatedAt.lt(to));
+        }
+
+        Ok(select.order_by_desc(entity::audit_log::Column::Id))
+    }
+
+    /// Newest-first page of entries matching the filters. Pages start at 1.
+    pub async fn query(
+        &self,
+        query: &dto::audit::AuditLogQuery,
+    ) -> Result<dto::audit::AuditLogPage, AuditError> {
+        let page = query.page.unwrap_or(1).max(1);
+        let per_page = query
+            .per_page
+            .unwrap_or(DEFAULT_PER_PAGE)
+            .clamp(1, MAX_PER_PAGE);
+
+        let select = Self::filtered(query)?;
+        let total = select.clone().count(&self.db).await?;
+        let entries = select
+            .offset((page - 1) * per_page)
+            .limit(per_page)
+            .all(&self.db)
+            .await?;
+
+        Ok(dto::audit::AuditLogPage {
+            entries: entries.into_iter().map(Into::into).collect(),
+            total,
+            page,
+            per_page,
+        })
+    }
+
+    /// Every entry matching the filters, rendered as CSV
+    pub async fn export_csv(
+        &self,
+        query: &dto::audit::AuditLogQuery,
+    ) -> Result<String, AuditError> {
+        let entries = Self::filtered(query)?.all(&self.db).await?;
+
+        let mut csv = String::from(
+            "id,created_at,user_id,username,api_token_id,action,target_type,target_id,success,ip,user_agent,details\n",
+        );
+        for entry in entries {
+            let fields = [
+                entry.id.to_string(),
+                entry.created_at.to_rfc3339(),
+                entry.user_id.map(|v| v.to_string()).unwrap_or_default(),
+                entry.username.unwrap_or_default(),
+                entry
+                    .api_token_id
+                    .map(|v| v.to_string())
+                    .unwrap_or_default(),
+                entry.action,
+                entry.target_type.unwrap_or_default(),
+                entry.target_id.unwrap_or_default(),
+                entry.success.to_string(),
+                entry.ip.unwrap_or_default(),
+                entry.user_agent.unwrap_or_default(),
+                entry.details.map(|v| v.to_string()).unwrap_or_default(),
+            ];
+            let row: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+            csv.push_str(&row.join(","));
+            csv.push('\n');
+        }
+
+        Ok(csv)
+    }
+}
+
+/// Quote a CSV field when needed, and neutralise values a spreadsheet would run as a formula
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{value}")
+    } else {
+        value.to_string()
+    };
+
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
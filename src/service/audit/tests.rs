@@ -0,0 +1,53 @@
+use super::*;
+use serde_json::json;
+
+#[test]
+fn test_csv_field_neutralises_formulas() {
+    assert_eq!(csv_field("=SUM(A1:A2)"), "'=SUM(A1:A2)");
+    assert_eq!(csv_field("+1"), "'+1");
+    assert_eq!(csv_field("-1"), "'-1");
+    assert_eq!(csv_field("@cmd"), "'@cmd");
+    assert_eq!(csv_field("\tcmd"), "'\tcmd");
+    assert_eq!(csv_field("admin"), "admin");
+}
+
+#[test]
+fn test_csv_field_quotes_separators_quotes_and_newlines() {
+    assert_eq!(csv_field("a,b"), "\"a,b\"");
+    assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    assert_eq!(csv_field("line\nbreak"), "\"line\nbreak\"");
+    assert_eq!(csv_field("=\"x\""), "\"'=\"\"x\"\"\"");
+}
+
+#[test]
+fn test_json_diff_flattens_nested_changes() {
+    let before = json!({
+        "name": "main",
+        "config": { "port": 7777, "map": { "name": "Island", "seed": 1 } },
+        "mods": [1, 2],
+        "removed": true
+    });
+    let after = json!({
+        "name": "main",
+        "config": { "port": 7779, "map": { "name": "Island", "seed": 2 }, "added": "x" },
+        "mods": [1, 2, 3]
+    });
+
+    assert_eq!(
+        Value::Object(json_diff(&before, &after)),
+        json!({
+            "config.added": { "before": null, "after": "x" },
+            "config.map.seed": { "before": 1, "after": 2 },
+            "config.port": { "before": 7777, "after": 7779 },
+            "mods": { "before": [1, 2], "after": [1, 2, 3] },
+            "removed": { "before": true, "after": null }
+        })
+    );
+}
+
+#[test]
+fn test_diff_of_identical_states_is_empty() {
+    let state = json!({ "config": { "port": 7777 } });
+
+    assert!(json_diff(&state, &state).is_empty());
+}
@@ -0,0 +1,481 @@
+//! The files of an instance: the directory layout below the data dir, and a file API that
+//! cannot reach outside of the instance root.
+
+use crate::dto::files::{FileContent, FileEntry, FileKind, FileListing};
+use crate::utils::error_response;
+use rand::{distributions::Alphanumeric, thread_rng, Rng};
+use rocket::{data::ToByteUnit, http::Status, response::Responder, Data};
+use std::path::{Component, Path, PathBuf};
+use thiserror::Error;
+
+#[cfg(test)]
+mod tests;
+
+/// Where SteamCMD installs the game and the server runs from
+pub const INSTALL_DIR: &str = "server";
+pub const SAVES_DIR: &str = "saves";
+pub const LOGS_DIR: &str = "logs";
+pub const BACKUPS_DIR: &str = "backups";
+
+/// The directories of the layout. Nothing else is kept directly in the instance root.
+pub const LAYOUT_DIRS: [&str; 4] = [INSTALL_DIR, SAVES_DIR, LOGS_DIR, BACKUPS_DIR];
+
+/// Directories of an instance, all below its root in DATA_DIR/instances
+#[derive(Debug, Clone)]
+pub struct InstanceLayout {
+    root: PathBuf,
+}
+
+impl InstanceLayout {
+    pub fn new(id: i32) -> Self {
+        Self::in_dir(&crate::utils::data_dir().join("instances"), id)
+    }
+
+    /// The layout of instance `id` below `instances`. Absolute, since SteamCMD and sandboxed
+    /// servers are handed these paths.
+    pub fn in_dir(instances: &Path, id: i32) -> Self {
+        let root = instances.join(id.to_string());
+        Self {
+            root: std::path::absolute(&root).unwrap_or(root),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn install(&self) -> PathBuf {
+        self.root.join(INSTALL_DIR)
+    }
+
+    pub fn saves(&self) -> PathBuf {
+        self.root.join(SAVES_DIR)
+    }
+
+    pub fn logs(&self) -> PathBuf {
+        self.root.join(LOGS_DIR)
+    }
+
+    pub fn backups(&self) -> PathBuf {
+        self.root.join(BACKUPS_DIR)
+    }
+
+    /// Create any missing directory of the layout
+    pub fn ensure(&self) -> std::io::Result<()> {
+        for dir in LAYOUT_DIRS {
+            std::fs::create_dir_all(self.root.join(dir))?;
+        }
+        Ok(())
+    }
+
+    /// Values of the command builder's layout variables
+    pub fn variables(&self) -> Vec<(&'static str, String)> {
+        let [install, saves, logs, backups] = crate::schema::command::LAYOUT_VARIABLES;
+        [
+            (install, self.install()),
+            (saves, self.saves()),
+            (logs, self.logs()),
+            (backups, self.backups()),
+        ]
+        .into_iter()
+        .map(|(name, dir)| (name, dir.to_string_lossy().to_string()))
+        .collect()
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum FilesError {
+    #[error("'{0}' is outside of the instance directory")]
+    OutsideRoot(String),
+
+    #[error("'{0}' does not exist")]
+    NotFound(String),
+
+    #[error("'{0}' already exists")]
+    AlreadyExists(String),
+
+    #[error("'{0}' is a directory")]
+    IsDirectory(String),
+
+    #[error("'{0}' is not a directory")]
+    NotDirectory(String),
+
+    #[error("'{0}' is not empty")]
+    NotEmpty(String),
+
+    #[error("'{0}' cannot be changed, only what is inside of server, saves, logs and backups")]
+    Protected(String),
+
+    #[error("'{0}' is not a text file, download it instead")]
+    NotText(String),
+
+    #[error("Files larger than {0} KiB cannot be edited, download it instead")]
+    TooLargeToEdit(u64),
+
+    #[error("Uploads are limited to {0} MiB")]
+    UploadTooLarge(u64),
+
+    #[error("Failed to access the instance's files: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl<'r> Responder<'r, 'static> for FilesError {
+    fn respond_to(self, _: &'r rocket::Request<'_>) -> rocket::response::Result<'static> {
+        let status = match self {
+            FilesError::OutsideRoot(_) | FilesError::Protected(_) => Status::Forbidden,
+            FilesError::NotFound(_) => Status::NotFound,
+            FilesError::AlreadyExists(_) | FilesError::NotEmpty(_) => Status::Conflict,
+            FilesError::IsDirectory(_) | FilesError::NotDirectory(_) | FilesError::NotText(_) => {
+                Status::UnprocessableEntity
+            }
+            FilesError::TooLargeToEdit(_) | FilesError::UploadTooLarge(_) => {
+                Status::PayloadTooLarge
+            }
+            FilesError::Io(_) => Status::InternalServerError,
+        };
+        error_response(self, status)
+    }
+}
+
+/// Size limits of the file API
+#[derive(Debug, Clone, Copy)]
+pub struct FileLimits {
+    pub edit_kib: u64,
+    pub upload_mib: u64,
+}
+
+impl FileLimits {
+    /// FILE_EDIT_MAX_KB and FILE_UPLOAD_MAX_MB, 1 MiB and 1 GiB by default
+    pub fn from_env() -> Self {
+        let read = |name: &str, default: u64| {
+            std::env::var(name)
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(default)
+        };
+        Self {
+            edit_kib: read("FILE_EDIT_MAX_KB", 1024),
+            upload_mib: read("FILE_UPLOAD_MAX_MB", 1024),
+        }
+    }
+}
+
+/// Clean up a path relative to the instance root. A leading `/` means the root itself, `..`
+/// is refused.
+pub fn normalize(path: &str) -> Result<PathBuf, FilesError> {
+    let mut clean = PathBuf::new();
+    for component in Path::new(path).components() {
+        match component {
+            Component::Normal(part) => clean.push(part),
+            Component::RootDir | Component::CurDir => {}
+            Component::ParentDir | Component::Prefix(_) => {
+                return Err(FilesError::OutsideRoot(path.to_string()))
+            }
+        }
+    }
+    Ok(clean)
+}
+
+/// Whether `relative` may be created, changed or removed: anything inside a layout directory,
+/// but neither those directories nor the root
+pub fn is_writable(relative: &Path) -> bool {
+    let mut components = relative.components();
+    matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(dir)), Some(_)) if LAYOUT_DIRS.iter().any(|d| dir == *d)
+    )
+}
+
+/// `/`-separated form of a relative path, as the API reports it
+fn display(relative: &Path) -> String {
+    relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn entry(relative: &Path, metadata: &std::fs::Metadata) -> FileEntry {
+    let kind = if metadata.is_symlink() {
+        FileKind::Symlink
+    } else if metadata.is_dir() {
+        FileKind::Directory
+    } else {
+        FileKind::File
+    };
+
+    FileEntry {
+        name: relative
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        path: display(relative),
+        kind,
+        size: if kind == FileKind::File {
+            metadata.len()
+        } else {
+            0
+        },
+        modified: metadata.modified().ok().map(Into::into),
+    }
+}
+
+/// File operations confined to one instance's root
+pub struct Files {
+    layout: InstanceLayout,
+    limits: FileLimits,
+}
+
+impl Files {
+    pub fn new(layout: InstanceLayout, limits: FileLimits) -> Self {
+        Self { layout, limits }
+    }
+
+    /// Resolve `path` below the root, returning it relative and absolute. Symlinks may not lead
+    /// outside of the root; unless `follow` is set the last component is left unresolved, so a
+    /// link itself can be renamed or deleted.
+    fn resolve(&self, path: &str, follow: bool) -> Result<(PathBuf, PathBuf), FilesError> {
+        self.layout.ensure()?;
+        let root = std::fs::canonicalize(self.layout.root())?;
+        let relative = normalize(path)?;
+        let absolute = root.join(&relative);
+
+        let mut existing = if follow {
+            absolute.as_path()
+        } else {
+            absolute.parent().unwrap_or(&root)
+        };
+        let resolved = loop {
+            match std::fs::canonicalize(existing) {
+                Ok(resolved) => break resolved,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    existing = existing.parent().unwrap_or(&root);
+                }
+                Err(e) => return Err(e.into()),
+            }
+        };
+        if !resolved.starts_with(&root) {
+            return Err(FilesError::OutsideRoot(path.to_string()));
+        }
+
+        Ok((relative, absolute))
+    }
+
+    fn resolve_writable(&self, path: &str, follow: bool) -> Result<(PathBuf, PathBuf), FilesError> {
+        let (relative, absolute) = self.resolve(path, follow)?;
+        if !is_writable(&relative) {
+            return Err(FilesError::Protected(display(&relative)));
+        }
+        Ok((relative, absolute))
+    }
+
+    fn metadata(path: &Path, relative: &Path) -> Result<std::fs::Metadata, FilesError> {
+        std::fs::metadata(path).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => FilesError::NotFound(display(relative)),
+            _ => e.into(),
+        })
+    }
+
+    pub async fn list(&self, path: &str) -> Result<FileListing, FilesError> {
+        let (relative, absolute) = self.resolve(path, true)?;
+        if !Self::metadata(&absolute, &relative)?.is_dir() {
+            return Err(FilesError::NotDirectory(display(&relative)));
+        }
+
+        let mut entries = Vec::new();
+        let mut dir = tokio::fs::read_dir(&absolute).await?;
+        while let Some(child) = dir.next_entry().await? {
+            let metadata = child.metadata().await?;
+            entries.push(entry(&relative.join(child.file_name()), &metadata));
+        }
+        entries.sort_by(|a, b| {
+            (a.kind != FileKind::Directory, &a.name).cmp(&(b.kind != FileKind::Directory, &b.name))
+        });
+
+        Ok(FileListing {
+            path: display(&relative),
+            entries,
+        })
+    }
+
+    pub async fn read(&self, path: &str) -> Result<FileContent, FilesError> {
+        let (relative, absolute) = self.resolve(path, true)?;
+        let metadata = Self::metadata(&absolute, &relative)?;
+        if metadata.is_dir() {
+            return Err(FilesError::IsDirectory(display(&relative)));
+        }
+        if metadata.len() > self.limits.edit_kib * 1024 {
+            return Err(FilesError::TooLargeToEdit(self.limits.edit_kib));
+        }
+
+        let content = String::from_utf8(tokio::fs::read(&absolute).await?)
+            .map_err(|_| FilesError::NotText(display(&relative)))?;
+        Ok(FileContent {
+            path: display(&relative),
+            size: content.len() as u64,
+            content,
+            modified: metadata.modified().ok().map(Into::into),
+        })
+    }
+
+    /// Where new content for `absolute` is written before it replaces the file
+    fn staging_path(absolute: &Path) -> PathBuf {
+        let name = absolute
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let suffix: String = thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(8)
+            .map(char::from)
+            .collect();
+        absolute.with_file_name(format!(".{name}.{suffix}.partial"))
+    }
+
+    /// Check that `absolute` can take a file and create its parent directories
+    async fn prepare_target(
+        relative: &Path,
+        absolute: &Path,
+        overwrite: bool,
+    ) -> Result<(), FilesError> {
+        match tokio::fs::metadata(absolute).await {
+            Ok(metadata) if metadata.is_dir() => {
+                return Err(FilesError::IsDirectory(display(relative)))
+            }
+            Ok(_) if !overwrite => return Err(FilesError::AlreadyExists(display(relative))),
+            _ => {}
+        }
+        if let Some(parent) = absolute.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        Ok(())
+    }
+
+    /// Replace the file at `absolute` with the staged one, or clean up after a failure
+    async fn commit(
+        staged: &Path,
+        absolute: &Path,
+        relative: &Path,
+        result: Result<(), FilesError>,
+    ) -> Result<FileEntry, FilesError> {
+        if let Err(e) = result {
+            _ = tokio::fs::remove_file(staged).await;
+            return Err(e);
+        }
+        if let Err(e) = tokio::fs::rename(staged, absolute).await {
+            _ = tokio::fs::remove_file(staged).await;
+            return Err(e.into());
+        }
+
+        Ok(entry(relative, &tokio::fs::metadata(absolute).await?))
+    }
+
+    /// Replace or create a text file with `content`
+    pub async fn write(&self, path: &str, content: &str) -> Result<FileEntry, FilesError> {
+        if content.len() as u64 > self.limits.edit_kib * 1024 {
+            return Err(FilesError::TooLargeToEdit(self.limits.edit_kib));
+        }
+        let (relative, absolute) = self.resolve_writable(path, true)?;
+        Self::prepare_target(&relative, &absolute, true).await?;
+
+        let staged = Self::staging_path(&absolute);
+        let result = tokio::fs::write(&staged, content).await.map_err(Into::into);
+        Self::commit(&staged, &absolute, &relative, result).await
+    }
+
+    /// The limit to read a text file's new content with
+    pub fn edit_limit(&self) -> rocket::data::ByteUnit {
+        (self.limits.edit_kib * 1024).bytes()
+    }
+
+    /// Store an uploaded file, streamed to disk up to the upload limit
+    pub async fn upload(
+        &self,
+        path: &str,
+        data: Data<'_>,
+        overwrite: bool,
+    ) -> Result<FileEntry, FilesError> {
+        let (relative, absolute) = self.resolve_writable(path, true)?;
+        Self::prepare_target(&relative, &absolute, overwrite).await?;
+
+        let staged = Self::staging_path(&absolute);
+        let result = match data
+            .open(self.limits.upload_mib.mebibytes())
+            .into_file(&staged)
+            .await
+        {
+            Ok(file) if file.is_complete() => Ok(()),
+            Ok(_) => Err(FilesError::UploadTooLarge(self.limits.upload_mib)),
+            Err(e) => Err(e.into()),
+        };
+        Self::commit(&staged, &absolute, &relative, result).await
+    }
+
+    /// A file to download
+    pub async fn download(&self, path: &str) -> Result<PathBuf, FilesError> {
+        let (relative, absolute) = self.resolve(path, true)?;
+        if Self::metadata(&absolute, &relative)?.is_dir() {
+            return Err(FilesError::IsDirectory(display(&relative)));
+        }
+        Ok(absolute)
+    }
+
+    pub async fn create_dir(&self, path: &str) -> Result<FileEntry, FilesError> {
+        let (relative, absolute) = self.resolve_writable(path, true)?;
+        if tokio::fs::symlink_metadata(&absolute).await.is_ok() {
+            return Err(FilesError::AlreadyExists(display(&relative)));
+        }
+        tokio::fs::create_dir_all(&absolute).await?;
+
+        Ok(entry(&relative, &tokio::fs::metadata(&absolute).await?))
+    }
+
+    /// Move a file or directory, also between the layout's directories
+    pub async fn rename(&self, from: &str, to: &str) -> Result<FileEntry, FilesError> {
+        let (from_relative, from_absolute) = self.resolve_writable(from, false)?;
+        let (to_relative, to_absolute) = self.resolve_writable(to, false)?;
+        if tokio::fs::symlink_metadata(&from_absolute).await.is_err() {
+            return Err(FilesError::NotFound(display(&from_relative)));
+        }
+        if tokio::fs::symlink_metadata(&to_absolute).await.is_ok() {
+            return Err(FilesError::AlreadyExists(display(&to_relative)));
+        }
+        if to_relative.starts_with(&from_relative) {
+            return Err(FilesError::Protected(display(&to_relative)));
+        }
+        if let Some(parent) = to_absolute.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::rename(&from_absolute, &to_absolute).await?;
+
+        Ok(entry(
+            &to_relative,
+            &tokio::fs::symlink_metadata(&to_absolute).await?,
+        ))
+    }
+
+    /// Remove a file, a symlink or an empty directory; with `recursive` also a directory and
+    /// everything in it
+    pub async fn delete(&self, path: &str, recursive: bool) -> Result<(), FilesError> {
+        let (relative, absolute) = self.resolve_writable(path, false)?;
+        let metadata = tokio::fs::symlink_metadata(&absolute)
+            .await
+            .map_err(|_| FilesError::NotFound(display(&relative)))?;
+
+        if !metadata.is_dir() {
+            tokio::fs::remove_file(&absolute).await?;
+        } else if recursive {
+            tokio::fs::remove_dir_all(&absolute).await?;
+        } else {
+            tokio::fs::remove_dir(&absolute)
+                .await
+                .map_err(|e| match e.kind() {
+                    std::io::ErrorKind::DirectoryNotEmpty => {
+                        FilesError::NotEmpty(display(&relative))
+                    }
+                    _ => e.into(),
+                })?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,115 @@
+use super::*;
+
+/// A fresh instance root below the system's temp directory
+fn layout(name: &str) -> InstanceLayout {
+    let instances = std::env::temp_dir().join(format!("server_ui-files-{name}"));
+    _ = std::fs::remove_dir_all(&instances);
+    let layout = InstanceLayout::in_dir(&instances, 1);
+    layout.ensure().unwrap();
+    layout
+}
+
+fn files(layout: &InstanceLayout) -> Files {
+    Files::new(
+        layout.clone(),
+        FileLimits {
+            edit_kib: 1,
+            upload_mib: 1,
+        },
+    )
+}
+
+#[test]
+fn test_normalize_refuses_parent_directories() {
+    assert_eq!(
+        normalize("/server//./Game.ini").unwrap(),
+        PathBuf::from("server/Game.ini")
+    );
+    assert_eq!(normalize("").unwrap(), PathBuf::new());
+    assert!(matches!(
+        normalize("saves/../../secrets"),
+        Err(FilesError::OutsideRoot(_))
+    ));
+}
+
+#[test]
+fn test_only_contents_of_layout_directories_are_writable() {
+    assert!(is_writable(Path::new("server/Game.ini")));
+    assert!(is_writable(Path::new("backups/2026/world.zip")));
+    assert!(!is_writable(Path::new("server")));
+    assert!(!is_writable(Path::new("notes.txt")));
+    assert!(!is_writable(Path::new("")));
+}
+
+#[tokio::test]
+async fn test_write_read_rename_and_delete() {
+    let layout = layout("roundtrip");
+    let files = files(&layout);
+
+    let entry = files
+        .write("server/cfg/Game.ini", "[Server]\n")
+        .await
+        .unwrap();
+    assert_eq!(entry.path, "server/cfg/Game.ini");
+    assert_eq!(
+        files.read("server/cfg/Game.ini").await.unwrap().content,
+        "[Server]\n"
+    );
+
+    let listing = files.list("server").await.unwrap();
+    assert_eq!(listing.entries[0].kind, FileKind::Directory);
+
+    files
+        .rename("server/cfg/Game.ini", "saves/Game.ini")
+        .await
+        .unwrap();
+    assert!(layout.saves().join("Game.ini").is_file());
+    assert!(matches!(files.delete("server/cfg", false).await, Ok(())));
+    assert!(matches!(
+        files.delete("saves", true).await,
+        Err(FilesError::Protected(_))
+    ));
+    assert!(matches!(
+        files.write("notes.txt", "").await,
+        Err(FilesError::Protected(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_size_and_text_limits() {
+    let layout = layout("limits");
+    let files = files(&layout);
+
+    assert!(matches!(
+        files.write("server/big.txt", &"x".repeat(2048)).await,
+        Err(FilesError::TooLargeToEdit(1))
+    ));
+    std::fs::write(layout.install().join("binary"), [0xff, 0xfe]).unwrap();
+    assert!(matches!(
+        files.read("server/binary").await,
+        Err(FilesError::NotText(_))
+    ));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_symlinks_cannot_lead_outside_the_root() {
+    let layout = layout("symlinks");
+    let files = files(&layout);
+    let outside = layout.root().parent().unwrap().join("outside");
+    std::fs::create_dir_all(&outside).unwrap();
+    std::fs::write(outside.join("secret"), "hunter2").unwrap();
+    std::os::unix::fs::symlink(&outside, layout.install().join("escape")).unwrap();
+
+    assert!(matches!(
+        files.read("server/escape/secret").await,
+        Err(FilesError::OutsideRoot(_))
+    ));
+    assert!(matches!(
+        files.write("server/escape/new", "").await,
+        Err(FilesError::OutsideRoot(_))
+    ));
+    // The link itself can still be removed
+    files.delete("server/escape", false).await.unwrap();
+    assert!(outside.join("secret").is_file());
+}
@@ -85,7 +85,7 @@ impl GameSchema {
         let active_model = entity::game_schema::ActiveModel {
             name: Set(new_schema.static_config.display_name),
             schema_version: Set(new_schema.static_config.schema_version),
-            steam_app_id: Set(new_schema.static_config.steam_app_id.into()),
+            steam_app_id: Set(new_schema.static_config.steam_app_id),
             schema_json: Set(schema_json),
             created_by: auth_user_id.clone(),
             updated_by: auth_user_id,
@@ -115,7 +115,7 @@ impl GameSchema {
             id: Set(id),
             name: Set(updated_schema.static_config.display_name),
             schema_version: Set(updated_schema.static_config.schema_version),
-            steam_app_id: Set(updated_schema.static_config.steam_app_id.into()),
+            steam_app_id: Set(updated_schema.static_config.steam_app_id),
             schema_json: Set(schema_json),
             updated_by: auth_user_id,
             updated_at,
@@ -0,0 +1,544 @@
+use crate::auth::{
+    self,
+    api_token::{RequiredScope, ScopeAccess},
+};
+use crate::dto;
+use crate::entity;
+use crate::models::user::UserRole;
+use crate::schema::{self, command::CommandError, server_config::ServerConfig};
+use crate::service::files::InstanceLayout;
+use crate::service::game_schema::GameSchemaError;
+use crate::service::ports::{self, PortError, PortRange};
+use crate::state::instance::{InstanceManager, InstanceProcess, InstanceProcessError};
+use crate::state::trigger::Triggers;
+use crate::utils::error_response;
+use rocket::{
+    http::Status,
+    request::{FromRequest, Outcome},
+    response::Responder,
+};
+use sea_orm::prelude::*;
+use sea_orm::ActiveValue::Set;
+use sea_orm::QueryOrder;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum InstanceError {
+    #[error("database connection not found")]
+    DbNotFound,
+
+    #[error("Instance with id {0} not found")]
+    NotFound(i32),
+
+    #[error("A database error occurred. Please review the logs for more details.")]
+    DbError(#[from] sea_orm::DbErr),
+
+    #[error(transparent)]
+    Schema(#[from] GameSchemaError),
+
+    #[error("Invalid instance config: {}", .0.join("; "))]
+    InvalidConfig(Vec<String>),
+
+    #[error("An instance needs a name")]
+    MissingName,
+
+    #[error("An instance named '{0}' already exists for this schema")]
+    DuplicateName(String),
+
+    #[error(transparent)]
+    Command(#[from] CommandError),
+
+    #[error(transparent)]
+    Process(#[from] InstanceProcessError),
+
+    #[error("Invalid resource limits: {}", .0.join("; "))]
+    InvalidLimits(Vec<String>),
+
+    #[error(transparent)]
+    Ports(#[from] PortError),
+}
+
+impl<'r> Responder<'r, 'static> for InstanceError {
+    fn respond_to(self, req: &'r rocket::Request<'_>) -> rocket::response::Result<'static> {
+        let status = match self {
+            InstanceError::DbNotFound | InstanceError::DbError(_) => Status::InternalServerError,
+            InstanceError::NotFound(_) => Status::NotFound,
+            InstanceError::Schema(e) => return e.respond_to(req),
+            InstanceError::Process(e) => return e.respond_to(req),
+            InstanceError::Ports(e) => return e.respond_to(req),
+            InstanceError::DuplicateName(_) => Status::Conflict,
+            InstanceError::InvalidConfig(_)
+            | InstanceError::MissingName
+            | InstanceError::InvalidLimits(_)
+            | InstanceError::Command(_) => Status::UnprocessableEntity,
+        };
+        error_response(self, status)
+    }
+}
+
+/// Directory holding an instance's game files, the install root of its layout. Absolute,
+/// since SteamCMD installs into it.
+pub fn instance_dir(id: i32) -> PathBuf {
+    InstanceLayout::new(id).install()
+}
+
+pub struct Instance {
+    db: DatabaseConnection,
+    auth_session: Option<auth::guards::AccessTokenGuard>,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Instance {
+    type Error = InstanceError;
+
+    async fn from_request(request: &'r rocket::Request<'_>) -> Outcome<Self, Self::Error> {
+        let auth_session = auth::guards::AccessTokenGuard::from_request(request)
+            .await
+            .succeeded();
+
+        match request.rocket().state::<DatabaseConnection>() {
+            Some(db) => Outcome::Success(Instance::new(db.clone(), auth_session)),
+            None => Outcome::Error((Status::InternalServerError, InstanceError::DbNotFound)),
+        }
+    }
+}
+
+impl Instance {
+    pub fn new(
+        db: DatabaseConnection,
+        auth_session: Option<auth::guards::AccessTokenGuard>,
+    ) -> Self {
+        Self { db, auth_session }
+    }
+
+    /// Whether the current user may see the RCON password: only those who may use the RCON
+    /// console, which API tokens without write access to instances may not
+    fn may_see_secrets(&self) -> bool {
+        let rcon = RequiredScope {
+            area: "instance".to_string(),
+            access: ScopeAccess::Write,
+        };
+        self.auth_session.as_ref().is_some_and(|session| {
+            matches!(session.role, UserRole::Admin | UserRole::Moderator) && session.allows(&rcon)
+        })
+    }
+
+    fn to_dto(
+        model: entity::game_config::Model,
+        schema: Option<entity::game_schema::Model>,
+        redact: bool,
+    ) -> dto::instance::Instance {
+        let mut instance = dto::instance::Instance::from(model);
+        if redact {
+            // Without a parsable schema there is no telling which field is the secret
+            match schema.and_then(|s| serde_json::from_value::<ServerConfig>(s.schema_json).ok()) {
+                Some(schema) => schema.redact_secrets(&mut instance.config),
+                None => instance.config.clear(),
+            }
+        }
+        instance
+    }
+
+    pub async fn list(&self) -> Result<Vec<dto::instance::Instance>, InstanceError> {
+        let instances = entity::game_config::Entity::find()
+            .find_also_related(entity::game_schema::Entity)
+            .order_by_asc(entity::game_config::Column::InstanceName)
+            .all(&self.db)
+            .await?;
+        let redact = !self.may_see_secrets();
+
+        Ok(instances
+            .into_iter()
+            .map(|(model, schema)| Self::to_dto(model, schema, redact))
+            .collect())
+    }
+
+    /// The instance as the current user may see it
+    pub async fn get(&self, id: i32) -> Result<dto::instance::Instance, InstanceError> {
+        self.find_dto(id, !self.may_see_secrets()).await
+    }
+
+    /// The instance with its secrets redacted, as it goes into the audit log
+    pub async fn find_redacted(&self, id: i32) -> Result<dto::instance::Instance, InstanceError> {
+        self.find_dto(id, true).await
+    }
+
+    async fn find_dto(
+        &self,
+        id: i32,
+        redact: bool,
+    ) -> Result<dto::instance::Instance, InstanceError> {
+        let (model, schema) = entity::game_config::Entity::find_by_id(id)
+            .find_also_related(entity::game_schema::Entity)
+            .one(&self.db)
+            .await?
+            .ok_or(InstanceError::NotFound(id))?;
+
+        Ok(Self::to_dto(model, schema, redact))
+    }
+
+    pub async fn find_by_id(&self, id: i32) -> Result<entity::game_config::Model, InstanceError> {
+        entity::game_config::Entity::find_by_id(id)
+            .one(&self.db)
+            .await?
+            .ok_or(InstanceError::NotFound(id))
+    }
+
+    /// The instance together with its parsed schema
+    pub async fn find_with_schema(
+        &self,
+        id: i32,
+    ) -> Result<(entity::game_config::Model, ServerConfig), InstanceError> {
+        let (instance, schema) = entity::game_config::Entity::find_by_id(id)
+            .find_also_related(entity::game_schema::Entity)
+            .one(&self.db)
+            .await?
+            .ok_or(InstanceError::NotFound(id))?;
+        let schema = schema.ok_or(GameSchemaError::NotFound)?;
+        let schema = serde_json::from_value(schema.schema_json).map_err(GameSchemaError::from)?;
+
+        Ok((instance, schema))
+    }
+
+    async fn schema(&self, schema_id: i32) -> Result<ServerConfig, InstanceError> {
+        let schema = entity::game_schema::Entity::find_by_id(schema_id)
+            .one(&self.db)
+            .await?
+            .ok_or(GameSchemaError::NotFound)?;
+
+        Ok(serde_json::from_value(schema.schema_json).map_err(GameSchemaError::from)?)
+    }
+
+    fn validate(
+        schema: &ServerConfig,
+        name: &str,
+        config: &schema::GameConfig,
+    ) -> Result<(), InstanceError> {
+        if name.is_empty() {
+            return Err(InstanceError::MissingName);
+        }
+
+        schema::validate_config::validate_config(schema, config).map_err(|errors| {
+            InstanceError::InvalidConfig(errors.iter().map(|e| e.to_string()).collect())
+        })
+    }
+
+    /// The ports of every instance but `except`, by instance name
+    async fn ports_in_use(
+        &self,
+        except: Option<i32>,
+    ) -> Result<Vec<(String, Vec<dto::instance::InstancePort>)>, InstanceError> {
+        let instances = entity::game_config::Entity::find()
+            .find_also_related(entity::game_schema::Entity)
+            .all(&self.db)
+            .await?;
+
+        Ok(instances
+            .into_iter()
+            .filter(|(instance, _)| Some(instance.id) != except)
+            .filter_map(|(instance, schema)| {
+                let schema: ServerConfig = serde_json::from_value(schema?.schema_json).ok()?;
+                let config: schema::GameConfig =
+                    serde_json::from_value(instance.config_json).unwrap_or_default();
+                Some((instance.instance_name, ports::bound_ports(&schema, &config)))
+            })
+            .collect())
+    }
+
+    /// Give unset port fields a free port, skipping those other instances or programs use
+    fn allocate_ports(
+        schema: &ServerConfig,
+        config: &mut schema::GameConfig,
+        others: &[(String, Vec<dto::instance::InstancePort>)],
+    ) -> Result<(), InstanceError> {
+        let taken: Vec<_> = others
+            .iter()
+            .flat_map(|(_, ports)| ports.iter().cloned())
+            .collect();
+        let probe = ports::probe_enabled();
+        ports::allocate(schema, config, &taken, PortRange::from_env(), |port| {
+            !probe || ports::probe(std::slice::from_ref(port)).is_ok()
+        })?;
+        Ok(())
+    }
+
+    fn ensure_free_ports(
+        schema: &ServerConfig,
+        config: &schema::GameConfig,
+        others: &[(String, Vec<dto::instance::InstancePort>)],
+    ) -> Result<(), InstanceError> {
+        let conflicts = ports::conflicts(&ports::bound_ports(schema, config), others);
+        if !conflicts.is_empty() {
+            return Err(PortError::Conflict(conflicts).into());
+        }
+        Ok(())
+    }
+
+    async fn ensure_unique_name(
+        &self,
+        schema_id: i32,
+        name: &str,
+        except: Option<i32>,
+    ) -> Result<(), InstanceError> {
+        let mut query = entity::game_config::Entity::find()
+            .filter(entity::game_config::Column::SchemaId.eq(schema_id))
+            .filter(entity::game_config::Column::InstanceName.eq(name));
+        if let Some(id) = except {
+            query = query.filter(entity::game_config::Column::Id.ne(id));
+        }
+
+        if query.count(&self.db).await? > 0 {
+            return Err(InstanceError::DuplicateName(name.to_string()));
+        }
+        Ok(())
+    }
+
+    pub async fn create(
+        &self,
+        new_instance: dto::instance::NewInstance,
+    ) -> Result<dto::instance::Instance, InstanceError> {
+        let name = new_instance.instance_name.trim().to_string();
+        let schema = self.schema(new_instance.schema_id).await?;
+        let mut config = new_instance.config;
+        let others = self.ports_in_use(None).await?;
+        Self::allocate_ports(&schema, &mut config, &others)?;
+        Self::validate(&schema, &name, &config)?;
+        self.ensure_unique_name(new_instance.schema_id, &name, None)
+            .await?;
+        Self::ensure_free_ports(&schema, &config, &others)?;
+
+        let auth_user_id = self
+            .auth_session
+            .as_ref()
+            .map(|a| Set(a.user_id))
+            .unwrap_or_default();
+        let active_model = entity::game_config::ActiveModel {
+            instance_name: Set(name),
+            schema_id: Set(new_instance.schema_id),
+            config_json: Set(serde_json::json!(config)),
+            restart_interval: Set(0),
+            backup_interval: Set(0),
+            created_by: auth_user_id.clone(),
+            updated_by: auth_user_id,
+            ..Default::default()
+        };
+        let model = active_model.insert(&self.db).await?;
+        // Also created on every start, so a failure here is not fatal
+        if let Err(e) = InstanceLayout::new(model.id).ensure() {
+            eprintln!(
+                "Failed to create the directories of instance {}: {e}",
+                model.id
+            );
+        }
+
+        Ok(model.into())
+    }
+
+    pub async fn update(
+        &self,
+        id: i32,
+        update: dto::instance::InstanceUpdate,
+    ) -> Result<dto::instance::Instance, InstanceError> {
+        let model = self.find_by_id(id).await?;
+        let name = update.instance_name.trim().to_string();
+        let schema = self.schema(model.schema_id).await?;
+        let mut config = update.config;
+        let others = self.ports_in_use(Some(id)).await?;
+        Self::allocate_ports(&schema, &mut config, &others)?;
+        Self::validate(&schema, &name, &config)?;
+        self.ensure_unique_name(model.schema_id, &name, Some(id))
+            .await?;
+        Self::ensure_free_ports(&schema, &config, &others)?;
+
+        let mut active_model: entity::game_config::ActiveModel = model.into();
+        active_model.instance_name = Set(name);
+        active_model.config_json = Set(serde_json::json!(config));
+        active_model.updated_at = Set(chrono::Utc::now());
+        if let Some(session) = &self.auth_session {
+            active_model.updated_by = Set(session.user_id);
+        }
+        let model = active_model.update(&self.db).await?;
+
+        Ok(model.into())
+    }
+
+    /// Replace the resource limits the instance's server runs under, from its next start on
+    pub async fn set_limits(
+        &self,
+        id: i32,
+        limits: dto::instance::ResourceLimits,
+    ) -> Result<dto::instance::ResourceLimits, InstanceError> {
+        crate::state::limits::validate(&limits).map_err(InstanceError::InvalidLimits)?;
+        let model = self.find_by_id(id).await?;
+
+        let mut active_model: entity::game_config::ActiveModel = model.into();
+        active_model.memory_max_mb = Set(limits.memory_max_mb.map(|v| v as i32));
+        active_model.cpu_quota_percent = Set(limits.cpu_quota_percent.map(|v| v as i32));
+        active_model.cpu_weight = Set(limits.cpu_weight.map(|v| v as i32));
+        active_model.max_processes = Set(limits.max_processes.map(|v| v as i32));
+        active_model.nice = Set(limits.nice);
+        active_model.max_open_files = Set(limits.max_open_files.map(|v| v as i32));
+        active_model.updated_at = Set(chrono::Utc::now());
+        if let Some(session) = &self.auth_session {
+            active_model.updated_by = Set(session.user_id);
+        }
+        let model = active_model.update(&self.db).await?;
+
+        Ok((&model).into())
+    }
+
+    /// Remove the instance and its mod list. Game files on disk are left in place.
+    pub async fn delete(&self, id: i32) -> Result<(), InstanceError> {
+        let res = entity::game_config::Entity::delete_by_id(id)
+            .exec(&self.db)
+            .await?;
+
+        match res.rows_affected {
+            0 => Err(InstanceError::NotFound(id)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Workshop item ids of the instance's enabled mods, in load order
+    pub async fn enabled_mods(&self, id: i32) -> Result<Vec<i64>, InstanceError> {
+        let mods = entity::instance_mod::Entity::find()
+            .filter(entity::instance_mod::Column::InstanceId.eq(id))
+            .filter(entity::instance_mod::Column::Enabled.eq(true))
+            .order_by_asc(entity::instance_mod::Column::LoadOrder)
+            .all(&self.db)
+            .await?;
+
+        Ok(mods.into_iter().map(|m| m.workshop_item_id).collect())
+    }
+
+    /// The ports the instance's server binds with its current config
+    pub async fn ports(&self, id: i32) -> Result<Vec<dto::instance::InstancePort>, InstanceError> {
+        let (instance, schema) = self.find_with_schema(id).await?;
+        let config: schema::GameConfig =
+            serde_json::from_value(instance.config_json).unwrap_or_default();
+
+        Ok(ports::bound_ports(&schema, &config))
+    }
+
+    /// The argv the instance would be started with
+    pub async fn command(&self, id: i32) -> Result<Vec<String>, InstanceError> {
+        self.render_command(id, false).await
+    }
+
+    /// The argv as the current user may see it
+    pub async fn command_preview(&self, id: i32) -> Result<Vec<String>, InstanceError> {
+        self.render_command(id, !self.may_see_secrets()).await
+    }
+
+    async fn render_command(&self, id: i32, redact: bool) -> Result<Vec<String>, InstanceError> {
+        let (instance, schema) = self.find_with_schema(id).await?;
+        let mut config: schema::GameConfig =
+            serde_json::from_value(instance.config_json).unwrap_or_default();
+        if redact {
+            schema.redact_secrets(&mut config);
+        }
+        let mods = self.enabled_mods(id).await?;
+
+        let dirs = InstanceLayout::new(id).variables();
+
+        Ok(schema::command::render_command(
+            &schema, &config, &mods, &dirs,
+        )?)
+    }
+
+    /// Launch the instance's server with its current config and mods
+    pub async fn start(
+        &self,
+        id: i32,
+        manager: &InstanceManager,
+    ) -> Result<Arc<InstanceProcess>, InstanceError> {
+        if manager.is_updating(id) {
+            return Err(InstanceProcessError::Updating(id).into());
+        }
+        let command = self.command(id).await?;
+        let (instance, schema) = self.find_with_schema(id).await?;
+        // Schemas are not validated on every path into the database, so a broken pattern
+        // only disables the triggers instead of blocking the start
+        let triggers = Triggers::new(&schema.triggers).unwrap_or_else(|e| {
+            eprintln!("Ignoring the triggers of instance {id}: {e}");
+            Triggers::default()
+        });
+
+        // A server that is already running holds its own ports; the manager refuses the start
+        let running = manager.process(id).await.is_some_and(|p| p.is_running());
+        if ports::probe_enabled() && !running {
+            let config: schema::GameConfig =
+                serde_json::from_value(instance.config_json.clone()).unwrap_or_default();
+            ports::probe(&ports::bound_ports(&schema, &config))?;
+        }
+
+        let fired = manager.subscribe_triggers();
+        let process = manager
+            .start(
+                id,
+                command,
+                &InstanceLayout::new(id),
+                triggers,
+                &dto::instance::ResourceLimits::from(&instance),
+                schema.sandbox.as_ref(),
+            )
+            .await?;
+
+        crate::service::player::Players::new(self.db.clone()).track(id, process.clone(), fired);
+        Ok(process)
+    }
+
+    /// Stop the instance's server the way its schema declares: run the stop commands and wait
+    /// for it to exit or print the `waitFor` line, then SIGTERM and finally SIGKILL it
+    pub async fn stop(&self, id: i32, manager: &InstanceManager) -> Result<(), InstanceError> {
+        let (_, schema) = self.find_with_schema(id).await?;
+        let sequence = schema.stop.unwrap_or_default();
+        let process = manager.begin_stop(id).await?;
+
+        let lines = process.subscribe();
+        let rcon = crate::service::rcon::Rcon::new(self.db.clone());
+        for command in &sequence.commands {
+            if let Err(e) = rcon.send(id, command, manager).await {
+                process
+                    .announce(&format!("Stop command '{command}' failed: {e}"))
+                    .await;
+            }
+        }
+        let pattern = sequence
+            .wait_for
+            .as_deref()
+            .and_then(|pattern| regex::Regex::new(pattern).ok());
+        if !sequence.commands.is_empty() || pattern.is_some() {
+            process
+                .wait_exit_or_line(
+                    lines,
+                    pattern.as_ref(),
+                    Duration::from_secs(sequence.timeout_secs),
+                )
+                .await;
+        }
+
+        process
+            .terminate(Duration::from_secs(sequence.kill_timeout_secs))
+            .await;
+        Ok(())
+    }
+
+    /// Stop every running instance in parallel, e.g. when the panel shuts down
+    pub async fn stop_all(&self, manager: &InstanceManager) {
+        let mut stops = tokio::task::JoinSet::new();
+        for id in manager.running().await {
+            let instances = Instance::new(self.db.clone(), None);
+            let manager = manager.clone();
+            stops.spawn(async move {
+                if let Err(e) = instances.stop(id, &manager).await {
+                    eprintln!("Failed to stop instance {id}: {e}");
+                }
+            });
+        }
+        stops.join_all().await;
+    }
+}
@@ -0,0 +1,359 @@
+use crate::auth::{
+    secrets::{SecretBox, SecretsError},
+    totp, TokenType,
+};
+use crate::dto;
+use crate::entity;
+use crate::models::user::UserRole;
+use crate::service::setting::{self, SettingError, Settings};
+use crate::utils::error_response;
+use rocket::{
+    http::Status,
+    request::{FromRequest, Outcome},
+    response::Responder,
+};
+use sea_orm::prelude::*;
+use sea_orm::sea_query::Expr;
+use sea_orm::ActiveValue::Set;
+use sea_orm::{Condition, TransactionTrait};
+use thiserror::Error;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Error, Debug)]
+pub enum MfaError {
+    #[error("database connection not found")]
+    DbNotFound,
+
+    #[error("secrets key not found")]
+    SecretsNotFound,
+
+    #[error("A database error occurred. Please review the logs for more details.")]
+    DbError(#[from] sea_orm::DbErr),
+
+    #[error(transparent)]
+    Secrets(#[from] SecretsError),
+
+    #[error(transparent)]
+    Setting(#[from] SettingError),
+
+    #[error("user with id {0} not found")]
+    UserNotFound(i32),
+
+    #[error("Two-factor authentication is already enabled")]
+    AlreadyEnabled,
+
+    #[error("Two-factor authentication is not enabled")]
+    NotEnabled,
+
+    #[error("Two-factor enrollment has not been started")]
+    EnrollmentNotStarted,
+
+    #[error("Invalid two-factor authentication code")]
+    InvalidCode,
+
+    #[error("Two-factor authentication is required for your role and cannot be disabled")]
+    RequiredByPolicy,
+
+    #[error("Failed to generate the authenticator URI")]
+    InvalidSecret,
+}
+
+impl<'r> Responder<'r, 'static> for MfaError {
+    fn respond_to(self, req: &'r rocket::Request<'_>) -> rocket::response::Result<'static> {
+        let status = match self {
+            MfaError::DbNotFound
+            | MfaError::SecretsNotFound
+            | MfaError::DbError(_)
+            | MfaError::Secrets(_)
+            | MfaError::InvalidSecret => Status::InternalServerError,
+            MfaError::Setting(e) => return e.respond_to(req),
+            MfaError::UserNotFound(_) => Status::NotFound,
+            MfaError::AlreadyEnabled | MfaError::NotEnabled | MfaError::EnrollmentNotStarted => {
+                Status::Conflict
+            }
+            MfaError::InvalidCode => Status::Unauthorized,
+            MfaError::RequiredByPolicy => Status::Forbidden,
+        };
+        error_response(self, status)
+    }
+}
+
+pub struct Mfa {
+    db: DatabaseConnection,
+    settings: Settings,
+    secrets: SecretBox,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Mfa {
+    type Error = MfaError;
+
+    async fn from_request(request: &'r rocket::Request<'_>) -> Outcome<Self, Self::Error> {
+        let Some(db) = request.rocket().state::<DatabaseConnection>() else {
+            return Outcome::Error((Status::InternalServerError, MfaError::DbNotFound));
+        };
+        let Some(secrets) = request.rocket().state::<SecretBox>() else {
+            return Outcome::Error((Status::InternalServerError, MfaError::SecretsNotFound));
+        };
+
+        Outcome::Success(Mfa {
+            db: db.clone(),
+            settings: Settings::new(db.clone()),
+            secrets: secrets.clone(),
+        })
+    }
+}
+
+impl Mfa {
+    async fn find_user(&self, user_id: i32) -> Result<entity::user::Model, MfaError> {
+        entity::user::Entity::find_by_id(user_id)
+            .one(&self.db)
+            .await?
+            .ok_or(MfaError::UserNotFound(user_id))
+    }
+
+    pub async fn admin_policy(&self) -> Result<dto::user::MfaPolicy, MfaError> {
+        let require_for_admin = self
+            .settings
+            .get_or(setting::REQUIRE_ADMIN_MFA, false)
+            .await?;
+
+        Ok(dto::user::MfaPolicy { require_for_admin })
+    }
+
+    pub async fn set_admin_policy(
+        &self,
+        policy: dto::user::MfaPolicy,
+        updated_by: i32,
+    ) -> Result<(), MfaError> {
+        self.settings
+            .set(
+                setting::REQUIRE_ADMIN_MFA,
+                &policy.require_for_admin,
+                Some(updated_by),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn is_required_for(&self, role: UserRole) -> Result<bool, MfaError> {
+        Ok(role == UserRole::Admin && self.admin_policy().await?.require_for_admin)
+    }
+
+    /// Decide whether a password login must be followed by an MFA step, and which kind
+    pub async fn login_challenge(
+        &self,
+        user: &entity::user::Model,
+        role: UserRole,
+    ) -> Result<Option<TokenType>, MfaError> {
+        if user.totp_enabled {
+            Ok(Some(TokenType::MfaChallenge))
+        } else if self.is_required_for(role).await? {
+            Ok(Some(TokenType::MfaEnrollment))
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub async fn status(
+        &self,
+        user_id: i32,
+        role: UserRole,
+    ) -> Result<dto::user::MfaStatus, MfaError> {
+        let user = self.find_user(user_id).await?;
+        let remaining_recovery_codes = entity::user_recovery_code::Entity::find()
+            .filter(entity::user_recovery_code::Column::UserId.eq(user_id))
+            .filter(entity::user_recovery_code::Column::UsedAt.is_null())
+            .count(&self.db)
+            .await?;
+
+        Ok(dto::user::MfaStatus {
+            enabled: user.totp_enabled,
+            required: self.is_required_for(role).await?,
+            remaining_recovery_codes,
+        })
+    }
+
+    /// The user's TOTP secret, decrypted
+    fn secret(&self, user: &entity::user::Model) -> Result<Option<String>, MfaError> {
+        Ok(user
+            .totp_secret
+            .as_deref()
+            .map(|sealed| self.secrets.decrypt(sealed))
+            .transpose()?)
+    }
+
+    /// Generate a new pending secret. It only takes effect once confirmed via [`Mfa::activate`].
+    /// The secret is stored encrypted.
+    pub async fn begin_enrollment(
+        &self,
+        user_id: i32,
+    ) -> Result<dto::user::MfaEnrollment, MfaError> {
+        let user = self.find_user(user_id).await?;
+        if user.totp_enabled {
+            return Err(MfaError::AlreadyEnabled);
+        }
+
+        let secret = totp::generate_secret();
+        let otpauth_uri =
+            totp::otpauth_uri(&secret, &user.name).map_err(|_| MfaError::InvalidSecret)?;
+
+        let active_model = entity::user::ActiveModel {
+            id: Set(user_id),
+            totp_secret: Set(Some(self.secrets.encrypt(&secret)?)),
+            totp_last_step: Set(None),
+            updated_at: Set(chrono::Utc::now()),
+            ..Default::default()
+        };
+        active_model.update(&self.db).await?;
+
+        Ok(dto::user::MfaEnrollment {
+            secret,
+            otpauth_uri,
+        })
+    }
+
+    /// Confirm enrollment with a code from the authenticator and issue recovery codes
+    pub async fn activate(
+        &self,
+        user_id: i32,
+        code: &str,
+    ) -> Result<dto::user::RecoveryCodes, MfaError> {
+        let user = self.find_user(user_id).await?;
+        if user.totp_enabled {
+            return Err(MfaError::AlreadyEnabled);
+        }
+        let secret = self.secret(&user)?.ok_or(MfaError::EnrollmentNotStarted)?;
+        let step = totp::verify(&secret, code, user.totp_last_step).ok_or(MfaError::InvalidCode)?;
+
+        let txn = self.db.begin().await?;
+
+        let active_model = entity::user::ActiveModel {
+            id: Set(user_id),
+            totp_enabled: Set(true),
+            totp_last_step: Set(Some(step)),
+            updated_at: Set(chrono::Utc::now()),
+            ..Default::default()
+        };
+        active_model.update(&txn).await?;
+        let codes = Self::replace_recovery_codes(&txn, user_id).await?;
+
+        txn.commit().await?;
+
+        Ok(codes)
+    }
+
+    /// Verify a TOTP or recovery code for a user with MFA enabled. Each code is accepted once.
+    pub async fn verify_code(&self, user_id: i32, code: &str) -> Result<(), MfaError> {
+        let user = self.find_user(user_id).await?;
+        let secret = match (self.secret(&user)?, user.totp_enabled) {
+            (Some(secret), true) => secret,
+            _ => return Err(MfaError::NotEnabled),
+        };
+
+        if let Some(step) = totp::verify(&secret, code, user.totp_last_step) {
+            // Only advance the step if nobody else consumed this (or a later) code concurrently
+            let res = entity::user::Entity::update_many()
+                .col_expr(entity::user::Column::TotpLastStep, Expr::value(step))
+                .filter(entity::user::Column::Id.eq(user_id))
+                .filter(
+                    Condition::any()
+                        .add(entity::user::Column::TotpLastStep.is_null())
+                        .add(entity::user::Column::TotpLastStep.lt(step)),
+                )
+                .exec(&self.db)
+                .await?;
+
+            return match res.rows_affected {
+                0 => Err(MfaError::InvalidCode),
+                _ => Ok(()),
+            };
+        }
+
+        let res = entity::user_recovery_code::Entity::update_many()
+            .col_expr(
+                entity::user_recovery_code::Column::UsedAt,
+                Expr::value(chrono::Utc::now()),
+            )
+            .filter(entity::user_recovery_code::Column::UserId.eq(user_id))
+            .filter(entity::user_recovery_code::Column::CodeHash.eq(totp::hash_recovery_code(code)))
+            .filter(entity::user_recovery_code::Column::UsedAt.is_null())
+            .exec(&self.db)
+            .await?;
+
+        match res.rows_affected {
+            0 => Err(MfaError::InvalidCode),
+            _ => Ok(()),
+        }
+    }
+
+    pub async fn regenerate_recovery_codes(
+        &self,
+        user_id: i32,
+        code: &str,
+    ) -> Result<dto::user::RecoveryCodes, MfaError> {
+        self.verify_code(user_id, code).await?;
+
+        let txn = self.db.begin().await?;
+        let codes = Self::replace_recovery_codes(&txn, user_id).await?;
+        txn.commit().await?;
+
+        Ok(codes)
+    }
+
+    pub async fn disable(&self, user_id: i32, role: UserRole, code: &str) -> Result<(), MfaError> {
+        if self.is_required_for(role).await? {
+            return Err(MfaError::RequiredByPolicy);
+        }
+        self.verify_code(user_id, code).await?;
+
+        let txn = self.db.begin().await?;
+
+        let active_model = entity::user::ActiveModel {
+            id: Set(user_id),
+            totp_secret: Set(None),
+            totp_enabled: Set(false),
+            totp_last_step: Set(None),
+            updated_at: Set(chrono::Utc::now()),
+            ..Default::default()
+        };
+        active_model.update(&txn).await?;
+        entity::user_recovery_code::Entity::delete_many()
+            .filter(entity::user_recovery_code::Column::UserId.eq(user_id))
+            .exec(&txn)
+            .await?;
+
+        txn.commit().await?;
+
+        Ok(())
+    }
+
+    async fn replace_recovery_codes<C: ConnectionTrait>(
+        conn: &C,
+        user_id: i32,
+    ) -> Result<dto::user::RecoveryCodes, MfaError> {
+        entity::user_recovery_code::Entity::delete_many()
+            .filter(entity::user_recovery_code::Column::UserId.eq(user_id))
+            .exec(conn)
+            .await?;
+
+        let codes = totp::generate_recovery_codes();
+        let now = chrono::Utc::now();
+        let models = codes
+            .iter()
+            .map(|code| entity::user_recovery_code::ActiveModel {
+                user_id: Set(user_id),
+                code_hash: Set(totp::hash_recovery_code(code)),
+                created_at: Set(now),
+                ..Default::default()
+            });
+        entity::user_recovery_code::Entity::insert_many(models)
+            .exec(conn)
+            .await?;
+
+        Ok(dto::user::RecoveryCodes { codes })
+    }
+}
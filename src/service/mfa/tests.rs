@@ -0,0 +1,93 @@
+use super::*;
+use totp_rs::{Builder, Secret};
+
+async fn service() -> (Mfa, i32) {
+    let db = crate::db::init("sqlite::memory:").await.unwrap();
+    let now = chrono::Utc::now();
+    let user = entity::user::ActiveModel {
+        name: Set("admin".to_string()),
+        password_hash: Set(String::new()),
+        active: Set(true),
+        role: Set(UserRole::Admin as i32),
+        created_at: Set(now),
+        updated_at: Set(now),
+        totp_enabled: Set(false),
+        ..Default::default()
+    }
+    .insert(&db)
+    .await
+    .unwrap();
+
+    let mfa = Mfa {
+        settings: Settings::new(db.clone()),
+        db,
+        secrets: SecretBox::new(&[7; 32]).unwrap(),
+    };
+    (mfa, user.id)
+}
+
+fn current_code(secret: &str) -> String {
+    Builder::new()
+        .with_secret(Secret::try_from_base32(secret).unwrap())
+        .build()
+        .unwrap()
+        .generate_current()
+        .to_string()
+}
+
+#[tokio::test]
+async fn test_secret_is_stored_encrypted() {
+    let (mfa, user_id) = service().await;
+
+    let enrollment = mfa.begin_enrollment(user_id).await.unwrap();
+
+    let stored = mfa.find_user(user_id).await.unwrap().totp_secret.unwrap();
+    assert_ne!(stored, enrollment.secret);
+    assert_eq!(mfa.secrets.decrypt(&stored).unwrap(), enrollment.secret);
+}
+
+#[tokio::test]
+async fn test_verify_code_rejects_a_replayed_code() {
+    let (mfa, user_id) = service().await;
+    let secret = mfa.begin_enrollment(user_id).await.unwrap().secret;
+    let code = current_code(&secret);
+
+    mfa.activate(user_id, &code).await.unwrap();
+
+    // Activation used up the current step
+    assert!(matches!(
+        mfa.verify_code(user_id, &code).await,
+        Err(MfaError::InvalidCode)
+    ));
+}
+
+#[tokio::test]
+async fn test_recovery_codes_are_single_use() {
+    let (mfa, user_id) = service().await;
+    let secret = mfa.begin_enrollment(user_id).await.unwrap().secret;
+    let codes = mfa
+        .activate(user_id, &current_code(&secret))
+        .await
+        .unwrap()
+        .codes;
+
+    mfa.verify_code(user_id, &codes[0]).await.unwrap();
+    assert!(matches!(
+        mfa.verify_code(user_id, &codes[0]).await,
+        Err(MfaError::InvalidCode)
+    ));
+    mfa.verify_code(user_id, &codes[1].to_uppercase())
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_verify_code_needs_mfa_enabled() {
+    let (mfa, user_id) = service().await;
+    let secret = mfa.begin_enrollment(user_id).await.unwrap().secret;
+
+    assert!(matches!(
+        mfa.verify_code(user_id, &current_code(&secret)).await,
+        Err(MfaError::NotEnabled)
+    ));
+}
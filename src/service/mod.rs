@@ -1,2 +1,15 @@
+pub mod api_token;
+pub mod audit;
+pub mod files;
 pub mod game_schema;
+pub mod instance;
+pub mod mfa;
+pub mod player;
+pub mod ports;
+pub mod rcon;
+pub mod setting;
+pub mod sso;
+pub mod steam_credential;
+pub mod update;
 pub mod user;
+pub mod workshop;
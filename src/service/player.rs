@@ -0,0 +1,457 @@
+use crate::auth::guards::ModeratorGuard;
+use crate::dto;
+use crate::dto::instance::TriggerFired;
+use crate::entity;
+use crate::models::player::PlayerAction;
+use crate::schema::{
+    command::{self, CommandError},
+    server_config::{ServerConfig, TriggerEvent},
+};
+use crate::service::instance::{Instance, InstanceError};
+use crate::service::rcon::{Rcon, RconError};
+use crate::state::instance::{InstanceManager, InstanceProcess};
+use crate::utils::error_response;
+use rocket::{
+    http::Status,
+    request::{FromRequest, Outcome},
+    response::Responder,
+};
+use sea_orm::prelude::*;
+use sea_orm::sea_query::Expr;
+use sea_orm::ActiveValue::Set;
+use sea_orm::{QueryOrder, QuerySelect};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use thiserror::Error;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+#[cfg(test)]
+mod tests;
+
+const DEFAULT_PER_PAGE: u64 = 50;
+const MAX_PER_PAGE: u64 = 500;
+
+#[derive(Error, Debug)]
+pub enum PlayerError {
+    #[error("database connection not found")]
+    DbNotFound,
+
+    #[error("A database error occurred. Please review the logs for more details.")]
+    DbError(#[from] sea_orm::DbErr),
+
+    #[error(transparent)]
+    Instance(#[from] InstanceError),
+
+    #[error(transparent)]
+    Rcon(#[from] RconError),
+
+    #[error(transparent)]
+    Command(#[from] CommandError),
+
+    #[error("This instance's game schema declares no {0} command")]
+    NotSupported(&'static str),
+
+    #[error("A player name is required")]
+    MissingName,
+
+    #[error("The {0} command needs the player's Steam ID")]
+    MissingSteamId(&'static str),
+
+    #[error("The player's {0} may not contain quotes, semicolons or control characters")]
+    InvalidValue(&'static str),
+}
+
+impl<'r> Responder<'r, 'static> for PlayerError {
+    fn respond_to(self, req: &'r rocket::Request<'_>) -> rocket::response::Result<'static> {
+        let status = match self {
+            PlayerError::DbNotFound | PlayerError::DbError(_) => Status::InternalServerError,
+            PlayerError::Instance(e) => return e.respond_to(req),
+            PlayerError::Rcon(e) => return e.respond_to(req),
+            PlayerError::Command(_)
+            | PlayerError::NotSupported(_)
+            | PlayerError::MissingName
+            | PlayerError::MissingSteamId(_)
+            | PlayerError::InvalidValue(_) => Status::UnprocessableEntity,
+        };
+        error_response(self, status)
+    }
+}
+
+/// A player named in a console line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeenPlayer {
+    pub name: String,
+    pub steam_id: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlayerEvent {
+    Joined(SeenPlayer),
+    Left(SeenPlayer),
+}
+
+impl PlayerEvent {
+    /// The player a `player_join` or `player_leave` trigger captured
+    pub fn from_trigger(fired: &TriggerFired) -> Option<Self> {
+        let name = fired.captures.get("name")?.trim();
+        if name.is_empty() {
+            return None;
+        }
+        let player = SeenPlayer {
+            name: name.to_string(),
+            steam_id: fired
+                .captures
+                .get("steamId")
+                .map(|id| id.trim().to_string())
+                .filter(|id| !id.is_empty()),
+        };
+
+        match fired.event {
+            TriggerEvent::PlayerJoin => Some(PlayerEvent::Joined(player)),
+            TriggerEvent::PlayerLeave => Some(PlayerEvent::Left(player)),
+            _ => None,
+        }
+    }
+}
+
+/// Values are pasted into console commands, where quotes and semicolons would let them
+/// break out of their argument or chain another command
+fn check_value(value: &str, what: &'static str) -> Result<(), PlayerError> {
+    if value.contains(['"', ';']) || value.chars().any(char::is_control) {
+        return Err(PlayerError::InvalidValue(what));
+    }
+    Ok(())
+}
+
+/// Render the console command the schema declares for `action`
+pub fn player_command(
+    schema: &ServerConfig,
+    action: PlayerAction,
+    name: &str,
+    steam_id: Option<&str>,
+    reason: Option<&str>,
+) -> Result<String, PlayerError> {
+    let template = schema
+        .players
+        .as_ref()
+        .and_then(|players| players.commands.template(action))
+        .ok_or(PlayerError::NotSupported(action.as_str()))?;
+
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(PlayerError::MissingName);
+    }
+    check_value(name, "name")?;
+    let steam_id = steam_id.map(str::trim).filter(|id| !id.is_empty());
+    if let Some(steam_id) = steam_id {
+        check_value(steam_id, "Steam ID")?;
+    }
+    let reason = reason.map(str::trim).unwrap_or_default();
+    check_value(reason, "reason")?;
+
+    if steam_id.is_none() && command::template_variables(template)?.contains(&"steamId") {
+        return Err(PlayerError::MissingSteamId(action.as_str()));
+    }
+
+    Ok(command::render_template(
+        template,
+        &[
+            ("name", name),
+            ("steamId", steam_id.unwrap_or_default()),
+            ("reason", reason),
+        ],
+    )?)
+}
+
+/// Who is banned and whitelisted, by replaying the moderation actions oldest first. Players
+/// are told apart by Steam ID when one was given and by name otherwise.
+pub fn restrictions(actions: &[entity::player_action::Model]) -> dto::player::PlayerRestrictions {
+    let mut banned = BTreeMap::new();
+    let mut whitelisted = BTreeMap::new();
+
+    for action in actions {
+        let key = action
+            .steam_id
+            .clone()
+            .unwrap_or_else(|| action.player_name.to_lowercase());
+        let restriction = || dto::player::PlayerRestriction {
+            name: action.player_name.clone(),
+            steam_id: action.steam_id.clone(),
+            reason: action.reason.clone(),
+            username: action.username.clone(),
+            since: action.created_at,
+        };
+        match PlayerAction::parse(&action.action) {
+            Some(PlayerAction::Ban) => {
+                banned.insert(key, restriction());
+            }
+            Some(PlayerAction::Unban) => {
+                banned.remove(&key);
+            }
+            Some(PlayerAction::WhitelistAdd) => {
+                whitelisted.insert(key, restriction());
+            }
+            Some(PlayerAction::WhitelistRemove) => {
+                whitelisted.remove(&key);
+            }
+            Some(PlayerAction::Kick) | None => {}
+        }
+    }
+
+    dto::player::PlayerRestrictions {
+        banned: banned.into_values().collect(),
+        whitelisted: whitelisted.into_values().collect(),
+    }
+}
+
+/// Player sessions and moderation of game server instances
+pub struct Players {
+    db: DatabaseConnection,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Players {
+    type Error = PlayerError;
+
+    async fn from_request(request: &'r rocket::Request<'_>) -> Outcome<Self, Self::Error> {
+        match request.rocket().state::<DatabaseConnection>() {
+            Some(db) => Outcome::Success(Players::new(db.clone())),
+            None => Outcome::Error((Status::InternalServerError, PlayerError::DbNotFound)),
+        }
+    }
+}
+
+impl Players {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// Newest-first page of an instance's player sessions. Pages start at 1.
+    pub async fn sessions(
+        &self,
+        id: i32,
+        query: &dto::player::PlayerSessionQuery,
+    ) -> Result<dto::player::PlayerSessionPage, PlayerError> {
+        Instance::new(self.db.clone(), None).find_by_id(id).await?;
+        let page = query.page.unwrap_or(1).max(1);
+        let per_page = query
+            .per_page
+            .unwrap_or(DEFAULT_PER_PAGE)
+            .clamp(1, MAX_PER_PAGE);
+
+        let mut select = entity::player_session::Entity::find()
+            .filter(entity::player_session::Column::InstanceId.eq(id));
+        if query.online == Some(true) {
+            select = select.filter(entity::player_session::Column::LeftAt.is_null());
+        }
+        if let Some(name) = query.name.as_deref().filter(|name| !name.is_empty()) {
+            select = select.filter(entity::player_session::Column::Name.contains(name));
+        }
+        if let Some(steam_id) = &query.steam_id {
+            select = select.filter(entity::player_session::Column::SteamId.eq(steam_id.as_str()));
+        }
+        let select = select.order_by_desc(entity::player_session::Column::Id);
+
+        let total = select.clone().count(&self.db).await?;
+        let sessions = select
+            .offset((page - 1) * per_page)
+            .limit(per_page)
+            .all(&self.db)
+            .await?;
+
+        Ok(dto::player::PlayerSessionPage {
+            sessions: sessions.into_iter().map(Into::into).collect(),
+            total,
+            page,
+            per_page,
+        })
+    }
+
+    /// Every moderation action taken on an instance, newest first
+    pub async fn actions(
+        &self,
+        id: i32,
+    ) -> Result<Vec<dto::player::PlayerActionEntry>, PlayerError> {
+        Instance::new(self.db.clone(), None).find_by_id(id).await?;
+
+        Ok(entity::player_action::Entity::find()
+            .filter(entity::player_action::Column::InstanceId.eq(id))
+            .order_by_desc(entity::player_action::Column::Id)
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    /// Players banned or whitelisted on an instance through the panel
+    pub async fn restrictions(
+        &self,
+        id: i32,
+    ) -> Result<dto::player::PlayerRestrictions, PlayerError> {
+        Instance::new(self.db.clone(), None).find_by_id(id).await?;
+        let actions = entity::player_action::Entity::find()
+            .filter(entity::player_action::Column::InstanceId.eq(id))
+            .order_by_asc(entity::player_action::Column::Id)
+            .all(&self.db)
+            .await?;
+
+        Ok(restrictions(&actions))
+    }
+
+    /// Send the schema's command for a moderation action to the running server and record
+    /// it against the moderator. Returns the action and how the command was sent.
+    pub async fn moderate(
+        &self,
+        id: i32,
+        request: &dto::player::PlayerModeration,
+        moderator: &ModeratorGuard,
+        manager: &InstanceManager,
+    ) -> Result<(dto::player::PlayerActionEntry, &'static str), PlayerError> {
+        let (_, schema) = Instance::new(self.db.clone(), None)
+            .find_with_schema(id)
+            .await?;
+        let command = player_command(
+            &schema,
+            request.action,
+            &request.name,
+            request.steam_id.as_deref(),
+            request.reason.as_deref(),
+        )?;
+
+        let via = Rcon::new(self.db.clone())
+            .send(id, &command, manager)
+            .await?;
+
+        let action = entity::player_action::ActiveModel {
+            instance_id: Set(id),
+            action: Set(request.action.as_str().to_string()),
+            player_name: Set(request.name.trim().to_string()),
+            steam_id: Set(request
+                .steam_id
+                .as_deref()
+                .map(str::trim)
+                .filter(|id| !id.is_empty())
+                .map(str::to_string)),
+            reason: Set(request
+                .reason
+                .as_deref()
+                .map(str::trim)
+                .filter(|reason| !reason.is_empty())
+                .map(str::to_string)),
+            command: Set(command),
+            user_id: Set(Some(moderator.user_id)),
+            username: Set(Some(moderator.username.clone())),
+            created_at: Set(chrono::Utc::now()),
+            ..Default::default()
+        }
+        .insert(&self.db)
+        .await?;
+
+        Ok((action.into(), via))
+    }
+
+    /// Mark sessions still open as ended now: those of one instance, or all of them when the
+    /// panel starts and nothing can still be running
+    pub async fn close_sessions(&self, id: Option<i32>) -> Result<(), sea_orm::DbErr> {
+        let mut update = entity::player_session::Entity::update_many()
+            .col_expr(
+                entity::player_session::Column::LeftAt,
+                Expr::value(chrono::Utc::now()),
+            )
+            .filter(entity::player_session::Column::LeftAt.is_null());
+        if let Some(id) = id {
+            update = update.filter(entity::player_session::Column::InstanceId.eq(id));
+        }
+        update.exec(&self.db).await?;
+        Ok(())
+    }
+
+    async fn open_session(
+        &self,
+        id: i32,
+        player: &SeenPlayer,
+    ) -> Result<Option<entity::player_session::Model>, sea_orm::DbErr> {
+        let mut select = entity::player_session::Entity::find()
+            .filter(entity::player_session::Column::InstanceId.eq(id))
+            .filter(entity::player_session::Column::LeftAt.is_null());
+        select = match &player.steam_id {
+            Some(steam_id) => {
+                select.filter(entity::player_session::Column::SteamId.eq(steam_id.as_str()))
+            }
+            None => select.filter(entity::player_session::Column::Name.eq(player.name.as_str())),
+        };
+        select
+            .order_by_desc(entity::player_session::Column::Id)
+            .one(&self.db)
+            .await
+    }
+
+    /// Record a join or leave seen on the console. A repeated join of someone already on the
+    /// server and a leave without a join are ignored.
+    pub async fn record(&self, id: i32, event: &PlayerEvent) -> Result<(), sea_orm::DbErr> {
+        match event {
+            PlayerEvent::Joined(player) => {
+                if self.open_session(id, player).await?.is_some() {
+                    return Ok(());
+                }
+                entity::player_session::ActiveModel {
+                    instance_id: Set(id),
+                    name: Set(player.name.clone()),
+                    steam_id: Set(player.steam_id.clone()),
+                    joined_at: Set(chrono::Utc::now()),
+                    ..Default::default()
+                }
+                .insert(&self.db)
+                .await?;
+            }
+            PlayerEvent::Left(player) => {
+                if let Some(session) = self.open_session(id, player).await? {
+                    let mut session: entity::player_session::ActiveModel = session.into();
+                    session.left_at = Set(Some(chrono::Utc::now()));
+                    session.update(&self.db).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Record the joins and leaves a freshly started server's triggers report, until the
+    /// server exits and everyone still on it is signed off. `fired` must be subscribed before
+    /// the server was started.
+    pub fn track(
+        self,
+        id: i32,
+        process: Arc<InstanceProcess>,
+        mut fired: broadcast::Receiver<(i32, TriggerFired)>,
+    ) {
+        tokio::spawn(async move {
+            let exited = process.wait_exit();
+            tokio::pin!(exited);
+
+            loop {
+                tokio::select! {
+                    _ = &mut exited => break,
+                    fired = fired.recv() => match fired {
+                        Ok((fired_id, fired)) if fired_id == id => {
+                            let Some(event) = PlayerEvent::from_trigger(&fired) else {
+                                continue;
+                            };
+                            if let Err(e) = self.record(id, &event).await {
+                                eprintln!("Failed to record player of instance {id}: {e}");
+                            }
+                        }
+                        Ok(_) => continue,
+                        // Events a slow database made us miss are gone
+                        Err(RecvError::Lagged(_)) => continue,
+                        Err(RecvError::Closed) => break,
+                    },
+                }
+            }
+
+            if let Err(e) = self.close_sessions(Some(id)).await {
+                eprintln!("Failed to close player sessions of instance {id}: {e}");
+            }
+        });
+    }
+}
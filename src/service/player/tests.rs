@@ -0,0 +1,162 @@
+use super::*;
+use serde_json::json;
+
+fn schema(players: serde_json::Value) -> ServerConfig {
+    serde_json::from_value(json!({
+        "steamAppId": 232250,
+        "executableName": "srcds_run",
+        "displayName": "TF2",
+        "players": players
+    }))
+    .unwrap()
+}
+
+fn tf2_players() -> serde_json::Value {
+    json!({
+        "commands": {
+            "kick": "kickid {{steamId}} {{reason}}",
+            "ban": "banid 0 {{steamId}} kick",
+            "whitelistAdd": "sm_whitelist_add \"{{name}}\""
+        }
+    })
+}
+
+#[test]
+fn test_player_events_come_from_player_triggers() {
+    let fired = |event, captures: &[(&str, &str)]| TriggerFired {
+        event,
+        name: None,
+        captures: captures
+            .iter()
+            .map(|(k, v)| (String::from(*k), String::from(*v)))
+            .collect(),
+        line: String::new(),
+        fired_at: chrono::Utc::now(),
+    };
+
+    assert_eq!(
+        PlayerEvent::from_trigger(&fired(
+            TriggerEvent::PlayerJoin,
+            &[("name", "alice"), ("steamId", "[U:1:1234]")]
+        )),
+        Some(PlayerEvent::Joined(SeenPlayer {
+            name: "alice".to_string(),
+            steam_id: Some("[U:1:1234]".to_string())
+        }))
+    );
+    assert_eq!(
+        PlayerEvent::from_trigger(&fired(TriggerEvent::PlayerLeave, &[("name", "bob")])),
+        Some(PlayerEvent::Left(SeenPlayer {
+            name: "bob".to_string(),
+            steam_id: None
+        }))
+    );
+    assert_eq!(
+        PlayerEvent::from_trigger(&fired(TriggerEvent::Error, &[("name", "bob")])),
+        None
+    );
+}
+
+#[test]
+fn test_player_command_renders_template() {
+    let schema = schema(tf2_players());
+
+    assert_eq!(
+        player_command(
+            &schema,
+            PlayerAction::Kick,
+            "alice",
+            Some("[U:1:1234]"),
+            Some("spamming")
+        )
+        .unwrap(),
+        "kickid [U:1:1234] spamming"
+    );
+    assert_eq!(
+        player_command(
+            &schema,
+            PlayerAction::Kick,
+            "alice",
+            Some("[U:1:1234]"),
+            None
+        )
+        .unwrap(),
+        "kickid [U:1:1234]"
+    );
+    assert_eq!(
+        player_command(&schema, PlayerAction::WhitelistAdd, "alice", None, None).unwrap(),
+        "sm_whitelist_add \"alice\""
+    );
+}
+
+#[test]
+fn test_player_command_rejects_unsafe_or_missing_values() {
+    let schema = schema(tf2_players());
+
+    assert!(matches!(
+        player_command(&schema, PlayerAction::WhitelistAdd, "x\"; quit", None, None),
+        Err(PlayerError::InvalidValue("name"))
+    ));
+    assert!(matches!(
+        player_command(
+            &schema,
+            PlayerAction::Kick,
+            "alice",
+            Some("1"),
+            Some("bye;quit")
+        ),
+        Err(PlayerError::InvalidValue("reason"))
+    ));
+    assert!(matches!(
+        player_command(&schema, PlayerAction::Ban, "alice", None, None),
+        Err(PlayerError::MissingSteamId("ban"))
+    ));
+    assert!(matches!(
+        player_command(&schema, PlayerAction::Unban, "alice", Some("1"), None),
+        Err(PlayerError::NotSupported("unban"))
+    ));
+}
+
+fn action(id: i32, action: PlayerAction, name: &str) -> entity::player_action::Model {
+    entity::player_action::Model {
+        id,
+        instance_id: 1,
+        action: action.as_str().to_string(),
+        player_name: name.to_string(),
+        steam_id: None,
+        reason: None,
+        command: String::new(),
+        user_id: Some(1),
+        username: Some("mod".to_string()),
+        created_at: chrono::Utc::now(),
+    }
+}
+
+#[test]
+fn test_restrictions_replay_actions_in_order() {
+    let restrictions = restrictions(&[
+        action(1, PlayerAction::Ban, "alice"),
+        action(2, PlayerAction::Ban, "bob"),
+        action(3, PlayerAction::Unban, "Alice"),
+        action(4, PlayerAction::WhitelistAdd, "carol"),
+        action(5, PlayerAction::Kick, "dave"),
+    ]);
+
+    let names = |list: &[dto::player::PlayerRestriction]| {
+        list.iter().map(|r| r.name.clone()).collect::<Vec<_>>()
+    };
+    assert_eq!(names(&restrictions.banned), vec!["bob"]);
+    assert_eq!(names(&restrictions.whitelisted), vec!["carol"]);
+}
+
+#[test]
+fn test_schema_validates_player_commands() {
+    let schema = schema(json!({ "commands": { "kick": "kick {{name}} {{why}}" } }));
+
+    assert_eq!(
+        schema.validate(),
+        Err(vec![
+            "Player kick command uses unknown variable 'why'".to_string()
+        ])
+    );
+}
@@ -0,0 +1,258 @@
+//! Ports of game servers: the ones an instance's config makes it bind, clashes between
+//! instances, and picking free ones from PORT_RANGE for fields left unset.
+
+use crate::dto::instance::InstancePort;
+use crate::schema::{
+    command::config_value,
+    server_config::{ArgumentType, DynamicField, PortProtocol, ServerConfig},
+    GameConfig,
+};
+use crate::utils::error_response;
+use rocket::{http::Status, response::Responder};
+use serde_json::Value;
+use std::fmt;
+use thiserror::Error;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Error, Debug)]
+pub enum PortError {
+    #[error("Port conflict: {}", .0.join("; "))]
+    Conflict(Vec<String>),
+
+    #[error("No free port left in {range} for field '{field}'")]
+    Exhausted { field: String, range: PortRange },
+
+    #[error("Port {0} is already in use on this host")]
+    InUse(String),
+}
+
+impl<'r> Responder<'r, 'static> for PortError {
+    fn respond_to(self, _req: &'r rocket::Request<'_>) -> rocket::response::Result<'static> {
+        error_response(self, Status::Conflict)
+    }
+}
+
+/// Ports handed out to fields without a value, both ends inclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PortRange {
+    pub start: u16,
+    pub end: u16,
+}
+
+impl Default for PortRange {
+    fn default() -> Self {
+        Self {
+            start: 27000,
+            end: 27999,
+        }
+    }
+}
+
+impl fmt::Display for PortRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.start, self.end)
+    }
+}
+
+impl PortRange {
+    /// Parse a range such as `27000-27999`
+    pub fn parse(value: &str) -> Option<Self> {
+        let (start, end) = value.split_once('-')?;
+        let range = Self {
+            start: start.trim().parse().ok()?,
+            end: end.trim().parse().ok()?,
+        };
+        (range.start > 0 && range.start <= range.end).then_some(range)
+    }
+
+    /// PORT_RANGE, or the default range when it is unset or invalid
+    pub fn from_env() -> Self {
+        match std::env::var("PORT_RANGE") {
+            Ok(value) if !value.trim().is_empty() => Self::parse(&value).unwrap_or_else(|| {
+                eprintln!("Ignoring PORT_RANGE '{value}', expected e.g. 27000-27999");
+                Self::default()
+            }),
+            _ => Self::default(),
+        }
+    }
+}
+
+/// Whether ports are test-bound before a server starts and before one is handed out.
+/// PORT_BIND_PROBE=off turns this off, e.g. when servers run in another network namespace.
+pub fn probe_enabled() -> bool {
+    !matches!(
+        std::env::var("PORT_BIND_PROBE").as_deref(),
+        Ok("off" | "false" | "0")
+    )
+}
+
+/// A port for display, such as `27015/udp` or `27015-27016/tcp+udp`
+pub fn describe(port: &InstancePort) -> String {
+    let protocol = match port.protocol {
+        PortProtocol::Tcp => "tcp",
+        PortProtocol::Udp => "udp",
+        PortProtocol::Both => "tcp+udp",
+    };
+    match port.count {
+        0 | 1 => format!("{}/{protocol}", port.port),
+        count => format!(
+            "{}-{}/{protocol}",
+            port.port,
+            u32::from(port.port) + u32::from(count) - 1
+        ),
+    }
+}
+
+/// Whether two ports share a number on a common protocol
+pub fn overlaps(a: &InstancePort, b: &InstancePort) -> bool {
+    let end = |p: &InstancePort| u32::from(p.port) + u32::from(p.count.max(1));
+    a.protocol.overlaps(b.protocol) && u32::from(a.port) < end(b) && u32::from(b.port) < end(a)
+}
+
+/// The ports the server binds with this config, unset fields at their default. Fields
+/// without a valid port number are left out.
+pub fn bound_ports(schema: &ServerConfig, config: &GameConfig) -> Vec<InstancePort> {
+    schema
+        .ports()
+        .into_iter()
+        .filter_map(|(field, port)| {
+            Some(InstancePort {
+                field: field.name.clone(),
+                role: port.role,
+                protocol: port.protocol,
+                port: config_value(field, config)?.parse().ok()?,
+                count: port.count,
+            })
+        })
+        .collect()
+}
+
+/// Clashes of `ports` among themselves and with the ports of `others`, by instance name
+pub fn conflicts(ports: &[InstancePort], others: &[(String, Vec<InstancePort>)]) -> Vec<String> {
+    let mut errors = Vec::new();
+    for (i, port) in ports.iter().enumerate() {
+        for other in ports[i + 1..].iter().filter(|other| overlaps(port, other)) {
+            errors.push(format!(
+                "Fields '{}' and '{}' both use port {}",
+                port.field,
+                other.field,
+                describe(port)
+            ));
+        }
+        for (name, theirs) in others {
+            if theirs.iter().any(|theirs| overlaps(port, theirs)) {
+                errors.push(format!(
+                    "Port {} of field '{}' is already used by instance '{name}'",
+                    describe(port),
+                    port.field
+                ));
+            }
+        }
+    }
+    errors
+}
+
+fn is_unset(config: &GameConfig, field: &str) -> bool {
+    match config.get(field) {
+        None | Some(Value::Null) => true,
+        Some(Value::String(s)) => s.is_empty(),
+        Some(_) => false,
+    }
+}
+
+/// Whether `port` is within the field's number bounds
+fn fits(field: &DynamicField, port: u16) -> bool {
+    match &field.arg_type {
+        ArgumentType::Number(bounds) => {
+            let port = f64::from(port);
+            bounds.min.is_none_or(|min| port >= min) && bounds.max.is_none_or(|max| port <= max)
+        }
+        _ => true,
+    }
+}
+
+/// Give the port fields left unset a port nothing in `taken` uses and `is_free` accepts: the
+/// field's default when possible, otherwise the lowest one in `range` with room for the
+/// field's whole block. Optional fields without a default stay unset, since leaving them out
+/// may turn the feature off. Returns the ports assigned.
+pub fn allocate(
+    schema: &ServerConfig,
+    config: &mut GameConfig,
+    taken: &[InstancePort],
+    range: PortRange,
+    is_free: impl Fn(&InstancePort) -> bool,
+) -> Result<Vec<InstancePort>, PortError> {
+    let ports = schema.ports();
+    let mut taken: Vec<InstancePort> = taken.to_vec();
+    taken.extend(
+        bound_ports(schema, config)
+            .into_iter()
+            .filter(|port| !is_unset(config, &port.field)),
+    );
+
+    let mut assigned = Vec::new();
+    for (field, port) in ports {
+        if !is_unset(config, &field.name) || (!field.required && field.default.is_none()) {
+            continue;
+        }
+
+        let candidate = |number: u16| InstancePort {
+            field: field.name.clone(),
+            role: port.role,
+            protocol: port.protocol,
+            port: number,
+            count: port.count,
+        };
+        let usable = |candidate: &InstancePort| {
+            let last = u32::from(candidate.port) + u32::from(candidate.count.max(1)) - 1;
+            last <= u32::from(u16::MAX)
+                && fits(field, candidate.port)
+                && !taken.iter().any(|other| overlaps(candidate, other))
+                && is_free(candidate)
+        };
+
+        let default = field.default.as_deref().and_then(|d| d.parse().ok());
+        let chosen = default
+            .into_iter()
+            .chain(range.start..=range.end.saturating_sub(port.count.max(1) - 1))
+            .map(candidate)
+            .find(usable)
+            .ok_or_else(|| PortError::Exhausted {
+                field: field.name.clone(),
+                range,
+            })?;
+
+        config.insert(field.name.clone(), Value::from(chosen.port));
+        taken.push(chosen.clone());
+        assigned.push(chosen);
+    }
+
+    Ok(assigned)
+}
+
+/// Bind every port briefly to find those another program already uses
+pub fn probe(ports: &[InstancePort]) -> Result<(), PortError> {
+    for port in ports {
+        let end = u32::from(port.port) + u32::from(port.count.max(1));
+        for number in (u32::from(port.port)..end).filter_map(|n| u16::try_from(n).ok()) {
+            let tcp = matches!(port.protocol, PortProtocol::Tcp | PortProtocol::Both);
+            let udp = matches!(port.protocol, PortProtocol::Udp | PortProtocol::Both);
+            let bound = (!tcp || std::net::TcpListener::bind(("0.0.0.0", number)).is_ok())
+                && (!udp || std::net::UdpSocket::bind(("0.0.0.0", number)).is_ok());
+            if !bound {
+                return Err(PortError::InUse(format!(
+                    "{} of field '{}'",
+                    describe(&InstancePort {
+                        port: number,
+                        count: 1,
+                        ..port.clone()
+                    }),
+                    port.field
+                )));
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,140 @@
+use super::*;
+use crate::schema::server_config::PortRole;
+use serde_json::json;
+
+fn schema() -> ServerConfig {
+    serde_json::from_value(json!({
+        "steamAppId": 376030,
+        "executableName": "ShooterGameServer",
+        "displayName": "ARK",
+        "args": [
+            { "name": "port", "flag": "-port", "type": "number", "default": "7777", "required": true,
+              "description": "Game port", "port": { "role": "game", "count": 2 } },
+            { "name": "queryPort", "flag": "-queryport", "type": "number", "default": "27015",
+              "description": "Query port" },
+            { "name": "rconPort", "flag": "-rconport", "type": "number", "description": "RCON port" },
+            { "name": "rconPassword", "flag": "-rconpass", "type": "string", "description": "RCON password" }
+        ],
+        "rcon": { "portField": "rconPort", "passwordField": "rconPassword" },
+        "query": { "portField": "queryPort" }
+    }))
+    .unwrap()
+}
+
+fn config(value: serde_json::Value) -> GameConfig {
+    serde_json::from_value(value).unwrap()
+}
+
+fn port(field: &str, protocol: PortProtocol, port: u16, count: u16) -> InstancePort {
+    InstancePort {
+        field: field.to_string(),
+        role: PortRole::Game,
+        protocol,
+        port,
+        count,
+    }
+}
+
+#[test]
+fn test_parse_port_range() {
+    assert_eq!(
+        PortRange::parse("27000-27100"),
+        Some(PortRange {
+            start: 27000,
+            end: 27100
+        })
+    );
+    assert_eq!(PortRange::parse("27100-27000"), None);
+    assert_eq!(PortRange::parse("0-100"), None);
+    assert_eq!(PortRange::parse("27000"), None);
+}
+
+#[test]
+fn test_bound_ports_include_rcon_and_query_fields() {
+    let ports = bound_ports(&schema(), &config(json!({ "rconPort": 27020 })));
+
+    assert_eq!(
+        ports,
+        vec![
+            InstancePort {
+                role: PortRole::Game,
+                ..port("port", PortProtocol::Udp, 7777, 2)
+            },
+            InstancePort {
+                role: PortRole::Query,
+                ..port("queryPort", PortProtocol::Udp, 27015, 1)
+            },
+            InstancePort {
+                role: PortRole::Rcon,
+                ..port("rconPort", PortProtocol::Tcp, 27020, 1)
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_ports_overlap_on_shared_protocol_and_numbers() {
+    let game = port("port", PortProtocol::Udp, 7777, 2);
+
+    assert!(overlaps(&game, &port("other", PortProtocol::Udp, 7778, 1)));
+    assert!(overlaps(&game, &port("other", PortProtocol::Both, 7777, 1)));
+    assert!(!overlaps(&game, &port("other", PortProtocol::Tcp, 7777, 1)));
+    assert!(!overlaps(&game, &port("other", PortProtocol::Udp, 7779, 1)));
+}
+
+#[test]
+fn test_conflicts_name_the_field_and_instance() {
+    let ports = bound_ports(&schema(), &config(json!({ "queryPort": 7778 })));
+    let others = vec![(
+        "main".to_string(),
+        vec![port("port", PortProtocol::Udp, 27015, 1)],
+    )];
+
+    assert_eq!(
+        conflicts(&ports, &others),
+        vec!["Fields 'port' and 'queryPort' both use port 7777-7778/udp"]
+    );
+
+    let ports = bound_ports(&schema(), &config(json!({})));
+    assert_eq!(
+        conflicts(&ports, &others),
+        vec!["Port 27015/udp of field 'queryPort' is already used by instance 'main'"]
+    );
+}
+
+#[test]
+fn test_allocate_prefers_defaults_then_range() {
+    let mut config = config(json!({}));
+    let taken = vec![port("port", PortProtocol::Udp, 7778, 1)];
+    let range = PortRange {
+        start: 27000,
+        end: 27010,
+    };
+
+    let assigned = allocate(&schema(), &mut config, &taken, range, |p| p.port != 27000).unwrap();
+
+    // The game port default overlaps a taken port, 27000 is not free on the host, and the RCON
+    // port is optional without a default
+    assert_eq!(
+        assigned.iter().map(|p| p.port).collect::<Vec<_>>(),
+        vec![27001, 27015]
+    );
+    assert_eq!(config["port"], json!(27001));
+    assert_eq!(config["queryPort"], json!(27015));
+    assert!(!config.contains_key("rconPort"));
+}
+
+#[test]
+fn test_allocate_keeps_configured_ports_and_fails_when_exhausted() {
+    let mut config = config(json!({ "queryPort": 27000 }));
+    let range = PortRange {
+        start: 27000,
+        end: 27001,
+    };
+
+    let error = allocate(&schema(), &mut config, &[], range, |p| p.port != 7777).unwrap_err();
+
+    // 27000 is the query port and the game port needs two in a row within the range
+    assert!(matches!(error, PortError::Exhausted { field, .. } if field == "port"));
+    assert_eq!(config["queryPort"], json!(27000));
+}
@@ -0,0 +1,183 @@
+use crate::schema::{self, command::config_value, server_config::ServerConfig};
+use crate::service::instance::{self, InstanceError};
+use crate::state::instance::InstanceManager;
+use crate::utils::error_response;
+use rocket::{
+    http::Status,
+    request::{FromRequest, Outcome},
+    response::Responder,
+};
+use sea_orm::DatabaseConnection;
+use std::time::Duration;
+use thiserror::Error;
+
+pub mod client;
+
+#[cfg(test)]
+mod tests;
+
+use client::{RconClient, RconClientError};
+
+/// How long to wait for an RCON server to accept a connection or answer a command
+const RCON_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Error, Debug)]
+pub enum RconError {
+    #[error("database connection not found")]
+    DbNotFound,
+
+    #[error(transparent)]
+    Instance(#[from] InstanceError),
+
+    #[error("This instance's game schema does not declare RCON support")]
+    NotSupported,
+
+    #[error("Instance {0} is not running")]
+    NotRunning(i32),
+
+    #[error("The instance has no value for its RCON {0} field")]
+    MissingSetting(&'static str),
+
+    #[error("'{0}' is not a valid RCON port")]
+    InvalidPort(String),
+
+    #[error(transparent)]
+    Client(#[from] RconClientError),
+}
+
+impl<'r> Responder<'r, 'static> for RconError {
+    fn respond_to(self, req: &'r rocket::Request<'_>) -> rocket::response::Result<'static> {
+        let status = match self {
+            RconError::DbNotFound => Status::InternalServerError,
+            RconError::Instance(e) => return e.respond_to(req),
+            RconError::NotRunning(_) => Status::Conflict,
+            RconError::NotSupported
+            | RconError::MissingSetting(_)
+            | RconError::InvalidPort(_)
+            | RconError::Client(RconClientError::InvalidCommand) => Status::UnprocessableEntity,
+            RconError::Client(RconClientError::Timeout) => Status::GatewayTimeout,
+            RconError::Client(_) => Status::BadGateway,
+        };
+        error_response(self, status)
+    }
+}
+
+/// Where an instance's RCON server listens and the password it expects
+#[derive(Debug, PartialEq, Eq)]
+pub struct RconTarget {
+    pub port: u16,
+    pub password: String,
+}
+
+/// Read the RCON port and password from the fields the schema declares for them
+pub fn rcon_target(
+    schema: &ServerConfig,
+    config: &schema::GameConfig,
+) -> Result<RconTarget, RconError> {
+    let support = schema.rcon.as_ref().ok_or(RconError::NotSupported)?;
+    let value = |name: &str, purpose: &'static str| {
+        schema
+            .args
+            .iter()
+            .find(|field| field.name == name)
+            .and_then(|field| config_value(field, config))
+            .ok_or(RconError::MissingSetting(purpose))
+    };
+
+    let port = value(&support.port_field, "port")?;
+    let port = match port.parse::<u16>() {
+        Ok(port) if port != 0 => port,
+        _ => return Err(RconError::InvalidPort(port)),
+    };
+    let password = value(&support.password_field, "password")?;
+
+    Ok(RconTarget { port, password })
+}
+
+/// Runs console commands on game servers over RCON
+pub struct Rcon {
+    db: DatabaseConnection,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Rcon {
+    type Error = RconError;
+
+    async fn from_request(request: &'r rocket::Request<'_>) -> Outcome<Self, Self::Error> {
+        match request.rocket().state::<DatabaseConnection>() {
+            Some(db) => Outcome::Success(Rcon::new(db.clone())),
+            None => Outcome::Error((Status::InternalServerError, RconError::DbNotFound)),
+        }
+    }
+}
+
+impl Rcon {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    pub async fn target(&self, id: i32) -> Result<RconTarget, RconError> {
+        let (instance, schema) = instance::Instance::new(self.db.clone(), None)
+            .find_with_schema(id)
+            .await?;
+        let config: schema::GameConfig =
+            serde_json::from_value(instance.config_json).unwrap_or_default();
+
+        rcon_target(&schema, &config)
+    }
+
+    /// Run `command` on a running instance and return its output. Both are added to the
+    /// instance's console so everyone watching it sees them.
+    pub async fn execute(
+        &self,
+        id: i32,
+        command: &str,
+        manager: &InstanceManager,
+    ) -> Result<String, RconError> {
+        let target = self.target(id).await?;
+        let process = manager
+            .process(id)
+            .await
+            .filter(|process| process.is_running())
+            .ok_or(RconError::NotRunning(id))?;
+
+        let command = command.trim();
+        let mut client =
+            RconClient::connect(("127.0.0.1", target.port), &target.password, RCON_TIMEOUT).await?;
+        let output = client.execute(command).await?;
+
+        process.echo(&format!("> {command}"));
+        for line in output.lines() {
+            process.echo(line);
+        }
+
+        Ok(output)
+    }
+
+    /// Run `command` over RCON when the schema declares it, or write it to the server's stdin
+    /// otherwise. Returns which way it went, `"rcon"` or `"stdin"`.
+    pub async fn send(
+        &self,
+        id: i32,
+        command: &str,
+        manager: &InstanceManager,
+    ) -> Result<&'static str, RconError> {
+        match self.execute(id, command, manager).await {
+            Ok(_) => return Ok("rcon"),
+            Err(RconError::NotSupported) => {}
+            Err(e) => return Err(e),
+        }
+
+        let process = manager
+            .process(id)
+            .await
+            .filter(|process| process.is_running())
+            .ok_or(RconError::NotRunning(id))?;
+        process
+            .send_input(command)
+            .await
+            .map_err(InstanceError::from)?;
+
+        Ok("stdin")
+    }
+}
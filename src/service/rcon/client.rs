@@ -0,0 +1,186 @@
+//! A client for the Source RCON protocol: little-endian packets of
+//! `size, id, type, body, \0, \0` over TCP.
+//! See <https://developer.valvesoftware.com/wiki/Source_RCON_Protocol>.
+
+use std::time::Duration;
+use thiserror::Error;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpStream, ToSocketAddrs},
+};
+
+const SERVERDATA_AUTH: i32 = 3;
+const SERVERDATA_AUTH_RESPONSE: i32 = 2;
+const SERVERDATA_EXECCOMMAND: i32 = 2;
+const SERVERDATA_RESPONSE_VALUE: i32 = 0;
+
+/// Servers reject packets with a larger body
+const MAX_REQUEST_BODY: usize = 4096 - 10;
+/// Responses are split into packets of about 4KB, but some servers send more in one
+const MAX_RESPONSE_PACKET: usize = 1024 * 1024;
+
+#[derive(Error, Debug)]
+pub enum RconClientError {
+    #[error("Could not talk to the RCON server: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("The RCON server rejected the password")]
+    AuthFailed,
+
+    #[error("The RCON server did not answer in time")]
+    Timeout,
+
+    #[error("The RCON server sent a malformed packet: {0}")]
+    Malformed(String),
+
+    #[error("RCON commands are limited to {MAX_REQUEST_BODY} bytes of text without line breaks")]
+    InvalidCommand,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Packet {
+    pub id: i32,
+    pub kind: i32,
+    pub body: String,
+}
+
+impl Packet {
+    pub fn encode(&self) -> Vec<u8> {
+        let size = (4 + 4 + self.body.len() + 2) as i32;
+        let mut bytes = Vec::with_capacity(size as usize + 4);
+        bytes.extend_from_slice(&size.to_le_bytes());
+        bytes.extend_from_slice(&self.id.to_le_bytes());
+        bytes.extend_from_slice(&self.kind.to_le_bytes());
+        bytes.extend_from_slice(self.body.as_bytes());
+        bytes.extend_from_slice(&[0, 0]);
+        bytes
+    }
+
+    /// Read one packet. Bodies that are not UTF-8 are decoded lossily.
+    pub async fn read(stream: &mut (impl AsyncReadExt + Unpin)) -> Result<Self, RconClientError> {
+        let size = stream.read_i32_le().await?;
+        if !(10..=MAX_RESPONSE_PACKET as i32).contains(&size) {
+            return Err(RconClientError::Malformed(format!("size {size}")));
+        }
+
+        let mut payload = vec![0; size as usize];
+        stream.read_exact(&mut payload).await?;
+        let id = i32::from_le_bytes([payload[0], payload[1], payload[2], payload[3]]);
+        let kind = i32::from_le_bytes([payload[4], payload[5], payload[6], payload[7]]);
+        let body = &payload[8..];
+        // The body ends in a NUL and the packet in another; tolerate servers that send only one
+        let body = body.strip_suffix(&[0]).unwrap_or(body);
+        let body = body.strip_suffix(&[0]).unwrap_or(body);
+
+        Ok(Self {
+            id,
+            kind,
+            body: String::from_utf8_lossy(body).into_owned(),
+        })
+    }
+}
+
+/// An authenticated RCON connection
+pub struct RconClient {
+    stream: TcpStream,
+    next_id: i32,
+    timeout: Duration,
+}
+
+impl RconClient {
+    /// Connect and log in. `timeout` applies to connecting and to every command.
+    pub async fn connect(
+        addr: impl ToSocketAddrs,
+        password: &str,
+        timeout: Duration,
+    ) -> Result<Self, RconClientError> {
+        let stream = tokio::time::timeout(timeout, TcpStream::connect(addr))
+            .await
+            .map_err(|_| RconClientError::Timeout)??;
+        let mut client = Self {
+            stream,
+            next_id: 1,
+            timeout,
+        };
+
+        tokio::time::timeout(timeout, client.authenticate(password))
+            .await
+            .map_err(|_| RconClientError::Timeout)??;
+
+        Ok(client)
+    }
+
+    fn next_id(&mut self) -> i32 {
+        let id = self.next_id;
+        self.next_id = self.next_id.checked_add(1).unwrap_or(1);
+        id
+    }
+
+    async fn send(&mut self, kind: i32, body: &str) -> Result<i32, RconClientError> {
+        let id = self.next_id();
+        let packet = Packet {
+            id,
+            kind,
+            body: body.to_string(),
+        };
+        self.stream.write_all(&packet.encode()).await?;
+        Ok(id)
+    }
+
+    async fn authenticate(&mut self, password: &str) -> Result<(), RconClientError> {
+        let id = self.send(SERVERDATA_AUTH, password).await?;
+
+        // Source servers send an empty response value before the actual auth response
+        loop {
+            let packet = Packet::read(&mut self.stream).await?;
+            match packet.kind {
+                SERVERDATA_AUTH_RESPONSE if packet.id == id => return Ok(()),
+                SERVERDATA_AUTH_RESPONSE if packet.id == -1 => {
+                    return Err(RconClientError::AuthFailed)
+                }
+                SERVERDATA_RESPONSE_VALUE => continue,
+                _ => {
+                    return Err(RconClientError::Malformed(format!(
+                        "unexpected packet {} of type {} during login",
+                        packet.id, packet.kind
+                    )))
+                }
+            }
+        }
+    }
+
+    /// Run a command and return its full output
+    pub async fn execute(&mut self, command: &str) -> Result<String, RconClientError> {
+        if command.len() > MAX_REQUEST_BODY || command.contains(['\n', '\r', '\0']) {
+            return Err(RconClientError::InvalidCommand);
+        }
+
+        let timeout = self.timeout;
+        tokio::time::timeout(timeout, self.exchange(command))
+            .await
+            .map_err(|_| RconClientError::Timeout)?
+    }
+
+    async fn exchange(&mut self, command: &str) -> Result<String, RconClientError> {
+        let id = self.send(SERVERDATA_EXECCOMMAND, command).await?;
+        // Long output arrives in several packets with nothing marking the last one. Servers
+        // answer packets in order, so an empty response value sent right after the command
+        // comes back once all of the command's output has.
+        let sentinel = self.send(SERVERDATA_RESPONSE_VALUE, "").await?;
+
+        let mut output = String::new();
+        loop {
+            let packet = Packet::read(&mut self.stream).await?;
+            if packet.id == sentinel {
+                break;
+            }
+            // Anything else is left over from an earlier command, such as the extra packet
+            // Source servers send after mirroring a sentinel
+            if packet.id == id && packet.kind == SERVERDATA_RESPONSE_VALUE {
+                output.push_str(&packet.body);
+            }
+        }
+
+        Ok(output)
+    }
+}
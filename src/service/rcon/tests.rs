@@ -0,0 +1,184 @@
+use super::client::{Packet, RconClient, RconClientError};
+use super::*;
+use serde_json::json;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpListener;
+
+const PASSWORD: &str = "hunter2";
+
+/// A Source RCON server that answers `echo <text>` with the text split over packets of at
+/// most 8 bytes, and mirrors sentinels the way srcds does
+async fn mock_server() -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                while let Ok(packet) = Packet::read(&mut stream).await {
+                    let reply = |id, kind, body: &str| {
+                        Packet {
+                            id,
+                            kind,
+                            body: body.to_string(),
+                        }
+                        .encode()
+                    };
+                    let mut out = Vec::new();
+                    match packet.kind {
+                        3 => {
+                            let id = if packet.body == PASSWORD {
+                                packet.id
+                            } else {
+                                -1
+                            };
+                            out.extend(reply(packet.id, 0, ""));
+                            out.extend(reply(id, 2, ""));
+                        }
+                        2 => {
+                            let text = packet.body.strip_prefix("echo ").unwrap_or_default();
+                            for chunk in text.as_bytes().chunks(8) {
+                                out.extend(reply(
+                                    packet.id,
+                                    0,
+                                    std::str::from_utf8(chunk).unwrap(),
+                                ));
+                            }
+                        }
+                        _ => {
+                            out.extend(reply(packet.id, 0, ""));
+                            out.extend(reply(packet.id, 0, "\u{1}\0\0\0"));
+                        }
+                    }
+                    if stream.write_all(&out).await.is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+    });
+
+    port
+}
+
+#[tokio::test]
+async fn test_client_joins_multi_packet_responses() {
+    let port = mock_server().await;
+    let mut client = RconClient::connect(("127.0.0.1", port), PASSWORD, RCON_TIMEOUT)
+        .await
+        .unwrap();
+
+    let long = "players online: alice, bob, carol, dave";
+    assert_eq!(client.execute(&format!("echo {long}")).await.unwrap(), long);
+    // The extra packet mirrored after the first sentinel must not leak into the next reply
+    assert_eq!(client.execute("echo second").await.unwrap(), "second");
+    assert_eq!(client.execute("status").await.unwrap(), "");
+}
+
+#[tokio::test]
+async fn test_client_reports_wrong_password() {
+    let port = mock_server().await;
+    let result = RconClient::connect(("127.0.0.1", port), "wrong", RCON_TIMEOUT).await;
+
+    assert!(matches!(result, Err(RconClientError::AuthFailed)));
+}
+
+#[tokio::test]
+async fn test_client_rejects_multi_line_commands() {
+    let port = mock_server().await;
+    let mut client = RconClient::connect(("127.0.0.1", port), PASSWORD, RCON_TIMEOUT)
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        client.execute("say hi\nquit").await,
+        Err(RconClientError::InvalidCommand)
+    ));
+}
+
+#[tokio::test]
+async fn test_client_times_out_on_silent_server() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        let (_stream, _) = listener.accept().await.unwrap();
+        tokio::time::sleep(Duration::from_secs(5)).await;
+    });
+
+    let result =
+        RconClient::connect(("127.0.0.1", port), PASSWORD, Duration::from_millis(100)).await;
+    assert!(matches!(result, Err(RconClientError::Timeout)));
+}
+
+fn tf2_schema(rcon: serde_json::Value) -> ServerConfig {
+    serde_json::from_value(json!({
+        "steamAppId": 232250,
+        "executableName": "srcds_run",
+        "displayName": "TF2",
+        "args": [
+            { "name": "rconPort", "flag": "-port", "type": "number", "default": "27015", "description": "Port" },
+            { "name": "rconPassword", "flag": "+rcon_password", "type": "string", "description": "Password" }
+        ],
+        "rcon": rcon
+    }))
+    .unwrap()
+}
+
+fn config(value: serde_json::Value) -> schema::GameConfig {
+    serde_json::from_value(value).unwrap()
+}
+
+#[test]
+fn test_target_reads_declared_fields() {
+    let schema = tf2_schema(json!({ "portField": "rconPort", "passwordField": "rconPassword" }));
+
+    assert_eq!(
+        rcon_target(&schema, &config(json!({ "rconPassword": "secret" }))).unwrap(),
+        RconTarget {
+            port: 27015,
+            password: "secret".to_string()
+        }
+    );
+    assert_eq!(
+        rcon_target(
+            &schema,
+            &config(json!({ "rconPort": 27020, "rconPassword": "secret" }))
+        )
+        .unwrap()
+        .port,
+        27020
+    );
+}
+
+#[test]
+fn test_target_needs_port_and_password() {
+    let schema = tf2_schema(json!({ "portField": "rconPort", "passwordField": "rconPassword" }));
+
+    assert!(matches!(
+        rcon_target(&schema, &config(json!({}))),
+        Err(RconError::MissingSetting("password"))
+    ));
+    assert!(matches!(
+        rcon_target(
+            &schema,
+            &config(json!({ "rconPort": 70000, "rconPassword": "secret" }))
+        ),
+        Err(RconError::InvalidPort(_))
+    ));
+    assert!(matches!(
+        rcon_target(&tf2_schema(serde_json::Value::Null), &config(json!({}))),
+        Err(RconError::NotSupported)
+    ));
+}
+
+#[test]
+fn test_schema_rcon_fields_must_exist() {
+    let schema = tf2_schema(json!({ "portField": "rconPort", "passwordField": "password" }));
+
+    assert_eq!(
+        schema.validate(),
+        Err(vec![
+            "RCON password field 'password' is not a field".to_string()
+        ])
+    );
+}
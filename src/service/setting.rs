@@ -0,0 +1,107 @@
+use crate::entity;
+use crate::utils::error_response;
+use rocket::{
+    http::Status,
+    request::{FromRequest, Outcome},
+    response::Responder,
+};
+use sea_orm::prelude::*;
+use sea_orm::sea_query::OnConflict;
+use sea_orm::ActiveValue::Set;
+use serde::{de::DeserializeOwned, Serialize};
+use thiserror::Error;
+
+/// Whether users with the Admin role must have TOTP enabled to log in
+pub const REQUIRE_ADMIN_MFA: &str = "require_admin_mfa";
+
+#[derive(Error, Debug)]
+pub enum SettingError {
+    #[error("database connection not found")]
+    DbNotFound,
+
+    #[error("A database error occurred. Please review the logs for more details.")]
+    DbError(#[from] sea_orm::DbErr),
+
+    #[error("Stored setting has an unexpected format: {0}")]
+    InvalidValue(#[from] serde_json::Error),
+}
+
+impl<'r> Responder<'r, 'static> for SettingError {
+    fn respond_to(self, _: &'r rocket::Request<'_>) -> rocket::response::Result<'static> {
+        error_response(self, Status::InternalServerError)
+    }
+}
+
+/// Key/value store for panel-wide settings that admins can change at runtime
+#[derive(Clone)]
+pub struct Settings {
+    db: DatabaseConnection,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Settings {
+    type Error = SettingError;
+
+    async fn from_request(request: &'r rocket::Request<'_>) -> Outcome<Self, Self::Error> {
+        match request.rocket().state::<DatabaseConnection>() {
+            Some(db) => Outcome::Success(Settings::new(db.clone())),
+            None => Outcome::Error((Status::InternalServerError, SettingError::DbNotFound)),
+        }
+    }
+}
+
+impl Settings {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// Read a setting, returning `None` if it has never been set
+    pub async fn get<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, SettingError> {
+        let model = entity::app_setting::Entity::find_by_id(key.to_string())
+            .one(&self.db)
+            .await?;
+
+        match model {
+            Some(model) => Ok(Some(serde_json::from_value(model.value)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn get_or<T: DeserializeOwned>(
+        &self,
+        key: &str,
+        default: T,
+    ) -> Result<T, SettingError> {
+        Ok(self.get(key).await?.unwrap_or(default))
+    }
+
+    /// Insert or replace a setting
+    pub async fn set<T: Serialize>(
+        &self,
+        key: &str,
+        value: &T,
+        updated_by: Option<i32>,
+    ) -> Result<(), SettingError> {
+        let active_model = entity::app_setting::ActiveModel {
+            key: Set(key.to_string()),
+            value: Set(serde_json::to_value(value)?),
+            updated_at: Set(chrono::Utc::now()),
+            updated_by: Set(updated_by),
+        };
+
+        entity::app_setting::Entity::insert(active_model)
+            .on_conflict(
+                OnConflict::column(entity::app_setting::Column::Key)
+                    .update_columns([
+                        entity::app_setting::Column::Value,
+                        entity::app_setting::Column::UpdatedAt,
+                        entity::app_setting::Column::UpdatedBy,
+                    ])
+                    .to_owned(),
+            )
+            .exec(&self.db)
+            .await?;
+
+        Ok(())
+    }
+}
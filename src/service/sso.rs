@@ -0,0 +1,150 @@
+use crate::auth::oidc::{self, IdTokenClaims, OidcError};
+use crate::dto;
+use crate::entity;
+use crate::models::user::UserRole;
+use crate::utils::error_response;
+use rocket::{
+    http::Status,
+    request::{FromRequest, Outcome},
+    response::Responder,
+};
+use sea_orm::prelude::*;
+use sea_orm::ActiveValue::Set;
+use sea_orm::TransactionTrait;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum SsoError {
+    #[error("database connection not found")]
+    DbNotFound,
+
+    #[error("A database error occurred. Please review the logs for more details.")]
+    DbError(#[from] sea_orm::DbErr),
+
+    #[error("Failed to hash the provided password")]
+    HashError,
+
+    #[error(transparent)]
+    Oidc(#[from] OidcError),
+
+    #[error("Your identity provider account is not allowed to access this panel")]
+    RoleNotMapped,
+
+    #[error("This account has been disabled")]
+    AccountDisabled,
+
+    #[error("A local user named '{0}' already exists and is not linked to this identity")]
+    UsernameTaken(String),
+}
+
+impl<'r> Responder<'r, 'static> for SsoError {
+    fn respond_to(self, req: &'r rocket::Request<'_>) -> rocket::response::Result<'static> {
+        let status = match self {
+            SsoError::DbNotFound | SsoError::DbError(_) | SsoError::HashError => {
+                Status::InternalServerError
+            }
+            SsoError::Oidc(e) => return e.respond_to(req),
+            SsoError::RoleNotMapped | SsoError::AccountDisabled => Status::Forbidden,
+            SsoError::UsernameTaken(_) => Status::Conflict,
+        };
+        error_response(self, status)
+    }
+}
+
+/// Maps identity provider accounts onto panel users
+pub struct Sso {
+    db: DatabaseConnection,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Sso {
+    type Error = SsoError;
+
+    async fn from_request(request: &'r rocket::Request<'_>) -> Outcome<Self, Self::Error> {
+        match request.rocket().state::<DatabaseConnection>() {
+            Some(db) => Outcome::Success(Sso { db: db.clone() }),
+            None => Outcome::Error((Status::InternalServerError, SsoError::DbNotFound)),
+        }
+    }
+}
+
+impl Sso {
+    /// Find the user linked to the ID token's `(iss, sub)`, creating one on first login.
+    /// The role is re-synced from the provider on every login.
+    pub async fn provision(
+        &self,
+        claims: &IdTokenClaims,
+        role: UserRole,
+    ) -> Result<entity::user::Model, SsoError> {
+        let now = chrono::Utc::now();
+        let txn = self.db.begin().await?;
+
+        let linked = entity::user_identity::Entity::find()
+            .filter(entity::user_identity::Column::Issuer.eq(&claims.iss))
+            .filter(entity::user_identity::Column::Subject.eq(&claims.sub))
+            .find_also_related(entity::user::Entity)
+            .one(&txn)
+            .await?;
+
+        let user = match linked {
+            Some((identity, Some(user))) => {
+                if !user.active {
+                    return Err(SsoError::AccountDisabled);
+                }
+
+                let mut identity: entity::user_identity::ActiveModel = identity.into();
+                identity.last_login_at = Set(Some(now));
+                identity.update(&txn).await?;
+
+                if user.role == role as i32 {
+                    user
+                } else {
+                    let mut user: entity::user::ActiveModel = user.into();
+                    user.role = Set(role as i32);
+                    user.updated_at = Set(now);
+                    user.update(&txn).await?
+                }
+            }
+            _ => {
+                let username = claims.username().to_string();
+                // Never link to an existing local account by name; that would let anyone who
+                // controls a matching IdP username take it over
+                let taken = entity::user::Entity::find()
+                    .filter(entity::user::Column::Name.eq(&username))
+                    .count(&txn)
+                    .await?;
+                if taken > 0 {
+                    return Err(SsoError::UsernameTaken(username));
+                }
+
+                // SSO users authenticate at the provider; the random password is never revealed
+                let user = dto::user::NewWithRole {
+                    username,
+                    password: oidc::random_token(),
+                    role,
+                }
+                .to_active_model()
+                .map_err(|_| SsoError::HashError)?
+                .insert(&txn)
+                .await?;
+
+                entity::user_identity::ActiveModel {
+                    user_id: Set(user.id),
+                    issuer: Set(claims.iss.clone()),
+                    subject: Set(claims.sub.clone()),
+                    last_login_at: Set(Some(now)),
+                    created_at: Set(now),
+                    ..Default::default()
+                }
+                .insert(&txn)
+                .await?;
+
+                user
+            }
+        };
+
+        txn.commit().await?;
+
+        Ok(user)
+    }
+}
@@ -0,0 +1,192 @@
+use crate::auth::secrets::{SecretBox, SecretsError};
+use crate::dto;
+use crate::entity;
+use crate::state::steamcmd::SteamLogin;
+use crate::utils::error_response;
+use rocket::{
+    http::Status,
+    request::{FromRequest, Outcome},
+    response::Responder,
+};
+use sea_orm::prelude::*;
+use sea_orm::ActiveValue::Set;
+use sea_orm::QueryOrder;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum SteamCredentialError {
+    #[error("database connection not found")]
+    DbNotFound,
+
+    #[error("secrets key not found")]
+    SecretsNotFound,
+
+    #[error("A database error occurred. Please review the logs for more details.")]
+    DbError(#[from] sea_orm::DbErr),
+
+    #[error(transparent)]
+    Secrets(#[from] SecretsError),
+
+    #[error("Steam credential with id {0} not found")]
+    NotFound(i32),
+
+    #[error("Credentials for Steam account '{0}' already exist")]
+    DuplicateUsername(String),
+
+    #[error("Steam usernames may only contain letters, digits and underscores")]
+    InvalidUsername,
+
+    #[error("Passwords may not be empty or contain quotes or control characters")]
+    InvalidPassword,
+}
+
+impl<'r> Responder<'r, 'static> for SteamCredentialError {
+    fn respond_to(self, _: &'r rocket::Request<'_>) -> rocket::response::Result<'static> {
+        let status = match self {
+            SteamCredentialError::DbNotFound
+            | SteamCredentialError::SecretsNotFound
+            | SteamCredentialError::DbError(_)
+            | SteamCredentialError::Secrets(_) => Status::InternalServerError,
+            SteamCredentialError::NotFound(_) => Status::NotFound,
+            SteamCredentialError::DuplicateUsername(_) => Status::Conflict,
+            SteamCredentialError::InvalidUsername | SteamCredentialError::InvalidPassword => {
+                Status::UnprocessableEntity
+            }
+        };
+        error_response(self, status)
+    }
+}
+
+fn validate_username(username: &str) -> Result<(), SteamCredentialError> {
+    if username.is_empty()
+        || !username
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        return Err(SteamCredentialError::InvalidUsername);
+    }
+    Ok(())
+}
+
+/// The password is handed to SteamCMD as a quoted console argument, which has no escaping
+fn validate_password(password: &str) -> Result<(), SteamCredentialError> {
+    if password.is_empty() || password.chars().any(|c| c == '"' || c.is_control()) {
+        return Err(SteamCredentialError::InvalidPassword);
+    }
+    Ok(())
+}
+
+pub struct SteamCredentials {
+    db: DatabaseConnection,
+    secrets: SecretBox,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for SteamCredentials {
+    type Error = SteamCredentialError;
+
+    async fn from_request(request: &'r rocket::Request<'_>) -> Outcome<Self, Self::Error> {
+        let Some(db) = request.rocket().state::<DatabaseConnection>() else {
+            return Outcome::Error((
+                Status::InternalServerError,
+                SteamCredentialError::DbNotFound,
+            ));
+        };
+        let Some(secrets) = request.rocket().state::<SecretBox>() else {
+            return Outcome::Error((
+                Status::InternalServerError,
+                SteamCredentialError::SecretsNotFound,
+            ));
+        };
+
+        Outcome::Success(SteamCredentials::new(db.clone(), secrets.clone()))
+    }
+}
+
+impl SteamCredentials {
+    pub fn new(db: DatabaseConnection, secrets: SecretBox) -> Self {
+        Self { db, secrets }
+    }
+
+    pub async fn list(&self) -> Result<Vec<dto::steamcmd::SteamCredential>, SteamCredentialError> {
+        let credentials = entity::steam_credential::Entity::find()
+            .order_by_asc(entity::steam_credential::Column::Username)
+            .all(&self.db)
+            .await?;
+
+        Ok(credentials.into_iter().map(Into::into).collect())
+    }
+
+    pub async fn create(
+        &self,
+        new_credential: dto::steamcmd::NewSteamCredential,
+    ) -> Result<dto::steamcmd::SteamCredential, SteamCredentialError> {
+        let username = new_credential.username.trim().to_string();
+        validate_username(&username)?;
+        validate_password(&new_credential.password)?;
+
+        let existing = entity::steam_credential::Entity::find()
+            .filter(entity::steam_credential::Column::Username.eq(&username))
+            .count(&self.db)
+            .await?;
+        if existing > 0 {
+            return Err(SteamCredentialError::DuplicateUsername(username));
+        }
+
+        let now = chrono::Utc::now();
+        let active_model = entity::steam_credential::ActiveModel {
+            username: Set(username),
+            password_encrypted: Set(self.secrets.encrypt(&new_credential.password)?),
+            created_at: Set(now),
+            updated_at: Set(now),
+            ..Default::default()
+        };
+        let model = active_model.insert(&self.db).await?;
+
+        Ok(model.into())
+    }
+
+    pub async fn update_password(
+        &self,
+        id: i32,
+        update: dto::steamcmd::SteamCredentialUpdate,
+    ) -> Result<dto::steamcmd::SteamCredential, SteamCredentialError> {
+        validate_password(&update.password)?;
+
+        let model = entity::steam_credential::Entity::find_by_id(id)
+            .one(&self.db)
+            .await?
+            .ok_or(SteamCredentialError::NotFound(id))?;
+
+        let mut active_model: entity::steam_credential::ActiveModel = model.into();
+        active_model.password_encrypted = Set(self.secrets.encrypt(&update.password)?);
+        active_model.updated_at = Set(chrono::Utc::now());
+        let model = active_model.update(&self.db).await?;
+
+        Ok(model.into())
+    }
+
+    pub async fn delete(&self, id: i32) -> Result<(), SteamCredentialError> {
+        let res = entity::steam_credential::Entity::delete_by_id(id)
+            .exec(&self.db)
+            .await?;
+
+        match res.rows_affected {
+            0 => Err(SteamCredentialError::NotFound(id)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Decrypt a stored credential into a SteamCMD login
+    pub async fn login(&self, id: i32) -> Result<SteamLogin, SteamCredentialError> {
+        let model = entity::steam_credential::Entity::find_by_id(id)
+            .one(&self.db)
+            .await?
+            .ok_or(SteamCredentialError::NotFound(id))?;
+
+        Ok(SteamLogin::Account {
+            password: self.secrets.decrypt(&model.password_encrypted)?,
+            username: model.username,
+        })
+    }
+}
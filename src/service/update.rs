@@ -0,0 +1,698 @@
+use crate::auth::secrets::{SecretBox, SecretsError};
+use crate::dto;
+use crate::entity;
+use crate::models::audit::AuditAction;
+use crate::models::instance::{InstanceStatus, SteamPlatform};
+use crate::models::user::UserRole;
+use crate::service::audit::{Audit, AuditEvent};
+use crate::service::instance::{self, InstanceError};
+use crate::service::steam_credential::{SteamCredentialError, SteamCredentials};
+use crate::state::instance::{set_status, InstanceManager, InstanceProcessError, UpdateLock};
+use crate::state::steamcmd::{
+    app_info::{self, DEFAULT_BRANCH},
+    SteamCMD, SteamCmdError, SteamCmdSession, SteamLogin,
+};
+use crate::utils::error_response;
+use chrono::{NaiveTime, Timelike};
+use rocket::{
+    http::Status,
+    request::{FromRequest, Outcome},
+    response::Responder,
+};
+use sea_orm::prelude::*;
+use sea_orm::sea_query::OnConflict;
+use sea_orm::ActiveValue::Set;
+use sea_orm::QueryOrder;
+use std::collections::{hash_map::Entry, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+
+#[cfg(test)]
+mod tests;
+
+/// Minutes between update checks unless `UPDATE_CHECK_INTERVAL` says otherwise; 0 disables them
+const DEFAULT_CHECK_INTERVAL_MINUTES: u64 = 60;
+
+#[derive(Error, Debug)]
+pub enum UpdateError {
+    #[error("database connection not found")]
+    DbNotFound,
+
+    #[error("secrets key not found")]
+    SecretsNotFound,
+
+    #[error("A database error occurred. Please review the logs for more details.")]
+    DbError(#[from] sea_orm::DbErr),
+
+    #[error(transparent)]
+    Secrets(#[from] SecretsError),
+
+    #[error(transparent)]
+    Instance(#[from] InstanceError),
+
+    #[error("Invalid maintenance window time '{0}', expected HH:MM")]
+    InvalidWindow(String),
+
+    #[error("Instance {0} is already being updated")]
+    InProgress(i32),
+
+    #[error("Invalid beta branch '{0}'. Branch names may only contain letters, digits, '.', '_' and '-'")]
+    InvalidBranch(String),
+
+    #[error("Beta passwords may not contain whitespace, quotes or control characters")]
+    InvalidBetaPassword,
+
+    #[error(transparent)]
+    SteamCmd(#[from] SteamCmdError),
+
+    #[error(transparent)]
+    Process(#[from] InstanceProcessError),
+
+    #[error(transparent)]
+    Credential(#[from] SteamCredentialError),
+
+    #[error("Only admins can choose the Steam credential an instance updates with")]
+    CredentialNotAllowed,
+}
+
+impl<'r> Responder<'r, 'static> for UpdateError {
+    fn respond_to(self, req: &'r rocket::Request<'_>) -> rocket::response::Result<'static> {
+        let status = match self {
+            UpdateError::DbNotFound
+            | UpdateError::SecretsNotFound
+            | UpdateError::DbError(_)
+            | UpdateError::Secrets(_) => Status::InternalServerError,
+            UpdateError::Instance(e) => return e.respond_to(req),
+            UpdateError::SteamCmd(e) => return e.respond_to(req),
+            UpdateError::Process(e) => return e.respond_to(req),
+            UpdateError::Credential(e) => return e.respond_to(req),
+            UpdateError::InvalidWindow(_)
+            | UpdateError::InvalidBranch(_)
+            | UpdateError::InvalidBetaPassword => Status::UnprocessableEntity,
+            UpdateError::InProgress(_) => Status::Conflict,
+            UpdateError::CredentialNotAllowed => Status::Forbidden,
+        };
+        error_response(self, status)
+    }
+}
+
+/// Steam credentials are managed by admins, so only they may point an instance at another one
+/// than it already uses
+pub fn ensure_may_choose_credential(
+    role: UserRole,
+    current: Option<i32>,
+    chosen: Option<i32>,
+) -> Result<(), UpdateError> {
+    match role {
+        UserRole::Admin => Ok(()),
+        _ if chosen.is_none() || chosen == current => Ok(()),
+        _ => Err(UpdateError::CredentialNotAllowed),
+    }
+}
+
+fn parse_time(value: &str) -> Result<NaiveTime, UpdateError> {
+    NaiveTime::parse_from_str(value, "%H:%M").map_err(|_| UpdateError::InvalidWindow(value.into()))
+}
+
+impl dto::instance::MaintenanceWindow {
+    /// Whether `now` falls inside the window. The start is inclusive, the end exclusive.
+    pub fn contains(&self, now: NaiveTime) -> Result<bool, UpdateError> {
+        let (start, end) = (parse_time(&self.start)?, parse_time(&self.end)?);
+        let now = now
+            .with_second(0)
+            .unwrap_or(now)
+            .with_nanosecond(0)
+            .unwrap_or(now);
+
+        Ok(if start <= end {
+            start <= now && now < end
+        } else {
+            now >= start || now < end
+        })
+    }
+}
+
+impl From<&entity::instance_update::Model> for dto::instance::UpdatePolicy {
+    fn from(model: &entity::instance_update::Model) -> Self {
+        dto::instance::UpdatePolicy {
+            auto_update: model.auto_update,
+            maintenance_window: model
+                .window_start
+                .as_ref()
+                .zip(model.window_end.as_ref())
+                .map(|(start, end)| dto::instance::MaintenanceWindow {
+                    start: start.clone(),
+                    end: end.clone(),
+                }),
+            credential_id: model.credential_id,
+        }
+    }
+}
+
+impl From<&entity::instance_update::Model> for dto::instance::InstallOptions {
+    fn from(model: &entity::instance_update::Model) -> Self {
+        dto::instance::InstallOptions {
+            branch: model.branch.clone(),
+            has_beta_password: model.beta_password_encrypted.is_some(),
+            validate: model.validate,
+            platform: model.platform.as_deref().and_then(SteamPlatform::parse),
+        }
+    }
+}
+
+fn validate_branch(branch: &str) -> Result<(), UpdateError> {
+    let valid = branch
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'));
+
+    if branch.is_empty() || !valid {
+        return Err(UpdateError::InvalidBranch(branch.to_string()));
+    }
+    Ok(())
+}
+
+fn validate_beta_password(password: &str) -> Result<(), UpdateError> {
+    if password
+        .chars()
+        .any(|c| c.is_whitespace() || c.is_control() || c == '"' || c == '\'')
+    {
+        return Err(UpdateError::InvalidBetaPassword);
+    }
+    Ok(())
+}
+
+/// SteamCMD commands that install or update `app_id` with the instance's install options
+pub fn app_update_commands(
+    app_id: i32,
+    options: &dto::instance::InstallOptions,
+    beta_password: Option<&str>,
+) -> Vec<String> {
+    let mut commands = Vec::new();
+    if let Some(platform) = options.platform {
+        commands.push(format!("@sSteamCmdForcePlatformType {}", platform.as_str()));
+    }
+
+    let mut app_update = format!("app_update {app_id}");
+    if let Some(branch) = &options.branch {
+        app_update.push_str(&format!(" -beta {branch}"));
+        if let Some(password) = beta_password {
+            app_update.push_str(&format!(" -betapassword {password}"));
+        }
+    }
+    if options.validate {
+        app_update.push_str(" validate");
+    }
+    commands.push(app_update);
+
+    commands
+}
+
+fn to_status(
+    instance: &entity::game_config::Model,
+    update: Option<&entity::instance_update::Model>,
+) -> dto::instance::InstanceUpdateStatus {
+    let installed = update.and_then(|u| u.installed_build_id);
+    let available = update.and_then(|u| u.available_build_id);
+
+    dto::instance::InstanceUpdateStatus {
+        instance_id: instance.id,
+        schema_id: instance.schema_id,
+        installed_build_id: installed,
+        available_build_id: available,
+        update_available: matches!((installed, available), (Some(i), Some(a)) if i != a),
+        checked_at: update.and_then(|u| u.checked_at),
+        updated_at: update.and_then(|u| u.updated_at),
+        last_error: update.and_then(|u| u.last_error.clone()),
+        policy: update.map(Into::into).unwrap_or_default(),
+        install_options: update.map(Into::into).unwrap_or_default(),
+    }
+}
+
+/// Tracks installed vs published game builds and runs `app_update` for instances
+pub struct Updates {
+    db: DatabaseConnection,
+    secrets: SecretBox,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Updates {
+    type Error = UpdateError;
+
+    async fn from_request(request: &'r rocket::Request<'_>) -> Outcome<Self, Self::Error> {
+        let Some(db) = request.rocket().state::<DatabaseConnection>() else {
+            return Outcome::Error((Status::InternalServerError, UpdateError::DbNotFound));
+        };
+        let Some(secrets) = request.rocket().state::<SecretBox>() else {
+            return Outcome::Error((Status::InternalServerError, UpdateError::SecretsNotFound));
+        };
+
+        Outcome::Success(Updates::new(db.clone(), secrets.clone()))
+    }
+}
+
+impl Updates {
+    pub fn new(db: DatabaseConnection, secrets: SecretBox) -> Self {
+        Self { db, secrets }
+    }
+
+    fn instances(&self) -> instance::Instance {
+        instance::Instance::new(self.db.clone(), None)
+    }
+
+    pub async fn list(&self) -> Result<Vec<dto::instance::InstanceUpdateStatus>, UpdateError> {
+        let instances = entity::game_config::Entity::find()
+            .find_also_related(entity::instance_update::Entity)
+            .order_by_asc(entity::game_config::Column::Id)
+            .all(&self.db)
+            .await?;
+
+        Ok(instances
+            .iter()
+            .map(|(instance, update)| to_status(instance, update.as_ref()))
+            .collect())
+    }
+
+    pub async fn status(
+        &self,
+        id: i32,
+    ) -> Result<dto::instance::InstanceUpdateStatus, UpdateError> {
+        let instance = self.instances().find_by_id(id).await?;
+        let update = entity::instance_update::Entity::find_by_id(id)
+            .one(&self.db)
+            .await?;
+
+        Ok(to_status(&instance, update.as_ref()))
+    }
+
+    /// Insert or update the instance's row, touching only the columns set in `model`
+    async fn upsert(
+        &self,
+        model: entity::instance_update::ActiveModel,
+        columns: Vec<entity::instance_update::Column>,
+    ) -> Result<(), UpdateError> {
+        entity::instance_update::Entity::insert(model)
+            .on_conflict(
+                OnConflict::column(entity::instance_update::Column::InstanceId)
+                    .update_columns(columns)
+                    .to_owned(),
+            )
+            .exec_without_returning(&self.db)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn set_policy(
+        &self,
+        id: i32,
+        policy: dto::instance::UpdatePolicy,
+    ) -> Result<dto::instance::InstanceUpdateStatus, UpdateError> {
+        self.instances().find_by_id(id).await?;
+        if let Some(window) = &policy.maintenance_window {
+            parse_time(&window.start)?;
+            parse_time(&window.end)?;
+        }
+
+        use entity::instance_update::Column;
+        self.upsert(
+            entity::instance_update::ActiveModel {
+                instance_id: Set(id),
+                auto_update: Set(policy.auto_update),
+                window_start: Set(policy.maintenance_window.as_ref().map(|w| w.start.clone())),
+                window_end: Set(policy.maintenance_window.as_ref().map(|w| w.end.clone())),
+                credential_id: Set(policy.credential_id),
+                ..Default::default()
+            },
+            vec![
+                Column::AutoUpdate,
+                Column::WindowStart,
+                Column::WindowEnd,
+                Column::CredentialId,
+            ],
+        )
+        .await?;
+
+        self.status(id).await
+    }
+
+    /// Change how the instance is installed. The options apply from the next update on.
+    pub async fn set_install_options(
+        &self,
+        id: i32,
+        options: dto::instance::InstallOptionsUpdate,
+    ) -> Result<dto::instance::InstanceUpdateStatus, UpdateError> {
+        self.instances().find_by_id(id).await?;
+        let branch = options
+            .branch
+            .map(|branch| branch.trim().to_string())
+            .filter(|branch| !branch.is_empty());
+        if let Some(branch) = &branch {
+            validate_branch(branch)?;
+        }
+
+        use entity::instance_update::Column;
+        let mut columns = vec![Column::Branch, Column::Validate, Column::Platform];
+        let mut model = entity::instance_update::ActiveModel {
+            instance_id: Set(id),
+            branch: Set(branch),
+            validate: Set(options.validate),
+            platform: Set(options.platform.map(|p| p.as_str().to_string())),
+            ..Default::default()
+        };
+        if let Some(password) = options.beta_password {
+            validate_beta_password(&password)?;
+            model.beta_password_encrypted = Set(match password.as_str() {
+                "" => None,
+                password => Some(self.secrets.encrypt(password)?),
+            });
+            columns.push(Column::BetaPasswordEncrypted);
+        }
+        self.upsert(model, columns).await?;
+
+        self.status(id).await
+    }
+
+    /// Compare installed builds with the latest published ones. Checks one instance, or all of
+    /// them when `only` is `None`; `app_info_print` runs once per Steam app.
+    pub async fn check(
+        &self,
+        steamcmd: &SteamCMD,
+        only: Option<i32>,
+    ) -> Result<Vec<dto::instance::InstanceUpdateStatus>, UpdateError> {
+        let mut query =
+            entity::game_config::Entity::find().find_also_related(entity::game_schema::Entity);
+        if let Some(id) = only {
+            query = query.filter(entity::game_config::Column::Id.eq(id));
+        }
+        let instances = query.all(&self.db).await?;
+        if let Some(id) = only.filter(|_| instances.is_empty()) {
+            return Err(InstanceError::NotFound(id).into());
+        }
+
+        let mut app_info: HashMap<i32, Result<String, String>> = HashMap::new();
+        for (instance, schema) in &instances {
+            let Some(schema) = schema else {
+                continue;
+            };
+            let app_id = schema.steam_app_id;
+            if let Entry::Vacant(entry) = app_info.entry(app_id) {
+                entry.insert(steamcmd.app_info(app_id).await.map_err(|e| e.to_string()));
+            }
+
+            let configured = entity::instance_update::Entity::find_by_id(instance.id)
+                .one(&self.db)
+                .await?
+                .and_then(|update| update.branch);
+            let installed =
+                app_info::installed_manifest(&instance::instance_dir(instance.id), app_id);
+            let branch = configured
+                .as_deref()
+                .or(installed.as_ref().and_then(|m| m.beta_key.as_deref()))
+                .unwrap_or(DEFAULT_BRANCH);
+            let (available, error) = match &app_info[&app_id] {
+                Ok(output) => match app_info::available_build(output, app_id, branch) {
+                    Some(build) => (Some(build), None),
+                    None => (
+                        None,
+                        Some(format!(
+                            "No build id for branch '{branch}' in app_info_print"
+                        )),
+                    ),
+                },
+                Err(e) => (None, Some(e.clone())),
+            };
+
+            use entity::instance_update::Column;
+            self.upsert(
+                entity::instance_update::ActiveModel {
+                    instance_id: Set(instance.id),
+                    installed_build_id: Set(installed.map(|m| m.build_id)),
+                    available_build_id: Set(available),
+                    checked_at: Set(Some(chrono::Utc::now())),
+                    last_error: Set(error),
+                    ..Default::default()
+                },
+                vec![
+                    Column::InstalledBuildId,
+                    Column::AvailableBuildId,
+                    Column::CheckedAt,
+                    Column::LastError,
+                ],
+            )
+            .await?;
+        }
+
+        match only {
+            Some(id) => Ok(vec![self.status(id).await?]),
+            None => self.list().await,
+        }
+    }
+
+    /// Stop the server if it runs, `app_update` it into the instance directory with its install
+    /// options, then start it again if it was running. Progress shows in the returned SteamCMD
+    /// session.
+    pub async fn update(
+        &self,
+        id: i32,
+        steamcmd: &SteamCMD,
+        manager: &InstanceManager,
+        login: SteamLogin,
+    ) -> Result<Arc<SteamCmdSession>, UpdateError> {
+        // Taken before anything else, so concurrent updates cannot both get past this and the
+        // server cannot be started until the update is over
+        let lock = manager.lock_update(id).ok_or(UpdateError::InProgress(id))?;
+        let (_, schema) = self.instances().find_with_schema(id).await?;
+        let app_id = schema.static_config.steam_app_id;
+        let dir = instance::instance_dir(id);
+
+        let options = entity::instance_update::Entity::find_by_id(id)
+            .one(&self.db)
+            .await?;
+        let beta_password = match options
+            .as_ref()
+            .and_then(|o| o.beta_password_encrypted.as_deref())
+        {
+            Some(sealed) => Some(self.secrets.decrypt(sealed)?),
+            None => None,
+        };
+        let commands = app_update_commands(
+            app_id,
+            &options.as_ref().map(Into::into).unwrap_or_default(),
+            beta_password.as_deref(),
+        );
+
+        let was_running = manager.is_running(id).await;
+        if was_running {
+            self.instances().stop(id, manager).await?;
+        }
+        let spawned = async {
+            set_status(&self.db, id, InstanceStatus::Updating).await?;
+            std::fs::create_dir_all(&dir).map_err(InstanceProcessError::FailedToStart)?;
+            Ok::<_, UpdateError>(
+                steamcmd
+                    .spawn_job_with_secrets(
+                        login,
+                        Some(&dir),
+                        commands,
+                        beta_password.into_iter().collect(),
+                    )
+                    .await?,
+            )
+        }
+        .await;
+        let session = match spawned {
+            Ok(session) => session,
+            Err(e) => {
+                self.abort_update(id, manager, was_running, lock).await;
+                return Err(e);
+            }
+        };
+
+        let updates = Updates::new(self.db.clone(), self.secrets.clone());
+        let manager = manager.clone();
+        let job = session.clone();
+        tokio::spawn(async move {
+            job.wait_exit().await;
+            let message = updates
+                .finish_update(id, app_id, &job, &manager, was_running, lock)
+                .await;
+            job.announce(&message).await;
+        });
+
+        Ok(session)
+    }
+
+    /// Put the instance back the way it was when an update could not be started
+    async fn abort_update(
+        &self,
+        id: i32,
+        manager: &InstanceManager,
+        was_running: bool,
+        lock: UpdateLock,
+    ) {
+        if let Err(e) = set_status(&self.db, id, InstanceStatus::Stopped).await {
+            eprintln!("Failed to record the status of instance {id}: {e}");
+        }
+        drop(lock);
+        if was_running {
+            if let Err(e) = self.instances().start(id, manager).await {
+                eprintln!("Failed to restart instance {id} after its update failed: {e}");
+            }
+        }
+    }
+
+    /// Record the outcome of an `app_update` job and restart the server if needed
+    async fn finish_update(
+        &self,
+        id: i32,
+        app_id: i32,
+        job: &SteamCmdSession,
+        manager: &InstanceManager,
+        was_running: bool,
+        lock: UpdateLock,
+    ) -> String {
+        let info = job.info();
+        let manifest = app_info::installed_manifest(&instance::instance_dir(id), app_id);
+        let error = match (&manifest, info.exit_code) {
+            (Some(_), Some(0)) => None,
+            (None, _) => Some(format!("App {app_id} is not installed after app_update")),
+            (_, code) => Some(format!(
+                "app_update failed ({})",
+                info.error
+                    .clone()
+                    .unwrap_or_else(|| format!("exit code {code:?}"))
+            )),
+        };
+
+        use entity::instance_update::Column;
+        let mut columns = vec![Column::InstalledBuildId, Column::LastError];
+        let mut model = entity::instance_update::ActiveModel {
+            instance_id: Set(id),
+            installed_build_id: Set(manifest.as_ref().map(|m| m.build_id)),
+            last_error: Set(error.clone()),
+            ..Default::default()
+        };
+        if error.is_none() {
+            model.updated_at = Set(Some(chrono::Utc::now()));
+            columns.push(Column::UpdatedAt);
+        }
+        let mut messages = vec![match (&error, &manifest) {
+            (Some(error), _) => format!("Update failed: {error}"),
+            (None, Some(m)) => format!("Updated to build {}", m.build_id),
+            (None, None) => "Update finished".to_string(),
+        }];
+
+        if let Err(e) = self.upsert(model, columns).await {
+            messages.push(format!("Failed to record update: {e}"));
+        }
+        if let Err(e) = set_status(&self.db, id, InstanceStatus::Stopped).await {
+            messages.push(format!("Failed to record instance status: {e}"));
+        }
+        drop(lock);
+        if was_running {
+            match self.instances().start(id, manager).await {
+                Ok(_) => messages.push("Server restarted".to_string()),
+                Err(e) => messages.push(format!("Failed to restart server: {e}")),
+            }
+        }
+
+        messages.join("; ")
+    }
+
+    /// Periodically check for updates and apply them to instances whose policy allows it.
+    /// The interval comes from `UPDATE_CHECK_INTERVAL` in minutes; 0 turns checking off.
+    pub fn spawn_scheduler(self, steamcmd: SteamCMD, manager: InstanceManager) {
+        let minutes = std::env::var("UPDATE_CHECK_INTERVAL")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_CHECK_INTERVAL_MINUTES);
+        if minutes == 0 {
+            return;
+        }
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(minutes * 60));
+            loop {
+                interval.tick().await;
+                if !steamcmd.status().installed {
+                    continue;
+                }
+                if let Err(e) = self.run_scheduled(&steamcmd, &manager).await {
+                    eprintln!("Scheduled update check failed: {e}");
+                }
+            }
+        });
+    }
+
+    async fn run_scheduled(
+        &self,
+        steamcmd: &SteamCMD,
+        manager: &InstanceManager,
+    ) -> Result<(), UpdateError> {
+        let now = chrono::Utc::now().time();
+        let audit = Audit::new(self.db.clone());
+
+        for status in self.check(steamcmd, None).await? {
+            let policy = &status.policy;
+            if !status.update_available || !policy.auto_update {
+                continue;
+            }
+            // A broken policy only holds back its own instance
+            if let Some(window) = &policy.maintenance_window {
+                match window.contains(now) {
+                    Ok(true) => {}
+                    Ok(false) => continue,
+                    Err(e) => {
+                        eprintln!(
+                            "Skipping automatic update of instance {}: {e}",
+                            status.instance_id
+                        );
+                        continue;
+                    }
+                }
+            }
+
+            let login = match policy.credential_id {
+                Some(credential_id) => {
+                    let login = SteamCredentials::new(self.db.clone(), self.secrets.clone())
+                        .login(credential_id)
+                        .await;
+                    match login {
+                        Ok(login) => login,
+                        Err(e) => {
+                            eprintln!(
+                                "Skipping automatic update of instance {}: {e}",
+                                status.instance_id
+                            );
+                            continue;
+                        }
+                    }
+                }
+                None => SteamLogin::Anonymous,
+            };
+            let event = AuditEvent::new(AuditAction::GameUpdate)
+                .target("instance", status.instance_id)
+                .details(serde_json::json!({
+                    "automatic": true,
+                    "from": status.installed_build_id,
+                    "to": status.available_build_id,
+                }));
+            let event = match self
+                .update(status.instance_id, steamcmd, manager, login)
+                .await
+            {
+                Ok(_) => event,
+                Err(e) => {
+                    eprintln!(
+                        "Automatic update of instance {} failed: {e}",
+                        status.instance_id
+                    );
+                    event.failed()
+                }
+            };
+            audit.record_for(None, "system", event).await;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,167 @@
+use super::*;
+use crate::dto::instance::MaintenanceWindow;
+
+fn window(start: &str, end: &str) -> MaintenanceWindow {
+    MaintenanceWindow {
+        start: start.to_string(),
+        end: end.to_string(),
+    }
+}
+
+fn at(time: &str) -> NaiveTime {
+    NaiveTime::parse_from_str(time, "%H:%M:%S").unwrap()
+}
+
+#[test]
+fn test_window_within_a_day() {
+    let window = window("03:00", "05:30");
+
+    assert!(window.contains(at("03:00:00")).unwrap());
+    assert!(window.contains(at("05:29:59")).unwrap());
+    assert!(!window.contains(at("05:30:00")).unwrap());
+    assert!(!window.contains(at("02:59:59")).unwrap());
+}
+
+#[test]
+fn test_window_wrapping_midnight() {
+    let window = window("23:00", "02:00");
+
+    assert!(window.contains(at("23:30:00")).unwrap());
+    assert!(window.contains(at("00:15:00")).unwrap());
+    assert!(!window.contains(at("02:00:00")).unwrap());
+    assert!(!window.contains(at("12:00:00")).unwrap());
+}
+
+#[test]
+fn test_window_rejects_bad_times() {
+    assert!(matches!(
+        window("25:00", "02:00").contains(at("00:00:00")),
+        Err(UpdateError::InvalidWindow(_))
+    ));
+    assert!(window("3pm", "02:00").contains(at("00:00:00")).is_err());
+}
+
+#[test]
+fn test_update_available_needs_both_builds() {
+    let instance = entity::game_config::Model {
+        id: 1,
+        instance_name: "test".to_string(),
+        schema_id: 2,
+        config_json: serde_json::json!({}),
+        restart_interval: 0,
+        backup_interval: 0,
+        max_backup_count: 20,
+        status: 0,
+        created_at: chrono::Utc::now(),
+        created_by: 1,
+        updated_at: chrono::Utc::now(),
+        updated_by: 1,
+        memory_max_mb: None,
+        cpu_quota_percent: None,
+        cpu_weight: None,
+        max_processes: None,
+        nice: None,
+        max_open_files: None,
+    };
+    let update = |installed, available| entity::instance_update::Model {
+        instance_id: 1,
+        installed_build_id: installed,
+        available_build_id: available,
+        checked_at: None,
+        updated_at: None,
+        last_error: None,
+        auto_update: true,
+        window_start: Some("03:00".to_string()),
+        window_end: Some("04:00".to_string()),
+        credential_id: None,
+        branch: Some("public-test".to_string()),
+        beta_password_encrypted: Some("sealed".to_string()),
+        validate: false,
+        platform: Some("windows".to_string()),
+    };
+
+    let status = to_status(&instance, Some(&update(Some(1), Some(2))));
+    assert!(status.update_available);
+    assert_eq!(
+        status.policy.maintenance_window,
+        Some(window("03:00", "04:00"))
+    );
+    assert_eq!(
+        status.install_options,
+        dto::instance::InstallOptions {
+            branch: Some("public-test".to_string()),
+            has_beta_password: true,
+            validate: false,
+            platform: Some(SteamPlatform::Windows),
+        }
+    );
+
+    assert!(!to_status(&instance, Some(&update(Some(2), Some(2)))).update_available);
+    assert!(!to_status(&instance, Some(&update(None, Some(2)))).update_available);
+    assert!(!to_status(&instance, None).update_available);
+    assert_eq!(to_status(&instance, None).policy, Default::default());
+}
+
+#[test]
+fn test_app_update_commands_defaults() {
+    let options = dto::instance::InstallOptions::default();
+
+    assert_eq!(
+        app_update_commands(896660, &options, None),
+        vec!["app_update 896660"]
+    );
+}
+
+#[test]
+fn test_app_update_commands_with_options() {
+    let options = dto::instance::InstallOptions {
+        branch: Some("public-test".to_string()),
+        has_beta_password: true,
+        validate: true,
+        platform: Some(SteamPlatform::Windows),
+    };
+
+    assert_eq!(
+        app_update_commands(896660, &options, Some("yesimadebackups")),
+        vec![
+            "@sSteamCmdForcePlatformType windows",
+            "app_update 896660 -beta public-test -betapassword yesimadebackups validate",
+        ]
+    );
+}
+
+#[test]
+fn test_beta_password_needs_a_branch() {
+    let options = dto::instance::InstallOptions {
+        validate: true,
+        ..Default::default()
+    };
+
+    assert_eq!(
+        app_update_commands(896660, &options, Some("secret")),
+        vec!["app_update 896660 validate"]
+    );
+}
+
+#[test]
+fn test_validate_branch_and_password() {
+    assert!(validate_branch("public-test").is_ok());
+    assert!(validate_branch("experimental_1.2").is_ok());
+    assert!(validate_branch("").is_err());
+    assert!(validate_branch("beta -validate").is_err());
+
+    assert!(validate_beta_password("yesimadebackups").is_ok());
+    assert!(validate_beta_password("two words").is_err());
+    assert!(validate_beta_password("quo\"te").is_err());
+}
+
+#[test]
+fn test_only_admins_choose_another_credential() {
+    assert!(ensure_may_choose_credential(UserRole::Admin, None, Some(2)).is_ok());
+    assert!(ensure_may_choose_credential(UserRole::Moderator, Some(2), Some(2)).is_ok());
+    assert!(ensure_may_choose_credential(UserRole::Moderator, Some(2), None).is_ok());
+    assert!(matches!(
+        ensure_may_choose_credential(UserRole::Moderator, Some(2), Some(3)),
+        Err(UpdateError::CredentialNotAllowed)
+    ));
+}
@@ -0,0 +1,458 @@
+use crate::dto;
+use crate::entity;
+use crate::schema::server_config::{ModInstallMethod, ModSupport};
+use crate::service::instance::{self, InstanceError};
+use crate::state::steamcmd::{SteamCMD, SteamCmdError, SteamCmdSession, SteamLogin};
+use crate::utils::error_response;
+use rocket::{
+    http::Status,
+    request::{FromRequest, Outcome},
+    response::Responder,
+};
+use sea_orm::prelude::*;
+use sea_orm::ActiveValue::Set;
+use sea_orm::{QueryOrder, TransactionTrait};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use thiserror::Error;
+
+#[cfg(test)]
+mod tests;
+
+/// Marker written into copied mod directories so they can be told apart from user files
+const COPY_MARKER: &str = ".server_ui_mod";
+
+#[derive(Error, Debug)]
+pub enum WorkshopError {
+    #[error("database connection not found")]
+    DbNotFound,
+
+    #[error("A database error occurred. Please review the logs for more details.")]
+    DbError(#[from] sea_orm::DbErr),
+
+    #[error(transparent)]
+    Instance(#[from] InstanceError),
+
+    #[error("This instance's game schema does not declare Workshop mod support")]
+    ModsNotSupported,
+
+    #[error("This instance has no enabled mods to install")]
+    NoMods,
+
+    #[error("Invalid Workshop item id: {0}")]
+    InvalidItemId(i64),
+
+    #[error("Workshop item {0} is listed more than once")]
+    DuplicateItem(i64),
+
+    #[error("Workshop item {0} is not in this instance's mod list")]
+    ModNotFound(i64),
+
+    #[error(transparent)]
+    SteamCmd(#[from] SteamCmdError),
+
+    #[error("Failed to update the instance's mod directories: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl<'r> Responder<'r, 'static> for WorkshopError {
+    fn respond_to(self, req: &'r rocket::Request<'_>) -> rocket::response::Result<'static> {
+        let status = match self {
+            WorkshopError::DbNotFound | WorkshopError::DbError(_) | WorkshopError::Io(_) => {
+                Status::InternalServerError
+            }
+            WorkshopError::Instance(e) => return e.respond_to(req),
+            WorkshopError::SteamCmd(e) => return e.respond_to(req),
+            WorkshopError::ModNotFound(_) => Status::NotFound,
+            WorkshopError::DuplicateItem(_) => Status::Conflict,
+            WorkshopError::ModsNotSupported
+            | WorkshopError::NoMods
+            | WorkshopError::InvalidItemId(_) => Status::UnprocessableEntity,
+        };
+        error_response(self, status)
+    }
+}
+
+/// Where SteamCMD downloads Workshop items; shared by every instance
+pub fn workshop_dir() -> PathBuf {
+    let dir = crate::utils::data_dir().join("workshop");
+    std::path::absolute(&dir).unwrap_or(dir)
+}
+
+/// Download location of a single Workshop item inside `workshop_dir`
+pub fn content_dir(workshop_dir: &Path, app_id: i32, item_id: i64) -> PathBuf {
+    workshop_dir
+        .join("steamapps/workshop/content")
+        .join(app_id.to_string())
+        .join(item_id.to_string())
+}
+
+fn workshop_app_id(support: &ModSupport, steam_app_id: i32) -> i32 {
+    support.workshop_app_id.unwrap_or(steam_app_id)
+}
+
+/// Whether `entry` was put in place by us (a symlink into the Workshop dir or a marked copy)
+fn is_managed(entry: &Path, workshop_dir: &Path) -> bool {
+    match std::fs::read_link(entry) {
+        Ok(target) => target.starts_with(workshop_dir),
+        Err(_) => entry.join(COPY_MARKER).is_file(),
+    }
+}
+
+fn remove_entry(entry: &Path) -> std::io::Result<()> {
+    let metadata = std::fs::symlink_metadata(entry)?;
+    if metadata.is_dir() {
+        std::fs::remove_dir_all(entry)
+    } else {
+        std::fs::remove_file(entry)
+    }
+}
+
+fn copy_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            std::fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Remove managed mod entries from the schema's mod directories that are not in `keep`.
+/// Anything we did not place there ourselves is left alone.
+pub fn prune_mods(
+    instance_dir: &Path,
+    workshop_dir: &Path,
+    support: &ModSupport,
+    keep: &[i64],
+) -> std::io::Result<()> {
+    let keep: HashSet<String> = keep.iter().map(|id| id.to_string()).collect();
+
+    for dir in &support.directories {
+        let Ok(entries) = std::fs::read_dir(instance_dir.join(dir)) else {
+            continue;
+        };
+        for entry in entries {
+            let path = entry?.path();
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            if name.parse::<i64>().is_err() || keep.contains(name.as_ref()) {
+                continue;
+            }
+            if is_managed(&path, workshop_dir) {
+                remove_entry(&path)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Place each downloaded mod into every mod directory of the instance as `<dir>/<item id>`,
+/// then prune mods that are no longer enabled. Returns the items that were placed.
+pub fn place_mods(
+    instance_dir: &Path,
+    workshop_dir: &Path,
+    support: &ModSupport,
+    app_id: i32,
+    mods: &[i64],
+) -> std::io::Result<Vec<i64>> {
+    let downloaded: Vec<i64> = mods
+        .iter()
+        .copied()
+        .filter(|id| content_dir(workshop_dir, app_id, *id).is_dir())
+        .collect();
+
+    for dir in &support.directories {
+        let dir = instance_dir.join(dir);
+        std::fs::create_dir_all(&dir)?;
+
+        for id in &downloaded {
+            let source = content_dir(workshop_dir, app_id, *id);
+            let target = dir.join(id.to_string());
+            if std::fs::symlink_metadata(&target).is_ok() {
+                if !is_managed(&target, workshop_dir) {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::AlreadyExists,
+                        format!(
+                            "{} exists and was not installed by server_ui",
+                            target.display()
+                        ),
+                    ));
+                }
+                remove_entry(&target)?;
+            }
+
+            match support.install_method {
+                ModInstallMethod::Symlink => {
+                    #[cfg(unix)]
+                    std::os::unix::fs::symlink(&source, &target)?;
+                    #[cfg(not(unix))]
+                    copy_dir(&source, &target)?;
+                }
+                ModInstallMethod::Copy => {
+                    copy_dir(&source, &target)?;
+                    std::fs::write(target.join(COPY_MARKER), id.to_string())?;
+                }
+            }
+        }
+    }
+
+    prune_mods(instance_dir, workshop_dir, support, mods)?;
+
+    Ok(downloaded)
+}
+
+pub struct Workshop {
+    db: DatabaseConnection,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Workshop {
+    type Error = WorkshopError;
+
+    async fn from_request(request: &'r rocket::Request<'_>) -> Outcome<Self, Self::Error> {
+        match request.rocket().state::<DatabaseConnection>() {
+            Some(db) => Outcome::Success(Workshop { db: db.clone() }),
+            None => Outcome::Error((Status::InternalServerError, WorkshopError::DbNotFound)),
+        }
+    }
+}
+
+impl Workshop {
+    fn instances(&self) -> instance::Instance {
+        instance::Instance::new(self.db.clone(), None)
+    }
+
+    /// The schema's mod support plus the App ID its Workshop items belong to
+    async fn mod_support(&self, instance_id: i32) -> Result<(ModSupport, i32), WorkshopError> {
+        let (_, schema) = self.instances().find_with_schema(instance_id).await?;
+        let app_id = schema.static_config.steam_app_id;
+        let support = schema.mods.ok_or(WorkshopError::ModsNotSupported)?;
+        let app_id = workshop_app_id(&support, app_id);
+
+        Ok((support, app_id))
+    }
+
+    pub async fn list(
+        &self,
+        instance_id: i32,
+    ) -> Result<Vec<dto::instance::InstanceMod>, WorkshopError> {
+        self.instances().find_by_id(instance_id).await?;
+
+        let mods = entity::instance_mod::Entity::find()
+            .filter(entity::instance_mod::Column::InstanceId.eq(instance_id))
+            .order_by_asc(entity::instance_mod::Column::LoadOrder)
+            .all(&self.db)
+            .await?;
+
+        Ok(mods.into_iter().map(Into::into).collect())
+    }
+
+    /// Append a mod to the end of the load order
+    pub async fn add(
+        &self,
+        instance_id: i32,
+        entry: dto::instance::InstanceModEntry,
+    ) -> Result<dto::instance::InstanceMod, WorkshopError> {
+        self.mod_support(instance_id).await?;
+        if entry.workshop_item_id <= 0 {
+            return Err(WorkshopError::InvalidItemId(entry.workshop_item_id));
+        }
+
+        let existing = entity::instance_mod::Entity::find()
+            .filter(entity::instance_mod::Column::InstanceId.eq(instance_id))
+            .all(&self.db)
+            .await?;
+        if existing
+            .iter()
+            .any(|m| m.workshop_item_id == entry.workshop_item_id)
+        {
+            return Err(WorkshopError::DuplicateItem(entry.workshop_item_id));
+        }
+        let load_order = existing.iter().map(|m| m.load_order + 1).max().unwrap_or(0);
+
+        let model = entity::instance_mod::ActiveModel {
+            instance_id: Set(instance_id),
+            workshop_item_id: Set(entry.workshop_item_id),
+            enabled: Set(entry.enabled),
+            load_order: Set(load_order),
+            created_at: Set(chrono::Utc::now()),
+            ..Default::default()
+        }
+        .insert(&self.db)
+        .await?;
+
+        Ok(model.into())
+    }
+
+    /// Replace the whole mod list; list position becomes the load order.
+    /// Mods that stay in the list keep their install time.
+    pub async fn replace(
+        &self,
+        instance_id: i32,
+        entries: Vec<dto::instance::InstanceModEntry>,
+    ) -> Result<Vec<dto::instance::InstanceMod>, WorkshopError> {
+        let (support, _) = self.mod_support(instance_id).await?;
+
+        let mut seen = HashSet::new();
+        for entry in &entries {
+            if entry.workshop_item_id <= 0 {
+                return Err(WorkshopError::InvalidItemId(entry.workshop_item_id));
+            }
+            if !seen.insert(entry.workshop_item_id) {
+                return Err(WorkshopError::DuplicateItem(entry.workshop_item_id));
+            }
+        }
+
+        let txn = self.db.begin().await?;
+        let existing = entity::instance_mod::Entity::find()
+            .filter(entity::instance_mod::Column::InstanceId.eq(instance_id))
+            .all(&txn)
+            .await?;
+        entity::instance_mod::Entity::delete_many()
+            .filter(entity::instance_mod::Column::InstanceId.eq(instance_id))
+            .exec(&txn)
+            .await?;
+
+        let now = chrono::Utc::now();
+        for (load_order, entry) in entries.iter().enumerate() {
+            let previous = existing
+                .iter()
+                .find(|m| m.workshop_item_id == entry.workshop_item_id);
+            entity::instance_mod::ActiveModel {
+                instance_id: Set(instance_id),
+                workshop_item_id: Set(entry.workshop_item_id),
+                enabled: Set(entry.enabled),
+                load_order: Set(load_order as i32),
+                installed_at: Set(previous.and_then(|m| m.installed_at)),
+                created_at: Set(previous.map(|m| m.created_at).unwrap_or(now)),
+                ..Default::default()
+            }
+            .insert(&txn)
+            .await?;
+        }
+        txn.commit().await?;
+
+        let enabled: Vec<i64> = entries
+            .iter()
+            .filter(|e| e.enabled)
+            .map(|e| e.workshop_item_id)
+            .collect();
+        self.prune(instance_id, support, enabled).await?;
+
+        self.list(instance_id).await
+    }
+
+    pub async fn remove(&self, instance_id: i32, item_id: i64) -> Result<(), WorkshopError> {
+        let (support, _) = self.mod_support(instance_id).await?;
+
+        let res = entity::instance_mod::Entity::delete_many()
+            .filter(entity::instance_mod::Column::InstanceId.eq(instance_id))
+            .filter(entity::instance_mod::Column::WorkshopItemId.eq(item_id))
+            .exec(&self.db)
+            .await?;
+        if res.rows_affected == 0 {
+            return Err(WorkshopError::ModNotFound(item_id));
+        }
+
+        let enabled = self.instances().enabled_mods(instance_id).await?;
+        self.prune(instance_id, support, enabled).await
+    }
+
+    async fn prune(
+        &self,
+        instance_id: i32,
+        support: ModSupport,
+        keep: Vec<i64>,
+    ) -> Result<(), WorkshopError> {
+        let instance_dir = instance::instance_dir(instance_id);
+        tokio::task::spawn_blocking(move || {
+            prune_mods(&instance_dir, &workshop_dir(), &support, &keep)
+        })
+        .await
+        .map_err(std::io::Error::other)??;
+
+        Ok(())
+    }
+
+    /// Download the instance's enabled mods with a SteamCMD job. Once the job exits the mods
+    /// are placed into the instance's mod directories and disabled ones are removed.
+    pub async fn install(
+        &self,
+        instance_id: i32,
+        steamcmd: &SteamCMD,
+        login: SteamLogin,
+    ) -> Result<Arc<SteamCmdSession>, WorkshopError> {
+        let (support, app_id) = self.mod_support(instance_id).await?;
+        let mods = self.instances().enabled_mods(instance_id).await?;
+        if mods.is_empty() {
+            return Err(WorkshopError::NoMods);
+        }
+
+        let workshop_dir = workshop_dir();
+        std::fs::create_dir_all(&workshop_dir)?;
+        let commands = mods
+            .iter()
+            .map(|id| format!("workshop_download_item {app_id} {id}"))
+            .collect();
+        let session = steamcmd
+            .spawn_job(login, Some(&workshop_dir), commands)
+            .await?;
+
+        let db = self.db.clone();
+        let job = session.clone();
+        tokio::spawn(async move {
+            job.wait_exit().await;
+
+            let instance_dir = instance::instance_dir(instance_id);
+            let wanted = mods.clone();
+            let placed = tokio::task::spawn_blocking(move || {
+                place_mods(&instance_dir, &workshop_dir, &support, app_id, &wanted)
+            })
+            .await
+            .map_err(std::io::Error::other)
+            .and_then(|res| res);
+
+            let placed = match placed {
+                Ok(placed) => placed,
+                Err(e) => {
+                    job.announce(&format!("Failed to install mods: {e}")).await;
+                    return;
+                }
+            };
+
+            if !placed.is_empty() {
+                let updated = entity::instance_mod::Entity::update_many()
+                    .col_expr(
+                        entity::instance_mod::Column::InstalledAt,
+                        Expr::value(chrono::Utc::now()),
+                    )
+                    .filter(entity::instance_mod::Column::InstanceId.eq(instance_id))
+                    .filter(entity::instance_mod::Column::WorkshopItemId.is_in(placed.clone()))
+                    .exec(&db)
+                    .await;
+                if let Err(e) = updated {
+                    eprintln!("Failed to record mod install for instance {instance_id}: {e}");
+                }
+            }
+
+            let missing: Vec<String> = mods
+                .iter()
+                .filter(|id| !placed.contains(id))
+                .map(|id| id.to_string())
+                .collect();
+            let mut summary = format!("Installed {} of {} mods", placed.len(), mods.len());
+            if !missing.is_empty() {
+                summary.push_str(&format!("; not downloaded: {}", missing.join(", ")));
+            }
+            job.announce(&summary).await;
+        });
+
+        Ok(session)
+    }
+}
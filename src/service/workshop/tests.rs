@@ -0,0 +1,121 @@
+use super::*;
+use crate::utils::TempDir;
+
+const APP_ID: i32 = 346110;
+
+fn support(install_method: ModInstallMethod) -> ModSupport {
+    ModSupport {
+        workshop_app_id: Some(APP_ID),
+        directories: vec!["Game/Mods".to_string()],
+        install_method,
+        format: "{id}".to_string(),
+        separator: ",".to_string(),
+    }
+}
+
+/// Fake a finished `workshop_download_item`
+fn download(workshop: &Path, id: i64) {
+    let dir = content_dir(workshop, APP_ID, id);
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("mod.info"), id.to_string()).unwrap();
+}
+
+#[test]
+fn test_place_mods_symlinks_downloaded_items() {
+    let dir = TempDir::new();
+    let (instance, workshop) = (dir.0.join("instance"), dir.0.join("workshop"));
+    download(&workshop, 1);
+
+    let placed = place_mods(
+        &instance,
+        &workshop,
+        &support(ModInstallMethod::Symlink),
+        APP_ID,
+        &[1, 2],
+    );
+
+    assert_eq!(placed.unwrap(), vec![1]);
+    let target = instance.join("Game/Mods/1");
+    assert_eq!(
+        std::fs::read_link(&target).unwrap(),
+        content_dir(&workshop, APP_ID, 1)
+    );
+    assert!(target.join("mod.info").is_file());
+    assert!(!instance.join("Game/Mods/2").exists());
+}
+
+#[test]
+fn test_place_mods_copies_with_marker() {
+    let dir = TempDir::new();
+    let (instance, workshop) = (dir.0.join("instance"), dir.0.join("workshop"));
+    download(&workshop, 7);
+
+    place_mods(
+        &instance,
+        &workshop,
+        &support(ModInstallMethod::Copy),
+        APP_ID,
+        &[7],
+    )
+    .unwrap();
+
+    let target = instance.join("Game/Mods/7");
+    assert!(std::fs::read_link(&target).is_err());
+    assert_eq!(
+        std::fs::read_to_string(target.join("mod.info")).unwrap(),
+        "7"
+    );
+    assert!(target.join(COPY_MARKER).is_file());
+}
+
+#[test]
+fn test_place_mods_removes_mods_no_longer_enabled() {
+    let dir = TempDir::new();
+    let (instance, workshop) = (dir.0.join("instance"), dir.0.join("workshop"));
+    download(&workshop, 1);
+    download(&workshop, 2);
+
+    for method in [ModInstallMethod::Symlink, ModInstallMethod::Copy] {
+        let support = support(method);
+        place_mods(&instance, &workshop, &support, APP_ID, &[1, 2]).unwrap();
+        place_mods(&instance, &workshop, &support, APP_ID, &[2]).unwrap();
+
+        assert!(std::fs::symlink_metadata(instance.join("Game/Mods/1")).is_err());
+        assert!(instance.join("Game/Mods/2").exists());
+
+        prune_mods(&instance, &workshop, &support, &[]).unwrap();
+        assert!(std::fs::symlink_metadata(instance.join("Game/Mods/2")).is_err());
+    }
+}
+
+#[test]
+fn test_prune_leaves_unmanaged_entries() {
+    let dir = TempDir::new();
+    let (instance, workshop) = (dir.0.join("instance"), dir.0.join("workshop"));
+    let mods_dir = instance.join("Game/Mods");
+    std::fs::create_dir_all(mods_dir.join("111")).unwrap();
+    std::fs::write(mods_dir.join("README"), "hand placed").unwrap();
+
+    prune_mods(&instance, &workshop, &support(ModInstallMethod::Copy), &[]).unwrap();
+
+    assert!(mods_dir.join("111").is_dir());
+    assert!(mods_dir.join("README").is_file());
+}
+
+#[test]
+fn test_place_mods_refuses_to_overwrite_unmanaged_entries() {
+    let dir = TempDir::new();
+    let (instance, workshop) = (dir.0.join("instance"), dir.0.join("workshop"));
+    download(&workshop, 5);
+    std::fs::create_dir_all(instance.join("Game/Mods/5")).unwrap();
+
+    let err = place_mods(
+        &instance,
+        &workshop,
+        &support(ModInstallMethod::Symlink),
+        APP_ID,
+        &[5],
+    );
+
+    assert_eq!(err.unwrap_err().kind(), std::io::ErrorKind::AlreadyExists);
+}
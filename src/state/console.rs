@@ -0,0 +1,171 @@
+//! Output history shared by the SteamCMD and game server consoles. Every line gets a sequence
+//! number so a client that reconnects can ask for just the lines it has not seen yet.
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex, Weak},
+};
+use tokio::sync::broadcast::{self, error::RecvError};
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConsoleLine {
+    /// Starts at 1 and increases by one per line for the lifetime of the history
+    pub seq: u64,
+    pub text: String,
+}
+
+struct Lines {
+    lines: VecDeque<ConsoleLine>,
+    next_seq: u64,
+}
+
+impl Lines {
+    /// Lines after the cursor `after`, and how many of those are no longer kept
+    fn since(&self, after: u64) -> (Vec<ConsoleLine>, u64) {
+        let first = self.lines.front().map_or(self.next_seq, |line| line.seq);
+        let missed = first.saturating_sub(after + 1);
+        let lines = self
+            .lines
+            .iter()
+            .filter(|line| line.seq > after)
+            .cloned()
+            .collect();
+
+        (lines, missed)
+    }
+}
+
+/// The most recent lines of a console plus a broadcast of new ones
+pub struct ConsoleHistory {
+    buffer: Mutex<Lines>,
+    tx: broadcast::Sender<ConsoleLine>,
+    capacity: usize,
+}
+
+impl ConsoleHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            buffer: Mutex::new(Lines {
+                lines: VecDeque::with_capacity(capacity),
+                next_seq: 1,
+            }),
+            tx: broadcast::channel(capacity.max(1)).0,
+            capacity,
+        }
+    }
+
+    /// Append a line and send it to the subscribers
+    pub fn push(&self, text: String) {
+        let mut lines = self.buffer.lock().unwrap_or_else(|e| e.into_inner());
+        let line = ConsoleLine {
+            seq: lines.next_seq,
+            text,
+        };
+        lines.next_seq += 1;
+        lines.lines.push_back(line.clone());
+        if lines.lines.len() > self.capacity {
+            lines.lines.pop_front();
+        }
+        // Sent under the lock so `follow` can never see a line both replayed and broadcast
+        _ = self.tx.send(line);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ConsoleLine> {
+        self.tx.subscribe()
+    }
+
+    /// The text of every line still kept, oldest first
+    pub fn lines(&self) -> Vec<String> {
+        let lines = self.buffer.lock().unwrap_or_else(|e| e.into_inner());
+        lines.lines.iter().map(|line| line.text.clone()).collect()
+    }
+
+    /// Replay the lines after the cursor `after` (or all of them), then follow new ones
+    pub fn follow(self: &Arc<Self>, after: Option<u64>) -> ConsoleFollower {
+        let lines = self.buffer.lock().unwrap_or_else(|e| e.into_inner());
+        let rx = self.tx.subscribe();
+
+        let mut follower = ConsoleFollower {
+            history: Arc::downgrade(self),
+            rx,
+            pending: VecDeque::new(),
+            next_seq: 1,
+        };
+        match after {
+            // A cursor we never handed out: the history was recreated since, e.g. by a restart
+            Some(after) if after >= lines.next_seq => {
+                follower.pending.push_back(Followed::Reset);
+                follower.queue(lines.since(0));
+            }
+            after => follower.queue(lines.since(after.unwrap_or(0))),
+        }
+        follower.next_seq = lines.next_seq;
+
+        follower
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Followed {
+    Line(ConsoleLine),
+    /// Lines between the previous one and the next were dropped from the history before they
+    /// could be sent
+    Resync {
+        missed: u64,
+    },
+    /// The cursor did not belong to this history; everything kept is replayed after this
+    Reset,
+}
+
+/// A subscriber that never loses its place: when it falls behind the broadcast it catches up
+/// from the history instead of giving up
+pub struct ConsoleFollower {
+    /// Weak so that dropping the console still ends the stream
+    history: Weak<ConsoleHistory>,
+    rx: broadcast::Receiver<ConsoleLine>,
+    pending: VecDeque<Followed>,
+    next_seq: u64,
+}
+
+impl ConsoleFollower {
+    fn queue(&mut self, (lines, missed): (Vec<ConsoleLine>, u64)) {
+        if missed > 0 {
+            self.pending.push_back(Followed::Resync { missed });
+        }
+        if let Some(last) = lines.last() {
+            self.next_seq = last.seq + 1;
+        }
+        self.pending.extend(lines.into_iter().map(Followed::Line));
+    }
+
+    /// The next line, or `None` once the console is gone. Cancel safe.
+    pub async fn recv(&mut self) -> Option<Followed> {
+        loop {
+            if let Some(next) = self.pending.pop_front() {
+                return Some(next);
+            }
+
+            match self.rx.recv().await {
+                // Already replayed from the history
+                Ok(line) if line.seq < self.next_seq => continue,
+                Ok(line) => {
+                    self.next_seq = line.seq + 1;
+                    return Some(Followed::Line(line));
+                }
+                Err(RecvError::Lagged(_)) => {
+                    let history = self.history.upgrade()?;
+                    let since = history
+                        .buffer
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .since(self.next_seq - 1);
+                    self.queue(since);
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+}
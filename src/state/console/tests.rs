@@ -0,0 +1,100 @@
+use super::*;
+
+fn history(capacity: usize, lines: u64) -> Arc<ConsoleHistory> {
+    let history = Arc::new(ConsoleHistory::new(capacity));
+    for n in 1..=lines {
+        history.push(format!("line {n}"));
+    }
+    history
+}
+
+fn line(seq: u64) -> Followed {
+    Followed::Line(ConsoleLine {
+        seq,
+        text: format!("line {seq}"),
+    })
+}
+
+async fn drain(follower: &mut ConsoleFollower) -> Vec<Followed> {
+    let mut items = Vec::new();
+    while let Ok(Some(item)) =
+        tokio::time::timeout(std::time::Duration::from_millis(20), follower.recv()).await
+    {
+        items.push(item);
+    }
+    items
+}
+
+#[tokio::test]
+async fn test_follow_replays_then_streams() {
+    let history = history(10, 2);
+    let mut follower = history.follow(None);
+    history.push("line 3".to_string());
+
+    assert_eq!(drain(&mut follower).await, vec![line(1), line(2), line(3)]);
+}
+
+#[tokio::test]
+async fn test_follow_resumes_after_cursor() {
+    let history = history(10, 5);
+
+    assert_eq!(
+        drain(&mut history.follow(Some(3))).await,
+        vec![line(4), line(5)]
+    );
+    assert!(drain(&mut history.follow(Some(5))).await.is_empty());
+}
+
+#[tokio::test]
+async fn test_follow_reports_lines_dropped_from_history() {
+    let history = history(3, 6);
+
+    assert_eq!(
+        drain(&mut history.follow(Some(1))).await,
+        vec![Followed::Resync { missed: 2 }, line(4), line(5), line(6)]
+    );
+}
+
+#[tokio::test]
+async fn test_follow_resets_unknown_cursor() {
+    let history = history(10, 2);
+
+    assert_eq!(
+        drain(&mut history.follow(Some(40))).await,
+        vec![Followed::Reset, line(1), line(2)]
+    );
+}
+
+#[tokio::test]
+async fn test_lagged_follower_resyncs_from_history() {
+    let history = history(4, 0);
+    let mut follower = history.follow(None);
+    for n in 1..=6 {
+        history.push(format!("line {n}"));
+    }
+
+    // The broadcast only holds four lines, so the first two are gone for good
+    assert_eq!(
+        drain(&mut follower).await,
+        vec![
+            Followed::Resync { missed: 2 },
+            line(3),
+            line(4),
+            line(5),
+            line(6)
+        ]
+    );
+
+    history.push("line 7".to_string());
+    assert_eq!(drain(&mut follower).await, vec![line(7)]);
+}
+
+#[tokio::test]
+async fn test_follower_ends_with_the_history() {
+    let history = history(4, 1);
+    let mut follower = history.follow(None);
+    drop(history);
+
+    assert_eq!(follower.recv().await, Some(line(1)));
+    assert_eq!(follower.recv().await, None);
+}
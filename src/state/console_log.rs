@@ -0,0 +1,482 @@
+use crate::dto::console_log::{ConsoleLogLine, ConsoleLogPage, ConsoleLogQuery};
+use crate::state::vt;
+use crate::utils::error_response;
+use chrono::{DateTime, NaiveDate, Utc};
+use flate2::{read::MultiGzDecoder, write::GzEncoder, Compression};
+use regex::Regex;
+use rocket::{http::Status, response::Responder, Request};
+use std::{
+    collections::{HashMap, VecDeque},
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    sync::mpsc,
+    time::Duration,
+};
+use thiserror::Error;
+
+#[cfg(test)]
+mod tests;
+
+/// Days of logs kept per stream unless `LOG_RETENTION_DAYS` says otherwise; 0 keeps everything
+const DEFAULT_RETENTION_DAYS: u64 = 30;
+/// Size limit per stream in MiB unless `LOG_MAX_SIZE_MB` says otherwise; 0 means no limit
+const DEFAULT_MAX_SIZE_MB: u64 = 256;
+
+/// How often the writer closes files of past days, compresses them and applies retention
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+const DEFAULT_PAGE_SIZE: u64 = 200;
+const MAX_PAGE_SIZE: u64 = 1000;
+
+#[derive(Error, Debug)]
+pub enum ConsoleLogError {
+    #[error("Invalid '{0}' timestamp. Expected RFC 3339, e.g. 2026-01-31T12:00:00Z")]
+    InvalidTimestamp(&'static str),
+
+    #[error("Invalid search pattern: {0}")]
+    InvalidRegex(#[from] regex::Error),
+
+    #[error("Failed to read the console logs: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl<'r> Responder<'r, 'static> for ConsoleLogError {
+    fn respond_to(self, _: &'r Request<'_>) -> rocket::response::Result<'static> {
+        let status = match self {
+            ConsoleLogError::InvalidTimestamp(_) | ConsoleLogError::InvalidRegex(_) => {
+                Status::UnprocessableEntity
+            }
+            ConsoleLogError::Io(_) => Status::InternalServerError,
+        };
+        error_response(self, status)
+    }
+}
+
+/// A console whose output is logged to its own directory
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogStream {
+    SteamCmd,
+    Instance(i32),
+}
+
+impl LogStream {
+    fn dir(&self) -> PathBuf {
+        match self {
+            LogStream::SteamCmd => PathBuf::from("steamcmd"),
+            LogStream::Instance(id) => Path::new("instances").join(id.to_string()),
+        }
+    }
+}
+
+/// How much history is kept for each stream
+#[derive(Clone, Copy, Debug)]
+pub struct Retention {
+    /// Day files older than this are deleted; 0 keeps them forever
+    pub days: u64,
+    /// The oldest day files are deleted while a stream uses more than this; 0 means no limit
+    pub max_bytes: u64,
+}
+
+impl Retention {
+    pub fn from_env() -> Self {
+        let var = |name: &str, default: u64| {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        };
+
+        Retention {
+            days: var("LOG_RETENTION_DAYS", DEFAULT_RETENTION_DAYS),
+            max_bytes: var("LOG_MAX_SIZE_MB", DEFAULT_MAX_SIZE_MB) * 1024 * 1024,
+        }
+    }
+}
+
+struct Entry {
+    dir: PathBuf,
+    source: Option<String>,
+    at: DateTime<Utc>,
+    text: String,
+}
+
+/// Writes the lines of one console into the log files
+#[derive(Clone)]
+pub struct LogWriter {
+    dir: PathBuf,
+    source: Option<String>,
+    tx: mpsc::Sender<Entry>,
+}
+
+impl LogWriter {
+    /// Queue a line of console output. Escape sequences are removed; the line is dropped if the
+    /// writer has stopped.
+    pub fn write(&self, line: &str) {
+        let text = vt::plain(line.trim_end_matches(['\r', '\n']));
+        if text.trim().is_empty() {
+            return;
+        }
+
+        _ = self.tx.send(Entry {
+            dir: self.dir.clone(),
+            source: self.source.clone(),
+            at: Utc::now(),
+            text,
+        });
+    }
+}
+
+/// Console output of SteamCMD and the game servers, kept as one file per stream and day under
+/// `DATA_DIR/logs`. Past days are gzip compressed.
+#[derive(Clone)]
+pub struct ConsoleLogs {
+    root: PathBuf,
+    tx: mpsc::Sender<Entry>,
+}
+
+impl ConsoleLogs {
+    /// Start the writer thread. It also compresses and prunes what previous runs left behind.
+    pub fn start(root: PathBuf, retention: Retention) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let mut files = LogFiles::new(root.clone(), retention);
+
+        std::thread::spawn(move || {
+            files.sweep(Utc::now().date_naive());
+            loop {
+                match rx.recv_timeout(SWEEP_INTERVAL) {
+                    Ok(entry) => {
+                        files.write(entry);
+                        // Write whatever else is queued before flushing
+                        while let Ok(entry) = rx.try_recv() {
+                            files.write(entry);
+                        }
+                        files.flush();
+                    }
+                    Err(mpsc::RecvTimeoutError::Timeout) => {
+                        files.sweep(Utc::now().date_naive());
+                    }
+                    Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                }
+            }
+            files.flush();
+        });
+
+        Self { root, tx }
+    }
+
+    /// A writer for `stream`. `source` tells apart consoles sharing a stream, such as
+    /// SteamCMD sessions.
+    pub fn writer(&self, stream: LogStream, source: Option<&str>) -> LogWriter {
+        LogWriter {
+            dir: stream.dir(),
+            source: source.map(String::from),
+            tx: self.tx.clone(),
+        }
+    }
+
+    /// Read the lines of `stream` matching `query`
+    pub async fn query(
+        &self,
+        stream: LogStream,
+        query: ConsoleLogQuery,
+    ) -> Result<ConsoleLogPage, ConsoleLogError> {
+        let dir = self.root.join(stream.dir());
+        tokio::task::spawn_blocking(move || read_page(&dir, &query))
+            .await
+            .map_err(std::io::Error::other)?
+    }
+}
+
+/// `2026-10-18.log` or `2026-10-18.log.gz`
+fn file_date(path: &Path) -> Option<NaiveDate> {
+    let name = path.file_name()?.to_str()?;
+    let date = name
+        .strip_suffix(".log.gz")
+        .or_else(|| name.strip_suffix(".log"))?;
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+}
+
+/// Day files of a stream, oldest first
+fn day_files(dir: &Path) -> Vec<(NaiveDate, PathBuf)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<_> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter_map(|path| Some((file_date(&path)?, path)))
+        .collect();
+    // A plain file next to a compressed one of the same day holds the later lines
+    files.sort_by(|a, b| (a.0, a.1.extension()).cmp(&(b.0, b.1.extension())));
+    files
+}
+
+/// Stream directories below `root` holding day files
+fn stream_dirs(root: &Path) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        let mut has_logs = false;
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if path.is_dir() {
+                pending.push(path);
+            } else if file_date(&path).is_some() {
+                has_logs = true;
+            }
+        }
+        if has_logs {
+            dirs.push(dir);
+        }
+    }
+    dirs
+}
+
+fn compress(path: &Path) -> std::io::Result<()> {
+    let target = path.with_extension("log.gz");
+    let partial = path.with_extension("log.gz.partial");
+
+    let mut encoder = GzEncoder::new(File::create(&partial)?, Compression::default());
+    std::io::copy(&mut File::open(path)?, &mut encoder)?;
+    encoder.finish()?.sync_all()?;
+
+    // Append to a compressed file of the same day instead of replacing it
+    if target.exists() {
+        let mut existing = File::options().append(true).open(&target)?;
+        std::io::copy(&mut File::open(&partial)?, &mut existing)?;
+        std::fs::remove_file(&partial)?;
+    } else {
+        std::fs::rename(&partial, &target)?;
+    }
+    std::fs::remove_file(path)
+}
+
+struct OpenFile {
+    date: NaiveDate,
+    file: BufWriter<File>,
+}
+
+/// State of the writer thread: open files of the current day and the retention settings
+struct LogFiles {
+    root: PathBuf,
+    retention: Retention,
+    open: HashMap<PathBuf, OpenFile>,
+}
+
+impl LogFiles {
+    fn new(root: PathBuf, retention: Retention) -> Self {
+        Self {
+            root,
+            retention,
+            open: HashMap::new(),
+        }
+    }
+
+    fn write(&mut self, entry: Entry) {
+        let date = entry.at.date_naive();
+        let dir = self.root.join(&entry.dir);
+
+        if self.open.get(&entry.dir).is_some_and(|f| f.date != date) {
+            // First line of a new day: finish the previous file
+            self.open.remove(&entry.dir);
+            self.sweep_dir(&dir, date);
+        }
+        if !self.open.contains_key(&entry.dir) {
+            let opened = std::fs::create_dir_all(&dir).and_then(|_| {
+                File::options()
+                    .create(true)
+                    .append(true)
+                    .open(dir.join(format!("{date}.log")))
+            });
+            match opened {
+                Ok(file) => {
+                    let file = BufWriter::new(file);
+                    self.open.insert(entry.dir.clone(), OpenFile { date, file });
+                }
+                Err(e) => {
+                    eprintln!("Failed to open console log in {}: {e}", dir.display());
+                    return;
+                }
+            }
+        }
+
+        let Some(open) = self.open.get_mut(&entry.dir) else {
+            return;
+        };
+        let line = format!(
+            "{}\t{}\t{}\n",
+            entry
+                .at
+                .to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            entry.source.as_deref().unwrap_or("-"),
+            entry.text
+        );
+        if let Err(e) = open.file.write_all(line.as_bytes()) {
+            eprintln!("Failed to write console log in {}: {e}", dir.display());
+        }
+    }
+
+    fn flush(&mut self) {
+        for open in self.open.values_mut() {
+            _ = open.file.flush();
+        }
+    }
+
+    /// Compress the past days of every stream and apply retention
+    fn sweep(&mut self, today: NaiveDate) {
+        self.flush();
+        self.open.retain(|_, open| open.date == today);
+        for dir in stream_dirs(&self.root) {
+            self.sweep_dir(&dir, today);
+        }
+    }
+
+    fn sweep_dir(&self, dir: &Path, today: NaiveDate) {
+        for (date, path) in day_files(dir) {
+            if date < today && path.extension().is_some_and(|ext| ext == "log") {
+                if let Err(e) = compress(&path) {
+                    eprintln!("Failed to compress {}: {e}", path.display());
+                }
+            }
+        }
+
+        let mut files: Vec<_> = day_files(dir)
+            .into_iter()
+            .map(|(date, path)| {
+                let size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+                (date, path, size)
+            })
+            .collect();
+        let mut total: u64 = files.iter().map(|(_, _, size)| size).sum();
+        let oldest_kept = (self.retention.days > 0)
+            .then(|| today - chrono::Days::new(self.retention.days.saturating_sub(1)));
+
+        files.retain(|(date, path, size)| {
+            let expired = oldest_kept.is_some_and(|oldest| *date < oldest);
+            let over_size =
+                self.retention.max_bytes > 0 && total > self.retention.max_bytes && *date < today;
+            if expired || over_size {
+                if std::fs::remove_file(path).is_ok() {
+                    total -= size;
+                }
+                return false;
+            }
+            true
+        });
+    }
+}
+
+enum Matcher {
+    All,
+    Text(String),
+    Regex(Regex),
+}
+
+impl Matcher {
+    fn matches(&self, text: &str) -> bool {
+        match self {
+            Matcher::All => true,
+            Matcher::Text(needle) => text.to_lowercase().contains(needle),
+            Matcher::Regex(regex) => regex.is_match(text),
+        }
+    }
+}
+
+fn parse_line(line: &str) -> Option<ConsoleLogLine> {
+    let mut parts = line.splitn(3, '\t');
+    let timestamp = DateTime::parse_from_rfc3339(parts.next()?)
+        .ok()?
+        .with_timezone(&Utc);
+    let source = match parts.next()? {
+        "-" => None,
+        source => Some(source.to_string()),
+    };
+
+    Some(ConsoleLogLine {
+        timestamp,
+        source,
+        text: parts.next()?.to_string(),
+    })
+}
+
+fn read_page(dir: &Path, query: &ConsoleLogQuery) -> Result<ConsoleLogPage, ConsoleLogError> {
+    let parse = |value: &Option<String>, name: &'static str| {
+        value
+            .as_deref()
+            .map(|v| {
+                DateTime::parse_from_rfc3339(v)
+                    .map(|t| t.with_timezone(&Utc))
+                    .map_err(|_| ConsoleLogError::InvalidTimestamp(name))
+            })
+            .transpose()
+    };
+    let from = parse(&query.from, "from")?;
+    let to = parse(&query.to, "to")?;
+    let matcher = match (query.search.as_deref(), query.regex.unwrap_or(false)) {
+        (None | Some(""), _) => Matcher::All,
+        (Some(pattern), true) => Matcher::Regex(Regex::new(pattern)?),
+        (Some(text), false) => Matcher::Text(text.to_lowercase()),
+    };
+    let offset = query.offset.unwrap_or(0);
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+    let tail = query.tail.unwrap_or(false);
+
+    let mut total = 0;
+    let mut lines = VecDeque::new();
+    for (date, path) in day_files(dir) {
+        if from.is_some_and(|from| date < from.date_naive())
+            || to.is_some_and(|to| date > to.date_naive())
+        {
+            continue;
+        }
+
+        let file = File::open(&path)?;
+        let reader: Box<dyn Read> = match path.extension().is_some_and(|ext| ext == "gz") {
+            // Appending to a compressed day adds another gzip member
+            true => Box::new(MultiGzDecoder::new(file)),
+            false => Box::new(file),
+        };
+        for line in BufReader::new(reader).lines() {
+            let Some(line) = parse_line(&line?) else {
+                continue;
+            };
+            if from.is_some_and(|from| line.timestamp < from)
+                || to.is_some_and(|to| line.timestamp >= to)
+                || query
+                    .source
+                    .as_ref()
+                    .is_some_and(|source| line.source.as_ref() != Some(source))
+                || !matcher.matches(&line.text)
+            {
+                continue;
+            }
+
+            total += 1;
+            if tail {
+                lines.push_back(line);
+                if lines.len() as u64 > offset + limit {
+                    lines.pop_front();
+                }
+            } else if total > offset && (lines.len() as u64) < limit {
+                lines.push_back(line);
+            }
+        }
+    }
+    if tail {
+        let keep = (lines.len() as u64).saturating_sub(offset) as usize;
+        lines.truncate(keep);
+    }
+
+    Ok(ConsoleLogPage {
+        lines: lines.into(),
+        total,
+        offset,
+        limit,
+    })
+}
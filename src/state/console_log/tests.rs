@@ -0,0 +1,232 @@
+use super::*;
+use crate::utils::TempDir;
+
+const UNLIMITED: Retention = Retention {
+    days: 0,
+    max_bytes: 0,
+};
+
+fn at(timestamp: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .unwrap()
+        .with_timezone(&Utc)
+}
+
+fn entry(timestamp: &str, source: Option<&str>, text: &str) -> Entry {
+    Entry {
+        dir: LogStream::Instance(7).dir(),
+        source: source.map(String::from),
+        at: at(timestamp),
+        text: text.to_string(),
+    }
+}
+
+fn day(date: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap()
+}
+
+/// Three days of output; the first two end up compressed
+fn write_days(root: &Path) -> PathBuf {
+    let mut files = LogFiles::new(root.to_path_buf(), UNLIMITED);
+    files.write(entry("2026-10-16T23:59:00Z", None, "Server started"));
+    files.write(entry("2026-10-16T23:59:30Z", None, "Player Alice joined"));
+    files.write(entry("2026-10-17T08:00:00Z", None, "Player Bob joined"));
+    files.write(entry(
+        "2026-10-17T09:00:00Z",
+        Some("job-1"),
+        "ERROR: disk full",
+    ));
+    files.write(entry("2026-10-18T10:00:00Z", None, "Player alice left"));
+    files.flush();
+    root.join(LogStream::Instance(7).dir())
+}
+
+fn query() -> ConsoleLogQuery {
+    ConsoleLogQuery::default()
+}
+
+fn texts(page: &ConsoleLogPage) -> Vec<&str> {
+    page.lines.iter().map(|l| l.text.as_str()).collect()
+}
+
+#[test]
+fn test_rotates_and_compresses_past_days() {
+    let root = TempDir::new();
+    let dir = write_days(&root.0);
+
+    let mut names: Vec<_> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().into_string().unwrap())
+        .collect();
+    names.sort();
+    assert_eq!(
+        names,
+        vec!["2026-10-16.log.gz", "2026-10-17.log.gz", "2026-10-18.log"]
+    );
+
+    let page = read_page(&dir, &query()).unwrap();
+    assert_eq!(page.total, 5);
+    assert_eq!(
+        texts(&page),
+        vec![
+            "Server started",
+            "Player Alice joined",
+            "Player Bob joined",
+            "ERROR: disk full",
+            "Player alice left"
+        ]
+    );
+    assert_eq!(page.lines[3].source.as_deref(), Some("job-1"));
+    assert_eq!(page.lines[3].timestamp, at("2026-10-17T09:00:00Z"));
+}
+
+#[test]
+fn test_compressing_into_an_existing_day_keeps_both() {
+    let root = TempDir::new();
+    let dir = write_days(&root.0);
+
+    // A restart late on the 18th, then the sweep after midnight
+    let mut files = LogFiles::new(root.0.clone(), UNLIMITED);
+    files.write(entry("2026-10-18T23:00:00Z", None, "Server restarted"));
+    files.sweep(day("2026-10-20"));
+    files.sweep(day("2026-10-20"));
+
+    let page = read_page(&dir, &query()).unwrap();
+    assert_eq!(page.total, 6);
+    assert_eq!(page.lines[5].text, "Server restarted");
+    assert!(!dir.join("2026-10-18.log").exists());
+}
+
+#[test]
+fn test_search_and_time_range() {
+    let root = TempDir::new();
+    let dir = write_days(&root.0);
+
+    let page = read_page(
+        &dir,
+        &ConsoleLogQuery {
+            search: Some("alice".to_string()),
+            ..query()
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        texts(&page),
+        vec!["Player Alice joined", "Player alice left"]
+    );
+
+    let page = read_page(
+        &dir,
+        &ConsoleLogQuery {
+            search: Some(r"^Player \w+ joined$".to_string()),
+            regex: Some(true),
+            from: Some("2026-10-17T00:00:00Z".to_string()),
+            ..query()
+        },
+    )
+    .unwrap();
+    assert_eq!(texts(&page), vec!["Player Bob joined"]);
+
+    let page = read_page(
+        &dir,
+        &ConsoleLogQuery {
+            source: Some("job-1".to_string()),
+            to: Some("2026-10-18T00:00:00Z".to_string()),
+            ..query()
+        },
+    )
+    .unwrap();
+    assert_eq!(texts(&page), vec!["ERROR: disk full"]);
+}
+
+#[test]
+fn test_paging_and_tail() {
+    let root = TempDir::new();
+    let dir = write_days(&root.0);
+
+    let page = |offset, tail| {
+        read_page(
+            &dir,
+            &ConsoleLogQuery {
+                offset: Some(offset),
+                limit: Some(2),
+                tail: Some(tail),
+                ..query()
+            },
+        )
+        .unwrap()
+    };
+
+    assert_eq!(
+        texts(&page(2, false)),
+        vec!["Player Bob joined", "ERROR: disk full"]
+    );
+    assert_eq!(
+        texts(&page(0, true)),
+        vec!["ERROR: disk full", "Player alice left"]
+    );
+    assert_eq!(
+        texts(&page(3, true)),
+        vec!["Server started", "Player Alice joined"]
+    );
+    assert!(page(5, true).lines.is_empty());
+    assert_eq!(page(5, true).total, 5);
+}
+
+#[test]
+fn test_invalid_filters() {
+    let root = TempDir::new();
+    let dir = write_days(&root.0);
+
+    assert!(matches!(
+        read_page(
+            &dir,
+            &ConsoleLogQuery {
+                from: Some("yesterday".to_string()),
+                ..query()
+            }
+        ),
+        Err(ConsoleLogError::InvalidTimestamp("from"))
+    ));
+    assert!(matches!(
+        read_page(
+            &dir,
+            &ConsoleLogQuery {
+                search: Some("(".to_string()),
+                regex: Some(true),
+                ..query()
+            }
+        ),
+        Err(ConsoleLogError::InvalidRegex(_))
+    ));
+}
+
+#[test]
+fn test_retention_by_age_and_size() {
+    let root = TempDir::new();
+    let dir = write_days(&root.0);
+
+    let mut files = LogFiles::new(
+        root.0.clone(),
+        Retention {
+            days: 2,
+            max_bytes: 0,
+        },
+    );
+    files.sweep(day("2026-10-18"));
+    assert_eq!(read_page(&dir, &query()).unwrap().total, 3);
+
+    let mut files = LogFiles::new(
+        root.0.clone(),
+        Retention {
+            days: 0,
+            max_bytes: 1,
+        },
+    );
+    files.sweep(day("2026-10-18"));
+    // The current day is never removed
+    assert_eq!(
+        texts(&read_page(&dir, &query()).unwrap()),
+        vec!["Player alice left"]
+    );
+}
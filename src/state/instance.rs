@@ -0,0 +1,793 @@
+use crate::dto::instance::{
+    AppliedLimits, LimitViolation, LimitViolationKind, LimitViolations, ResourceLimits,
+    TriggerFired,
+};
+use crate::entity;
+use crate::models::instance::InstanceStatus;
+use crate::schema::server_config::{SandboxConfig, TriggerEvent};
+use crate::service::files::InstanceLayout;
+use crate::state::console::{ConsoleFollower, ConsoleHistory, ConsoleLine};
+use crate::state::console_log::{ConsoleLogs, LogStream, LogWriter};
+use crate::state::limits::{self, Cgroups};
+use crate::state::sandbox::Sandbox;
+use crate::state::trigger::Triggers;
+use crate::utils::error_response;
+use rocket::{futures::lock::Mutex, http::Status, response::Responder, Request};
+use sea_orm::prelude::*;
+use sea_orm::sea_query::Expr;
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    process::Stdio,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use thiserror::Error;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, AsyncWriteExt, BufReader},
+    process::{Child, ChildStdin, Command},
+    sync::{broadcast, watch},
+};
+use tokio_util::sync::CancellationToken;
+
+/// Output lines kept per instance for clients that connect late. The history outlives single
+/// runs, so a console stays attached across restarts.
+const HISTORY_CAPACITY: usize = 500;
+
+/// How deep to look inside the instance directory for the server executable
+const EXECUTABLE_SEARCH_DEPTH: usize = 6;
+
+/// How often the cgroup of a running server is checked for limit violations
+const LIMIT_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Prefix of lines the server itself writes into an instance's output
+const LIFECYCLE_PREFIX: &str = "[server_ui]";
+
+#[derive(Error, Debug)]
+pub enum InstanceProcessError {
+    #[error("Instance {0} is already running")]
+    AlreadyRunning(i32),
+
+    #[error("Instance {0} is not running")]
+    NotRunning(i32),
+
+    #[error("The instance's command line is empty")]
+    EmptyCommand,
+
+    #[error("Console input must be a single, non-empty line")]
+    InvalidInput,
+
+    #[error("The game server is not reading console input")]
+    StdinClosed,
+
+    #[error("Failed to start the game server: {0}")]
+    FailedToStart(std::io::Error),
+
+    #[error("Instance {0} is being updated")]
+    Updating(i32),
+
+    #[error("The server must run sandboxed, but {0}")]
+    SandboxUnavailable(String),
+
+    #[error("A database error occurred. Please review the logs for more details.")]
+    DbError(#[from] sea_orm::DbErr),
+}
+
+impl<'r> Responder<'r, 'static> for InstanceProcessError {
+    fn respond_to(self, _: &'r Request<'_>) -> rocket::response::Result<'static> {
+        let status = match self {
+            InstanceProcessError::AlreadyRunning(_)
+            | InstanceProcessError::NotRunning(_)
+            | InstanceProcessError::Updating(_) => Status::Conflict,
+            InstanceProcessError::StdinClosed => Status::Conflict,
+            InstanceProcessError::EmptyCommand
+            | InstanceProcessError::InvalidInput
+            | InstanceProcessError::SandboxUnavailable(_) => Status::UnprocessableEntity,
+            InstanceProcessError::FailedToStart(_) | InstanceProcessError::DbError(_) => {
+                Status::InternalServerError
+            }
+        };
+        error_response(self, status)
+    }
+}
+
+/// Persist an instance's lifecycle state
+pub async fn set_status(
+    db: &DatabaseConnection,
+    id: i32,
+    status: InstanceStatus,
+) -> Result<(), sea_orm::DbErr> {
+    entity::game_config::Entity::update_many()
+        .col_expr(
+            entity::game_config::Column::Status,
+            Expr::value(status as i32),
+        )
+        .filter(entity::game_config::Column::Id.eq(id))
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+/// Find the program to run: a path relative to the instance directory, or an executable with
+/// that name somewhere below it. Anything else is left to `PATH`.
+fn resolve_executable(dir: &Path, program: &str) -> PathBuf {
+    let relative = dir.join(program);
+    if relative.is_file() {
+        return relative;
+    }
+    if program.contains('/') {
+        return PathBuf::from(program);
+    }
+
+    fn find(dir: &Path, name: &str, depth: usize) -> Option<PathBuf> {
+        let mut subdirs = Vec::new();
+        for entry in std::fs::read_dir(dir).ok()?.flatten() {
+            let path = entry.path();
+            let file_type = entry.file_type().ok()?;
+            if file_type.is_file() && entry.file_name() == name {
+                return Some(path);
+            }
+            if file_type.is_dir() {
+                subdirs.push(path);
+            }
+        }
+        if depth == 0 {
+            return None;
+        }
+        subdirs
+            .into_iter()
+            .find_map(|subdir| find(&subdir, name, depth - 1))
+    }
+
+    find(dir, program, EXECUTABLE_SEARCH_DEPTH).unwrap_or_else(|| PathBuf::from(program))
+}
+
+/// One run of a game server and its output
+pub struct InstanceProcess {
+    pid: Option<u32>,
+    started_at: chrono::DateTime<chrono::Utc>,
+    stdin: Mutex<Option<ChildStdin>>,
+    history: Arc<ConsoleHistory>,
+    stop_requested: AtomicBool,
+    kill_token: CancellationToken,
+    running: watch::Sender<bool>,
+    log: Option<LogWriter>,
+    limits: std::sync::Mutex<AppliedLimits>,
+}
+
+fn push_line(history: &ConsoleHistory, log: Option<&LogWriter>, line: String) {
+    if let Some(log) = log {
+        log.write(&line);
+    }
+    history.push(line);
+}
+
+impl InstanceProcess {
+    pub fn pid(&self) -> Option<u32> {
+        self.pid
+    }
+
+    pub fn started_at(&self) -> chrono::DateTime<chrono::Utc> {
+        self.started_at
+    }
+
+    pub fn is_running(&self) -> bool {
+        *self.running.borrow()
+    }
+
+    /// The limits this run is held to, and how often it ran into them
+    pub fn limits(&self) -> AppliedLimits {
+        self.limits
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Output lines as sanitized by [`crate::state::vt::sanitize`]: visible text plus SGR
+    /// colour codes
+    pub fn subscribe(&self) -> broadcast::Receiver<ConsoleLine> {
+        self.history.subscribe()
+    }
+
+    pub async fn get_last_lines(&self) -> Vec<String> {
+        self.history.lines()
+    }
+
+    /// Write a server-generated line into the instance's output
+    pub async fn announce(&self, message: &str) {
+        let line = format!("{LIFECYCLE_PREFIX} {message}\n");
+        push_line(&self.history, self.log.as_ref(), line);
+    }
+
+    /// Add a line to the instance's output as if the server had written it, e.g. the replies
+    /// to RCON commands
+    pub fn echo(&self, line: &str) {
+        let line = crate::state::vt::sanitize(line);
+        push_line(&self.history, self.log.as_ref(), format!("{line}\n"));
+    }
+
+    /// Write a line to the server's stdin, for game consoles that read commands from it. The
+    /// input is added to the output so everyone watching the console sees it.
+    pub async fn send_input(&self, input: &str) -> Result<(), InstanceProcessError> {
+        if input.trim().is_empty() || input.chars().any(char::is_control) {
+            return Err(InstanceProcessError::InvalidInput);
+        }
+
+        let mut stdin = self.stdin.lock().await;
+        let stdin = stdin.as_mut().ok_or(InstanceProcessError::StdinClosed)?;
+        stdin
+            .write_all(format!("{input}\n").as_bytes())
+            .await
+            .map_err(|_| InstanceProcessError::StdinClosed)?;
+        stdin
+            .flush()
+            .await
+            .map_err(|_| InstanceProcessError::StdinClosed)?;
+        push_line(&self.history, self.log.as_ref(), format!("> {input}\n"));
+
+        Ok(())
+    }
+
+    /// Wait until the process has exited and its status was recorded
+    pub async fn wait_exit(&self) {
+        let mut running = self.running.subscribe();
+        _ = running.wait_for(|running| !*running).await;
+    }
+
+    /// Wait up to `timeout` for the process to exit, or for a line from `lines` to match
+    /// `pattern`. Lines the panel wrote itself do not count. Returns whether the process exited.
+    pub async fn wait_exit_or_line(
+        &self,
+        mut lines: broadcast::Receiver<ConsoleLine>,
+        pattern: Option<&regex::Regex>,
+        timeout: Duration,
+    ) -> bool {
+        let matched = async {
+            let Some(pattern) = pattern else {
+                return std::future::pending().await;
+            };
+            loop {
+                match lines.recv().await {
+                    Ok(line) => {
+                        if line.text.starts_with(LIFECYCLE_PREFIX) || line.text.starts_with("> ") {
+                            continue;
+                        }
+                        let plain = crate::state::vt::plain(line.text.trim_end_matches('\n'));
+                        if pattern.is_match(&plain) {
+                            return;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => std::future::pending().await,
+                }
+            }
+        };
+
+        tokio::select! {
+            _ = self.wait_exit() => true,
+            _ = matched => false,
+            _ = tokio::time::sleep(timeout) => false,
+        }
+    }
+
+    /// Send SIGTERM to the server and everything it spawned, then SIGKILL if it is still
+    /// running after `grace`. Waits for the process to exit.
+    pub async fn terminate(&self, grace: Duration) {
+        if !self.is_running() {
+            return;
+        }
+
+        #[cfg(unix)]
+        if let Some(pid) = self.pid {
+            self.announce("Sending SIGTERM").await;
+            signal_group(pid, libc::SIGTERM);
+            if tokio::time::timeout(grace, self.wait_exit()).await.is_ok() {
+                return;
+            }
+            self.announce(&format!(
+                "Server did not exit within {}s, killing it",
+                grace.as_secs()
+            ))
+            .await;
+        }
+        #[cfg(not(unix))]
+        let _ = grace;
+
+        self.kill_token.cancel();
+        self.wait_exit().await;
+    }
+
+    fn read_output(&self, stream: impl AsyncRead + Unpin + Send + 'static) {
+        let history = self.history.clone();
+        let log = self.log.clone();
+
+        tokio::spawn(async move {
+            let mut reader = BufReader::new(stream);
+            let mut buffer = Vec::new();
+            loop {
+                buffer.clear();
+                match reader.read_until(b'\n', &mut buffer).await {
+                    Ok(0) | Err(_) => break,
+                    Ok(_) => {
+                        // Colours are kept for consoles that render them, see `vt::spans`
+                        let line = crate::state::vt::sanitize(
+                            String::from_utf8_lossy(&buffer).trim_end_matches(['\r', '\n']),
+                        );
+                        push_line(&history, log.as_ref(), format!("{line}\n"));
+                    }
+                }
+            }
+        });
+    }
+}
+
+/// Send `signal` to the process group led by `pid`. Servers are started in their own group,
+/// so wrapper scripts pass the signal on to the actual server and a Ctrl-C in the panel's
+/// terminal does not reach them.
+#[cfg(unix)]
+fn signal_group(pid: u32, signal: libc::c_int) {
+    // SAFETY: kill(2) takes no pointers; a negative pid addresses the process group
+    unsafe {
+        libc::kill(-(pid as libc::pid_t), signal);
+    }
+}
+
+/// Running game server processes, keyed by instance id. Clones share the same processes.
+#[derive(Clone)]
+pub struct InstanceManager {
+    db: DatabaseConnection,
+    processes: Arc<Mutex<HashMap<i32, Arc<InstanceProcess>>>>,
+    histories: Arc<std::sync::Mutex<HashMap<i32, Arc<ConsoleHistory>>>>,
+    triggers: broadcast::Sender<(i32, TriggerFired)>,
+    violations: broadcast::Sender<(i32, LimitViolation)>,
+    cgroups: Arc<Result<Cgroups, String>>,
+    sandbox: Arc<Result<Sandbox, String>>,
+    logs: Option<ConsoleLogs>,
+    updating: Arc<std::sync::Mutex<HashSet<i32>>>,
+}
+
+/// Held while SteamCMD writes an instance's files. Its server cannot start until this is dropped.
+pub struct UpdateLock {
+    updating: Arc<std::sync::Mutex<HashSet<i32>>>,
+    id: i32,
+}
+
+impl Drop for UpdateLock {
+    fn drop(&mut self) {
+        self.updating
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&self.id);
+    }
+}
+
+impl InstanceManager {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self {
+            db,
+            processes: Default::default(),
+            histories: Default::default(),
+            triggers: broadcast::channel(256).0,
+            violations: broadcast::channel(64).0,
+            cgroups: Arc::new(Err("cgroups were not set up".to_string())),
+            sandbox: Arc::new(Err("the sandbox was not set up".to_string())),
+            logs: None,
+            updating: Default::default(),
+        }
+    }
+
+    /// Also write the output of every server to `logs`
+    pub fn with_logs(mut self, logs: ConsoleLogs) -> Self {
+        self.logs = Some(logs);
+        self
+    }
+
+    /// Run every server in its own cgroup below `cgroups`, or explain why limits that need one
+    /// are skipped
+    pub fn with_cgroups(mut self, cgroups: Result<Cgroups, String>) -> Self {
+        self.cgroups = Arc::new(cgroups);
+        self
+    }
+
+    /// Launch servers whose schema asks for it inside `sandbox`, or refuse to with the reason
+    /// it is unavailable
+    pub fn with_sandbox(mut self, sandbox: Result<Sandbox, String>) -> Self {
+        self.sandbox = Arc::new(sandbox);
+        self
+    }
+
+    /// Nothing survives a restart of the panel, so any instance still marked as active is
+    /// stopped now
+    pub async fn reset_statuses(&self) -> Result<(), sea_orm::DbErr> {
+        entity::game_config::Entity::update_many()
+            .col_expr(
+                entity::game_config::Column::Status,
+                Expr::value(InstanceStatus::Stopped as i32),
+            )
+            .filter(entity::game_config::Column::Status.ne(InstanceStatus::Stopped as i32))
+            .exec(&self.db)
+            .await?;
+        Ok(())
+    }
+
+    /// Mark the instance as being updated, `None` if it already is
+    pub fn lock_update(&self, id: i32) -> Option<UpdateLock> {
+        let mut updating = self.updating.lock().unwrap_or_else(|e| e.into_inner());
+        updating.insert(id).then(|| UpdateLock {
+            updating: self.updating.clone(),
+            id,
+        })
+    }
+
+    pub fn is_updating(&self, id: i32) -> bool {
+        self.updating
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .contains(&id)
+    }
+
+    /// The current or most recent run of an instance
+    pub async fn process(&self, id: i32) -> Option<Arc<InstanceProcess>> {
+        self.processes.lock().await.get(&id).cloned()
+    }
+
+    /// Replay the instance's output after the cursor `after`, then follow it live, across
+    /// restarts. `None` if the server has not been started since the panel came up.
+    pub fn follow(&self, id: i32, after: Option<u64>) -> Option<ConsoleFollower> {
+        let histories = self.histories.lock().unwrap_or_else(|e| e.into_inner());
+        histories.get(&id).map(|history| history.follow(after))
+    }
+
+    /// Triggers fired by any instance's output from now on, tagged with the instance id
+    pub fn subscribe_triggers(&self) -> broadcast::Receiver<(i32, TriggerFired)> {
+        self.triggers.subscribe()
+    }
+
+    /// Servers running into their cgroup limits, tagged with their instance id
+    pub fn subscribe_violations(&self) -> broadcast::Receiver<(i32, LimitViolation)> {
+        self.violations.subscribe()
+    }
+
+    /// Drop everything kept about a deleted instance
+    pub async fn forget(&self, id: i32) {
+        self.processes.lock().await.remove(&id);
+        self.histories
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&id);
+    }
+
+    /// Ids of the instances whose server is running right now
+    pub async fn running(&self) -> Vec<i32> {
+        let processes = self.processes.lock().await;
+        processes
+            .iter()
+            .filter(|(_, process)| process.is_running())
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
+    pub async fn is_running(&self, id: i32) -> bool {
+        self.process(id)
+            .await
+            .is_some_and(|process| process.is_running())
+    }
+
+    /// Launch `command` from the instance's install root under `limits`, inside the sandbox when
+    /// the schema configures one, and watch its output for `triggers`. With a `ready` trigger
+    /// the instance stays Starting until it fires.
+    pub async fn start(
+        &self,
+        id: i32,
+        command: Vec<String>,
+        layout: &InstanceLayout,
+        triggers: Triggers,
+        limits: &ResourceLimits,
+        sandbox: Option<&SandboxConfig>,
+    ) -> Result<Arc<InstanceProcess>, InstanceProcessError> {
+        let mut processes = self.processes.lock().await;
+        if processes.get(&id).is_some_and(|p| p.is_running()) {
+            return Err(InstanceProcessError::AlreadyRunning(id));
+        }
+        if self.is_updating(id) {
+            return Err(InstanceProcessError::Updating(id));
+        }
+        let (program, args) = command
+            .split_first()
+            .ok_or(InstanceProcessError::EmptyCommand)?;
+
+        let sandbox = match sandbox {
+            Some(config) => match self.sandbox.as_ref() {
+                Ok(sandbox) => Some((sandbox, config)),
+                Err(reason) => {
+                    return Err(InstanceProcessError::SandboxUnavailable(reason.clone()))
+                }
+            },
+            None => None,
+        };
+
+        layout
+            .ensure()
+            .map_err(InstanceProcessError::FailedToStart)?;
+        if let Some((sandbox, _)) = sandbox {
+            sandbox
+                .prepare_dir(layout)
+                .await
+                .map_err(InstanceProcessError::FailedToStart)?;
+        }
+        set_status(&self.db, id, InstanceStatus::Starting).await?;
+
+        let dir = layout.install();
+        let program = resolve_executable(&dir, program);
+        let mut command = match sandbox {
+            Some((sandbox, config)) => {
+                let argv: Vec<String> = std::iter::once(program.to_string_lossy().to_string())
+                    .chain(args.iter().cloned())
+                    .collect();
+                sandbox.command(config, layout, &argv)
+            }
+            None => {
+                let mut command = Command::new(program);
+                command.args(args);
+                command
+            }
+        };
+        command
+            .current_dir(&dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        #[cfg(unix)]
+        command.process_group(0);
+        let (plan, applied) = limits::prepare(
+            id,
+            limits,
+            self.cgroups.as_ref().as_ref().map_err(String::as_str),
+        );
+        plan.apply(&mut command);
+        if let Some((sandbox, _)) = sandbox {
+            sandbox.drop_privileges(&mut command);
+        }
+        let spawned = command.spawn();
+        let mut child = match spawned {
+            Ok(child) => child,
+            Err(e) => {
+                set_status(&self.db, id, InstanceStatus::Crashed).await?;
+                return Err(InstanceProcessError::FailedToStart(e));
+            }
+        };
+
+        let history = self
+            .histories
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(id)
+            .or_insert_with(|| Arc::new(ConsoleHistory::new(HISTORY_CAPACITY)))
+            .clone();
+        let process = Arc::new(InstanceProcess {
+            pid: child.id(),
+            started_at: chrono::Utc::now(),
+            stdin: Mutex::new(child.stdin.take()),
+            history,
+            stop_requested: AtomicBool::new(false),
+            kill_token: CancellationToken::new(),
+            running: watch::channel(true).0,
+            log: self
+                .logs
+                .as_ref()
+                .map(|logs| logs.writer(LogStream::Instance(id), None)),
+            limits: std::sync::Mutex::new(applied.clone()),
+        });
+        // Subscribed before any output is read, so no line escapes the triggers
+        let lines = process.subscribe();
+        if let Some(stdout) = child.stdout.take() {
+            process.read_output(stdout);
+        }
+        if let Some(stderr) = child.stderr.take() {
+            process.read_output(stderr);
+        }
+        process
+            .announce(&match process.pid {
+                Some(pid) => format!("Server started (pid {pid})"),
+                None => "Server started".to_string(),
+            })
+            .await;
+        if let Some((sandbox, _)) = sandbox {
+            process
+                .announce(&match sandbox.user() {
+                    Some(user) => format!("Running sandboxed as uid {}", user.uid),
+                    None => "Running sandboxed".to_string(),
+                })
+                .await;
+        }
+        for skipped in &applied.skipped {
+            process
+                .announce(&format!(
+                    "Limit {} is not enforced: {}",
+                    skipped.limit, skipped.reason
+                ))
+                .await;
+        }
+        if let Some(cgroup) = applied.cgroup {
+            self.watch_limits(id, process.clone(), PathBuf::from(cgroup));
+        }
+
+        if triggers.has(TriggerEvent::Ready) {
+            process.announce("Waiting for the server to be ready").await;
+        } else {
+            set_status(&self.db, id, InstanceStatus::Running).await?;
+        }
+        if !triggers.is_empty() {
+            self.watch_triggers(id, process.clone(), lines, triggers);
+        }
+        self.watch(id, child, process.clone());
+        processes.insert(id, process.clone());
+
+        Ok(process)
+    }
+
+    /// Reap the process and record how it ended
+    fn watch(&self, id: i32, mut child: Child, process: Arc<InstanceProcess>) {
+        let db = self.db.clone();
+
+        tokio::spawn(async move {
+            let exit = tokio::select! {
+                exit = child.wait() => exit,
+                _ = process.kill_token.cancelled() => {
+                    #[cfg(unix)]
+                    if let Some(pid) = process.pid {
+                        signal_group(pid, libc::SIGKILL);
+                    }
+                    _ = child.start_kill();
+                    child.wait().await
+                }
+            };
+            let code = exit.ok().and_then(|status| status.code());
+            process.stdin.lock().await.take();
+
+            let status = if process.stop_requested.load(Ordering::SeqCst) || code == Some(0) {
+                InstanceStatus::Stopped
+            } else {
+                InstanceStatus::Crashed
+            };
+            process
+                .announce(&match code {
+                    Some(code) => format!("Server exited with code {code}"),
+                    None => "Server was killed by a signal".to_string(),
+                })
+                .await;
+            if let Err(e) = set_status(&db, id, status).await {
+                eprintln!("Failed to record status of instance {id}: {e}");
+            }
+            process.running.send_replace(false);
+        });
+    }
+
+    /// Check the server's cgroup for limit violations until it exits, announcing new ones, and
+    /// remove the cgroup afterwards
+    fn watch_limits(&self, id: i32, process: Arc<InstanceProcess>, cgroup: PathBuf) {
+        let sender = self.violations.clone();
+
+        tokio::spawn(async move {
+            let mut seen = LimitViolations::default();
+            let mut interval = tokio::time::interval(LIMIT_CHECK_INTERVAL);
+            loop {
+                let exited = tokio::select! {
+                    _ = process.wait_exit() => true,
+                    _ = interval.tick() => false,
+                };
+
+                let current = limits::read_violations(&cgroup);
+                for (kind, count) in limits::new_violations(&seen, &current) {
+                    let message = match kind {
+                        LimitViolationKind::MemoryMax => "Server reached its memory limit",
+                        LimitViolationKind::OomKill => {
+                            "A server process was killed for exceeding the memory limit"
+                        }
+                        LimitViolationKind::PidsMax => {
+                            "Server reached its process limit and could not fork"
+                        }
+                    };
+                    process.announce(&format!("{message} ({count}x)")).await;
+                    // Nobody listening is fine
+                    let _ = sender.send((
+                        id,
+                        LimitViolation {
+                            kind,
+                            count,
+                            detected_at: chrono::Utc::now(),
+                        },
+                    ));
+                }
+                // Keep the highest counts seen, the counters are gone with the cgroup
+                seen = LimitViolations {
+                    memory_max: seen.memory_max.max(current.memory_max),
+                    oom_kills: seen.oom_kills.max(current.oom_kills),
+                    pids_max: seen.pids_max.max(current.pids_max),
+                    cpu_throttled: seen.cpu_throttled.max(current.cpu_throttled),
+                };
+                process
+                    .limits
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .violations = seen;
+
+                if exited {
+                    // Fails while stray processes are left in it, which is harmless
+                    _ = std::fs::remove_dir(&cgroup);
+                    break;
+                }
+            }
+        });
+    }
+
+    /// Match the server's output against its triggers until it exits, announcing every fired
+    /// trigger to subscribers. Lines the panel wrote itself, including echoed console input,
+    /// are skipped so that nobody can fire a trigger by typing its text.
+    fn watch_triggers(
+        &self,
+        id: i32,
+        process: Arc<InstanceProcess>,
+        mut lines: broadcast::Receiver<ConsoleLine>,
+        triggers: Triggers,
+    ) {
+        let db = self.db.clone();
+        let sender = self.triggers.clone();
+
+        tokio::spawn(async move {
+            let exited = process.wait_exit();
+            tokio::pin!(exited);
+            let mut ready = false;
+
+            loop {
+                let line = tokio::select! {
+                    _ = &mut exited => break,
+                    line = lines.recv() => match line {
+                        Ok(line) => line,
+                        Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    },
+                };
+                if line.text.starts_with(LIFECYCLE_PREFIX) || line.text.starts_with("> ") {
+                    continue;
+                }
+
+                let plain = crate::state::vt::plain(line.text.trim_end_matches('\n'));
+                for fired in triggers.matches(&plain) {
+                    if fired.event == TriggerEvent::Ready && !ready {
+                        ready = true;
+                        if !process.stop_requested.load(Ordering::SeqCst) {
+                            if let Err(e) = set_status(&db, id, InstanceStatus::Running).await {
+                                eprintln!("Failed to record status of instance {id}: {e}");
+                            }
+                            process.announce("Server is ready").await;
+                        }
+                    }
+                    // Nobody listening is fine
+                    let _ = sender.send((id, fired));
+                }
+            }
+        });
+    }
+
+    /// Mark the instance's server as stopping, so that its exit is not taken for a crash. The
+    /// caller then runs the stop sequence and [`InstanceProcess::terminate`]s it.
+    pub async fn begin_stop(&self, id: i32) -> Result<Arc<InstanceProcess>, InstanceProcessError> {
+        let process = self
+            .process(id)
+            .await
+            .filter(|process| process.is_running())
+            .ok_or(InstanceProcessError::NotRunning(id))?;
+
+        set_status(&self.db, id, InstanceStatus::Stopping).await?;
+        if !process.stop_requested.swap(true, Ordering::SeqCst) {
+            process.announce("Stopping server").await;
+        }
+
+        Ok(process)
+    }
+}
@@ -0,0 +1,541 @@
+//! Resource limits of game servers: rlimits and the nice level are set between fork and exec,
+//! memory, CPU and process caps through a cgroup v2 per instance when the host delegates a
+//! writable hierarchy to the panel.
+
+use crate::dto::instance::{
+    AppliedLimit, AppliedLimits, LimitViolationKind, LimitViolations, ResourceLimits, SkippedLimit,
+};
+use crate::utils;
+use std::{
+    ffi::CString,
+    path::{Path, PathBuf},
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Controllers the panel hands to instance cgroups
+const CONTROLLERS: [&str; 3] = ["cpu", "memory", "pids"];
+
+/// Leaf the panel moves itself into, since a cgroup that hands controllers to its children
+/// cannot hold processes of its own
+const PANEL_LEAF: &str = "server_ui";
+
+/// Period of the CPU quota, in microseconds
+const CPU_PERIOD_US: u64 = 100_000;
+
+pub fn validate(limits: &ResourceLimits) -> Result<(), Vec<String>> {
+    let mut errors = Vec::new();
+
+    if limits.memory_max_mb.is_some_and(|mb| mb < 16) {
+        errors.push("The memory limit must be at least 16 MiB".to_string());
+    }
+    if limits.cpu_quota_percent == Some(0) {
+        errors.push("The CPU quota must be at least 1%".to_string());
+    }
+    if limits
+        .cpu_weight
+        .is_some_and(|weight| !(1..=10_000).contains(&weight))
+    {
+        errors.push("The CPU weight must be between 1 and 10000".to_string());
+    }
+    if limits.max_processes == Some(0) {
+        errors.push("The process limit must be at least 1".to_string());
+    }
+    if limits.nice.is_some_and(|nice| !(-20..=19).contains(&nice)) {
+        errors.push("The nice level must be between -20 and 19".to_string());
+    }
+    if limits.max_open_files.is_some_and(|files| files < 16) {
+        errors.push("The open file limit must be at least 16".to_string());
+    }
+    let stored = [
+        limits.memory_max_mb,
+        limits.cpu_quota_percent,
+        limits.cpu_weight,
+        limits.max_processes,
+        limits.max_open_files,
+    ];
+    if stored
+        .into_iter()
+        .flatten()
+        .any(|value| value > i32::MAX as u32)
+    {
+        errors.push(format!("Limits cannot exceed {}", i32::MAX));
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Where cgroup v2 is mounted, from the contents of `/proc/self/mounts`
+pub fn cgroup2_mount(mounts: &str) -> Option<PathBuf> {
+    mounts.lines().find_map(|line| {
+        let mut fields = line.split_whitespace();
+        let (_, mount_point, fs_type) = (fields.next()?, fields.next()?, fields.next()?);
+        (fs_type == "cgroup2").then(|| PathBuf::from(mount_point))
+    })
+}
+
+/// The cgroup v2 path of a process, from the contents of `/proc/<pid>/cgroup`
+pub fn own_cgroup(cgroup: &str) -> Option<&str> {
+    cgroup.lines().find_map(|line| line.strip_prefix("0::"))
+}
+
+/// Read `key value` lines such as those of `memory.events` or `cpu.stat`
+pub fn flat_keyed(content: &str, key: &str) -> Option<u64> {
+    content.lines().find_map(|line| {
+        let (name, value) = line.split_once(' ')?;
+        (name == key).then(|| value.trim().parse().ok()).flatten()
+    })
+}
+
+/// The value written to `cpu.max` for a quota in percent of one core
+pub fn cpu_max(quota_percent: Option<u32>) -> String {
+    match quota_percent {
+        Some(percent) => format!("{} {CPU_PERIOD_US}", percent as u64 * CPU_PERIOD_US / 100),
+        None => format!("max {CPU_PERIOD_US}"),
+    }
+}
+
+/// Kinds of violations whose count went up between two readings, with by how much
+pub fn new_violations(
+    before: &LimitViolations,
+    after: &LimitViolations,
+) -> Vec<(LimitViolationKind, u64)> {
+    [
+        (
+            LimitViolationKind::MemoryMax,
+            before.memory_max,
+            after.memory_max,
+        ),
+        (
+            LimitViolationKind::OomKill,
+            before.oom_kills,
+            after.oom_kills,
+        ),
+        (LimitViolationKind::PidsMax, before.pids_max, after.pids_max),
+    ]
+    .into_iter()
+    .filter(|(_, before, after)| after > before)
+    .map(|(kind, before, after)| (kind, after - before))
+    .collect()
+}
+
+/// Read the violation counters of an instance cgroup. Files of controllers that are not
+/// enabled count as zero.
+pub fn read_violations(cgroup: &Path) -> LimitViolations {
+    let read = |file: &str, key: &str| {
+        std::fs::read_to_string(cgroup.join(file))
+            .ok()
+            .and_then(|content| flat_keyed(&content, key))
+            .unwrap_or(0)
+    };
+
+    LimitViolations {
+        memory_max: read("memory.events", "max"),
+        oom_kills: read("memory.events", "oom_kill"),
+        pids_max: read("pids.events", "max"),
+        cpu_throttled: read("cpu.stat", "nr_throttled"),
+    }
+}
+
+/// A cgroup v2 hierarchy delegated to the panel, under which every instance gets a cgroup
+#[derive(Debug)]
+pub struct Cgroups {
+    base: PathBuf,
+    controllers: Vec<String>,
+}
+
+impl Cgroups {
+    /// Take over the hierarchy CGROUP_ROOT names for instance cgroups, `self` being the cgroup
+    /// the panel runs in. Processes already in it move into a leaf first. Unset, cgroups are
+    /// not touched, since the panel's cgroup may belong to systemd or a container manager.
+    pub fn detect() -> Result<Self, String> {
+        let root = match std::env::var("CGROUP_ROOT") {
+            Ok(root) if !root.trim().is_empty() && root != "off" => root,
+            _ => return Err("CGROUP_ROOT is not set".to_string()),
+        };
+
+        let base = match root.as_str() {
+            "self" => {
+                let mounts = std::fs::read_to_string("/proc/self/mounts").unwrap_or_default();
+                let mount = cgroup2_mount(&mounts).ok_or("cgroup v2 is not mounted")?;
+                let cgroup = std::fs::read_to_string("/proc/self/cgroup").unwrap_or_default();
+                let path = own_cgroup(&cgroup).ok_or("the panel is not in a cgroup v2")?;
+                mount.join(path.trim().trim_start_matches('/'))
+            }
+            root => PathBuf::from(root),
+        };
+        let describe =
+            |file: &str, e: std::io::Error| format!("{}: {e}", base.join(file).display());
+
+        let available = std::fs::read_to_string(base.join("cgroup.controllers"))
+            .map_err(|e| describe("cgroup.controllers", e))?;
+        let wanted: Vec<&str> = CONTROLLERS
+            .into_iter()
+            .filter(|c| available.split_whitespace().any(|a| a == *c))
+            .collect();
+        if wanted.is_empty() {
+            return Err(format!(
+                "{} offers none of the cpu, memory and pids controllers",
+                base.display()
+            ));
+        }
+
+        // Every process still in the base cgroup, such as SteamCMD, moves along with the panel
+        let procs = std::fs::read_to_string(base.join("cgroup.procs"))
+            .map_err(|e| describe("cgroup.procs", e))?;
+        if procs.lines().any(|line| !line.trim().is_empty()) {
+            let leaf = base.join(PANEL_LEAF);
+            std::fs::create_dir_all(&leaf).map_err(|e| describe(PANEL_LEAF, e))?;
+            for pid in procs.lines().map(str::trim).filter(|pid| !pid.is_empty()) {
+                // Processes may exit while being moved
+                if let Err(e) = std::fs::write(leaf.join("cgroup.procs"), pid) {
+                    if e.raw_os_error() != Some(libc::ESRCH) {
+                        return Err(describe(&format!("{PANEL_LEAF}/cgroup.procs"), e));
+                    }
+                }
+            }
+        }
+        for controller in &wanted {
+            std::fs::write(
+                base.join("cgroup.subtree_control"),
+                format!("+{controller}"),
+            )
+            .map_err(|e| describe("cgroup.subtree_control", e))?;
+        }
+
+        Ok(Self {
+            controllers: wanted.into_iter().map(str::to_string).collect(),
+            base,
+        })
+    }
+
+    pub fn base(&self) -> &Path {
+        &self.base
+    }
+
+    fn has(&self, controller: &str) -> bool {
+        self.controllers.iter().any(|c| c == controller)
+    }
+
+    pub fn instance_path(&self, id: i32) -> PathBuf {
+        self.base.join(format!("instance-{id}"))
+    }
+
+    /// Create the instance's cgroup and write its limits, resetting those left over from an
+    /// earlier run. Limits of missing controllers are skipped.
+    fn prepare(
+        &self,
+        id: i32,
+        limits: &ResourceLimits,
+        applied: &mut AppliedLimits,
+    ) -> std::io::Result<PathBuf> {
+        let path = self.instance_path(id);
+        std::fs::create_dir_all(&path)?;
+
+        let settings = [
+            (
+                "memoryMaxMb",
+                "memory",
+                "memory.max",
+                limits.memory_max_mb.is_some(),
+                limits
+                    .memory_max_mb
+                    .map(|mb| (mb as u64 * 1024 * 1024).to_string())
+                    .unwrap_or_else(|| "max".to_string()),
+            ),
+            (
+                "cpuQuotaPercent",
+                "cpu",
+                "cpu.max",
+                limits.cpu_quota_percent.is_some(),
+                cpu_max(limits.cpu_quota_percent),
+            ),
+            (
+                "cpuWeight",
+                "cpu",
+                "cpu.weight",
+                limits.cpu_weight.is_some(),
+                limits.cpu_weight.unwrap_or(100).to_string(),
+            ),
+            (
+                "maxProcesses",
+                "pids",
+                "pids.max",
+                limits.max_processes.is_some(),
+                limits
+                    .max_processes
+                    .map(|max| max.to_string())
+                    .unwrap_or_else(|| "max".to_string()),
+            ),
+        ];
+        for (limit, controller, file, set, value) in settings {
+            if !self.has(controller) {
+                // Without the pids controller the process limit falls back to an rlimit
+                if set && controller != "pids" {
+                    applied.skipped.push(SkippedLimit {
+                        limit: limit.to_string(),
+                        reason: format!("the {controller} cgroup controller is not delegated"),
+                    });
+                }
+                continue;
+            }
+            std::fs::write(path.join(file), value)?;
+            if set {
+                applied.applied.push(AppliedLimit {
+                    limit: limit.to_string(),
+                    via: format!("cgroup {file}"),
+                });
+            }
+        }
+
+        Ok(path)
+    }
+}
+
+/// Whether an rlimit can be set to `requested`, given the current hard limit. Only privileged
+/// processes may raise a hard limit.
+pub fn rlimit_allowed(requested: u64, hard: u64, privileged: bool) -> Result<(), String> {
+    if requested > hard && !privileged {
+        return Err(format!("above the panel's own hard limit of {hard}"));
+    }
+    Ok(())
+}
+
+/// What the child sets up for itself between fork and exec, prepared beforehand since little
+/// more than plain system calls is allowed there
+#[derive(Debug, Default)]
+pub struct LimitPlan {
+    cgroup_procs: Option<CString>,
+    nice: Option<i32>,
+    open_files: Option<u64>,
+    processes: Option<u64>,
+}
+
+#[cfg(unix)]
+fn hard_rlimit(resource: Rlimit) -> u64 {
+    let mut limit = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    // SAFETY: getrlimit writes into the struct it is given
+    let result = unsafe {
+        match resource {
+            Rlimit::OpenFiles => libc::getrlimit(libc::RLIMIT_NOFILE, &mut limit),
+            Rlimit::Processes => libc::getrlimit(libc::RLIMIT_NPROC, &mut limit),
+        }
+    };
+    if result == 0 {
+        limit.rlim_max
+    } else {
+        u64::MAX
+    }
+}
+
+#[cfg(unix)]
+#[derive(Clone, Copy)]
+enum Rlimit {
+    OpenFiles,
+    Processes,
+}
+
+/// Work out how each limit is enforced for a new run of instance `id`
+pub fn prepare(
+    id: i32,
+    limits: &ResourceLimits,
+    cgroups: Result<&Cgroups, &str>,
+) -> (LimitPlan, AppliedLimits) {
+    let mut plan = LimitPlan::default();
+    let mut applied = AppliedLimits::default();
+    let skip = |applied: &mut AppliedLimits, limit: &str, reason: String| {
+        applied.skipped.push(SkippedLimit {
+            limit: limit.to_string(),
+            reason,
+        })
+    };
+
+    match cgroups {
+        Ok(cgroups) => match cgroups.prepare(id, limits, &mut applied) {
+            Ok(path) => {
+                plan.cgroup_procs = CString::new(
+                    path.join("cgroup.procs")
+                        .to_string_lossy()
+                        .as_bytes()
+                        .to_vec(),
+                )
+                .ok();
+                applied.cgroup = Some(path.to_string_lossy().to_string());
+            }
+            Err(e) => {
+                applied = AppliedLimits::default();
+                let reason = format!("the instance cgroup could not be set up: {e}");
+                for limit in cgroup_limits(limits) {
+                    skip(&mut applied, limit, reason.clone());
+                }
+            }
+        },
+        Err(reason) => {
+            for limit in cgroup_limits(limits) {
+                skip(
+                    &mut applied,
+                    limit,
+                    format!("cgroups are unavailable: {reason}"),
+                );
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    {
+        if let Some(nice) = limits.nice {
+            // SAFETY: getpriority only reads the calling process's priority
+            let current = unsafe { libc::getpriority(libc::PRIO_PROCESS, 0) };
+            if nice < current && !utils::privileged() {
+                skip(
+                    &mut applied,
+                    "nice",
+                    format!("raising the priority above the panel's ({current}) needs root"),
+                );
+            } else {
+                plan.nice = Some(nice);
+                applied.applied.push(AppliedLimit {
+                    limit: "nice".to_string(),
+                    via: "setpriority".to_string(),
+                });
+            }
+        }
+
+        if let Some(files) = limits.max_open_files {
+            let hard = hard_rlimit(Rlimit::OpenFiles);
+            match rlimit_allowed(files as u64, hard, utils::privileged()) {
+                Ok(()) => {
+                    plan.open_files = Some(files as u64);
+                    applied.applied.push(AppliedLimit {
+                        limit: "maxOpenFiles".to_string(),
+                        via: "RLIMIT_NOFILE".to_string(),
+                    });
+                }
+                Err(reason) => skip(&mut applied, "maxOpenFiles", reason),
+            }
+        }
+
+        let counted_by_cgroup = applied.applied.iter().any(|a| a.limit == "maxProcesses");
+        if let (Some(processes), false) = (limits.max_processes, counted_by_cgroup) {
+            let hard = hard_rlimit(Rlimit::Processes);
+            if utils::privileged() {
+                skip(
+                    &mut applied,
+                    "maxProcesses",
+                    "the pids cgroup controller is unavailable and RLIMIT_NPROC does not apply \
+                     to root"
+                        .to_string(),
+                );
+            } else {
+                match rlimit_allowed(processes as u64, hard, false) {
+                    Ok(()) => {
+                        plan.processes = Some(processes as u64);
+                        applied.applied.push(AppliedLimit {
+                            limit: "maxProcesses".to_string(),
+                            // Counts every process of the user, not just the server's
+                            via: "RLIMIT_NPROC".to_string(),
+                        });
+                    }
+                    Err(reason) => skip(&mut applied, "maxProcesses", reason),
+                }
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    for limit in ["nice", "maxOpenFiles", "maxProcesses"] {
+        skip(
+            &mut applied,
+            limit,
+            "not supported on this platform".to_string(),
+        );
+    }
+
+    (plan, applied)
+}
+
+/// The configured limits only a cgroup can enforce. The process limit falls back to an rlimit.
+fn cgroup_limits(limits: &ResourceLimits) -> Vec<&'static str> {
+    [
+        ("memoryMaxMb", limits.memory_max_mb.is_some()),
+        ("cpuQuotaPercent", limits.cpu_quota_percent.is_some()),
+        ("cpuWeight", limits.cpu_weight.is_some()),
+    ]
+    .into_iter()
+    .filter(|(_, set)| *set)
+    .map(|(limit, _)| limit)
+    .collect()
+}
+
+impl LimitPlan {
+    /// Have the child join its cgroup and set its limits before it runs the server
+    #[cfg(unix)]
+    pub fn apply(self, command: &mut tokio::process::Command) {
+        if self.cgroup_procs.is_none()
+            && self.nice.is_none()
+            && self.open_files.is_none()
+            && self.processes.is_none()
+        {
+            return;
+        }
+
+        let set_rlimit = |resource: Rlimit, value: u64| {
+            let limit = libc::rlimit {
+                rlim_cur: value as libc::rlim_t,
+                rlim_max: value as libc::rlim_t,
+            };
+            // SAFETY: setrlimit only reads the struct it is given
+            let result = unsafe {
+                match resource {
+                    Rlimit::OpenFiles => libc::setrlimit(libc::RLIMIT_NOFILE, &limit),
+                    Rlimit::Processes => libc::setrlimit(libc::RLIMIT_NPROC, &limit),
+                }
+            };
+            match result {
+                0 => Ok(()),
+                _ => Err(std::io::Error::last_os_error()),
+            }
+        };
+
+        // SAFETY: the closure only makes async-signal-safe system calls on data prepared
+        // before the fork, and allocates nothing
+        unsafe {
+            command.pre_exec(move || {
+                if let Some(procs) = &self.cgroup_procs {
+                    // Writing 0 moves the writing process itself
+                    let fd = libc::open(procs.as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC);
+                    if fd < 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                    let written = libc::write(fd, b"0".as_ptr().cast(), 1);
+                    libc::close(fd);
+                    if written != 1 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                }
+                if let Some(nice) = self.nice {
+                    if libc::setpriority(libc::PRIO_PROCESS, 0, nice) != 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                }
+                if let Some(files) = self.open_files {
+                    set_rlimit(Rlimit::OpenFiles, files)?;
+                }
+                if let Some(processes) = self.processes {
+                    set_rlimit(Rlimit::Processes, processes)?;
+                }
+                Ok(())
+            });
+        }
+    }
+
+    #[cfg(not(unix))]
+    pub fn apply(self, _command: &mut tokio::process::Command) {}
+}
@@ -0,0 +1,97 @@
+use super::*;
+
+#[test]
+fn test_validate_reports_every_bad_limit() {
+    assert_eq!(validate(&ResourceLimits::default()), Ok(()));
+
+    let limits = ResourceLimits {
+        memory_max_mb: Some(8),
+        cpu_quota_percent: Some(0),
+        cpu_weight: Some(20_000),
+        max_processes: Some(64),
+        nice: Some(25),
+        max_open_files: Some(u32::MAX),
+    };
+    let errors = validate(&limits).unwrap_err();
+    assert_eq!(errors.len(), 5);
+    assert!(errors[0].contains("16 MiB"));
+    assert!(errors[4].contains("cannot exceed"));
+}
+
+#[test]
+fn test_cgroup_paths_from_proc() {
+    let mounts = "proc /proc proc rw,nosuid 0 0\n\
+                  cgroup /sys/fs/cgroup/cpu cgroup rw,cpu 0 0\n\
+                  cgroup2 /sys/fs/cgroup/unified cgroup2 rw,nsdelegate 0 0\n";
+    assert_eq!(
+        cgroup2_mount(mounts),
+        Some(PathBuf::from("/sys/fs/cgroup/unified"))
+    );
+    assert_eq!(cgroup2_mount("proc /proc proc rw 0 0\n"), None);
+
+    let cgroup = "12:cpu,cpuacct:/user.slice\n0::/system.slice/server_ui.service\n";
+    assert_eq!(own_cgroup(cgroup), Some("/system.slice/server_ui.service"));
+    assert_eq!(own_cgroup("12:cpu,cpuacct:/\n"), None);
+}
+
+#[test]
+fn test_cgroup_file_values() {
+    let events = "low 0\nhigh 0\nmax 12\noom 1\noom_kill 1\n";
+    assert_eq!(flat_keyed(events, "max"), Some(12));
+    assert_eq!(flat_keyed(events, "oom_kill"), Some(1));
+    assert_eq!(flat_keyed(events, "oom_group_kill"), None);
+
+    assert_eq!(cpu_max(Some(150)), "150000 100000");
+    assert_eq!(cpu_max(None), "max 100000");
+}
+
+#[test]
+fn test_new_violations_only_reports_increases() {
+    let before = LimitViolations {
+        memory_max: 3,
+        oom_kills: 0,
+        pids_max: 2,
+        cpu_throttled: 10,
+    };
+    let after = LimitViolations {
+        memory_max: 5,
+        oom_kills: 1,
+        pids_max: 2,
+        cpu_throttled: 40,
+    };
+
+    assert_eq!(
+        new_violations(&before, &after),
+        vec![
+            (LimitViolationKind::MemoryMax, 2),
+            (LimitViolationKind::OomKill, 1)
+        ]
+    );
+    assert!(new_violations(&after, &before).is_empty());
+}
+
+#[test]
+fn test_rlimits_cannot_exceed_the_hard_limit_unprivileged() {
+    assert_eq!(rlimit_allowed(1024, 4096, false), Ok(()));
+    assert!(rlimit_allowed(8192, 4096, false).is_err());
+    assert_eq!(rlimit_allowed(8192, 4096, true), Ok(()));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_prepare_without_cgroups_skips_memory_and_cpu() {
+    let limits = ResourceLimits {
+        memory_max_mb: Some(2048),
+        cpu_quota_percent: Some(200),
+        max_open_files: Some(64),
+        ..Default::default()
+    };
+
+    let (_, applied) = prepare(1, &limits, Err("not mounted"));
+    assert_eq!(applied.cgroup, None);
+    let skipped: Vec<_> = applied.skipped.iter().map(|s| s.limit.as_str()).collect();
+    assert_eq!(skipped, vec!["memoryMaxMb", "cpuQuotaPercent"]);
+    assert!(applied.skipped[0].reason.contains("not mounted"));
+    assert_eq!(applied.applied.len(), 1);
+    assert_eq!(applied.applied[0].via, "RLIMIT_NOFILE");
+}
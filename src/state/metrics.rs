@@ -0,0 +1,429 @@
+use crate::dto::metrics::{MetricsHistory, MetricsHistoryQuery, MetricsPoint, MetricsSample};
+use crate::entity;
+use crate::service::files::InstanceLayout;
+use crate::state::instance::InstanceManager;
+use crate::utils::error_response;
+use rocket::{http::Status, response::Responder};
+use sea_orm::{prelude::*, ActiveValue::Set, QueryOrder};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use thiserror::Error;
+use tokio::sync::broadcast;
+
+pub mod procfs;
+
+#[cfg(test)]
+mod tests;
+
+use procfs::TreeUsage;
+
+/// Seconds between samples of every running server unless METRICS_SAMPLE_INTERVAL says otherwise
+const DEFAULT_SAMPLE_INTERVAL_SECONDS: u64 = 10;
+
+/// Days of hourly history kept unless METRICS_RETENTION_DAYS says otherwise
+const DEFAULT_RETENTION_DAYS: u64 = 30;
+
+/// Samples are stored averaged over a minute, and rolled up into hours after a day
+const MINUTE: u32 = 60;
+const HOUR: u32 = 3600;
+const MINUTES_KEPT: chrono::TimeDelta = chrono::TimeDelta::hours(24);
+
+/// How often minutes are rolled up into hours and old history is dropped
+const ROLLUP_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Walking a big install is slow, so the directory is measured every this many samples
+const DISK_SAMPLE_EVERY: u64 = 6;
+
+/// The most points a history answer holds when the resolution is picked from the range
+const MAX_HISTORY_POINTS: i64 = 720;
+
+/// Resolutions the history picks from, in seconds
+const HISTORY_RESOLUTIONS: [u32; 6] = [MINUTE, 300, 900, HOUR, 6 * HOUR, 24 * HOUR];
+
+#[derive(Error, Debug)]
+pub enum MetricsError {
+    #[error("A database error occurred. Please review the logs for more details.")]
+    DbError(#[from] sea_orm::DbErr),
+
+    #[error("Invalid '{0}' timestamp. Expected RFC 3339, e.g. 2026-01-31T12:00:00Z")]
+    InvalidTimestamp(&'static str),
+
+    #[error("'from' must be before 'to'")]
+    InvalidRange,
+
+    #[error("The resolution must be a positive multiple of 60 seconds")]
+    InvalidResolution,
+}
+
+impl<'r> Responder<'r, 'static> for MetricsError {
+    fn respond_to(self, _: &'r rocket::Request<'_>) -> rocket::response::Result<'static> {
+        let status = match self {
+            MetricsError::DbError(_) => Status::InternalServerError,
+            MetricsError::InvalidTimestamp(_)
+            | MetricsError::InvalidRange
+            | MetricsError::InvalidResolution => Status::UnprocessableEntity,
+        };
+        error_response(self, status)
+    }
+}
+
+/// Start of the `resolution`-long window `time` falls into
+pub fn window_start(
+    time: chrono::DateTime<chrono::Utc>,
+    resolution: u32,
+) -> chrono::DateTime<chrono::Utc> {
+    let seconds = time.timestamp();
+    let start = seconds - seconds.rem_euclid(resolution as i64);
+    chrono::DateTime::from_timestamp(start, 0).unwrap_or(time)
+}
+
+/// Turn two readings of the same process tree into a sample
+pub fn sample(
+    previous: &TreeUsage,
+    current: &TreeUsage,
+    elapsed: Duration,
+    clock_ticks: u64,
+    disk_bytes: Option<u64>,
+) -> MetricsSample {
+    let seconds = elapsed.as_secs_f64().max(f64::EPSILON);
+    // Counters of processes that exited in between are gone, so they can shrink
+    let rate = |before: u64, after: u64| after.saturating_sub(before) as f64 / seconds;
+
+    MetricsSample {
+        sampled_at: chrono::Utc::now(),
+        cpu_percent: rate(previous.cpu_ticks, current.cpu_ticks) / clock_ticks as f64 * 100.0,
+        memory_bytes: current.rss_bytes,
+        threads: current.threads,
+        processes: current.processes,
+        read_bytes: current.read_bytes,
+        write_bytes: current.write_bytes,
+        read_bytes_per_sec: rate(previous.read_bytes, current.read_bytes),
+        write_bytes_per_sec: rate(previous.write_bytes, current.write_bytes),
+        disk_bytes,
+    }
+}
+
+/// A sample as a point of its own, to be merged with the others of its window
+pub fn point(sample: &MetricsSample, resolution: u32) -> MetricsPoint {
+    MetricsPoint {
+        recorded_at: window_start(sample.sampled_at, resolution),
+        resolution_secs: resolution,
+        samples: 1,
+        cpu_percent: sample.cpu_percent,
+        cpu_percent_max: sample.cpu_percent,
+        memory_bytes: sample.memory_bytes,
+        threads: sample.threads,
+        read_bytes_per_sec: sample.read_bytes_per_sec,
+        write_bytes_per_sec: sample.write_bytes_per_sec,
+        disk_bytes: sample.disk_bytes,
+    }
+}
+
+/// Fold `other`, which comes later, into `point`. Averages are weighted by sample count;
+/// memory and threads keep their peak.
+pub fn merge(point: &mut MetricsPoint, other: &MetricsPoint) {
+    let (a, b) = (point.samples as f64, other.samples as f64);
+    let average = |x: f64, y: f64| (x * a + y * b) / (a + b).max(1.0);
+
+    point.cpu_percent = average(point.cpu_percent, other.cpu_percent);
+    point.cpu_percent_max = point.cpu_percent_max.max(other.cpu_percent_max);
+    point.memory_bytes = point.memory_bytes.max(other.memory_bytes);
+    point.threads = point.threads.max(other.threads);
+    point.read_bytes_per_sec = average(point.read_bytes_per_sec, other.read_bytes_per_sec);
+    point.write_bytes_per_sec = average(point.write_bytes_per_sec, other.write_bytes_per_sec);
+    point.disk_bytes = other.disk_bytes.or(point.disk_bytes);
+    point.samples += other.samples;
+}
+
+/// Merge time-ordered points into `resolution`-long windows. Points that already span more
+/// keep their own resolution.
+pub fn downsample(points: Vec<MetricsPoint>, resolution: u32) -> Vec<MetricsPoint> {
+    let mut merged: Vec<MetricsPoint> = Vec::new();
+    for mut point in points {
+        let resolution = resolution.max(point.resolution_secs);
+        point.recorded_at = window_start(point.recorded_at, resolution);
+        point.resolution_secs = resolution;
+        match merged.last_mut() {
+            Some(last) if last.recorded_at == point.recorded_at => merge(last, &point),
+            _ => merged.push(point),
+        }
+    }
+    merged
+}
+
+/// The coarsest resolution needed to show `range` in at most [`MAX_HISTORY_POINTS`] points
+pub fn history_resolution(range: chrono::TimeDelta) -> u32 {
+    HISTORY_RESOLUTIONS
+        .into_iter()
+        .find(|resolution| range.num_seconds() / *resolution as i64 <= MAX_HISTORY_POINTS)
+        .unwrap_or(24 * HOUR)
+}
+
+/// What the sampler remembers about one running instance between samples
+struct Tracker {
+    pid: u32,
+    read_at: Instant,
+    usage: TreeUsage,
+    readings: u64,
+    disk_bytes: Option<u64>,
+    window: Option<MetricsPoint>,
+}
+
+/// Samples the resource usage of running game servers, keeps the latest sample of each and
+/// stores their history. Clones share the same samples.
+#[derive(Clone)]
+pub struct InstanceMetrics {
+    db: DatabaseConnection,
+    current: Arc<Mutex<HashMap<i32, MetricsSample>>>,
+    updates: broadcast::Sender<(i32, MetricsSample)>,
+}
+
+impl InstanceMetrics {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self {
+            db,
+            current: Default::default(),
+            updates: broadcast::channel(64).0,
+        }
+    }
+
+    pub fn get(&self, id: i32) -> Option<MetricsSample> {
+        let current = self.current.lock().unwrap_or_else(|e| e.into_inner());
+        current.get(&id).cloned()
+    }
+
+    /// Every sample taken from now on, tagged with its instance id
+    pub fn subscribe(&self) -> broadcast::Receiver<(i32, MetricsSample)> {
+        self.updates.subscribe()
+    }
+
+    /// Drop the latest sample of a server that stopped or was deleted
+    pub fn forget(&self, id: i32) {
+        let mut current = self.current.lock().unwrap_or_else(|e| e.into_inner());
+        current.remove(&id);
+    }
+
+    fn store(&self, id: i32, sample: MetricsSample) {
+        let mut current = self.current.lock().unwrap_or_else(|e| e.into_inner());
+        current.insert(id, sample.clone());
+        // Nobody listening is fine
+        let _ = self.updates.send((id, sample));
+    }
+
+    /// Stored history of an instance, downsampled to the requested resolution
+    pub async fn history(
+        &self,
+        id: i32,
+        query: &MetricsHistoryQuery,
+    ) -> Result<MetricsHistory, MetricsError> {
+        let parse = |value: &Option<String>, name: &'static str| {
+            value
+                .as_deref()
+                .map(|v| {
+                    chrono::DateTime::parse_from_rfc3339(v)
+                        .map(|t| t.with_timezone(&chrono::Utc))
+                        .map_err(|_| MetricsError::InvalidTimestamp(name))
+                })
+                .transpose()
+        };
+        let to = parse(&query.to, "to")?.unwrap_or_else(chrono::Utc::now);
+        let from = parse(&query.from, "from")?.unwrap_or(to - chrono::TimeDelta::hours(1));
+        if from >= to {
+            return Err(MetricsError::InvalidRange);
+        }
+        let resolution = match query.resolution {
+            Some(resolution) if resolution == 0 || resolution % MINUTE != 0 => {
+                return Err(MetricsError::InvalidResolution)
+            }
+            Some(resolution) => resolution,
+            None => history_resolution(to - from),
+        };
+
+        let rows = entity::instance_metric::Entity::find()
+            .filter(entity::instance_metric::Column::InstanceId.eq(id))
+            .filter(entity::instance_metric::Column::RecordedAt.gte(from))
+            .filter(entity::instance_metric::Column::RecordedAt.lt(to))
+            .order_by_asc(entity::instance_metric::Column::RecordedAt)
+            .all(&self.db)
+            .await?;
+
+        Ok(MetricsHistory {
+            resolution_secs: resolution,
+            points: downsample(rows.into_iter().map(Into::into).collect(), resolution),
+        })
+    }
+
+    async fn save(&self, id: i32, point: MetricsPoint) {
+        let model = entity::instance_metric::ActiveModel {
+            instance_id: Set(id),
+            resolution_secs: Set(point.resolution_secs as i32),
+            recorded_at: Set(point.recorded_at),
+            samples: Set(point.samples as i32),
+            cpu_percent: Set(point.cpu_percent),
+            cpu_percent_max: Set(point.cpu_percent_max),
+            memory_bytes: Set(point.memory_bytes as i64),
+            threads: Set(point.threads as i32),
+            read_bytes_per_sec: Set(point.read_bytes_per_sec),
+            write_bytes_per_sec: Set(point.write_bytes_per_sec),
+            disk_bytes: Set(point.disk_bytes.map(|bytes| bytes as i64)),
+            ..Default::default()
+        };
+        // The instance may have been deleted since the window opened
+        if let Err(e) = model.insert(&self.db).await {
+            eprintln!("Failed to store metrics of instance {id}: {e}");
+        }
+    }
+
+    /// Read the process tree of a running instance. The first reading of a run only sets the
+    /// baseline for the rates; every later one is stored and added to its minute.
+    async fn sample(&self, id: i32, pid: u32, trackers: &mut HashMap<i32, Tracker>, ticks: u64) {
+        let Ok(Some(usage)) = tokio::task::spawn_blocking(move || procfs::tree_usage(pid)).await
+        else {
+            return;
+        };
+        let read_at = Instant::now();
+
+        let tracker = match trackers.get_mut(&id) {
+            Some(tracker) if tracker.pid == pid => tracker,
+            _ => {
+                // A new run of the server
+                if let Some(window) = trackers.remove(&id).and_then(|t| t.window) {
+                    self.save(id, window).await;
+                }
+                trackers.insert(
+                    id,
+                    Tracker {
+                        pid,
+                        read_at,
+                        usage,
+                        readings: 1,
+                        disk_bytes: None,
+                        window: None,
+                    },
+                );
+                return;
+            }
+        };
+
+        if tracker.readings % DISK_SAMPLE_EVERY == 1 {
+            let dir = InstanceLayout::new(id).root().to_path_buf();
+            tracker.disk_bytes = tokio::task::spawn_blocking(move || procfs::directory_size(&dir))
+                .await
+                .ok();
+        }
+        let sample = sample(
+            &tracker.usage,
+            &usage,
+            read_at - tracker.read_at,
+            ticks,
+            tracker.disk_bytes,
+        );
+        tracker.usage = usage;
+        tracker.read_at = read_at;
+        tracker.readings += 1;
+
+        let point = point(&sample, MINUTE);
+        match &mut tracker.window {
+            Some(window) if window.recorded_at == point.recorded_at => merge(window, &point),
+            window => {
+                if let Some(done) = window.replace(point) {
+                    self.save(id, done).await;
+                }
+            }
+        }
+        self.store(id, sample);
+    }
+
+    /// Roll minutes older than a day up into hours, and drop hours older than `retention`
+    async fn roll_up(&self, retention: chrono::TimeDelta) -> Result<(), sea_orm::DbErr> {
+        let cutoff = window_start(chrono::Utc::now() - MINUTES_KEPT, HOUR);
+        let minutes = entity::instance_metric::Entity::find()
+            .filter(entity::instance_metric::Column::ResolutionSecs.eq(MINUTE as i32))
+            .filter(entity::instance_metric::Column::RecordedAt.lt(cutoff))
+            .order_by_asc(entity::instance_metric::Column::InstanceId)
+            .order_by_asc(entity::instance_metric::Column::RecordedAt)
+            .all(&self.db)
+            .await?;
+
+        let mut by_instance: HashMap<i32, Vec<MetricsPoint>> = HashMap::new();
+        for row in minutes {
+            by_instance
+                .entry(row.instance_id)
+                .or_default()
+                .push(row.into());
+        }
+        for (id, points) in by_instance {
+            for hour in downsample(points, HOUR) {
+                self.save(id, hour).await;
+            }
+        }
+
+        entity::instance_metric::Entity::delete_many()
+            .filter(entity::instance_metric::Column::ResolutionSecs.eq(MINUTE as i32))
+            .filter(entity::instance_metric::Column::RecordedAt.lt(cutoff))
+            .exec(&self.db)
+            .await?;
+        entity::instance_metric::Entity::delete_many()
+            .filter(entity::instance_metric::Column::RecordedAt.lt(chrono::Utc::now() - retention))
+            .exec(&self.db)
+            .await?;
+        Ok(())
+    }
+
+    /// Sample every running server in the background, every METRICS_SAMPLE_INTERVAL seconds.
+    /// 0 disables sampling. Hourly history is kept for METRICS_RETENTION_DAYS.
+    pub fn spawn_sampler(self, manager: InstanceManager) {
+        let env = |name: &str, default: u64| {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        };
+        let seconds = env("METRICS_SAMPLE_INTERVAL", DEFAULT_SAMPLE_INTERVAL_SECONDS);
+        let retention =
+            chrono::TimeDelta::days(env("METRICS_RETENTION_DAYS", DEFAULT_RETENTION_DAYS) as i64);
+        if seconds == 0 {
+            return;
+        }
+
+        tokio::spawn(async move {
+            let ticks = procfs::clock_ticks();
+            let mut trackers: HashMap<i32, Tracker> = HashMap::new();
+            let mut rolled_up: Option<Instant> = None;
+            let mut interval = tokio::time::interval(Duration::from_secs(seconds));
+            loop {
+                interval.tick().await;
+
+                let running = manager.running().await;
+                let stopped: Vec<i32> = trackers
+                    .keys()
+                    .filter(|id| !running.contains(id))
+                    .copied()
+                    .collect();
+                for id in stopped {
+                    // Keep the last minute of a run even though it is cut short
+                    if let Some(window) = trackers.remove(&id).and_then(|t| t.window) {
+                        self.save(id, window).await;
+                    }
+                    self.forget(id);
+                }
+
+                for id in running {
+                    if let Some(pid) = manager.process(id).await.and_then(|p| p.pid()) {
+                        self.sample(id, pid, &mut trackers, ticks).await;
+                    }
+                }
+
+                if rolled_up.is_none_or(|at| at.elapsed() >= ROLLUP_INTERVAL) {
+                    if let Err(e) = self.roll_up(retention).await {
+                        eprintln!("Failed to roll up metrics: {e}");
+                    }
+                    rolled_up = Some(Instant::now());
+                }
+            }
+        });
+    }
+}
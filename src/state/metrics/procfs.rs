@@ -0,0 +1,141 @@
+//! Resource usage of a process tree, read from `/proc`
+
+use std::path::Path;
+
+/// The fields of `/proc/<pid>/stat` the sampler needs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProcStat {
+    pub pid: u32,
+    pub ppid: u32,
+    /// User and system time, including that of reaped children, in clock ticks
+    pub cpu_ticks: u64,
+    pub threads: u64,
+    pub rss_pages: u64,
+}
+
+/// Parse `/proc/<pid>/stat`. The command name in parentheses may itself contain spaces and
+/// parentheses, so the fields are counted from the last `)`.
+pub fn parse_stat(content: &str) -> Option<ProcStat> {
+    let (head, rest) = content.rsplit_once(')')?;
+    let pid = head.split_once('(')?.0.trim().parse().ok()?;
+    let fields: Vec<&str> = rest.split_whitespace().collect();
+    // Numbered as in proc(5), where the state after the command name is field 3
+    let field = |number: usize| fields.get(number - 3)?.parse::<u64>().ok();
+
+    Some(ProcStat {
+        pid,
+        ppid: field(4)? as u32,
+        cpu_ticks: field(14)? + field(15)? + field(16)? + field(17)?,
+        threads: field(20)?,
+        rss_pages: field(24)?,
+    })
+}
+
+/// Bytes read from and written to storage according to `/proc/<pid>/io`
+pub fn parse_io(content: &str) -> Option<(u64, u64)> {
+    let value = |key: &str| {
+        content
+            .lines()
+            .find_map(|line| line.strip_prefix(key)?.trim().parse().ok())
+    };
+    Some((value("read_bytes:")?, value("write_bytes:")?))
+}
+
+/// `root` followed by all of its descendants
+pub fn process_tree(root: u32, stats: &[ProcStat]) -> Vec<ProcStat> {
+    let mut tree: Vec<ProcStat> = stats.iter().filter(|s| s.pid == root).copied().collect();
+    let mut next = 0;
+    while next < tree.len() {
+        let parent = tree[next].pid;
+        tree.extend(stats.iter().filter(|s| s.ppid == parent && s.pid != root));
+        next += 1;
+    }
+    tree
+}
+
+/// Usage summed over a process tree
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TreeUsage {
+    pub processes: u64,
+    pub cpu_ticks: u64,
+    pub threads: u64,
+    pub rss_bytes: u64,
+    pub read_bytes: u64,
+    pub write_bytes: u64,
+}
+
+/// Read the usage of `root` and its descendants, or `None` once `root` is gone
+pub fn tree_usage(root: u32) -> Option<TreeUsage> {
+    let stats: Vec<ProcStat> = std::fs::read_dir("/proc")
+        .ok()?
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().parse::<u32>().is_ok())
+        .filter_map(|entry| std::fs::read_to_string(entry.path().join("stat")).ok())
+        .filter_map(|content| parse_stat(&content))
+        .collect();
+
+    let tree = process_tree(root, &stats);
+    if tree.is_empty() {
+        return None;
+    }
+
+    let page_size = page_size();
+    let mut usage = TreeUsage::default();
+    for process in tree {
+        usage.processes += 1;
+        usage.cpu_ticks += process.cpu_ticks;
+        usage.threads += process.threads;
+        usage.rss_bytes += process.rss_pages * page_size;
+        // Only readable for processes of the same user
+        let io = std::fs::read_to_string(format!("/proc/{}/io", process.pid)).ok();
+        if let Some((read, written)) = io.as_deref().and_then(parse_io) {
+            usage.read_bytes += read;
+            usage.write_bytes += written;
+        }
+    }
+    Some(usage)
+}
+
+/// Total size of the files below `dir`, without following symlinks
+pub fn directory_size(dir: &Path) -> u64 {
+    let mut size = 0;
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            match entry.metadata() {
+                Ok(meta) if meta.is_dir() => pending.push(entry.path()),
+                Ok(meta) if meta.is_file() => size += meta.len(),
+                _ => {}
+            }
+        }
+    }
+    size
+}
+
+/// Clock ticks per second, the unit of the CPU times in `/proc`
+pub fn clock_ticks() -> u64 {
+    #[cfg(unix)]
+    {
+        // SAFETY: sysconf only reads a configuration value
+        let ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+        if ticks > 0 {
+            return ticks as u64;
+        }
+    }
+    100
+}
+
+fn page_size() -> u64 {
+    #[cfg(unix)]
+    {
+        // SAFETY: sysconf only reads a configuration value
+        let size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+        if size > 0 {
+            return size as u64;
+        }
+    }
+    4096
+}
@@ -0,0 +1,133 @@
+use super::procfs::*;
+use super::*;
+
+#[test]
+fn test_parse_stat_counts_fields_after_the_command_name() {
+    let stat = "4242 (srcds (main) x) S 4200 4242 4242 0 -1 4194560 1000 0 0 0 \
+                150 50 7 3 20 0 12 0 123456 987654321 2048 18446744073709551615";
+
+    assert_eq!(
+        parse_stat(stat),
+        Some(ProcStat {
+            pid: 4242,
+            ppid: 4200,
+            cpu_ticks: 210,
+            threads: 12,
+            rss_pages: 2048,
+        })
+    );
+    assert_eq!(parse_stat("4242 (truncated) S 1"), None);
+}
+
+#[test]
+fn test_parse_io_reads_storage_bytes() {
+    let io = "rchar: 100\nwchar: 200\nsyscr: 3\nsyscw: 4\nread_bytes: 4096\nwrite_bytes: 8192\n\
+              cancelled_write_bytes: 0\n";
+
+    assert_eq!(parse_io(io), Some((4096, 8192)));
+    assert_eq!(parse_io("rchar: 100\n"), None);
+}
+
+#[test]
+fn test_process_tree_follows_descendants_only() {
+    let stat = |pid, ppid| ProcStat {
+        pid,
+        ppid,
+        cpu_ticks: 0,
+        threads: 1,
+        rss_pages: 0,
+    };
+    let stats = [
+        stat(1, 0),
+        stat(10, 1),
+        stat(11, 10),
+        stat(12, 11),
+        stat(13, 1),
+    ];
+
+    let pids: Vec<u32> = process_tree(10, &stats).iter().map(|s| s.pid).collect();
+    assert_eq!(pids, vec![10, 11, 12]);
+    assert!(process_tree(99, &stats).is_empty());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_tree_usage_of_own_process() {
+    let usage = tree_usage(std::process::id()).unwrap();
+
+    assert!(usage.processes >= 1);
+    assert!(usage.threads >= 1);
+    assert!(usage.rss_bytes > 0);
+}
+
+#[test]
+fn test_sample_turns_counters_into_rates() {
+    let previous = TreeUsage {
+        cpu_ticks: 100,
+        read_bytes: 1000,
+        write_bytes: 5000,
+        ..Default::default()
+    };
+    let current = TreeUsage {
+        processes: 2,
+        cpu_ticks: 400,
+        threads: 9,
+        rss_bytes: 1 << 20,
+        read_bytes: 21000,
+        // A child that wrote a lot exited in between
+        write_bytes: 3000,
+    };
+
+    let sample = sample(&previous, &current, Duration::from_secs(2), 100, Some(7));
+    assert_eq!(sample.cpu_percent, 150.0);
+    assert_eq!(sample.read_bytes_per_sec, 10000.0);
+    assert_eq!(sample.write_bytes_per_sec, 0.0);
+    assert_eq!(sample.memory_bytes, 1 << 20);
+    assert_eq!(sample.disk_bytes, Some(7));
+}
+
+fn minute(at: &str, samples: u32, cpu: f64, memory: u64) -> MetricsPoint {
+    MetricsPoint {
+        recorded_at: at.parse().unwrap(),
+        resolution_secs: MINUTE,
+        samples,
+        cpu_percent: cpu,
+        cpu_percent_max: cpu,
+        memory_bytes: memory,
+        threads: 4,
+        read_bytes_per_sec: 0.0,
+        write_bytes_per_sec: 0.0,
+        disk_bytes: None,
+    }
+}
+
+#[test]
+fn test_downsample_weights_averages_and_keeps_peaks() {
+    let points = vec![
+        minute("2026-10-18T10:58:00Z", 6, 10.0, 100),
+        minute("2026-10-18T10:59:00Z", 2, 50.0, 300),
+        minute("2026-10-18T11:00:00Z", 6, 20.0, 200),
+    ];
+
+    let hours = downsample(points, HOUR);
+    assert_eq!(hours.len(), 2);
+    assert_eq!(
+        hours[0].recorded_at.to_rfc3339(),
+        "2026-10-18T10:00:00+00:00"
+    );
+    assert_eq!(hours[0].resolution_secs, HOUR);
+    assert_eq!(hours[0].samples, 8);
+    assert_eq!(hours[0].cpu_percent, 20.0);
+    assert_eq!(hours[0].cpu_percent_max, 50.0);
+    assert_eq!(hours[0].memory_bytes, 300);
+    assert_eq!(hours[1].samples, 6);
+}
+
+#[test]
+fn test_history_resolution_fits_the_range() {
+    assert_eq!(history_resolution(chrono::TimeDelta::hours(1)), MINUTE);
+    assert_eq!(history_resolution(chrono::TimeDelta::hours(12)), MINUTE);
+    assert_eq!(history_resolution(chrono::TimeDelta::days(2)), 300);
+    assert_eq!(history_resolution(chrono::TimeDelta::days(30)), HOUR);
+    assert_eq!(history_resolution(chrono::TimeDelta::days(3650)), 24 * HOUR);
+}
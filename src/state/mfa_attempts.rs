@@ -0,0 +1,49 @@
+use std::{collections::HashMap, sync::Mutex};
+
+#[cfg(test)]
+mod tests;
+
+/// Codes a user may get wrong within [`WINDOW_SECS`]. The count is kept per user across MFA
+/// challenges, so logging in again for a fresh challenge does not allow more guesses.
+pub const MAX_ATTEMPTS: u32 = 5;
+
+/// Length of the window the attempts are counted in, from the first attempt
+pub const WINDOW_SECS: i64 = 15 * 60;
+
+struct Attempts {
+    count: u32,
+    /// Start of the window, as a unix timestamp
+    since: i64,
+}
+
+/// MFA codes tried per user within the current window
+#[derive(Default)]
+pub struct MfaAttempts {
+    attempts: Mutex<HashMap<i32, Attempts>>,
+}
+
+impl MfaAttempts {
+    /// Count an attempt by the user before their code is checked, so parallel requests cannot
+    /// get past the limit. `false` once the user is out of attempts for this window.
+    pub fn begin(&self, user_id: i32) -> bool {
+        self.begin_at(user_id, chrono::Utc::now().timestamp())
+    }
+
+    fn begin_at(&self, user_id: i32, now: i64) -> bool {
+        let mut attempts = self.attempts.lock().unwrap_or_else(|e| e.into_inner());
+        attempts.retain(|_, a| a.since + WINDOW_SECS > now);
+
+        let entry = attempts.entry(user_id).or_insert(Attempts {
+            count: 0,
+            since: now,
+        });
+        entry.count = entry.count.saturating_add(1);
+        entry.count <= MAX_ATTEMPTS
+    }
+
+    /// Clear the user's attempts once they got a code right
+    pub fn succeeded(&self, user_id: i32) {
+        let mut attempts = self.attempts.lock().unwrap_or_else(|e| e.into_inner());
+        attempts.remove(&user_id);
+    }
+}
@@ -0,0 +1,34 @@
+use super::*;
+
+#[test]
+fn test_user_is_locked_out_after_max_attempts() {
+    let attempts = MfaAttempts::default();
+
+    for _ in 0..MAX_ATTEMPTS {
+        assert!(attempts.begin_at(1, 1000));
+    }
+    assert!(!attempts.begin_at(1, 1000));
+    assert!(attempts.begin_at(2, 1000));
+}
+
+#[test]
+fn test_attempts_are_counted_until_the_window_ends() {
+    let attempts = MfaAttempts::default();
+
+    for _ in 0..MAX_ATTEMPTS {
+        assert!(attempts.begin_at(1, 1000));
+    }
+    assert!(!attempts.begin_at(1, 1000 + WINDOW_SECS - 1));
+    assert!(attempts.begin_at(1, 1000 + WINDOW_SECS));
+}
+
+#[test]
+fn test_success_clears_the_attempts() {
+    let attempts = MfaAttempts::default();
+
+    for _ in 0..MAX_ATTEMPTS {
+        assert!(attempts.begin_at(1, 1000));
+    }
+    attempts.succeeded(1);
+    assert!(attempts.begin_at(1, 1000));
+}
@@ -1 +1,13 @@
+pub mod console;
+pub mod console_log;
+pub mod instance;
+pub mod limits;
+pub mod metrics;
+pub mod mfa_attempts;
+pub mod oidc;
+pub mod sandbox;
+pub mod server_query;
 pub mod steamcmd;
+pub mod stream_ticket;
+pub mod trigger;
+pub mod vt;
@@ -0,0 +1,77 @@
+use crate::auth::oidc::{self, IdTokenClaims, OidcClient, OidcError};
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// How long a user has to complete the login at the identity provider
+const LOGIN_TTL: Duration = Duration::from_secs(10 * 60);
+
+struct PendingLogin {
+    code_verifier: String,
+    nonce: String,
+    expires_at: Instant,
+}
+
+/// Single sign-on client plus the in-flight logins, keyed by their `state` parameter
+pub struct OidcLogins {
+    client: Option<OidcClient>,
+    pending: Mutex<HashMap<String, PendingLogin>>,
+}
+
+impl OidcLogins {
+    pub fn new(client: Option<OidcClient>) -> Self {
+        Self {
+            client,
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn client(&self) -> Result<&OidcClient, OidcError> {
+        self.client.as_ref().ok_or(OidcError::Disabled)
+    }
+
+    /// Start a login and return the provider URL to redirect the browser to
+    pub async fn begin(&self) -> Result<String, OidcError> {
+        let client = self.client()?;
+
+        let state = oidc::random_token();
+        let nonce = oidc::random_token();
+        let code_verifier = oidc::random_token();
+        let url = client
+            .authorization_url(&state, &nonce, &oidc::pkce_challenge(&code_verifier))
+            .await?;
+
+        let now = Instant::now();
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        pending.retain(|_, login| login.expires_at > now);
+        pending.insert(
+            state,
+            PendingLogin {
+                code_verifier,
+                nonce,
+                expires_at: now + LOGIN_TTL,
+            },
+        );
+
+        Ok(url)
+    }
+
+    /// Finish a login started by [`OidcLogins::begin`]. Each `state` can only be used once.
+    pub async fn complete(&self, state: &str, code: &str) -> Result<IdTokenClaims, OidcError> {
+        let client = self.client()?;
+
+        let login = self
+            .pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(state)
+            .filter(|login| login.expires_at > Instant::now())
+            .ok_or(OidcError::InvalidState)?;
+
+        client
+            .exchange_code(code, &login.code_verifier, &login.nonce)
+            .await
+    }
+}
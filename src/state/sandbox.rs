@@ -0,0 +1,303 @@
+//! Sandboxed launches: the server runs under bubblewrap in its own namespaces, with the host
+//! and the instance root mounted read-only, its saves and logs the only writable paths and the
+//! panel's data hidden. A panel running as root drops to SANDBOX_USER first, who only owns the
+//! saves and logs.
+
+use crate::schema::server_config::SandboxConfig;
+use crate::service::files::InstanceLayout;
+use crate::utils;
+use std::path::{Path, PathBuf};
+
+#[cfg(test)]
+mod tests;
+
+/// Variables of the panel's environment every sandboxed server keeps
+const KEPT_ENV: [&str; 3] = ["PATH", "LANG", "TZ"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SandboxUser {
+    pub uid: u32,
+    pub gid: u32,
+}
+
+/// Resolve SANDBOX_USER, either `uid:gid` or a user name looked up in the contents of
+/// `/etc/passwd`
+pub fn parse_user(value: &str, passwd: &str) -> Result<SandboxUser, String> {
+    if let Some((uid, gid)) = value.split_once(':') {
+        return match (uid.parse(), gid.parse()) {
+            (Ok(uid), Ok(gid)) => Ok(SandboxUser { uid, gid }),
+            _ => Err(format!("SANDBOX_USER '{value}' is not a uid:gid pair")),
+        };
+    }
+
+    passwd
+        .lines()
+        .find_map(|line| {
+            let mut fields = line.split(':');
+            if fields.next()? != value {
+                return None;
+            }
+            let (_, uid, gid) = (fields.next()?, fields.next()?, fields.next()?);
+            Some(SandboxUser {
+                uid: uid.parse().ok()?,
+                gid: gid.parse().ok()?,
+            })
+        })
+        .ok_or_else(|| format!("SANDBOX_USER '{value}' does not exist"))
+}
+
+/// The file of a SQLite database URL, `None` for in-memory databases
+pub fn sqlite_file(url: &str) -> Option<PathBuf> {
+    let path = url
+        .strip_prefix("sqlite://")
+        .or_else(|| url.strip_prefix("sqlite:"))?;
+    let path = path.split('?').next().unwrap_or_default();
+
+    match path {
+        "" | ":memory:" => None,
+        path => Some(PathBuf::from(path)),
+    }
+}
+
+/// The existing directories among `candidates`, absolute and leaving out those already hidden
+/// by another one. `/` cannot be hidden and is left out.
+pub fn hidden_paths(candidates: impl IntoIterator<Item = PathBuf>) -> Vec<PathBuf> {
+    let mut hidden: Vec<PathBuf> = candidates
+        .into_iter()
+        .filter_map(|path| std::fs::canonicalize(path).ok())
+        .filter(|path| path.is_dir() && path.parent().is_some())
+        .collect();
+    hidden.sort();
+    hidden.dedup_by(|path, kept| path.starts_with(kept));
+    hidden
+}
+
+/// Arguments to bubblewrap that run `command` from `workdir` with `home` as HOME. `root` is
+/// visible read-only and only the `writable` directories below it can be written to.
+#[allow(clippy::too_many_arguments)]
+pub fn bwrap_args(
+    config: &SandboxConfig,
+    root: &Path,
+    writable: &[PathBuf],
+    workdir: &Path,
+    home: &Path,
+    hidden: &[PathBuf],
+    env: &[(String, String)],
+    command: &[String],
+) -> Vec<String> {
+    let root = root.to_string_lossy().to_string();
+    let dir = workdir.to_string_lossy().to_string();
+    let mut args: Vec<String> = ["--unshare-all", "--ro-bind", "/", "/"]
+        .map(str::to_string)
+        .to_vec();
+    if config.network {
+        args.insert(1, "--share-net".to_string());
+    }
+    for (flag, path) in [("--dev", "/dev"), ("--proc", "/proc"), ("--tmpfs", "/tmp")] {
+        args.extend([flag.to_string(), path.to_string()]);
+    }
+    for path in hidden {
+        args.extend(["--tmpfs".to_string(), path.to_string_lossy().to_string()]);
+    }
+    // Mounted after the hidden directories, which usually hold it
+    args.extend(["--ro-bind".to_string(), root.clone(), root]);
+    for path in writable {
+        let path = path.to_string_lossy().to_string();
+        args.extend(["--bind".to_string(), path.clone(), path]);
+    }
+    args.extend(["--chdir".to_string(), dir]);
+
+    args.push("--clearenv".to_string());
+    args.extend([
+        "--setenv".to_string(),
+        "HOME".to_string(),
+        home.to_string_lossy().to_string(),
+    ]);
+    for (name, value) in env {
+        args.extend(["--setenv".to_string(), name.clone(), value.clone()]);
+    }
+
+    args.push("--".to_string());
+    args.extend(command.iter().cloned());
+    args
+}
+
+/// Bubblewrap, checked to work on this host, and the user sandboxed servers run as
+#[derive(Debug)]
+pub struct Sandbox {
+    bwrap: PathBuf,
+    user: Option<SandboxUser>,
+    hidden: Vec<PathBuf>,
+}
+
+impl Sandbox {
+    /// Find bubblewrap, resolve SANDBOX_USER and try to create a sandbox, so a host that lacks
+    /// support is reported before any server is started. `hidden` are the panel's directories.
+    #[cfg(unix)]
+    pub async fn detect(hidden: impl IntoIterator<Item = PathBuf>) -> Result<Self, String> {
+        let bwrap = std::env::var_os("PATH")
+            .map(|path| std::env::split_paths(&path).collect::<Vec<_>>())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|dir| dir.join("bwrap"))
+            .find(|path| path.is_file())
+            .ok_or("bubblewrap (bwrap) is not installed")?;
+
+        let user = match std::env::var("SANDBOX_USER") {
+            Ok(value) if !value.is_empty() => {
+                let passwd = std::fs::read_to_string("/etc/passwd").unwrap_or_default();
+                Some(parse_user(&value, &passwd)?)
+            }
+            _ => None,
+        };
+        match user {
+            None if utils::privileged() => {
+                return Err(
+                    "the panel runs as root, so SANDBOX_USER must name the user servers run as"
+                        .to_string(),
+                )
+            }
+            Some(SandboxUser { uid: 0, .. }) => {
+                return Err("SANDBOX_USER must not be root".to_string())
+            }
+            Some(_) if !utils::privileged() => {
+                return Err(
+                    "running servers as SANDBOX_USER needs the panel to run as root".to_string(),
+                )
+            }
+            _ => {}
+        }
+
+        let sandbox = Self {
+            bwrap,
+            user,
+            hidden: hidden_paths(hidden),
+        };
+        let mut probe = tokio::process::Command::new(&sandbox.bwrap);
+        probe
+            .args([
+                "--unshare-all",
+                "--ro-bind",
+                "/",
+                "/",
+                "--proc",
+                "/proc",
+                "--",
+                "true",
+            ])
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::piped());
+        sandbox.drop_privileges(&mut probe);
+        let output = probe
+            .output()
+            .await
+            .map_err(|e| format!("bubblewrap could not be run: {e}"))?;
+        if !output.status.success() {
+            return Err(format!(
+                "bubblewrap cannot create a sandbox on this host: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+
+        Ok(sandbox)
+    }
+
+    #[cfg(not(unix))]
+    pub async fn detect(_hidden: impl IntoIterator<Item = PathBuf>) -> Result<Self, String> {
+        Err("sandboxing is not supported on this platform".to_string())
+    }
+
+    pub fn user(&self) -> Option<SandboxUser> {
+        self.user
+    }
+
+    /// Bubblewrap set up to run `command` from the install root inside the sandbox, able to
+    /// write to the saves and logs only. HOME is the saves directory.
+    pub fn command(
+        &self,
+        config: &SandboxConfig,
+        layout: &InstanceLayout,
+        command: &[String],
+    ) -> tokio::process::Command {
+        let env: Vec<(String, String)> = KEPT_ENV
+            .iter()
+            .copied()
+            .chain(config.keep_env.iter().map(String::as_str))
+            .filter_map(|name| Some((name.to_string(), std::env::var(name).ok()?)))
+            .collect();
+
+        let mut sandboxed = tokio::process::Command::new(&self.bwrap);
+        sandboxed.args(bwrap_args(
+            config,
+            layout.root(),
+            &[layout.saves(), layout.logs()],
+            &layout.install(),
+            &layout.saves(),
+            &self.hidden,
+            &env,
+            command,
+        ));
+        sandboxed
+    }
+
+    /// Hand the saves and logs to the sandbox user. The rest of the instance, the game install
+    /// included, stays the panel's and read-only to the server: SteamCMD and mod installs write
+    /// there as the panel and must not follow links the server could plant.
+    pub async fn prepare_dir(&self, layout: &InstanceLayout) -> std::io::Result<()> {
+        let Some(user) = self.user else {
+            return Ok(());
+        };
+        let dirs = [layout.saves(), layout.logs()];
+        tokio::task::spawn_blocking(move || dirs.iter().try_for_each(|dir| chown_tree(dir, user)))
+            .await
+            .map_err(std::io::Error::other)?
+    }
+
+    /// Have the child switch to the sandbox user right before it runs bubblewrap. Registered
+    /// after the resource limits, which may need the panel's privileges.
+    #[cfg(unix)]
+    pub fn drop_privileges(&self, command: &mut tokio::process::Command) {
+        let Some(SandboxUser { uid, gid }) = self.user else {
+            return;
+        };
+
+        // SAFETY: the closure only makes async-signal-safe system calls and allocates nothing
+        unsafe {
+            command.pre_exec(move || {
+                if libc::setgroups(0, std::ptr::null()) != 0
+                    || libc::setgid(gid) != 0
+                    || libc::setuid(uid) != 0
+                {
+                    return Err(std::io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+    }
+
+    #[cfg(not(unix))]
+    pub fn drop_privileges(&self, _command: &mut tokio::process::Command) {}
+}
+
+#[cfg(unix)]
+fn chown_tree(path: &Path, user: SandboxUser) -> std::io::Result<()> {
+    use std::os::unix::fs::MetadataExt;
+
+    let metadata = std::fs::symlink_metadata(path)?;
+    if metadata.uid() != user.uid || metadata.gid() != user.gid {
+        std::os::unix::fs::lchown(path, Some(user.uid), Some(user.gid))?;
+    }
+    // Symlinks are not followed out of the directory
+    if metadata.is_dir() {
+        for entry in std::fs::read_dir(path)? {
+            chown_tree(&entry?.path(), user)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn chown_tree(_path: &Path, _user: SandboxUser) -> std::io::Result<()> {
+    Ok(())
+}
@@ -0,0 +1,109 @@
+use super::*;
+
+#[test]
+fn test_parse_user_by_name_or_ids() {
+    let passwd = "root:x:0:0:root:/root:/bin/bash\n\
+                  gameserver:x:1001:1002::/home/gameserver:/usr/sbin/nologin\n";
+
+    assert_eq!(
+        parse_user("gameserver", passwd),
+        Ok(SandboxUser {
+            uid: 1001,
+            gid: 1002
+        })
+    );
+    assert_eq!(
+        parse_user("2000:2000", passwd),
+        Ok(SandboxUser {
+            uid: 2000,
+            gid: 2000
+        })
+    );
+    assert!(parse_user("games", passwd).is_err());
+    assert!(parse_user("games:staff", passwd).is_err());
+}
+
+#[test]
+fn test_sqlite_file_of_database_urls() {
+    assert_eq!(
+        sqlite_file("sqlite:///var/lib/server_ui/db.sqlite?mode=rwc"),
+        Some(PathBuf::from("/var/lib/server_ui/db.sqlite"))
+    );
+    assert_eq!(
+        sqlite_file("sqlite://data/server_ui.db"),
+        Some(PathBuf::from("data/server_ui.db"))
+    );
+    assert_eq!(sqlite_file("sqlite::memory:"), None);
+    assert_eq!(sqlite_file("postgres://localhost/server_ui"), None);
+}
+
+#[test]
+fn test_hidden_paths_skip_root_nested_and_missing_directories() {
+    let dir = std::env::temp_dir();
+    let nested = dir.join("server_ui-sandbox-nested");
+    std::fs::create_dir_all(&nested).unwrap();
+    let hidden = hidden_paths([
+        nested,
+        dir.clone(),
+        dir.join("."),
+        PathBuf::from("/"),
+        dir.join("server_ui-sandbox-missing"),
+    ]);
+
+    assert_eq!(hidden, vec![std::fs::canonicalize(&dir).unwrap()]);
+}
+
+#[test]
+fn test_bwrap_args_make_only_saves_and_logs_writable() {
+    let config: SandboxConfig = serde_json::from_value(serde_json::json!({})).unwrap();
+    assert!(config.network);
+
+    let args = bwrap_args(
+        &config,
+        Path::new("/srv/data/instances/3"),
+        &[
+            PathBuf::from("/srv/data/instances/3/saves"),
+            PathBuf::from("/srv/data/instances/3/logs"),
+        ],
+        Path::new("/srv/data/instances/3/server"),
+        Path::new("/srv/data/instances/3/saves"),
+        &[PathBuf::from("/srv/data")],
+        &[("PATH".to_string(), "/usr/bin".to_string())],
+        &[
+            "/srv/data/instances/3/server/GameServer".to_string(),
+            "-port".to_string(),
+        ],
+    );
+    let joined = args.join(" ");
+
+    assert!(joined.starts_with("--unshare-all --share-net --ro-bind / /"));
+    let hidden = joined.find("--tmpfs /srv/data").unwrap();
+    let root = joined
+        .find("--ro-bind /srv/data/instances/3 /srv/data/instances/3")
+        .unwrap();
+    let saves = joined
+        .find("--bind /srv/data/instances/3/saves /srv/data/instances/3/saves")
+        .unwrap();
+    assert!(hidden < root && root < saves);
+    assert!(joined.contains("--bind /srv/data/instances/3/logs /srv/data/instances/3/logs"));
+    assert!(!joined.contains("--bind /srv/data/instances/3/server"));
+    assert!(joined.contains("--chdir /srv/data/instances/3/server --clearenv"));
+    assert!(joined.contains("--setenv HOME /srv/data/instances/3/saves --setenv PATH /usr/bin"));
+    assert!(joined.ends_with("-- /srv/data/instances/3/server/GameServer -port"));
+
+    let offline = SandboxConfig {
+        network: false,
+        keep_env: Vec::new(),
+    };
+    let args = bwrap_args(
+        &offline,
+        Path::new("/srv"),
+        &[],
+        Path::new("/srv/server"),
+        Path::new("/srv/saves"),
+        &[],
+        &[],
+        &["x".to_string()],
+    );
+    assert!(!args.contains(&"--share-net".to_string()));
+}
@@ -0,0 +1,206 @@
+use crate::dto::instance::{QueryPlayer, ServerQuery};
+use crate::schema::{self, command::config_value, server_config::ServerConfig};
+use crate::service::instance::Instance;
+use crate::state::instance::InstanceManager;
+use rocket::futures::future::join_all;
+use sea_orm::DatabaseConnection;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use thiserror::Error;
+use tokio::sync::broadcast;
+
+pub mod a2s;
+
+#[cfg(test)]
+mod tests;
+
+use a2s::{A2sClient, A2sError};
+
+/// Seconds between queries of every running server unless QUERY_POLL_INTERVAL says otherwise
+const DEFAULT_POLL_INTERVAL_SECONDS: u64 = 15;
+
+/// How long a server gets to answer each query
+const QUERY_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Error, Debug)]
+pub enum QueryError {
+    #[error("This instance's game schema does not declare a query port")]
+    NotSupported,
+
+    #[error("The instance has no value for its query port field")]
+    MissingPort,
+
+    #[error("'{0}' is not a valid query port")]
+    InvalidPort(String),
+
+    #[error(transparent)]
+    A2s(#[from] A2sError),
+}
+
+/// Read the query port from the field the schema declares for it
+pub fn query_port(schema: &ServerConfig, config: &schema::GameConfig) -> Result<u16, QueryError> {
+    let support = schema.query.as_ref().ok_or(QueryError::NotSupported)?;
+    let port = schema
+        .args
+        .iter()
+        .find(|field| field.name == support.port_field)
+        .and_then(|field| config_value(field, config))
+        .ok_or(QueryError::MissingPort)?;
+
+    match port.parse::<u16>() {
+        Ok(port) if port != 0 => Ok(port),
+        _ => Err(QueryError::InvalidPort(port)),
+    }
+}
+
+/// Ask a server for its info, players and rules. Only the info is required; plenty of
+/// servers keep their player list or rules to themselves.
+pub async fn query_server(port: u16) -> Result<ServerQuery, QueryError> {
+    let client = A2sClient::connect(("127.0.0.1", port), QUERY_TIMEOUT).await?;
+    let info = client.info().await?;
+    let player_list = client
+        .players()
+        .await
+        .map(|players| {
+            players
+                .into_iter()
+                .map(|player| QueryPlayer {
+                    name: player.name,
+                    score: player.score,
+                    duration: player.duration,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let rules = client
+        .rules()
+        .await
+        .map(|rules| rules.into_iter().collect())
+        .unwrap_or_default();
+
+    Ok(ServerQuery {
+        queried_at: chrono::Utc::now(),
+        online: true,
+        name: info.name,
+        map: info.map,
+        game: info.game,
+        version: info.version,
+        players: info.players,
+        max_players: info.max_players,
+        bots: info.bots,
+        player_list,
+        rules,
+        error: None,
+    })
+}
+
+/// The latest query answer of every running instance. Clones share the same cache.
+#[derive(Clone)]
+pub struct ServerQueries {
+    db: DatabaseConnection,
+    cache: Arc<Mutex<HashMap<i32, ServerQuery>>>,
+    updates: broadcast::Sender<(i32, ServerQuery)>,
+}
+
+impl ServerQueries {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self {
+            db,
+            cache: Default::default(),
+            updates: broadcast::channel(64).0,
+        }
+    }
+
+    pub fn get(&self, id: i32) -> Option<ServerQuery> {
+        let cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        cache.get(&id).cloned()
+    }
+
+    /// Every answer stored from now on, tagged with its instance id
+    pub fn subscribe(&self) -> broadcast::Receiver<(i32, ServerQuery)> {
+        self.updates.subscribe()
+    }
+
+    /// Drop the answer of a server that stopped or was deleted
+    pub fn forget(&self, id: i32) {
+        let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        cache.remove(&id);
+    }
+
+    fn store(&self, id: i32, query: ServerQuery) {
+        let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        cache.insert(id, query.clone());
+        // Nobody listening is fine
+        let _ = self.updates.send((id, query));
+    }
+
+    /// Query one instance and store the answer. A failure keeps the previous answer, marked
+    /// offline with the reason. Instances whose schema declares no query port are skipped.
+    pub async fn poll(&self, id: i32) {
+        let result = match self.port(id).await {
+            Ok(port) => query_server(port).await,
+            Err(e) => Err(e),
+        };
+
+        let query = match result {
+            Ok(query) => query,
+            Err(QueryError::NotSupported) => return,
+            Err(e) => ServerQuery {
+                queried_at: chrono::Utc::now(),
+                online: false,
+                error: Some(e.to_string()),
+                ..self.get(id).unwrap_or_default()
+            },
+        };
+        self.store(id, query);
+    }
+
+    async fn port(&self, id: i32) -> Result<u16, QueryError> {
+        // An instance deleted in the meantime has nothing to query either
+        let (instance, schema) = Instance::new(self.db.clone(), None)
+            .find_with_schema(id)
+            .await
+            .map_err(|_| QueryError::NotSupported)?;
+        let config: schema::GameConfig =
+            serde_json::from_value(instance.config_json).unwrap_or_default();
+
+        query_port(&schema, &config)
+    }
+
+    /// Query every running server in the background, every QUERY_POLL_INTERVAL seconds.
+    /// 0 disables polling.
+    pub fn spawn_poller(self, manager: InstanceManager) {
+        let seconds = std::env::var("QUERY_POLL_INTERVAL")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_POLL_INTERVAL_SECONDS);
+        if seconds == 0 {
+            return;
+        }
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(seconds));
+            loop {
+                interval.tick().await;
+
+                let running = manager.running().await;
+                let stopped: Vec<i32> = {
+                    let cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+                    cache
+                        .keys()
+                        .filter(|id| !running.contains(id))
+                        .copied()
+                        .collect()
+                };
+                for id in stopped {
+                    self.forget(id);
+                }
+
+                join_all(running.into_iter().map(|id| self.poll(id))).await;
+            }
+        });
+    }
+}
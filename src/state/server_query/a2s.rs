@@ -0,0 +1,304 @@
+//! A client for the Steam server queries A2S_INFO, A2S_PLAYER and A2S_RULES over UDP.
+//! See <https://developer.valvesoftware.com/wiki/Server_queries>.
+
+use std::time::Duration;
+use thiserror::Error;
+use tokio::net::{ToSocketAddrs, UdpSocket};
+
+const SINGLE_PACKET: i32 = -1;
+const SPLIT_PACKET: i32 = -2;
+
+const A2S_INFO: u8 = b'T';
+const A2S_PLAYER: u8 = b'U';
+const A2S_RULES: u8 = b'V';
+const S2C_CHALLENGE: u8 = b'A';
+const INFO_RESPONSE: u8 = b'I';
+const PLAYER_RESPONSE: u8 = b'D';
+const RULES_RESPONSE: u8 = b'E';
+
+/// Servers answering with a fresh challenge every time are given up on after this many
+const MAX_CHALLENGES: usize = 3;
+/// Packets are at most 1400 bytes, but some servers go over
+const MAX_PACKET: usize = 4096;
+
+#[derive(Error, Debug)]
+pub enum A2sError {
+    #[error("Could not query the server: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("The server did not answer the query in time")]
+    Timeout,
+
+    #[error("The server sent a malformed answer: {0}")]
+    Malformed(String),
+
+    #[error("The server sent a compressed answer, which is not supported")]
+    Compressed,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerInfo {
+    pub name: String,
+    pub map: String,
+    pub folder: String,
+    pub game: String,
+    pub app_id: i16,
+    pub players: u8,
+    pub max_players: u8,
+    pub bots: u8,
+    pub version: String,
+    /// The game port, when the server reports it
+    pub port: Option<u16>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Player {
+    pub name: String,
+    pub score: i32,
+    /// Seconds connected
+    pub duration: f32,
+}
+
+/// Little-endian fields and NUL-terminated strings of a response
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], A2sError> {
+        if self.bytes.len() < n {
+            return Err(A2sError::Malformed("response ends early".to_string()));
+        }
+        let (head, rest) = self.bytes.split_at(n);
+        self.bytes = rest;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8, A2sError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn i16(&mut self) -> Result<i16, A2sError> {
+        Ok(i16::from_le_bytes(
+            self.take(2)?.try_into().unwrap_or_default(),
+        ))
+    }
+
+    fn i32(&mut self) -> Result<i32, A2sError> {
+        Ok(i32::from_le_bytes(
+            self.take(4)?.try_into().unwrap_or_default(),
+        ))
+    }
+
+    fn f32(&mut self) -> Result<f32, A2sError> {
+        Ok(f32::from_le_bytes(
+            self.take(4)?.try_into().unwrap_or_default(),
+        ))
+    }
+
+    fn string(&mut self) -> Result<String, A2sError> {
+        let end = self
+            .bytes
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or_else(|| A2sError::Malformed("unterminated string".to_string()))?;
+        let text = String::from_utf8_lossy(&self.bytes[..end]).into_owned();
+        self.bytes = &self.bytes[end + 1..];
+        Ok(text)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+}
+
+pub fn parse_info(response: &[u8]) -> Result<ServerInfo, A2sError> {
+    let mut reader = Reader { bytes: response };
+    if reader.u8()? != INFO_RESPONSE {
+        return Err(A2sError::Malformed("not an info response".to_string()));
+    }
+    let _protocol = reader.u8()?;
+    let name = reader.string()?;
+    let map = reader.string()?;
+    let folder = reader.string()?;
+    let game = reader.string()?;
+    let app_id = reader.i16()?;
+    let players = reader.u8()?;
+    let max_players = reader.u8()?;
+    let bots = reader.u8()?;
+    // Server type, environment, visibility and VAC
+    reader.take(4)?;
+    let version = reader.string()?;
+
+    let mut port = None;
+    if !reader.is_empty() {
+        let extra = reader.u8()?;
+        if extra & 0x80 != 0 {
+            port = Some(reader.i16()? as u16);
+        }
+    }
+
+    Ok(ServerInfo {
+        name,
+        map,
+        folder,
+        game,
+        app_id,
+        players,
+        max_players,
+        bots,
+        version,
+        port,
+    })
+}
+
+pub fn parse_players(response: &[u8]) -> Result<Vec<Player>, A2sError> {
+    let mut reader = Reader { bytes: response };
+    if reader.u8()? != PLAYER_RESPONSE {
+        return Err(A2sError::Malformed("not a player response".to_string()));
+    }
+
+    let count = reader.u8()?;
+    let mut players = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let _index = reader.u8()?;
+        players.push(Player {
+            name: reader.string()?,
+            score: reader.i32()?,
+            duration: reader.f32()?,
+        });
+    }
+
+    Ok(players)
+}
+
+pub fn parse_rules(response: &[u8]) -> Result<Vec<(String, String)>, A2sError> {
+    let mut reader = Reader { bytes: response };
+    if reader.u8()? != RULES_RESPONSE {
+        return Err(A2sError::Malformed("not a rules response".to_string()));
+    }
+
+    let count = reader.i16()?.max(0);
+    let mut rules = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let name = reader.string()?;
+        let value = reader.string()?;
+        rules.push((name, value));
+    }
+
+    Ok(rules)
+}
+
+/// Queries one server. Every query waits at most `timeout` for the whole answer.
+pub struct A2sClient {
+    socket: UdpSocket,
+    timeout: Duration,
+}
+
+impl A2sClient {
+    pub async fn connect(addr: impl ToSocketAddrs, timeout: Duration) -> Result<Self, A2sError> {
+        let socket = UdpSocket::bind(("0.0.0.0", 0)).await?;
+        socket.connect(addr).await?;
+
+        Ok(Self { socket, timeout })
+    }
+
+    pub async fn info(&self) -> Result<ServerInfo, A2sError> {
+        let response = self
+            .query(A2S_INFO, b"Source Engine Query\0".to_vec(), false)
+            .await?;
+        parse_info(&response)
+    }
+
+    pub async fn players(&self) -> Result<Vec<Player>, A2sError> {
+        parse_players(&self.query(A2S_PLAYER, Vec::new(), true).await?)
+    }
+
+    pub async fn rules(&self) -> Result<Vec<(String, String)>, A2sError> {
+        parse_rules(&self.query(A2S_RULES, Vec::new(), true).await?)
+    }
+
+    /// Send a query, answering challenges until the server sends the real response.
+    /// `challenged` queries carry a challenge from the start (`-1` to ask for one); A2S_INFO
+    /// only gets one appended once the server demands it.
+    async fn query(
+        &self,
+        kind: u8,
+        payload: Vec<u8>,
+        challenged: bool,
+    ) -> Result<Vec<u8>, A2sError> {
+        let mut challenge = challenged.then_some([0xff; 4]);
+
+        for _ in 0..MAX_CHALLENGES {
+            let mut request = SINGLE_PACKET.to_le_bytes().to_vec();
+            request.push(kind);
+            request.extend_from_slice(&payload);
+            if let Some(challenge) = challenge {
+                request.extend_from_slice(&challenge);
+            }
+            self.socket.send(&request).await?;
+
+            let response = tokio::time::timeout(self.timeout, self.receive())
+                .await
+                .map_err(|_| A2sError::Timeout)??;
+            match response.split_first() {
+                Some((&S2C_CHALLENGE, rest)) if rest.len() >= 4 => {
+                    challenge = Some([rest[0], rest[1], rest[2], rest[3]]);
+                }
+                Some(_) => return Ok(response),
+                None => return Err(A2sError::Malformed("empty response".to_string())),
+            }
+        }
+
+        Err(A2sError::Malformed(
+            "the server keeps sending challenges".to_string(),
+        ))
+    }
+
+    /// Receive one response, reassembling it if it was split over several packets
+    async fn receive(&self) -> Result<Vec<u8>, A2sError> {
+        let mut buffer = [0u8; MAX_PACKET];
+        let mut parts: Vec<Option<Vec<u8>>> = Vec::new();
+        let mut split_id = None;
+
+        loop {
+            let read = self.socket.recv(&mut buffer).await?;
+            let mut reader = Reader {
+                bytes: &buffer[..read],
+            };
+
+            match reader.i32()? {
+                SINGLE_PACKET => return Ok(reader.bytes.to_vec()),
+                SPLIT_PACKET => {
+                    let id = reader.i32()?;
+                    if id as u32 & 0x8000_0000 != 0 {
+                        return Err(A2sError::Compressed);
+                    }
+                    let total = reader.u8()? as usize;
+                    let number = reader.u8()? as usize;
+                    let _size = reader.i16()?;
+                    if total == 0 || number >= total {
+                        return Err(A2sError::Malformed("bad split packet header".to_string()));
+                    }
+                    // A late packet of an earlier answer; wait for the current one
+                    if split_id.is_some_and(|current| current != id) {
+                        continue;
+                    }
+                    split_id = Some(id);
+                    parts.resize(total, None);
+                    parts[number] = Some(reader.bytes.to_vec());
+
+                    if parts.iter().all(Option::is_some) {
+                        let joined: Vec<u8> = parts.into_iter().flatten().flatten().collect();
+                        let mut reader = Reader { bytes: &joined };
+                        if reader.i32()? != SINGLE_PACKET {
+                            return Err(A2sError::Malformed("bad split payload".to_string()));
+                        }
+                        return Ok(reader.bytes.to_vec());
+                    }
+                }
+                other => return Err(A2sError::Malformed(format!("packet header {other}"))),
+            }
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use super::a2s::{parse_info, A2sClient, A2sError, Player};
+use super::*;
+use serde_json::json;
+use tokio::net::UdpSocket;
+
+const CHALLENGE: [u8; 4] = [0x12, 0x34, 0x56, 0x78];
+
+fn single(payload: &[u8]) -> Vec<u8> {
+    let mut packet = (-1i32).to_le_bytes().to_vec();
+    packet.extend_from_slice(payload);
+    packet
+}
+
+fn info_payload() -> Vec<u8> {
+    let mut payload = vec![b'I', 17];
+    for text in ["Test Server", "cp_badlands", "tf", "Team Fortress"] {
+        payload.extend_from_slice(text.as_bytes());
+        payload.push(0);
+    }
+    payload.extend_from_slice(&440i16.to_le_bytes());
+    payload.extend_from_slice(&[2, 24, 1, b'd', b'l', 0, 1]);
+    payload.extend_from_slice(b"8835751\0");
+    payload.push(0x80);
+    payload.extend_from_slice(&27015i16.to_le_bytes());
+    payload
+}
+
+fn players_payload() -> Vec<u8> {
+    let mut payload = vec![b'D', 2];
+    for (index, name, score, duration) in [(0u8, "alice", 12i32, 61.5f32), (1, "bob", 3, 5.0)] {
+        payload.push(index);
+        payload.extend_from_slice(name.as_bytes());
+        payload.push(0);
+        payload.extend_from_slice(&score.to_le_bytes());
+        payload.extend_from_slice(&duration.to_le_bytes());
+    }
+    payload
+}
+
+fn rules_payload() -> Vec<u8> {
+    let mut payload = vec![b'E'];
+    payload.extend_from_slice(&2i16.to_le_bytes());
+    payload.extend_from_slice(b"mp_timelimit\x0030\x00sv_gravity\x00800\x00");
+    payload
+}
+
+/// An A2S server that demands a challenge for every query, like current Source servers do,
+/// and sends its rules split over two packets
+async fn mock_server() -> u16 {
+    let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let port = socket.local_addr().unwrap().port();
+
+    tokio::spawn(async move {
+        let mut buffer = [0u8; 1400];
+        while let Ok((read, peer)) = socket.recv_from(&mut buffer).await {
+            let request = &buffer[4..read];
+            let challenged = request.ends_with(&CHALLENGE);
+            let replies = match (request[0], challenged) {
+                (_, false) => {
+                    let mut challenge = vec![b'A'];
+                    challenge.extend_from_slice(&CHALLENGE);
+                    vec![single(&challenge)]
+                }
+                (b'T', true) => vec![single(&info_payload())],
+                (b'U', true) => vec![single(&players_payload())],
+                (b'V', true) => {
+                    let whole = single(&rules_payload());
+                    let (first, second) = whole.split_at(whole.len() / 2);
+                    // Out of order, the client has to put them back together
+                    [(1u8, second), (0u8, first)]
+                        .into_iter()
+                        .map(|(number, part)| {
+                            let mut packet = (-2i32).to_le_bytes().to_vec();
+                            packet.extend_from_slice(&7i32.to_le_bytes());
+                            packet.extend_from_slice(&[2, number]);
+                            packet.extend_from_slice(&1248i16.to_le_bytes());
+                            packet.extend_from_slice(part);
+                            packet
+                        })
+                        .collect()
+                }
+                _ => continue,
+            };
+            for reply in replies {
+                socket.send_to(&reply, peer).await.unwrap();
+            }
+        }
+    });
+
+    port
+}
+
+#[tokio::test]
+async fn test_client_answers_challenges() {
+    let port = mock_server().await;
+    let client = A2sClient::connect(("127.0.0.1", port), QUERY_TIMEOUT)
+        .await
+        .unwrap();
+
+    let info = client.info().await.unwrap();
+    assert_eq!(info.map, "cp_badlands");
+    assert_eq!((info.players, info.max_players, info.bots), (2, 24, 1));
+    assert_eq!(info.version, "8835751");
+    assert_eq!(info.port, Some(27015));
+
+    assert_eq!(
+        client.players().await.unwrap(),
+        vec![
+            Player {
+                name: "alice".to_string(),
+                score: 12,
+                duration: 61.5
+            },
+            Player {
+                name: "bob".to_string(),
+                score: 3,
+                duration: 5.0
+            }
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_client_joins_split_packets() {
+    let port = mock_server().await;
+    let client = A2sClient::connect(("127.0.0.1", port), QUERY_TIMEOUT)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        client.rules().await.unwrap(),
+        vec![
+            ("mp_timelimit".to_string(), "30".to_string()),
+            ("sv_gravity".to_string(), "800".to_string())
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_query_server_collects_everything() {
+    let port = mock_server().await;
+    let query = query_server(port).await.unwrap();
+
+    assert!(query.online);
+    assert_eq!(query.name, "Test Server");
+    assert_eq!(query.player_list.len(), 2);
+    assert_eq!(
+        query.rules.get("sv_gravity").map(String::as_str),
+        Some("800")
+    );
+}
+
+#[tokio::test]
+async fn test_client_times_out_on_silent_server() {
+    let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let port = socket.local_addr().unwrap().port();
+
+    let client = A2sClient::connect(("127.0.0.1", port), Duration::from_millis(100))
+        .await
+        .unwrap();
+    assert!(matches!(client.info().await, Err(A2sError::Timeout)));
+}
+
+#[test]
+fn test_truncated_info_is_malformed() {
+    let payload = info_payload();
+    assert!(matches!(
+        parse_info(&payload[..20]),
+        Err(A2sError::Malformed(_))
+    ));
+}
+
+fn tf2_schema(query: serde_json::Value) -> ServerConfig {
+    serde_json::from_value(json!({
+        "steamAppId": 232250,
+        "executableName": "srcds_run",
+        "displayName": "TF2",
+        "args": [
+            { "name": "port", "flag": "-port", "type": "number", "default": "27015", "description": "Port" }
+        ],
+        "query": query
+    }))
+    .unwrap()
+}
+
+fn config(value: serde_json::Value) -> schema::GameConfig {
+    serde_json::from_value(value).unwrap()
+}
+
+#[test]
+fn test_query_port_reads_declared_field() {
+    let schema = tf2_schema(json!({ "portField": "port" }));
+
+    assert_eq!(query_port(&schema, &config(json!({}))).unwrap(), 27015);
+    assert_eq!(
+        query_port(&schema, &config(json!({ "port": 27016 }))).unwrap(),
+        27016
+    );
+    assert!(matches!(
+        query_port(&schema, &config(json!({ "port": 0 }))),
+        Err(QueryError::InvalidPort(_))
+    ));
+    assert!(matches!(
+        query_port(&tf2_schema(serde_json::Value::Null), &config(json!({}))),
+        Err(QueryError::NotSupported)
+    ));
+}
+
+#[test]
+fn test_schema_query_port_field_must_exist() {
+    let schema = tf2_schema(json!({ "portField": "queryPort" }));
+
+    assert_eq!(
+        schema.validate(),
+        Err(vec![
+            "Query port field 'queryPort' is not a field".to_string()
+        ])
+    );
+}
@@ -1,36 +1,55 @@
+pub mod app_info;
+pub mod bootstrap;
+pub mod events;
+pub mod vdf;
+
 use rocket::{futures::lock::Mutex, http::Status, response::Responder, Request};
-use std::{collections::VecDeque, path::PathBuf, process::Stdio, sync::Arc, thread};
+use std::{
+    path::{Path, PathBuf},
+    process::Stdio,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Weak,
+    },
+    time::Duration,
+};
 use thiserror::Error;
 use tokio::{
     io::{AsyncWriteExt, BufReader},
-    process::{Child, ChildStdin, Command},
-    sync::broadcast,
+    process::{Child, ChildStdin, ChildStdout, Command},
+    sync::{broadcast, watch},
     task::JoinHandle,
 };
 use tokio_util::sync::CancellationToken;
 use which::which;
 
+use crate::dto::steamcmd::{
+    SteamCmdEvent, SteamCmdSessionInfo, SteamCmdSessionState, SteamCmdStatus, SteamGuardPrompt,
+};
+use crate::state::console::{ConsoleFollower, ConsoleHistory, ConsoleLine};
+use crate::state::console_log::{ConsoleLogs, LogStream, LogWriter};
 use crate::utils::error_response;
 
-fn strip_ansi_codes(s: &str) -> String {
-    let mut result = String::new();
-    let mut in_escape = false;
+#[cfg(test)]
+mod tests;
 
-    for ch in s.chars() {
-        if ch == '\x1b' {
-            in_escape = true;
-        } else if in_escape {
-            if ch == 'm' {
-                in_escape = false;
-            }
-            // Skip everything while in escape sequence
-        } else {
-            result.push(ch);
-        }
-    }
+/// Id of the long-lived anonymous session started with the server
+pub const GLOBAL_SESSION_ID: &str = "global";
 
-    result
-}
+/// Finished job sessions kept around so their output can still be read
+const MAX_FINISHED_JOBS: usize = 20;
+
+/// Delay before the first respawn of a crashed global session, doubled after every failure
+const RESPAWN_BACKOFF_INITIAL: Duration = Duration::from_secs(1);
+const RESPAWN_BACKOFF_MAX: Duration = Duration::from_secs(60);
+/// A process that stayed up this long resets the backoff
+const RESPAWN_STABLE_AFTER: Duration = Duration::from_secs(60);
+
+/// `app_info_print` output of a large app runs to a few thousand lines; keep all of it
+const APP_INFO_HISTORY: usize = 20_000;
+
+/// Prefix of lines the server itself writes into a session's output
+const LIFECYCLE_PREFIX: &str = "[server_ui]";
 
 #[derive(Error, Debug)]
 pub enum SteamCmdError {
@@ -39,196 +58,939 @@ pub enum SteamCmdError {
 
     #[error("Failed to start steamcmd process")]
     FailedToStart,
+
+    #[error("SteamCMD session '{0}' not found")]
+    SessionNotFound(String),
+
+    #[error("The SteamCMD process is not running")]
+    NotRunning,
+
+    #[error("SteamCMD is not waiting for a Steam Guard code")]
+    NoSteamGuardPrompt,
+
+    #[error("Steam Guard codes are short and alphanumeric")]
+    InvalidSteamGuardCode,
+
+    #[error("Invalid SteamCMD command '{0}'. Logins are handled by the session and commands may not contain line breaks")]
+    InvalidCommand(String),
+
+    #[error("A job needs at least one command")]
+    NoCommands,
+
+    #[error("The global SteamCMD session cannot be removed")]
+    GlobalSession,
 }
 
 impl<'r> Responder<'r, 'static> for SteamCmdError {
     fn respond_to(self, _: &'r Request<'_>) -> rocket::response::Result<'static> {
         let status = match self {
-            Self::CommandNotFound | Self::FailedToStart => Status::InternalServerError,
+            Self::CommandNotFound => Status::ServiceUnavailable,
+            Self::FailedToStart => Status::InternalServerError,
+            Self::SessionNotFound(_) => Status::NotFound,
+            Self::NotRunning | Self::NoSteamGuardPrompt | Self::GlobalSession => Status::Conflict,
+            Self::InvalidSteamGuardCode | Self::InvalidCommand(_) | Self::NoCommands => {
+                Status::UnprocessableEntity
+            }
         };
 
         error_response(self, status)
     }
 }
 
-pub struct SteamCMD {
-    path: PathBuf,
-    child: Arc<Mutex<Option<Child>>>,
-    stdin: Arc<Mutex<Option<ChildStdin>>>,
-    stdout_tx: broadcast::Sender<String>,
-    stdout_task: Arc<Mutex<Option<JoinHandle<()>>>>,
-    cancel_token: CancellationToken,
-    last_lines: Arc<Mutex<VecDeque<String>>>,
-    history_capacity: usize,
+/// How a SteamCMD process logs in to Steam
+#[derive(Clone)]
+pub enum SteamLogin {
+    Anonymous,
+    Account { username: String, password: String },
 }
 
-impl Drop for SteamCMD {
-    fn drop(&mut self) {
-        self.cancel_token.cancel();
+impl SteamLogin {
+    pub fn account(&self) -> &str {
+        match self {
+            SteamLogin::Anonymous => "anonymous",
+            SteamLogin::Account { username, .. } => username,
+        }
+    }
+}
+
+/// What a line of SteamCMD output says about the login in progress
+#[derive(Debug, PartialEq)]
+enum LoginSignal {
+    LoggedIn,
+    Failed(String),
+    SteamGuard(SteamGuardPrompt),
+}
 
-        let child_arc = self.child.clone();
-        let stdin_arc = self.stdin.clone();
-        let task_arc = self.stdout_task.clone();
+fn login_signal(line: &str) -> Option<LoginSignal> {
+    let line = line.trim();
+
+    if line.ends_with("Steam Guard code:") {
+        Some(LoginSignal::SteamGuard(SteamGuardPrompt::Email))
+    } else if line.ends_with("Two-factor code:") {
+        Some(LoginSignal::SteamGuard(SteamGuardPrompt::TwoFactor))
+    } else if line.contains("Waiting for user info...OK") || line.contains("Logged in OK") {
+        Some(LoginSignal::LoggedIn)
+    } else if let Some(reason) = line.split_once("FAILED login with result code ") {
+        Some(LoginSignal::Failed(reason.1.to_string()))
+    } else if line.starts_with("Logging in user") {
+        line.split_once("...FAILED")
+            .map(|(_, reason)| LoginSignal::Failed(reason.trim().to_string()))
+    } else {
+        None
+    }
+}
 
-        _ = thread::spawn(async move || {
-            // Wait for stdout task to finish
-            if let Some(handle) = task_arc.lock().await.take() {
-                let _ = handle.await;
-            }
+fn validate_command(command: &str) -> Result<(), SteamCmdError> {
+    let verb = command.split_whitespace().next().unwrap_or_default();
+    let reserved = ["login", "logout", "quit", "exit"];
+
+    if verb.is_empty()
+        || command.chars().any(char::is_control)
+        || reserved
+            .iter()
+            .any(|r| verb.trim_start_matches('+').eq_ignore_ascii_case(r))
+    {
+        return Err(SteamCmdError::InvalidCommand(command.to_string()));
+    }
 
-            let mut child_guard = child_arc.lock().await;
-            let mut stdin_guard = stdin_arc.lock().await;
+    Ok(())
+}
 
-            if let Some(mut stdin) = stdin_guard.take() {
-                _ = stdin.write_all(b"quit\n").await;
-                _ = stdin.flush().await;
-            }
+/// Delay before each respawn of a crashed global session: doubled after every failure up to
+/// `max`, and back to `initial` once a process stayed up for `stable_after`
+#[derive(Debug, Clone, Copy)]
+struct RespawnBackoff {
+    initial: Duration,
+    max: Duration,
+    stable_after: Duration,
+    next: Duration,
+}
 
-            if let Some(mut child) = child_guard.take() {
-                _ = child.kill().await;
-            }
-        })
-        .join();
+impl Default for RespawnBackoff {
+    fn default() -> Self {
+        Self::new(
+            RESPAWN_BACKOFF_INITIAL,
+            RESPAWN_BACKOFF_MAX,
+            RESPAWN_STABLE_AFTER,
+        )
     }
 }
 
-impl SteamCMD {
-    /// Default capacity for the history of last lines.
-    const DEFAULT_HISTORY_CAPACITY: usize = 200;
+impl RespawnBackoff {
+    fn new(initial: Duration, max: Duration, stable_after: Duration) -> Self {
+        Self {
+            initial,
+            max,
+            stable_after,
+            next: initial,
+        }
+    }
 
-    pub fn create(history_capacity: Option<usize>) -> Result<Self, SteamCmdError> {
-        let path = which("steamcmd").map_err(|_| SteamCmdError::CommandNotFound)?;
-        let capacity = history_capacity.unwrap_or(Self::DEFAULT_HISTORY_CAPACITY);
-        let (tx, _) = broadcast::channel(capacity);
+    /// How long to wait before respawning a process that exited after running for `uptime`
+    fn delay(&mut self, uptime: Duration) -> Duration {
+        if uptime >= self.stable_after {
+            self.next = self.initial;
+        }
+        let delay = self.next;
+        self.next = (self.next * 2).min(self.max);
+        delay
+    }
+}
+
+struct SessionStatus {
+    state: SteamCmdSessionState,
+    steam_guard_prompt: Option<SteamGuardPrompt>,
+    started_at: Option<chrono::DateTime<chrono::Utc>>,
+    pid: Option<u32>,
+    /// Exit code of the previous process, kept across respawns
+    exit_code: Option<i32>,
+    restarts: u32,
+    error: Option<String>,
+}
+
+impl Default for SessionStatus {
+    fn default() -> Self {
+        Self {
+            state: SteamCmdSessionState::Exited,
+            steam_guard_prompt: None,
+            started_at: None,
+            pid: None,
+            exit_code: None,
+            restarts: 0,
+            error: None,
+        }
+    }
+}
 
-        Ok(Self {
-            path,
+impl SessionStatus {
+    fn is_running(&self) -> bool {
+        self.pid.is_some()
+    }
+}
+
+fn push_line(history: &ConsoleHistory, log: Option<&LogWriter>, line: String) {
+    if let Some(log) = log {
+        log.write(&line);
+    }
+    history.push(line);
+}
+
+fn describe_exit(code: Option<i32>) -> String {
+    match code {
+        Some(code) => format!("exited with code {code}"),
+        None => "was killed by a signal".to_string(),
+    }
+}
+
+/// A single SteamCMD process, its output history and login state
+pub struct SteamCmdSession {
+    id: String,
+    account: std::sync::Mutex<String>,
+    child: Arc<Mutex<Option<Child>>>,
+    stdin: Arc<Mutex<Option<ChildStdin>>>,
+    history: Arc<ConsoleHistory>,
+    events_tx: broadcast::Sender<SteamCmdEvent>,
+    tasks: Mutex<Vec<JoinHandle<()>>>,
+    cancel_token: CancellationToken,
+    /// Set once the session was asked to quit for good, so it is not respawned
+    quitting: AtomicBool,
+    status: Arc<std::sync::Mutex<SessionStatus>>,
+    running: watch::Sender<bool>,
+    /// Values masked in the output besides the login password, e.g. beta passwords
+    secrets: Vec<String>,
+    log: Option<LogWriter>,
+}
+
+impl Drop for SteamCmdSession {
+    fn drop(&mut self) {
+        self.cancel_token.cancel();
+
+        if let Some(mut child) = self.child.try_lock().and_then(|mut guard| guard.take()) {
+            _ = child.start_kill();
+        }
+    }
+}
+
+impl SteamCmdSession {
+    fn new(id: String, history_capacity: usize) -> Self {
+        Self {
+            id,
+            account: std::sync::Mutex::new(SteamLogin::Anonymous.account().to_string()),
             child: Default::default(),
             stdin: Default::default(),
-            stdout_tx: tx,
-            stdout_task: Default::default(),
+            history: Arc::new(ConsoleHistory::new(history_capacity)),
+            events_tx: broadcast::channel(history_capacity).0,
+            tasks: Default::default(),
             cancel_token: CancellationToken::new(),
-            last_lines: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
-            history_capacity: capacity,
-        })
+            quitting: AtomicBool::new(false),
+            status: Default::default(),
+            running: watch::channel(false).0,
+            secrets: Vec::new(),
+            log: None,
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
     }
 
-    pub fn subscribe(&self) -> broadcast::Receiver<String> {
-        self.stdout_tx.subscribe()
+    pub fn subscribe(&self) -> broadcast::Receiver<ConsoleLine> {
+        self.history.subscribe()
+    }
+
+    /// Replay the output after the cursor `after`, then follow it live
+    pub fn follow(&self, after: Option<u64>) -> ConsoleFollower {
+        self.history.follow(after)
+    }
+
+    /// Typed events recognised in the output, see [`events::parse`]
+    pub fn subscribe_events(&self) -> broadcast::Receiver<SteamCmdEvent> {
+        self.events_tx.subscribe()
     }
 
     pub async fn get_last_lines(&self) -> Vec<String> {
-        self.last_lines.lock().await.iter().cloned().collect()
+        self.history.lines()
+    }
+
+    pub fn info(&self) -> SteamCmdSessionInfo {
+        let account = self
+            .account
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        let status = self.status.lock().unwrap_or_else(|e| e.into_inner());
+
+        SteamCmdSessionInfo {
+            id: self.id.clone(),
+            account,
+            state: status.state,
+            steam_guard_prompt: status.steam_guard_prompt,
+            started_at: status.started_at,
+            pid: status.pid,
+            exit_code: status.exit_code,
+            restarts: status.restarts,
+            error: status.error.clone(),
+        }
     }
 
-    pub async fn init(&mut self) -> Result<(), SteamCmdError> {
-        let mut cmd = Command::new(self.path.as_path())
-            .arg("+login anonymous")
+    /// Process liveness for the health endpoint
+    pub fn status(&self) -> SteamCmdStatus {
+        let status = self.status.lock().unwrap_or_else(|e| e.into_inner());
+        let uptime = status
+            .started_at
+            .filter(|_| status.is_running())
+            .map(|started_at| (chrono::Utc::now() - started_at).num_seconds().max(0) as u64);
+
+        SteamCmdStatus {
+            installed: true,
+            running: status.is_running(),
+            pid: status.pid,
+            uptime_seconds: uptime,
+            last_exit_code: status.exit_code,
+            restarts: status.restarts,
+        }
+    }
+
+    /// Write a server-generated line into the session's output
+    pub async fn announce(&self, message: &str) {
+        let line = format!("{LIFECYCLE_PREFIX} {message}\n");
+        push_line(&self.history, self.log.as_ref(), line);
+    }
+
+    /// Start the process, log in, and once logged in run `commands`. Sessions given commands
+    /// quit when they are done; sessions without any stay at the `Steam>` prompt.
+    /// SteamCMD wants `force_install_dir` before logging in, so it is set here rather than
+    /// passed as one of the commands.
+    async fn start(
+        &self,
+        path: &Path,
+        install_dir: Option<&Path>,
+        login: SteamLogin,
+        commands: Vec<String>,
+    ) -> Result<(), SteamCmdError> {
+        let mut command = Command::new(path);
+        // Anonymous logins go on the command line as before. Account passwords are written to
+        // stdin instead so they never show up in the process list.
+        if matches!(login, SteamLogin::Anonymous) {
+            if let Some(dir) = install_dir {
+                command.arg("+force_install_dir").arg(dir);
+            }
+            command.arg("+login anonymous");
+        }
+
+        let mut cmd = command
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
+            .kill_on_drop(true)
             .spawn()
             .map_err(|_| SteamCmdError::FailedToStart)?;
 
         let stdin = cmd.stdin.take();
         let stdout = cmd.stdout.take();
 
-        let (Some(stdin), Some(stdout)) = (stdin, stdout) else {
+        let (Some(mut stdin), Some(stdout)) = (stdin, stdout) else {
             let _ = cmd.kill().await;
 
             return Err(SteamCmdError::FailedToStart);
         };
 
-        {
-            let mut guard = self.stdin.lock().await;
-            *guard = Some(stdin);
+        let mut redact = self.secrets.clone();
+        match &login {
+            SteamLogin::Anonymous => {}
+            SteamLogin::Account { username, password } => {
+                let mut line = String::new();
+                if let Some(dir) = install_dir {
+                    line.push_str(&format!("force_install_dir \"{}\"\n", dir.display()));
+                }
+                line.push_str(&format!("login {username} \"{password}\"\n"));
+                if stdin.write_all(line.as_bytes()).await.is_err() || stdin.flush().await.is_err() {
+                    let _ = cmd.kill().await;
+
+                    return Err(SteamCmdError::FailedToStart);
+                }
+                redact.push(password.clone());
+            }
         }
+
+        let pid = cmd.id();
+        *self.account.lock().unwrap_or_else(|e| e.into_inner()) = login.account().to_string();
         {
-            let tx_clone = self.stdout_tx.clone();
-            let cancel_token = self.cancel_token.clone();
-            let last_lines = self.last_lines.clone();
-            let capacity = self.history_capacity;
-
-            let handle = tokio::spawn(async move {
-                use tokio::io::AsyncReadExt;
-
-                let mut reader = BufReader::new(stdout);
-                let mut buffer = [0u8; 1024];
-                let mut line_buffer = String::new();
-                let mut flush_interval =
-                    tokio::time::interval(tokio::time::Duration::from_millis(500));
-
-                loop {
-                    tokio::select! {
-                        _ = cancel_token.cancelled() => break,
-                        _ = flush_interval.tick() => {
-                            // Periodically flush any buffered content (e.g., prompts without newlines)
-                            // Send without adding newline since the original output didn't have one
-                            if !line_buffer.trim().is_empty() {
-                                let clean_line = strip_ansi_codes(&line_buffer);
-                                if !clean_line.trim().is_empty() {
-                                    let mut cache = last_lines.lock().await;
-                                    cache.push_back(clean_line.clone());
-                                    if cache.len() > capacity {
-                                        cache.pop_front();
-                                    }
-                                    _ = tx_clone.send(clean_line);
-                                }
-                                line_buffer.clear();
+            let mut status = self.status.lock().unwrap_or_else(|e| e.into_inner());
+            *status = SessionStatus {
+                state: SteamCmdSessionState::Starting,
+                started_at: Some(chrono::Utc::now()),
+                pid,
+                exit_code: status.exit_code,
+                restarts: status.restarts,
+                ..Default::default()
+            };
+        }
+        *self.stdin.lock().await = Some(stdin);
+        *self.child.lock().await = Some(cmd);
+        self.running.send_replace(true);
+        self.announce(&match pid {
+            Some(pid) => format!("SteamCMD started (pid {pid})"),
+            None => "SteamCMD started".to_string(),
+        })
+        .await;
+
+        let run_token = self.cancel_token.child_token();
+        let driver = self.drive_login(commands, run_token.clone());
+        let reader = self.read_stdout(stdout, redact, run_token);
+
+        let mut tasks = self.tasks.lock().await;
+        tasks.retain(|task| !task.is_finished());
+        tasks.push(driver);
+        tasks.push(reader);
+
+        Ok(())
+    }
+
+    /// Watch the output for the login result and Steam Guard prompts, then run the queued commands
+    fn drive_login(&self, commands: Vec<String>, run_token: CancellationToken) -> JoinHandle<()> {
+        let mut rx = self.history.subscribe();
+        let stdin = self.stdin.clone();
+        let status = self.status.clone();
+        let mut pending = (!commands.is_empty()).then_some(commands);
+
+        tokio::spawn(async move {
+            loop {
+                let line = tokio::select! {
+                    biased;
+                    msg = rx.recv() => match msg {
+                        Ok(line) => line.text,
+                        Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    },
+                    _ = run_token.cancelled() => break,
+                };
+
+                let Some(signal) = login_signal(&line) else {
+                    continue;
+                };
+
+                let queued = {
+                    let mut status = status.lock().unwrap_or_else(|e| e.into_inner());
+                    match signal {
+                        LoginSignal::LoggedIn => {
+                            status.state = SteamCmdSessionState::Ready;
+                            status.steam_guard_prompt = None;
+                            pending.take().map(|mut commands| {
+                                commands.push("quit".to_string());
+                                commands
+                            })
+                        }
+                        LoginSignal::SteamGuard(prompt) => {
+                            status.state = SteamCmdSessionState::SteamGuardRequired;
+                            status.steam_guard_prompt = Some(prompt);
+                            None
+                        }
+                        LoginSignal::Failed(reason) => {
+                            status.state = SteamCmdSessionState::LoginFailed;
+                            status.steam_guard_prompt = None;
+                            status.error = Some(reason);
+                            pending.take().map(|_| vec!["quit".to_string()])
+                        }
+                    }
+                };
+
+                if let Some(lines) = queued {
+                    if let Some(stdin) = stdin.lock().await.as_mut() {
+                        for line in lines {
+                            _ = stdin.write_all(format!("{line}\n").as_bytes()).await;
+                        }
+                        _ = stdin.flush().await;
+                    }
+                }
+            }
+        })
+    }
+
+    fn read_stdout(
+        &self,
+        stdout: ChildStdout,
+        redact: Vec<String>,
+        run_token: CancellationToken,
+    ) -> JoinHandle<()> {
+        let history = self.history.clone();
+        let events_tx = self.events_tx.clone();
+        let log = self.log.clone();
+        let publish_log = self.log.clone();
+        let child = self.child.clone();
+        let stdin = self.stdin.clone();
+        let status = self.status.clone();
+        let running = self.running.clone();
+
+        let publish = move |line: &str| {
+            let mut clean_line = crate::state::vt::plain(line);
+            for secret in redact.iter().filter(|s| !s.is_empty()) {
+                clean_line = clean_line.replace(secret.as_str(), "********");
+            }
+            if let Some(event) = events::parse(&clean_line) {
+                _ = events_tx.send(event);
+            }
+            if let Some(log) = &publish_log {
+                log.write(&clean_line);
+            }
+            clean_line
+        };
+
+        tokio::spawn(async move {
+            use tokio::io::AsyncReadExt;
+
+            let mut reader = BufReader::new(stdout);
+            let mut buffer = [0u8; 1024];
+            let mut line_buffer = String::new();
+            let mut flush_interval = tokio::time::interval(tokio::time::Duration::from_millis(500));
+
+            loop {
+                tokio::select! {
+                    _ = run_token.cancelled() => break,
+                    _ = flush_interval.tick() => {
+                        // Periodically flush any buffered content (e.g., prompts without newlines)
+                        // Send without adding newline since the original output didn't have one
+                        if !line_buffer.trim().is_empty() {
+                            let clean_line = publish(&line_buffer);
+                            if !clean_line.trim().is_empty() {
+                                history.push(clean_line);
                             }
+                            line_buffer.clear();
                         }
-                        result = reader.read(&mut buffer) => {
-                            match result {
-                                Ok(0) => break, // EOF
-                                Ok(n) => {
-                                    // Reset the flush interval timer on data arrival
-                                    flush_interval.reset();
-
-                                    let chunk = String::from_utf8_lossy(&buffer[..n]);
-
-                                    for ch in chunk.chars() {
-                                        if ch == '\n' {
-                                            let clean_line = strip_ansi_codes(&line_buffer);
-                                            if !clean_line.trim().is_empty() {
-                                                let line_with_newline = format!("{}\n", clean_line);
-                                                let mut cache = last_lines.lock().await;
-                                                cache.push_back(line_with_newline.clone());
-                                                if cache.len() > capacity {
-                                                    cache.pop_front();
-                                                }
-                                                _ = tx_clone.send(line_with_newline);
-                                            }
-                                            line_buffer.clear();
-                                        } else {
-                                            line_buffer.push(ch);
+                    }
+                    result = reader.read(&mut buffer) => {
+                        match result {
+                            Ok(0) => break, // EOF
+                            Ok(n) => {
+                                // Reset the flush interval timer on data arrival
+                                flush_interval.reset();
+
+                                let chunk = String::from_utf8_lossy(&buffer[..n]);
+
+                                for ch in chunk.chars() {
+                                    if ch == '\n' {
+                                        let clean_line = publish(&line_buffer);
+                                        if !clean_line.trim().is_empty() {
+                                            history.push(format!("{}\n", clean_line));
                                         }
+                                        line_buffer.clear();
+                                    } else {
+                                        line_buffer.push(ch);
                                     }
                                 }
-                                Err(_) => break,
                             }
+                            Err(_) => break,
                         }
                     }
                 }
+            }
 
-                // Send any remaining content in the buffer (e.g., the prompt without newline)
-                // Send without adding newline since the original output didn't have one
-                let clean_line = strip_ansi_codes(&line_buffer);
-                if !clean_line.trim().is_empty() {
-                    let mut cache = last_lines.lock().await;
-                    cache.push_back(clean_line.clone());
-                    if cache.len() > capacity {
-                        cache.pop_front();
-                    }
-                    _ = tx_clone.send(clean_line);
+            // Send any remaining content in the buffer (e.g., the prompt without newline)
+            // Send without adding newline since the original output didn't have one
+            let clean_line = publish(&line_buffer);
+            if !clean_line.trim().is_empty() {
+                history.push(clean_line);
+            }
+
+            // Reap the process so its exit code can be reported
+            stdin.lock().await.take();
+            let exit_code = match child.lock().await.take() {
+                Some(mut child) => child.wait().await.ok().and_then(|s| s.code()),
+                None => None,
+            };
+
+            {
+                let mut status = status.lock().unwrap_or_else(|e| e.into_inner());
+                status.state = match status.state {
+                    SteamCmdSessionState::LoginFailed => SteamCmdSessionState::LoginFailed,
+                    _ => SteamCmdSessionState::Exited,
+                };
+                status.steam_guard_prompt = None;
+                status.pid = None;
+                status.exit_code = exit_code;
+            }
+
+            let line = format!("{LIFECYCLE_PREFIX} SteamCMD {}\n", describe_exit(exit_code));
+            push_line(&history, log.as_ref(), line);
+
+            // Let the login driver drain what is left and stop, then wake the supervisor
+            run_token.cancel();
+            running.send_replace(false);
+        })
+    }
+
+    /// Respawn the process with exponential backoff whenever it exits, until the session is
+    /// dropped. Only holds a weak reference so dropping the session still stops everything.
+    fn supervise(self: &Arc<Self>, path: PathBuf, login: SteamLogin) {
+        let session = Arc::downgrade(self);
+        let mut running = self.running.subscribe();
+        let cancel_token = self.cancel_token.clone();
+
+        tokio::spawn(async move {
+            let mut backoff = RespawnBackoff::default();
+
+            loop {
+                let started = tokio::time::Instant::now();
+                tokio::select! {
+                    _ = cancel_token.cancelled() => break,
+                    res = running.wait_for(|running| !*running) => if res.is_err() { break },
                 }
-            });
+                let delay = backoff.delay(started.elapsed());
 
-            let mut guard = self.stdout_task.lock().await;
-            *guard = Some(handle);
+                let Some(current) = Weak::upgrade(&session) else {
+                    break;
+                };
+                if current.quitting.load(Ordering::SeqCst) {
+                    break;
+                }
+                current
+                    .announce(&format!("Restarting SteamCMD in {}s", delay.as_secs()))
+                    .await;
+                drop(current);
+
+                tokio::select! {
+                    _ = cancel_token.cancelled() => break,
+                    _ = tokio::time::sleep(delay) => {}
+                }
+
+                let Some(current) = Weak::upgrade(&session) else {
+                    break;
+                };
+                current
+                    .status
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .restarts += 1;
+                if let Err(e) = current.start(&path, None, login.clone(), Vec::new()).await {
+                    current
+                        .announce(&format!("Failed to restart SteamCMD: {e}"))
+                        .await;
+                }
+            }
+        });
+    }
+
+    /// Answer a pending Steam Guard prompt
+    pub async fn submit_steam_guard_code(&self, code: &str) -> Result<(), SteamCmdError> {
+        let code = code.trim();
+        if code.is_empty() || code.len() > 16 || !code.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(SteamCmdError::InvalidSteamGuardCode);
+        }
+
+        {
+            let mut status = self.status.lock().unwrap_or_else(|e| e.into_inner());
+            if status.steam_guard_prompt.take().is_none() {
+                return Err(SteamCmdError::NoSteamGuardPrompt);
+            }
+            status.state = SteamCmdSessionState::Starting;
+        }
+
+        let mut stdin = self.stdin.lock().await;
+        let stdin = stdin.as_mut().ok_or(SteamCmdError::NotRunning)?;
+        stdin
+            .write_all(format!("{code}\n").as_bytes())
+            .await
+            .map_err(|_| SteamCmdError::NotRunning)?;
+        stdin.flush().await.map_err(|_| SteamCmdError::NotRunning)
+    }
+
+    /// Type a command at the `Steam>` prompt. SteamCMD does not echo piped input, so the command
+    /// is added to the output for everyone watching the console.
+    pub async fn send_command(&self, command: &str) -> Result<(), SteamCmdError> {
+        let command = command.trim();
+        validate_command(command)?;
+
+        let mut stdin = self.stdin.lock().await;
+        let stdin = stdin.as_mut().ok_or(SteamCmdError::NotRunning)?;
+        stdin
+            .write_all(format!("{command}\n").as_bytes())
+            .await
+            .map_err(|_| SteamCmdError::NotRunning)?;
+        stdin.flush().await.map_err(|_| SteamCmdError::NotRunning)?;
+        push_line(&self.history, self.log.as_ref(), format!("> {command}\n"));
+
+        Ok(())
+    }
+
+    /// Wait until the current process has exited and been reaped
+    pub async fn wait_exit(&self) {
+        let mut running = self.running.subscribe();
+        _ = running.wait_for(|running| !*running).await;
+    }
+
+    /// Type `quit` and give SteamCMD `timeout` to exit before killing it. The session is not
+    /// respawned afterwards.
+    pub async fn quit(&self, timeout: Duration) {
+        self.quitting.store(true, Ordering::SeqCst);
+        if let Some(stdin) = self.stdin.lock().await.as_mut() {
+            _ = stdin.write_all(b"quit\n").await;
+            _ = stdin.flush().await;
         }
+        if tokio::time::timeout(timeout, self.wait_exit())
+            .await
+            .is_err()
         {
-            let mut guard = self.child.lock().await;
-            *guard = Some(cmd);
+            self.kill().await;
+            self.wait_exit().await;
+        }
+    }
+
+    /// Kill the process. Its output stays readable until the session is dropped.
+    pub async fn kill(&self) {
+        if let Some(child) = self.child.lock().await.as_mut() {
+            _ = child.start_kill();
+        }
+    }
+}
+
+/// The global anonymous SteamCMD session plus any per-job sessions, each its own process.
+/// Without a SteamCMD binary the server runs degraded until one is bootstrapped.
+/// Clones share the same sessions, so background tasks can hold one.
+#[derive(Clone)]
+pub struct SteamCMD {
+    path: Arc<std::sync::Mutex<Option<PathBuf>>>,
+    install_dir: PathBuf,
+    history_capacity: usize,
+    global: Arc<SteamCmdSession>,
+    jobs: Arc<Mutex<Vec<Arc<SteamCmdSession>>>>,
+    next_job_id: Arc<AtomicU64>,
+    initialized: Arc<AtomicBool>,
+    bootstrapping: Arc<AtomicBool>,
+    logs: Option<ConsoleLogs>,
+}
+
+impl SteamCMD {
+    /// Default capacity for the history of last lines.
+    const DEFAULT_HISTORY_CAPACITY: usize = 200;
+
+    /// Use `steamcmd` from PATH, falling back to a previous bootstrap in `<data_dir>/steamcmd`
+    pub fn create(history_capacity: Option<usize>, data_dir: &Path) -> Self {
+        let install_dir = data_dir.join("steamcmd");
+        let path = which("steamcmd")
+            .ok()
+            .or_else(|| bootstrap::installed_path(&install_dir));
+        let capacity = history_capacity.unwrap_or(Self::DEFAULT_HISTORY_CAPACITY);
+
+        Self {
+            path: Arc::new(std::sync::Mutex::new(path)),
+            install_dir,
+            history_capacity: capacity,
+            global: Arc::new(SteamCmdSession::new(
+                GLOBAL_SESSION_ID.to_string(),
+                capacity,
+            )),
+            jobs: Default::default(),
+            next_job_id: Arc::new(AtomicU64::new(1)),
+            initialized: Default::default(),
+            bootstrapping: Default::default(),
+            logs: None,
         }
+    }
+
+    /// Also write the output of every session to `logs`. Call before [`SteamCMD::init`].
+    pub fn with_logs(mut self, logs: ConsoleLogs) -> Self {
+        let mut global = SteamCmdSession::new(GLOBAL_SESSION_ID.to_string(), self.history_capacity);
+        global.log = Some(logs.writer(LogStream::SteamCmd, Some(GLOBAL_SESSION_ID)));
+        self.global = Arc::new(global);
+        self.logs = Some(logs);
+        self
+    }
+
+    fn path(&self) -> Result<PathBuf, SteamCmdError> {
+        self.path
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+            .ok_or(SteamCmdError::CommandNotFound)
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ConsoleLine> {
+        self.global.subscribe()
+    }
+
+    pub async fn get_last_lines(&self) -> Vec<String> {
+        self.global.get_last_lines().await
+    }
+
+    /// Start the global session and keep it running
+    pub async fn init(&self) -> Result<(), SteamCmdError> {
+        let path = self.path()?;
+        self.global
+            .start(&path, None, SteamLogin::Anonymous, Vec::new())
+            .await?;
+        self.global.supervise(path, SteamLogin::Anonymous);
+        self.initialized.store(true, Ordering::SeqCst);
+
+        Ok(())
+    }
+
+    pub fn status(&self) -> SteamCmdStatus {
+        let mut status = self.global.status();
+        status.installed = self.path().is_ok();
+        status
+    }
+
+    /// Install SteamCMD into the data directory and start the global session
+    pub async fn bootstrap(
+        &self,
+        config: &bootstrap::BootstrapConfig,
+    ) -> Result<PathBuf, bootstrap::BootstrapError> {
+        if self.initialized.load(Ordering::SeqCst) {
+            return Err(bootstrap::BootstrapError::AlreadyInstalled);
+        }
+        if self.bootstrapping.swap(true, Ordering::SeqCst) {
+            return Err(bootstrap::BootstrapError::InProgress);
+        }
+
+        let result = async {
+            let path = bootstrap::install(config, &self.install_dir).await?;
+            *self.path.lock().unwrap_or_else(|e| e.into_inner()) = Some(path.clone());
+            self.init().await?;
+            Ok(path)
+        }
+        .await;
+
+        self.bootstrapping.store(false, Ordering::SeqCst);
+        result
+    }
+
+    /// Start a dedicated SteamCMD process that logs in, runs `commands` and quits
+    pub async fn spawn_job(
+        &self,
+        login: SteamLogin,
+        install_dir: Option<&Path>,
+        commands: Vec<String>,
+    ) -> Result<Arc<SteamCmdSession>, SteamCmdError> {
+        self.spawn_job_with_history(
+            login,
+            install_dir,
+            commands,
+            Vec::new(),
+            self.history_capacity,
+        )
+        .await
+    }
+
+    /// Like [`SteamCMD::spawn_job`], masking `secrets` wherever they appear in the output
+    pub async fn spawn_job_with_secrets(
+        &self,
+        login: SteamLogin,
+        install_dir: Option<&Path>,
+        commands: Vec<String>,
+        secrets: Vec<String>,
+    ) -> Result<Arc<SteamCmdSession>, SteamCmdError> {
+        self.spawn_job_with_history(login, install_dir, commands, secrets, self.history_capacity)
+            .await
+    }
+
+    /// Fetch fresh app info for `app_id` and return the job's complete output
+    pub async fn app_info(&self, app_id: i32) -> Result<String, SteamCmdError> {
+        let session = self
+            .spawn_job_with_history(
+                SteamLogin::Anonymous,
+                None,
+                vec![
+                    "app_info_update 1".to_string(),
+                    format!("app_info_print {app_id}"),
+                ],
+                Vec::new(),
+                APP_INFO_HISTORY,
+            )
+            .await?;
+        session.wait_exit().await;
+
+        Ok(session.get_last_lines().await.concat())
+    }
+
+    async fn spawn_job_with_history(
+        &self,
+        login: SteamLogin,
+        install_dir: Option<&Path>,
+        commands: Vec<String>,
+        secrets: Vec<String>,
+        history_capacity: usize,
+    ) -> Result<Arc<SteamCmdSession>, SteamCmdError> {
+        let commands: Vec<String> = commands.iter().map(|c| c.trim().to_string()).collect();
+        if commands.is_empty() {
+            return Err(SteamCmdError::NoCommands);
+        }
+        for command in &commands {
+            validate_command(command)?;
+        }
+
+        let id = format!("job-{}", self.next_job_id.fetch_add(1, Ordering::Relaxed));
+        let mut session = SteamCmdSession::new(id.clone(), history_capacity);
+        session.secrets = secrets;
+        session.log = self
+            .logs
+            .as_ref()
+            .map(|logs| logs.writer(LogStream::SteamCmd, Some(&id)));
+        let session = Arc::new(session);
+        session
+            .start(&self.path()?, install_dir, login, commands)
+            .await?;
+
+        let mut jobs = self.jobs.lock().await;
+        let finished = |job: &Arc<SteamCmdSession>| {
+            matches!(
+                job.info().state,
+                SteamCmdSessionState::Exited | SteamCmdSessionState::LoginFailed
+            )
+        };
+        if jobs.iter().filter(|job| finished(job)).count() >= MAX_FINISHED_JOBS {
+            if let Some(oldest) = jobs.iter().position(finished) {
+                jobs.remove(oldest);
+            }
+        }
+        jobs.push(session.clone());
+
+        Ok(session)
+    }
+
+    pub async fn session(&self, id: &str) -> Result<Arc<SteamCmdSession>, SteamCmdError> {
+        if id == GLOBAL_SESSION_ID {
+            return Ok(self.global.clone());
+        }
+
+        self.jobs
+            .lock()
+            .await
+            .iter()
+            .find(|job| job.id() == id)
+            .cloned()
+            .ok_or_else(|| SteamCmdError::SessionNotFound(id.to_string()))
+    }
+
+    pub async fn sessions(&self) -> Vec<SteamCmdSessionInfo> {
+        let mut sessions = vec![self.global.info()];
+        sessions.extend(self.jobs.lock().await.iter().map(|job| job.info()));
+        sessions
+    }
+
+    /// Quit the global session and every job session in parallel, e.g. when the panel shuts
+    /// down
+    pub async fn shutdown(&self, timeout: Duration) {
+        let mut sessions = vec![self.global.clone()];
+        sessions.extend(self.jobs.lock().await.iter().cloned());
+
+        let mut quits = tokio::task::JoinSet::new();
+        for session in sessions {
+            quits.spawn(async move { session.quit(timeout).await });
+        }
+        quits.join_all().await;
+    }
+
+    /// Kill a job session and forget it
+    pub async fn remove_job(&self, id: &str) -> Result<(), SteamCmdError> {
+        if id == GLOBAL_SESSION_ID {
+            return Err(SteamCmdError::GlobalSession);
+        }
+
+        let mut jobs = self.jobs.lock().await;
+        let index = jobs
+            .iter()
+            .position(|job| job.id() == id)
+            .ok_or_else(|| SteamCmdError::SessionNotFound(id.to_string()))?;
+        jobs.remove(index).kill().await;
 
         Ok(())
     }
@@ -0,0 +1,66 @@
+//! Build ids of installed apps (from `appmanifest_<appid>.acf`) and of the latest
+//! published builds (from `app_info_print`).
+
+use super::vdf::{self, Vdf};
+use std::path::Path;
+
+#[cfg(test)]
+mod tests;
+
+/// Branch a server follows when no beta is selected
+pub const DEFAULT_BRANCH: &str = "public";
+
+/// What an `appmanifest_<appid>.acf` says about an installed app
+#[derive(Debug, Clone, PartialEq)]
+pub struct AppManifest {
+    pub build_id: i64,
+    /// Beta branch the install was made from, if any
+    pub beta_key: Option<String>,
+}
+
+impl AppManifest {
+    pub fn parse(contents: &str) -> Option<Self> {
+        let (key, root) = vdf::parse(contents)?;
+        if !key.eq_ignore_ascii_case("AppState") {
+            return None;
+        }
+
+        Some(Self {
+            build_id: root.get("buildid")?.as_str()?.parse().ok()?,
+            beta_key: root
+                .path(&["UserConfig", "BetaKey"])
+                .and_then(Vdf::as_str)
+                .filter(|key| !key.is_empty())
+                .map(str::to_string),
+        })
+    }
+}
+
+/// The manifest of `app_id` installed into `install_dir`, if it is installed
+pub fn installed_manifest(install_dir: &Path, app_id: i32) -> Option<AppManifest> {
+    let path = install_dir
+        .join("steamapps")
+        .join(format!("appmanifest_{app_id}.acf"));
+    AppManifest::parse(&std::fs::read_to_string(path).ok()?)
+}
+
+/// Latest build id of `branch` in the output of `app_info_print <app_id>`.
+/// The output can contain anything before the `"<app_id>" { ... }` block.
+pub fn available_build(output: &str, app_id: i32, branch: &str) -> Option<i64> {
+    let header = format!("\"{app_id}\"");
+    let start = output
+        .split_inclusive('\n')
+        .scan(0, |offset, line| {
+            let start = *offset;
+            *offset += line.len();
+            Some((start, line))
+        })
+        .find(|(_, line)| line.trim() == header)
+        .map(|(start, _)| start)?;
+
+    let (_, info) = vdf::parse(&output[start..])?;
+    info.path(&["depots", "branches", branch, "buildid"])?
+        .as_str()?
+        .parse()
+        .ok()
+}
@@ -0,0 +1,126 @@
+use super::*;
+
+const APP_MANIFEST: &str = r#""AppState"
+{
+	"appid"		"896660"
+	"Universe"		"1"
+	"name"		"Valheim Dedicated Server"
+	"StateFlags"		"4"
+	"installdir"		"Valheim dedicated server"
+	"LastUpdated"		"1729000000"
+	"SizeOnDisk"		"1073741824"
+	"buildid"		"15810291"
+	"LastOwner"		"0"
+	"UpdateResult"		"0"
+	"BytesToDownload"		"0"
+	"AutoUpdateBehavior"		"0"
+	"InstalledDepots"
+	{
+		"896661"
+		{
+			"manifest"		"5383929512931285153"
+			"size"		"1073741824"
+		}
+	}
+	"UserConfig"
+	{
+		"BetaKey"		"public-test"
+	}
+}
+"#;
+
+const APP_INFO_PRINT: &str = r#"[server_ui] SteamCMD started (pid 4242)
+Redirecting stderr to '/home/steam/Steam/logs/stderr.txt'
+Loading Steam API...OK
+Connecting anonymously to Steam Public...OK
+Waiting for user info...OK
+Steam>AppID : 896660, change number : 25312345/0, last change : Tue Oct 15 10:00:00 2026
+"896660"
+{
+	"common"
+	{
+		"name"		"Valheim Dedicated Server"
+		"type"		"Tool"
+	}
+	"depots"
+	{
+		"896661"
+		{
+			"config"
+			{
+				"oslist"		"linux"
+			}
+		}
+		"branches"
+		{
+			"public"
+			{
+				"buildid"		"15900000"
+				"timeupdated"		"1729500000"
+			}
+			"public-test"
+			{
+				"buildid"		"15950000"
+				"description"		"Public test branch"
+				"pwdrequired"		"1"
+				"timeupdated"		"1729600000"
+			}
+		}
+	}
+}
+Steam>quit
+"#;
+
+#[test]
+fn test_manifest_reads_build_id_and_beta() {
+    let manifest = AppManifest::parse(APP_MANIFEST).unwrap();
+
+    assert_eq!(
+        manifest,
+        AppManifest {
+            build_id: 15810291,
+            beta_key: Some("public-test".to_string()),
+        }
+    );
+}
+
+#[test]
+fn test_manifest_without_beta() {
+    let contents = APP_MANIFEST.replace("\"BetaKey\"\t\t\"public-test\"", "");
+
+    assert_eq!(AppManifest::parse(&contents).unwrap().beta_key, None);
+}
+
+#[test]
+fn test_manifest_rejects_other_files() {
+    assert!(AppManifest::parse("\"LibraryFolders\" { \"buildid\" \"1\" }").is_none());
+    assert!(AppManifest::parse("not a manifest").is_none());
+}
+
+#[test]
+fn test_available_build_per_branch() {
+    assert_eq!(
+        available_build(APP_INFO_PRINT, 896660, DEFAULT_BRANCH),
+        Some(15900000)
+    );
+    assert_eq!(
+        available_build(APP_INFO_PRINT, 896660, "public-test"),
+        Some(15950000)
+    );
+    assert_eq!(available_build(APP_INFO_PRINT, 896660, "missing"), None);
+}
+
+#[test]
+fn test_available_build_needs_matching_app() {
+    assert_eq!(
+        available_build(APP_INFO_PRINT, 376030, DEFAULT_BRANCH),
+        None
+    );
+}
+
+#[test]
+fn test_available_build_with_truncated_output() {
+    let truncated = &APP_INFO_PRINT[..APP_INFO_PRINT.find("\"branches\"").unwrap()];
+
+    assert_eq!(available_build(truncated, 896660, DEFAULT_BRANCH), None);
+}
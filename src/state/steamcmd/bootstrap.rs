@@ -0,0 +1,207 @@
+use crate::utils::error_response;
+use rocket::{http::Status, response::Responder, Request};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use super::SteamCmdError;
+
+const DEFAULT_DOWNLOAD_URL: &str =
+    "https://steamcdn-a.akamaihd.net/client/installer/steamcmd_linux.tar.gz";
+
+/// The official tarball is a few megabytes; anything far larger is not it
+const MAX_DOWNLOAD_BYTES: usize = 64 * 1024 * 1024;
+
+/// Entry point inside the tarball, and inside the install directory once unpacked
+const ENTRY_POINT: &str = "steamcmd.sh";
+
+#[derive(Error, Debug)]
+pub enum BootstrapError {
+    #[error("SteamCMD is already installed and running")]
+    AlreadyInstalled,
+
+    #[error("A SteamCMD bootstrap is already in progress")]
+    InProgress,
+
+    #[error("Failed to download SteamCMD: {0}")]
+    Download(String),
+
+    #[error("Downloaded SteamCMD archive is larger than {} MiB", MAX_DOWNLOAD_BYTES / 1024 / 1024)]
+    TooLarge,
+
+    #[error("Checksum mismatch: expected {expected}, got {actual}")]
+    ChecksumMismatch { expected: String, actual: String },
+
+    #[error("Invalid SteamCMD archive: {0}")]
+    InvalidArchive(String),
+
+    #[error("Failed to install SteamCMD: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error(transparent)]
+    Init(#[from] SteamCmdError),
+}
+
+impl<'r> Responder<'r, 'static> for BootstrapError {
+    fn respond_to(self, req: &'r Request<'_>) -> rocket::response::Result<'static> {
+        let status = match self {
+            Self::AlreadyInstalled | Self::InProgress => Status::Conflict,
+            Self::Download(_)
+            | Self::TooLarge
+            | Self::ChecksumMismatch { .. }
+            | Self::InvalidArchive(_) => Status::BadGateway,
+            Self::Io(_) => Status::InternalServerError,
+            Self::Init(e) => return e.respond_to(req),
+        };
+
+        error_response(self, status)
+    }
+}
+
+/// Where the SteamCMD tarball is fetched from
+pub struct BootstrapConfig {
+    /// `http(s)://` URL, or `file://` for a local fixture
+    pub url: String,
+    /// Expected hex SHA-256 of the tarball. Valve rotates the file, so this is opt-in.
+    pub sha256: Option<String>,
+}
+
+impl BootstrapConfig {
+    /// `STEAMCMD_DOWNLOAD_URL` and `STEAMCMD_DOWNLOAD_SHA256`
+    pub fn from_env() -> Self {
+        let non_empty = |key| std::env::var(key).ok().filter(|v| !v.trim().is_empty());
+
+        Self {
+            url: non_empty("STEAMCMD_DOWNLOAD_URL")
+                .unwrap_or_else(|| DEFAULT_DOWNLOAD_URL.to_string()),
+            sha256: non_empty("STEAMCMD_DOWNLOAD_SHA256").map(|s| s.trim().to_lowercase()),
+        }
+    }
+}
+
+/// The SteamCMD entry point in `install_dir`, if a previous bootstrap put it there
+pub fn installed_path(install_dir: &Path) -> Option<PathBuf> {
+    let path = install_dir.join(ENTRY_POINT);
+    path.is_file().then_some(path)
+}
+
+async fn download(url: &str) -> Result<Vec<u8>, BootstrapError> {
+    let parsed = url::Url::parse(url).map_err(|e| BootstrapError::Download(e.to_string()))?;
+
+    if parsed.scheme() == "file" {
+        let path = parsed
+            .to_file_path()
+            .map_err(|_| BootstrapError::Download(format!("invalid file URL '{url}'")))?;
+        let data = tokio::fs::read(&path)
+            .await
+            .map_err(|e| BootstrapError::Download(format!("{}: {e}", path.display())))?;
+        if data.len() > MAX_DOWNLOAD_BYTES {
+            return Err(BootstrapError::TooLarge);
+        }
+        return Ok(data);
+    }
+
+    let mut response = reqwest::get(parsed)
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| BootstrapError::Download(e.to_string()))?;
+
+    let mut data = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| BootstrapError::Download(e.to_string()))?
+    {
+        if data.len() + chunk.len() > MAX_DOWNLOAD_BYTES {
+            return Err(BootstrapError::TooLarge);
+        }
+        data.extend_from_slice(&chunk);
+    }
+
+    Ok(data)
+}
+
+/// Unpack the gzipped tarball into `staging`, refusing anything but plain files and directories
+fn unpack(archive: &[u8], staging: &Path) -> Result<(), BootstrapError> {
+    let invalid = |e: std::io::Error| BootstrapError::InvalidArchive(e.to_string());
+    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(archive));
+    archive.set_preserve_permissions(true);
+
+    let mut found_entry_point = false;
+    for entry in archive.entries().map_err(invalid)? {
+        let mut entry = entry.map_err(invalid)?;
+        let entry_type = entry.header().entry_type();
+        if !(entry_type.is_file() || entry_type.is_dir()) {
+            let path = entry.path().map_err(invalid)?;
+            return Err(BootstrapError::InvalidArchive(format!(
+                "unsupported entry '{}'",
+                path.display()
+            )));
+        }
+
+        let path = entry.path().map_err(invalid)?.into_owned();
+        if !entry.unpack_in(staging).map_err(invalid)? {
+            return Err(BootstrapError::InvalidArchive(format!(
+                "entry '{}' escapes the install directory",
+                path.display()
+            )));
+        }
+        found_entry_point |= path.components().eq(Path::new(ENTRY_POINT).components());
+    }
+
+    if !found_entry_point {
+        return Err(BootstrapError::InvalidArchive(format!(
+            "{ENTRY_POINT} is missing"
+        )));
+    }
+
+    Ok(())
+}
+
+/// Download, verify and unpack SteamCMD into `install_dir`, replacing what was there.
+/// Returns the path of the entry point.
+pub async fn install(
+    config: &BootstrapConfig,
+    install_dir: &Path,
+) -> Result<PathBuf, BootstrapError> {
+    let archive = download(&config.url).await?;
+
+    if let Some(expected) = &config.sha256 {
+        let actual = Sha256::digest(&archive)
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect::<String>();
+        if &actual != expected {
+            return Err(BootstrapError::ChecksumMismatch {
+                expected: expected.clone(),
+                actual,
+            });
+        }
+    }
+
+    let install_dir = install_dir.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let staging = install_dir.with_extension("partial");
+        if staging.exists() {
+            std::fs::remove_dir_all(&staging)?;
+        }
+        std::fs::create_dir_all(&staging)?;
+
+        if let Err(e) = unpack(&archive, &staging) {
+            _ = std::fs::remove_dir_all(&staging);
+            return Err(e);
+        }
+
+        if install_dir.exists() {
+            std::fs::remove_dir_all(&install_dir)?;
+        }
+        std::fs::rename(&staging, &install_dir)?;
+
+        Ok(install_dir.join(ENTRY_POINT))
+    })
+    .await
+    .map_err(|e| BootstrapError::Io(std::io::Error::other(e)))?
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,150 @@
+use super::*;
+use crate::utils::TempDir;
+use std::io::Write;
+
+fn append_file(builder: &mut tar::Builder<impl Write>, path: &str, contents: &[u8]) {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(contents.len() as u64);
+    header.set_mode(0o755);
+    header.set_cksum();
+    builder.append_data(&mut header, path, contents).unwrap();
+}
+
+/// A tarball shaped like the official one
+fn fixture_tarball() -> Vec<u8> {
+    let mut builder = tar::Builder::new(Vec::new());
+    append_file(&mut builder, "steamcmd.sh", b"#!/bin/sh\necho steamcmd\n");
+    append_file(&mut builder, "linux32/steamcmd", b"\x7fELF");
+    gzip(&builder.into_inner().unwrap())
+}
+
+fn gzip(data: &[u8]) -> Vec<u8> {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+fn file_config(dir: &TempDir, tarball: &[u8], sha256: Option<String>) -> BootstrapConfig {
+    let path = dir.0.join("steamcmd_linux.tar.gz");
+    std::fs::write(&path, tarball).unwrap();
+
+    BootstrapConfig {
+        url: url::Url::from_file_path(&path).unwrap().to_string(),
+        sha256,
+    }
+}
+
+#[tokio::test]
+async fn test_install_unpacks_fixture() {
+    let dir = TempDir::new();
+    let tarball = fixture_tarball();
+    let config = file_config(&dir, &tarball, Some(sha256_hex(&tarball)));
+    let install_dir = dir.0.join("steamcmd");
+
+    let entry_point = install(&config, &install_dir).await.unwrap();
+
+    assert_eq!(entry_point, install_dir.join("steamcmd.sh"));
+    assert_eq!(installed_path(&install_dir), Some(entry_point.clone()));
+    assert!(install_dir.join("linux32/steamcmd").is_file());
+    assert!(!install_dir.with_extension("partial").exists());
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&entry_point)
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o111, 0o111, "entry point should stay executable");
+    }
+}
+
+#[tokio::test]
+async fn test_install_replaces_previous_install() {
+    let dir = TempDir::new();
+    let install_dir = dir.0.join("steamcmd");
+    std::fs::create_dir_all(&install_dir).unwrap();
+    std::fs::write(install_dir.join("stale"), b"old").unwrap();
+
+    let config = file_config(&dir, &fixture_tarball(), None);
+    install(&config, &install_dir).await.unwrap();
+
+    assert!(!install_dir.join("stale").exists());
+    assert!(install_dir.join("steamcmd.sh").is_file());
+}
+
+#[tokio::test]
+async fn test_install_rejects_checksum_mismatch() {
+    let dir = TempDir::new();
+    let config = file_config(&dir, &fixture_tarball(), Some("00".repeat(32)));
+    let install_dir = dir.0.join("steamcmd");
+
+    let err = install(&config, &install_dir).await.unwrap_err();
+
+    assert!(matches!(err, BootstrapError::ChecksumMismatch { .. }));
+    assert!(!install_dir.exists());
+}
+
+#[tokio::test]
+async fn test_install_rejects_archive_without_entry_point() {
+    let dir = TempDir::new();
+    let mut builder = tar::Builder::new(Vec::new());
+    append_file(&mut builder, "readme.txt", b"not steamcmd");
+    let config = file_config(&dir, &gzip(&builder.into_inner().unwrap()), None);
+    let install_dir = dir.0.join("steamcmd");
+
+    let err = install(&config, &install_dir).await.unwrap_err();
+
+    assert!(matches!(err, BootstrapError::InvalidArchive(_)));
+    assert!(!install_dir.exists());
+    assert!(!install_dir.with_extension("partial").exists());
+}
+
+#[tokio::test]
+async fn test_install_rejects_symlinks() {
+    let dir = TempDir::new();
+    let mut builder = tar::Builder::new(Vec::new());
+    append_file(&mut builder, "steamcmd.sh", b"#!/bin/sh\n");
+    let mut header = tar::Header::new_gnu();
+    header.set_entry_type(tar::EntryType::Symlink);
+    header.set_size(0);
+    builder
+        .append_link(&mut header, "linux32/escape", "/etc/passwd")
+        .unwrap();
+    let config = file_config(&dir, &gzip(&builder.into_inner().unwrap()), None);
+
+    let err = install(&config, &dir.0.join("steamcmd")).await.unwrap_err();
+
+    assert!(matches!(err, BootstrapError::InvalidArchive(_)));
+}
+
+#[tokio::test]
+async fn test_install_rejects_garbage() {
+    let dir = TempDir::new();
+    let config = file_config(&dir, b"<html>Not Found</html>", None);
+
+    let err = install(&config, &dir.0.join("steamcmd")).await.unwrap_err();
+
+    assert!(matches!(err, BootstrapError::InvalidArchive(_)));
+}
+
+#[tokio::test]
+async fn test_install_reports_missing_file() {
+    let config = BootstrapConfig {
+        url: "file:///nonexistent/steamcmd_linux.tar.gz".to_string(),
+        sha256: None,
+    };
+
+    let err = install(&config, Path::new("/nonexistent/steamcmd"))
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, BootstrapError::Download(_)));
+}
@@ -0,0 +1,90 @@
+use super::{login_signal, LoginSignal};
+use crate::dto::steamcmd::SteamCmdEvent;
+
+#[cfg(test)]
+mod tests;
+
+/// Text between the first pair of single quotes, e.g. the app id in `App '740' fully installed`
+fn quoted(text: &str) -> Option<&str> {
+    let (_, rest) = text.split_once('\'')?;
+    rest.split_once('\'').map(|(quoted, _)| quoted)
+}
+
+/// `Update state (0x61) downloading, progress: 42.13 (1234 / 2930)`
+fn progress(line: &str) -> Option<SteamCmdEvent> {
+    let (_, rest) = line.split_once("Update state (0x")?;
+    let (code, rest) = rest.split_once(')')?;
+    let (state, rest) = rest.split_once(", progress: ")?;
+    let (progress, rest) = rest.split_once(" (")?;
+    let (current, rest) = rest.split_once(" / ")?;
+    let (total, _) = rest.split_once(')')?;
+
+    Some(SteamCmdEvent::Progress {
+        code: u32::from_str_radix(code, 16).ok()?,
+        state: state.trim().to_string(),
+        progress: progress.trim().parse().ok()?,
+        current: current.trim().parse().ok()?,
+        total: total.trim().parse().ok()?,
+    })
+}
+
+/// `Success. Downloaded item 731604991 to "/path/to/content/346110/731604991" (1024 bytes)`
+fn item_downloaded(line: &str) -> Option<SteamCmdEvent> {
+    let (_, rest) = line.split_once("Downloaded item ")?;
+    let (item_id, rest) = rest.split_once(" to \"")?;
+    let (path, _) = rest.rsplit_once('"')?;
+
+    Some(SteamCmdEvent::ItemDownloaded {
+        item_id: item_id.trim().parse().ok()?,
+        path: path.to_string(),
+    })
+}
+
+/// `ERROR! Failed to install app '740' (No subscription)`
+fn error(line: &str) -> Option<SteamCmdEvent> {
+    let message = ["ERROR! ", "Error! "]
+        .iter()
+        .find_map(|prefix| line.split_once(prefix))
+        .map(|(_, message)| message.trim())?;
+    let reason = message
+        .trim_end_matches('.')
+        .strip_suffix(')')
+        .and_then(|m| m.rsplit_once('('))
+        .map(|(_, reason)| reason.to_string());
+
+    Some(SteamCmdEvent::Error {
+        message: message.to_string(),
+        reason,
+    })
+}
+
+/// Recognise a line of SteamCMD output. Lines with nothing of interest give `None`.
+pub fn parse(line: &str) -> Option<SteamCmdEvent> {
+    let line = line.trim();
+
+    if let Some(signal) = login_signal(line) {
+        return Some(match signal {
+            LoginSignal::LoggedIn => SteamCmdEvent::LoggedIn,
+            LoginSignal::Failed(reason) => SteamCmdEvent::LoginFailed { reason },
+            LoginSignal::SteamGuard(prompt) => SteamCmdEvent::SteamGuardRequired { prompt },
+        });
+    }
+    if line.contains("Update state (0x") {
+        return progress(line);
+    }
+    if let Some(rest) = line.strip_prefix("Success! App ") {
+        let app_id = quoted(rest)?.parse().ok()?;
+        if rest.contains("fully installed") {
+            return Some(SteamCmdEvent::AppInstalled { app_id });
+        }
+        if rest.contains("already up to date") {
+            return Some(SteamCmdEvent::AppUpToDate { app_id });
+        }
+        return None;
+    }
+    if line.starts_with("Success. Downloaded item ") {
+        return item_downloaded(line);
+    }
+
+    error(line)
+}
@@ -0,0 +1,138 @@
+use super::*;
+use crate::dto::steamcmd::SteamGuardPrompt;
+
+/// `app_update 896660 validate` on a fresh install, as printed by SteamCMD
+const APP_UPDATE: &str = r#"Redirecting stderr to '/home/steam/Steam/logs/stderr.txt'
+[  0%] Checking for available updates...
+[----] Verifying installation...
+Steam Console Client (c) Valve Corporation - version 1729804816
+-- type 'quit' to exit --
+Loading Steam API...OK
+Connecting anonymously to Steam Public...OK
+Waiting for client config...OK
+Waiting for user info...OK
+Steam>force_install_dir "/srv/data/instances/1"
+Steam>app_update 896660 validate
+ Update state (0x3) reconfiguring, progress: 0.00 (0 / 0)
+ Update state (0x61) downloading, progress: 0.00 (0 / 1073741824)
+ Update state (0x61) downloading, progress: 42.13 (452371234 / 1073741824)
+ Update state (0x81) verifying update, progress: 97.51 (1046999040 / 1073741824)
+Success! App '896660' fully installed.
+Steam>quit
+"#;
+
+/// A private app without a license, then a workshop download
+const FAILURES: &str = r#"Logging in user 'serverops' [U:1:0] to Steam Public...This computer has not been authenticated for your account using Steam Guard.
+Please check your email for the message from Steam, and enter the Steam Guard
+ code from that message.
+You can also enter this code at any time using 'set_steam_guard_code'
+ at the console.
+Steam Guard code:
+OK
+Waiting for client config...OK
+Waiting for user info...OK
+Steam>app_update 376030
+ERROR! Failed to install app '376030' (No subscription)
+Steam>app_update 896660
+Success! App '896660' already up to date.
+Steam>workshop_download_item 346110 731604991
+Downloading item 731604991 ...
+Success. Downloaded item 731604991 to "/srv/data/workshop/steamapps/workshop/content/346110/731604991" (1048576 bytes)
+Steam>workshop_download_item 346110 404
+ERROR! Download item 404 failed (File Not Found).
+"#;
+
+fn events(transcript: &str) -> Vec<SteamCmdEvent> {
+    transcript.lines().filter_map(parse).collect()
+}
+
+#[test]
+fn test_app_update_transcript() {
+    assert_eq!(
+        events(APP_UPDATE),
+        vec![
+            SteamCmdEvent::LoggedIn,
+            SteamCmdEvent::Progress {
+                code: 0x3,
+                state: "reconfiguring".to_string(),
+                progress: 0.0,
+                current: 0,
+                total: 0,
+            },
+            SteamCmdEvent::Progress {
+                code: 0x61,
+                state: "downloading".to_string(),
+                progress: 0.0,
+                current: 0,
+                total: 1073741824,
+            },
+            SteamCmdEvent::Progress {
+                code: 0x61,
+                state: "downloading".to_string(),
+                progress: 42.13,
+                current: 452371234,
+                total: 1073741824,
+            },
+            SteamCmdEvent::Progress {
+                code: 0x81,
+                state: "verifying update".to_string(),
+                progress: 97.51,
+                current: 1046999040,
+                total: 1073741824,
+            },
+            SteamCmdEvent::AppInstalled { app_id: 896660 },
+        ]
+    );
+}
+
+#[test]
+fn test_failures_transcript() {
+    assert_eq!(
+        events(FAILURES),
+        vec![
+            SteamCmdEvent::SteamGuardRequired {
+                prompt: SteamGuardPrompt::Email
+            },
+            SteamCmdEvent::LoggedIn,
+            SteamCmdEvent::Error {
+                message: "Failed to install app '376030' (No subscription)".to_string(),
+                reason: Some("No subscription".to_string()),
+            },
+            SteamCmdEvent::AppUpToDate { app_id: 896660 },
+            SteamCmdEvent::ItemDownloaded {
+                item_id: 731604991,
+                path: "/srv/data/workshop/steamapps/workshop/content/346110/731604991".to_string(),
+            },
+            SteamCmdEvent::Error {
+                message: "Download item 404 failed (File Not Found).".to_string(),
+                reason: Some("File Not Found".to_string()),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_login_failure() {
+    assert_eq!(
+        parse("Logging in user 'serverops' [U:1:0] to Steam Public...FAILED (Invalid Password)"),
+        Some(SteamCmdEvent::LoginFailed {
+            reason: "(Invalid Password)".to_string()
+        })
+    );
+    assert_eq!(
+        parse("FAILED login with result code Rate Limit Exceeded"),
+        Some(SteamCmdEvent::LoginFailed {
+            reason: "Rate Limit Exceeded".to_string()
+        })
+    );
+}
+
+#[test]
+fn test_malformed_progress_is_ignored() {
+    assert_eq!(parse(" Update state (0x61) downloading, progress: "), None);
+    assert_eq!(
+        parse(" Update state (0xzz) downloading, progress: 1.00 (1 / 2)"),
+        None
+    );
+    assert_eq!(parse("Success! App 'abc' fully installed."), None);
+}
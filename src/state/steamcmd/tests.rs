@@ -0,0 +1,137 @@
+use super::*;
+
+#[test]
+fn test_login_signal_classifies_login_output() {
+    assert_eq!(
+        login_signal("Logging in user 'someone' [U:1:0] to Steam Public...OK"),
+        None
+    );
+    assert_eq!(
+        login_signal("Waiting for user info...OK"),
+        Some(LoginSignal::LoggedIn)
+    );
+    assert_eq!(login_signal("Logged in OK\n"), Some(LoginSignal::LoggedIn));
+    assert_eq!(
+        login_signal(
+            "Please check your email for the message from Steam, and enter the Steam Guard code:"
+        ),
+        Some(LoginSignal::SteamGuard(SteamGuardPrompt::Email))
+    );
+    assert_eq!(
+        login_signal(
+            "Please confirm the login in the Steam Mobile app on your phone.\nTwo-factor code:"
+        ),
+        Some(LoginSignal::SteamGuard(SteamGuardPrompt::TwoFactor))
+    );
+}
+
+#[test]
+fn test_login_signal_reports_failures_with_their_reason() {
+    assert_eq!(
+        login_signal("FAILED login with result code Invalid Password"),
+        Some(LoginSignal::Failed("Invalid Password".to_string()))
+    );
+    assert_eq!(
+        login_signal("Logging in user 'someone' to Steam Public...FAILED (Rate Limit Exceeded)"),
+        Some(LoginSignal::Failed("(Rate Limit Exceeded)".to_string()))
+    );
+}
+
+#[test]
+fn test_login_signal_ignores_other_output() {
+    for line in [
+        "",
+        "Redirecting stderr to '/root/Steam/logs/stderr.txt'",
+        "Loading Steam API...OK",
+        "Steam>",
+        "Success! App '896660' fully installed.",
+        "Error! App '896660' state is 0x202 after update job.",
+    ] {
+        assert_eq!(login_signal(line), None, "{line:?}");
+    }
+}
+
+#[test]
+fn test_validate_command_accepts_plain_commands() {
+    for command in [
+        "app_update 896660 validate",
+        "app_info_print 896660",
+        "+force_install_dir /srv/game",
+        "workshop_download_item 346110 123456",
+        "logout_extra",
+    ] {
+        assert!(validate_command(command).is_ok(), "{command:?}");
+    }
+}
+
+#[test]
+fn test_validate_command_rejects_logins_quits_and_line_breaks() {
+    for command in [
+        "",
+        "   ",
+        "login someone hunter2",
+        "LOGIN anonymous",
+        "+login anonymous",
+        "logout",
+        "quit",
+        "++exit",
+        "app_update 896660\nquit",
+        "app_update 896660\rquit",
+        "app_update\t896660\0",
+    ] {
+        assert!(
+            matches!(
+                validate_command(command),
+                Err(SteamCmdError::InvalidCommand(_))
+            ),
+            "{command:?}"
+        );
+    }
+}
+
+#[test]
+fn test_respawn_backoff_doubles_up_to_its_cap() {
+    let mut backoff = RespawnBackoff::new(
+        Duration::from_secs(1),
+        Duration::from_secs(10),
+        Duration::from_secs(60),
+    );
+    let crashed = Duration::from_secs(2);
+
+    let delays: Vec<u64> = (0..6).map(|_| backoff.delay(crashed).as_secs()).collect();
+    assert_eq!(delays, vec![1, 2, 4, 8, 10, 10]);
+}
+
+#[test]
+fn test_respawn_backoff_resets_after_a_stable_run() {
+    let mut backoff = RespawnBackoff::new(
+        Duration::from_secs(1),
+        Duration::from_secs(10),
+        Duration::from_secs(60),
+    );
+    for _ in 0..4 {
+        backoff.delay(Duration::ZERO);
+    }
+
+    assert_eq!(
+        backoff.delay(Duration::from_secs(59)),
+        Duration::from_secs(10)
+    );
+    assert_eq!(
+        backoff.delay(Duration::from_secs(60)),
+        Duration::from_secs(1)
+    );
+    assert_eq!(backoff.delay(Duration::ZERO), Duration::from_secs(2));
+}
+
+#[test]
+fn test_default_respawn_backoff() {
+    let mut backoff = RespawnBackoff::default();
+
+    assert_eq!(backoff.delay(Duration::ZERO), RESPAWN_BACKOFF_INITIAL);
+    for _ in 0..10 {
+        backoff.delay(Duration::ZERO);
+    }
+    assert_eq!(backoff.delay(Duration::ZERO), RESPAWN_BACKOFF_MAX);
+    assert_eq!(backoff.delay(RESPAWN_STABLE_AFTER), RESPAWN_BACKOFF_INITIAL);
+}
@@ -0,0 +1,125 @@
+//! Minimal reader for Valve's KeyValues text format, as used by `appmanifest_*.acf` files
+//! and `app_info_print` output.
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Vdf {
+    Value(String),
+    Object(Vec<(String, Vdf)>),
+}
+
+impl Vdf {
+    /// Child of an object by key. Keys are matched case-insensitively like Steam does.
+    pub fn get(&self, key: &str) -> Option<&Vdf> {
+        match self {
+            Vdf::Object(entries) => entries
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(key))
+                .map(|(_, v)| v),
+            Vdf::Value(_) => None,
+        }
+    }
+
+    /// Follow a path of keys, e.g. `["depots", "branches", "public", "buildid"]`
+    pub fn path(&self, keys: &[&str]) -> Option<&Vdf> {
+        keys.iter().try_fold(self, |node, key| node.get(key))
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Vdf::Value(value) => Some(value),
+            Vdf::Object(_) => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Text(String),
+    Open,
+    Close,
+}
+
+fn tokenize(input: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '{' => tokens.push(Token::Open),
+            '}' => tokens.push(Token::Close),
+            '"' => {
+                let mut text = String::new();
+                while let Some(ch) = chars.next() {
+                    match ch {
+                        '"' => break,
+                        '\\' => match chars.next() {
+                            Some('n') => text.push('\n'),
+                            Some('t') => text.push('\t'),
+                            Some(other) => text.push(other),
+                            None => break,
+                        },
+                        _ => text.push(ch),
+                    }
+                }
+                tokens.push(Token::Text(text));
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                for ch in chars.by_ref() {
+                    if ch == '\n' {
+                        break;
+                    }
+                }
+            }
+            ch if ch.is_whitespace() => {}
+            _ => {
+                let mut text = ch.to_string();
+                while let Some(&next) = chars.peek() {
+                    if next.is_whitespace() || matches!(next, '{' | '}' | '"') {
+                        break;
+                    }
+                    text.push(next);
+                    chars.next();
+                }
+                tokens.push(Token::Text(text));
+            }
+        }
+    }
+
+    tokens
+}
+
+fn parse_object(tokens: &mut std::iter::Peekable<std::vec::IntoIter<Token>>) -> Option<Vdf> {
+    let mut entries = Vec::new();
+
+    loop {
+        match tokens.next()? {
+            Token::Close => return Some(Vdf::Object(entries)),
+            Token::Text(key) => {
+                let value = match tokens.next()? {
+                    Token::Text(value) => Vdf::Value(value),
+                    Token::Open => parse_object(tokens)?,
+                    Token::Close => return None,
+                };
+                entries.push((key, value));
+            }
+            Token::Open => return None,
+        }
+    }
+}
+
+/// Parse the first `"key" { ... }` block in `input`, ignoring anything after it.
+/// Returns the key and its object, or `None` if the input is truncated or malformed.
+pub fn parse(input: &str) -> Option<(String, Vdf)> {
+    let mut tokens = tokenize(input).into_iter().peekable();
+
+    let Token::Text(key) = tokens.next()? else {
+        return None;
+    };
+    match tokens.next()? {
+        Token::Open => Some((key, parse_object(&mut tokens)?)),
+        _ => None,
+    }
+}
@@ -0,0 +1,62 @@
+use super::*;
+
+#[test]
+fn test_parse_nested_objects() {
+    let (key, root) = parse(
+        r#"
+"AppState"
+{
+	"appid"		"376030"
+	"buildid"		"12345678"
+	"UserConfig"
+	{
+		"BetaKey"		"beta"
+	}
+}
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(key, "AppState");
+    assert_eq!(root.get("buildid").and_then(Vdf::as_str), Some("12345678"));
+    assert_eq!(
+        root.path(&["userconfig", "betakey"]).and_then(Vdf::as_str),
+        Some("beta")
+    );
+    assert!(root.get("missing").is_none());
+}
+
+#[test]
+fn test_parse_escapes_comments_and_bare_tokens() {
+    let (_, root) = parse(
+        r#"root {
+    // a comment
+    "quoted"  "say \"hi\"\n"
+    bare      value
+}"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        root.get("quoted").and_then(Vdf::as_str),
+        Some("say \"hi\"\n")
+    );
+    assert_eq!(root.get("bare").and_then(Vdf::as_str), Some("value"));
+}
+
+#[test]
+fn test_parse_ignores_trailing_output() {
+    let (_, root) = parse("\"a\" { \"b\" \"c\" }\nSteam>quit\n").unwrap();
+
+    assert_eq!(
+        root,
+        Vdf::Object(vec![("b".into(), Vdf::Value("c".into()))])
+    );
+}
+
+#[test]
+fn test_parse_rejects_truncated_input() {
+    assert!(parse("\"a\" { \"b\" { \"c\" \"d\" }").is_none());
+    assert!(parse("\"a\" \"b\"").is_none());
+    assert!(parse("").is_none());
+}
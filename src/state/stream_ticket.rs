@@ -0,0 +1,109 @@
+use crate::auth::guards::AccessTokenGuard;
+use crate::utils::error_response;
+use rand::{distributions::Alphanumeric, thread_rng, Rng};
+use rocket::{http::Status, response::Responder, Request};
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use thiserror::Error;
+
+#[cfg(test)]
+mod tests;
+
+/// Routes that browsers open with `EventSource`/`WebSocket`, which cannot send an
+/// `Authorization` header. Only these accept a `?ticket=` query parameter.
+/// A `*` segment matches any single path segment.
+pub const STREAM_ROUTES: &[&str] = &[
+    "/api/steamcmd/stdout",
+    "/api/steamcmd/sessions/*/stdout",
+    "/api/instance/*/stdout",
+    "/api/steamcmd/console",
+    "/api/steamcmd/sessions/*/console",
+    "/api/instance/*/console",
+    "/api/instance/*/status/events",
+    "/api/instance/*/metrics/events",
+];
+
+/// How long an unredeemed ticket stays valid
+const TICKET_TTL: Duration = Duration::from_secs(30);
+
+#[derive(Error, Debug)]
+pub enum StreamTicketError {
+    #[error("'{0}' is not a stream route")]
+    UnknownRoute(String),
+}
+
+impl<'r> Responder<'r, 'static> for StreamTicketError {
+    fn respond_to(self, _: &'r Request<'_>) -> rocket::response::Result<'static> {
+        error_response(self, Status::UnprocessableEntity)
+    }
+}
+
+fn route_matches(route: &str, path: &str) -> bool {
+    let mut route = route.split('/');
+    let mut path = path.split('/');
+
+    loop {
+        match (route.next(), path.next()) {
+            (None, None) => return true,
+            (Some("*"), Some(segment)) if !segment.is_empty() => {}
+            (Some(expected), Some(segment)) if expected == segment => {}
+            _ => return false,
+        }
+    }
+}
+
+struct Ticket {
+    session: AccessTokenGuard,
+    path: String,
+    expires_at: Instant,
+}
+
+/// Short-lived, single-use tickets exchanged for an access token so that streaming routes
+/// never need the token itself in the URL
+#[derive(Default)]
+pub struct StreamTickets {
+    tickets: Mutex<HashMap<String, Ticket>>,
+}
+
+impl StreamTickets {
+    pub fn issue(
+        &self,
+        session: AccessTokenGuard,
+        path: &str,
+    ) -> Result<(String, Duration), StreamTicketError> {
+        if !STREAM_ROUTES.iter().any(|route| route_matches(route, path)) {
+            return Err(StreamTicketError::UnknownRoute(path.to_string()));
+        }
+
+        let ticket: String = thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(32)
+            .map(char::from)
+            .collect();
+        let now = Instant::now();
+
+        let mut tickets = self.tickets.lock().unwrap_or_else(|e| e.into_inner());
+        tickets.retain(|_, t| t.expires_at > now);
+        tickets.insert(
+            ticket.clone(),
+            Ticket {
+                session,
+                path: path.to_string(),
+                expires_at: now + TICKET_TTL,
+            },
+        );
+
+        Ok((ticket, TICKET_TTL))
+    }
+
+    /// Consume a ticket. It is removed even when presented on the wrong route.
+    pub fn redeem(&self, ticket: &str, path: &str) -> Option<AccessTokenGuard> {
+        let mut tickets = self.tickets.lock().unwrap_or_else(|e| e.into_inner());
+        let ticket = tickets.remove(ticket)?;
+
+        (ticket.path == path && ticket.expires_at > Instant::now()).then_some(ticket.session)
+    }
+}
@@ -0,0 +1,105 @@
+use super::*;
+use crate::models::user::UserRole;
+
+fn session() -> AccessTokenGuard {
+    AccessTokenGuard {
+        user_id: 1,
+        username: "admin".to_string(),
+        role: UserRole::Admin,
+        api_token_id: None,
+        scopes: Vec::new(),
+    }
+}
+
+#[test]
+fn test_route_matches_single_segment_wildcards() {
+    assert!(route_matches(
+        "/api/instance/*/stdout",
+        "/api/instance/3/stdout"
+    ));
+    assert!(route_matches(
+        "/api/steamcmd/stdout",
+        "/api/steamcmd/stdout"
+    ));
+
+    assert!(!route_matches(
+        "/api/instance/*/stdout",
+        "/api/instance//stdout"
+    ));
+    assert!(!route_matches(
+        "/api/instance/*/stdout",
+        "/api/instance/3/4/stdout"
+    ));
+    assert!(!route_matches(
+        "/api/instance/*/stdout",
+        "/api/instance/3/stdout/x"
+    ));
+    assert!(!route_matches(
+        "/api/instance/*/stdout",
+        "/api/instance/3/stdouts"
+    ));
+    assert!(!route_matches(
+        "/api/steamcmd/stdout",
+        "/api/steamcmd/stdout/extra"
+    ));
+}
+
+#[test]
+fn test_tickets_are_only_issued_for_stream_routes() {
+    let tickets = StreamTickets::default();
+
+    assert!(tickets.issue(session(), "/api/instance/3/console").is_ok());
+    assert!(matches!(
+        tickets.issue(session(), "/api/instance/3"),
+        Err(StreamTicketError::UnknownRoute(_))
+    ));
+    assert!(matches!(
+        tickets.issue(session(), "/api/instance/3/stdout/../delete"),
+        Err(StreamTicketError::UnknownRoute(_))
+    ));
+}
+
+#[test]
+fn test_ticket_is_single_use() {
+    let tickets = StreamTickets::default();
+    let (ticket, _) = tickets.issue(session(), "/api/instance/3/stdout").unwrap();
+
+    let redeemed = tickets.redeem(&ticket, "/api/instance/3/stdout").unwrap();
+    assert_eq!(redeemed.user_id, 1);
+    assert!(tickets.redeem(&ticket, "/api/instance/3/stdout").is_none());
+}
+
+#[test]
+fn test_ticket_only_works_on_its_own_route() {
+    let tickets = StreamTickets::default();
+
+    for other in [
+        "/api/instance/4/stdout",
+        "/api/instance/3/console",
+        "/api/instance/3",
+        "/api/instance/3/stdout/extra",
+        "/api/instance/31/stdout",
+    ] {
+        let (ticket, _) = tickets.issue(session(), "/api/instance/3/stdout").unwrap();
+        assert!(tickets.redeem(&ticket, other).is_none(), "{other}");
+        // Presenting it on the wrong route used it up
+        assert!(tickets.redeem(&ticket, "/api/instance/3/stdout").is_none());
+    }
+}
+
+#[test]
+fn test_expired_ticket_is_rejected() {
+    let tickets = StreamTickets::default();
+    let (ticket, ttl) = tickets.issue(session(), "/api/instance/3/stdout").unwrap();
+    assert_eq!(ttl, Duration::from_secs(30));
+
+    tickets
+        .tickets
+        .lock()
+        .unwrap()
+        .get_mut(&ticket)
+        .unwrap()
+        .expires_at = Instant::now();
+
+    assert!(tickets.redeem(&ticket, "/api/instance/3/stdout").is_none());
+}
@@ -0,0 +1,62 @@
+use crate::dto::instance::TriggerFired;
+use crate::schema::server_config::{LogTrigger, TriggerEvent};
+use regex::Regex;
+
+#[cfg(test)]
+mod tests;
+
+/// The compiled triggers of a schema
+#[derive(Default)]
+pub struct Triggers {
+    triggers: Vec<(Regex, TriggerEvent, Option<String>)>,
+}
+
+impl Triggers {
+    pub fn new(triggers: &[LogTrigger]) -> Result<Self, regex::Error> {
+        Ok(Self {
+            triggers: triggers
+                .iter()
+                .map(|trigger| {
+                    Ok((
+                        Regex::new(&trigger.pattern)?,
+                        trigger.event,
+                        trigger.name.clone(),
+                    ))
+                })
+                .collect::<Result<_, regex::Error>>()?,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.triggers.is_empty()
+    }
+
+    pub fn has(&self, event: TriggerEvent) -> bool {
+        self.triggers.iter().any(|(_, e, _)| *e == event)
+    }
+
+    /// Every trigger a console line matches, in schema order. `line` is plain text, without
+    /// colour codes or its line break.
+    pub fn matches(&self, line: &str) -> Vec<TriggerFired> {
+        self.triggers
+            .iter()
+            .filter_map(|(regex, event, name)| {
+                let captures = regex.captures(line)?;
+                Some(TriggerFired {
+                    event: *event,
+                    name: name.clone(),
+                    captures: regex
+                        .capture_names()
+                        .flatten()
+                        .filter_map(|group| {
+                            let value = captures.name(group)?;
+                            Some((group.to_string(), value.as_str().to_string()))
+                        })
+                        .collect(),
+                    line: line.to_string(),
+                    fired_at: chrono::Utc::now(),
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,64 @@
+use super::*;
+use serde_json::json;
+
+fn triggers(value: serde_json::Value) -> Triggers {
+    let triggers: Vec<LogTrigger> = serde_json::from_value(value).unwrap();
+    Triggers::new(&triggers).unwrap()
+}
+
+#[test]
+fn test_matches_report_named_groups() {
+    let triggers = triggers(json!([
+        { "pattern": r"^Player (?P<name>\w+) joined(?: \((?P<steamId>\d+)\))?", "event": "player_join" },
+        { "pattern": "joined", "event": "custom", "name": "anyJoin" }
+    ]));
+
+    let fired = triggers.matches("Player alice joined");
+    assert_eq!(fired.len(), 2);
+    assert_eq!(fired[0].event, TriggerEvent::PlayerJoin);
+    // Groups that did not take part in the match are left out
+    assert_eq!(
+        fired[0].captures.clone().into_iter().collect::<Vec<_>>(),
+        vec![("name".to_string(), "alice".to_string())]
+    );
+    assert_eq!(fired[1].name.as_deref(), Some("anyJoin"));
+
+    assert_eq!(
+        triggers.matches("Player bob joined (7656)")[0]
+            .captures
+            .get("steamId")
+            .map(String::as_str),
+        Some("7656")
+    );
+    assert!(triggers.matches("Loading map").is_empty());
+}
+
+#[test]
+fn test_has_ready_trigger() {
+    let triggers = triggers(json!([{ "pattern": "^Server started", "event": "ready" }]));
+
+    assert!(triggers.has(TriggerEvent::Ready));
+    assert!(!triggers.has(TriggerEvent::Error));
+    assert!(Triggers::default().is_empty());
+}
+
+#[test]
+fn test_schema_validates_triggers() {
+    let schema: crate::schema::server_config::ServerConfig = serde_json::from_value(json!({
+        "steamAppId": 232250,
+        "executableName": "srcds_run",
+        "displayName": "TF2",
+        "triggers": [
+            { "pattern": r"(?P<player>\w+) joined", "event": "player_join" },
+            { "pattern": "(", "event": "error", "name": "broken" },
+            { "pattern": "Saved", "event": "custom" }
+        ]
+    }))
+    .unwrap();
+
+    let errors = schema.validate().unwrap_err();
+    assert_eq!(errors.len(), 3);
+    assert_eq!(errors[0], "Trigger #1 needs a 'name' group");
+    assert!(errors[1].starts_with("Trigger broken pattern is invalid"));
+    assert_eq!(errors[2], "Custom trigger #3 needs a name");
+}
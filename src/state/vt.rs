@@ -0,0 +1,304 @@
+//! A small VT100/ANSI interpreter for console output. Each line is replayed onto a single row
+//! of cells so carriage returns, erases and cursor movement end up as the text a terminal would
+//! show, and colours can be kept as SGR codes or turned into [`ConsoleSpan`]s.
+
+use crate::dto::instance::ConsoleSpan;
+use std::fmt::Write;
+use std::iter::Peekable;
+use std::str::Chars;
+
+#[cfg(test)]
+mod tests;
+
+const ESC: char = '\x1b';
+const BEL: char = '\x07';
+
+const COLOR_NAMES: [&str; 8] = [
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Color {
+    /// One of the 256 xterm palette entries; the first 16 are the themeable ANSI colours
+    Indexed(u8),
+    Rgb(u8, u8, u8),
+}
+
+impl Color {
+    fn css(self) -> String {
+        let (r, g, b) = match self {
+            Color::Indexed(n) if n < 8 => return COLOR_NAMES[n as usize].to_string(),
+            Color::Indexed(n) if n < 16 => {
+                let name = COLOR_NAMES[n as usize - 8];
+                return format!("bright{}{}", name[..1].to_uppercase(), &name[1..]);
+            }
+            Color::Indexed(n) if n < 232 => {
+                let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+                let n = n - 16;
+                (level(n / 36), level(n / 6 % 6), level(n % 6))
+            }
+            Color::Indexed(n) => {
+                let gray = 8 + (n - 232) * 10;
+                (gray, gray, gray)
+            }
+            Color::Rgb(r, g, b) => (r, g, b),
+        };
+        format!("#{r:02x}{g:02x}{b:02x}")
+    }
+
+    /// SGR parameters selecting this colour; `base` is 30 for foreground, 40 for background
+    fn sgr(self, base: u8, out: &mut String) {
+        match self {
+            Color::Indexed(n) if n < 8 => _ = write!(out, ";{}", base + n),
+            Color::Indexed(n) if n < 16 => _ = write!(out, ";{}", base + 60 + n - 8),
+            Color::Indexed(n) => _ = write!(out, ";{};5;{n}", base + 8),
+            Color::Rgb(r, g, b) => _ = write!(out, ";{};2;{r};{g};{b}", base + 8),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct Style {
+    fg: Option<Color>,
+    bg: Option<Color>,
+    bold: bool,
+    dim: bool,
+    italic: bool,
+    underline: bool,
+    inverse: bool,
+}
+
+impl Style {
+    /// Parse `38;5;n` or `38;2;r;g;b` after the 38/48 that introduced it
+    fn extended_color(params: &mut impl Iterator<Item = u16>) -> Option<Color> {
+        match params.next()? {
+            5 => Some(Color::Indexed(params.next()?.min(255) as u8)),
+            2 => {
+                let mut channel = || params.next().map(|v| v.min(255) as u8);
+                Some(Color::Rgb(channel()?, channel()?, channel()?))
+            }
+            _ => None,
+        }
+    }
+
+    fn apply_sgr(&mut self, params: &[u16]) {
+        if params.is_empty() {
+            *self = Style::default();
+            return;
+        }
+
+        let mut params = params.iter().copied();
+        while let Some(param) = params.next() {
+            match param {
+                0 => *self = Style::default(),
+                1 => self.bold = true,
+                2 => self.dim = true,
+                3 => self.italic = true,
+                4 => self.underline = true,
+                7 => self.inverse = true,
+                22 => (self.bold, self.dim) = (false, false),
+                23 => self.italic = false,
+                24 => self.underline = false,
+                27 => self.inverse = false,
+                30..=37 => self.fg = Some(Color::Indexed((param - 30) as u8)),
+                38 => self.fg = Self::extended_color(&mut params),
+                39 => self.fg = None,
+                40..=47 => self.bg = Some(Color::Indexed((param - 40) as u8)),
+                48 => self.bg = Self::extended_color(&mut params),
+                49 => self.bg = None,
+                90..=97 => self.fg = Some(Color::Indexed((param - 90 + 8) as u8)),
+                100..=107 => self.bg = Some(Color::Indexed((param - 100 + 8) as u8)),
+                _ => {}
+            }
+        }
+    }
+
+    /// The shortest SGR sequence switching from the default style to this one
+    fn sgr(&self) -> String {
+        let mut out = String::from("\x1b[0");
+        for (set, code) in [
+            (self.bold, 1),
+            (self.dim, 2),
+            (self.italic, 3),
+            (self.underline, 4),
+            (self.inverse, 7),
+        ] {
+            if set {
+                _ = write!(out, ";{code}");
+            }
+        }
+        if let Some(fg) = self.fg {
+            fg.sgr(30, &mut out);
+        }
+        if let Some(bg) = self.bg {
+            bg.sgr(40, &mut out);
+        }
+        out.push('m');
+        out
+    }
+}
+
+/// One row of the terminal with a cursor
+#[derive(Default)]
+struct Row {
+    cells: Vec<(char, Style)>,
+    cursor: usize,
+    style: Style,
+}
+
+impl Row {
+    fn put(&mut self, ch: char) {
+        if self.cursor < self.cells.len() {
+            self.cells[self.cursor] = (ch, self.style);
+        } else {
+            self.cells.resize(self.cursor, (' ', Style::default()));
+            self.cells.push((ch, self.style));
+        }
+        self.cursor += 1;
+    }
+
+    fn erase(&mut self, mode: u16) {
+        match mode {
+            0 => self.cells.truncate(self.cursor),
+            1 => {
+                let end = (self.cursor + 1).min(self.cells.len());
+                self.cells[..end].fill((' ', Style::default()));
+            }
+            _ => self.cells.clear(),
+        }
+    }
+
+    fn csi(&mut self, chars: &mut Peekable<Chars>) {
+        let mut raw = String::new();
+        let final_byte = loop {
+            match chars.next() {
+                // Parameter and intermediate bytes
+                Some(ch @ '\x20'..='\x3f') => raw.push(ch),
+                Some(ch @ '\x40'..='\x7e') => break ch,
+                // Malformed or cut off; drop the sequence
+                _ => return,
+            }
+        };
+        if raw.starts_with(['?', '<', '=', '>']) {
+            // Private modes such as cursor visibility change nothing in the text
+            return;
+        }
+
+        let params: Vec<u16> = raw
+            .split(';')
+            .filter(|p| !p.is_empty())
+            .map(|p| p.parse().unwrap_or(0))
+            .collect();
+        let first = params.first().copied().unwrap_or(0);
+        let count = first.max(1) as usize;
+
+        match final_byte {
+            'm' => self.style.apply_sgr(&params),
+            'K' => self.erase(first),
+            'J' => self.erase(if first == 0 { 0 } else { 2 }),
+            'G' | '`' => self.cursor = count - 1,
+            'H' | 'f' => self.cursor = params.get(1).copied().unwrap_or(1).max(1) as usize - 1,
+            'C' | 'a' => self.cursor += count,
+            'D' => self.cursor = self.cursor.saturating_sub(count),
+            // Moving to the start of another line; only the column can be kept
+            'E' | 'F' => self.cursor = 0,
+            _ => {}
+        }
+    }
+
+    /// Skip an OSC, DCS or similar string up to its BEL or `ESC \` terminator
+    fn skip_string(chars: &mut Peekable<Chars>) {
+        while let Some(ch) = chars.next() {
+            match ch {
+                BEL => return,
+                ESC if chars.peek() == Some(&'\\') => {
+                    chars.next();
+                    return;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn feed(&mut self, input: &str) {
+        let mut chars = input.chars().peekable();
+        while let Some(ch) = chars.next() {
+            match ch {
+                ESC => match chars.next() {
+                    Some('[') => self.csi(&mut chars),
+                    Some(']' | 'P' | 'X' | '^' | '_') => Self::skip_string(&mut chars),
+                    // Character set selection takes one more byte
+                    Some('(' | ')' | '*' | '+' | '#' | '%') => _ = chars.next(),
+                    _ => {}
+                },
+                '\r' => self.cursor = 0,
+                '\x08' => self.cursor = self.cursor.saturating_sub(1),
+                '\t' => self.put('\t'),
+                ch if ch.is_control() => {}
+                ch => self.put(ch),
+            }
+        }
+    }
+
+    /// Runs of cells sharing a style
+    fn runs(&self) -> impl Iterator<Item = (Style, String)> + '_ {
+        self.cells
+            .chunk_by(|a, b| a.1 == b.1)
+            .map(|run| (run[0].1, run.iter().map(|(ch, _)| *ch).collect()))
+    }
+}
+
+fn render(line: &str) -> Row {
+    let mut row = Row::default();
+    row.feed(line);
+    row
+}
+
+/// The text a terminal would show for `line`, without any escape sequences
+pub fn plain(line: &str) -> String {
+    render(line).cells.iter().map(|(ch, _)| *ch).collect()
+}
+
+/// `line` reduced to its visible text plus SGR colour codes. Cursor movement, erases, OSC
+/// titles and carriage return rewrites are resolved; feeding the result to [`plain`] or
+/// [`spans`] gives the same as the original.
+pub fn sanitize(line: &str) -> String {
+    let mut out = String::new();
+    let mut styled = false;
+    for (style, text) in render(line).runs() {
+        if style != Style::default() {
+            out.push_str(&style.sgr());
+            styled = true;
+        } else if styled {
+            out.push_str("\x1b[0m");
+            styled = false;
+        }
+        out.push_str(&text);
+    }
+    if styled {
+        out.push_str("\x1b[0m");
+    }
+    out
+}
+
+/// Split `line` into styled runs of text for rendering a coloured console
+pub fn spans(line: &str) -> Vec<ConsoleSpan> {
+    render(line)
+        .runs()
+        .map(|(style, text)| {
+            let (fg, bg) = match style.inverse {
+                true => (style.bg, style.fg),
+                false => (style.fg, style.bg),
+            };
+            ConsoleSpan {
+                text,
+                fg: fg.map(Color::css),
+                bg: bg.map(Color::css),
+                bold: style.bold,
+                dim: style.dim,
+                italic: style.italic,
+                underline: style.underline,
+            }
+        })
+        .collect()
+}
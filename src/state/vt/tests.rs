@@ -0,0 +1,93 @@
+use super::*;
+
+fn span(text: &str, fg: Option<&str>) -> ConsoleSpan {
+    ConsoleSpan {
+        text: text.to_string(),
+        fg: fg.map(String::from),
+        bg: None,
+        bold: false,
+        dim: false,
+        italic: false,
+        underline: false,
+    }
+}
+
+#[test]
+fn test_plain_text_is_unchanged() {
+    assert_eq!(plain("Loading Steam API...OK"), "Loading Steam API...OK");
+    assert_eq!(sanitize("Loading Steam API...OK"), "Loading Steam API...OK");
+}
+
+#[test]
+fn test_cursor_sequences_do_not_swallow_text() {
+    // The old stripper only ended escapes on `m` and ate everything up to the next one
+    assert_eq!(plain("\x1b[2Kprogress \x1b[1Adone"), "progress done");
+    assert_eq!(plain("\x1b[?25lhidden cursor\x1b[?25h"), "hidden cursor");
+    assert_eq!(plain("\x1b(Bcharset"), "charset");
+}
+
+#[test]
+fn test_osc_titles_are_dropped() {
+    assert_eq!(
+        plain("\x1b]0;Valheim server\x07Game started"),
+        "Game started"
+    );
+    assert_eq!(plain("\x1b]2;title\x1b\\Game started"), "Game started");
+    assert_eq!(plain("unterminated \x1b]0;title"), "unterminated ");
+}
+
+#[test]
+fn test_carriage_return_overwrites() {
+    assert_eq!(plain("10%\r20%\r30%"), "30%");
+    assert_eq!(plain("Downloading 100%\rDone"), "Doneloading 100%");
+    assert_eq!(plain("Downloading 100%\r\x1b[2KDone"), "Done");
+    assert_eq!(plain("Downloading 100%\r\x1b[KDone"), "Done");
+    assert_eq!(plain("abc\x08\x08X"), "aXc");
+}
+
+#[test]
+fn test_cursor_columns() {
+    assert_eq!(plain("name\x1b[10Gvalue"), "name     value");
+    assert_eq!(plain("abcdef\x1b[3DXY"), "abcXYf");
+    assert_eq!(plain("abcdef\x1b[3G\x1b[1K"), "   def");
+}
+
+#[test]
+fn test_colours_become_spans() {
+    let line = "\x1b[32mINFO\x1b[0m Server \x1b[1;91mERROR\x1b[39m still bold";
+    let mut error = span("ERROR", Some("brightRed"));
+    error.bold = true;
+    let mut bold = span(" still bold", None);
+    bold.bold = true;
+
+    assert_eq!(
+        spans(line),
+        vec![
+            span("INFO", Some("green")),
+            span(" Server ", None),
+            error,
+            bold
+        ]
+    );
+}
+
+#[test]
+fn test_extended_and_inverse_colours() {
+    let rendered = spans("\x1b[38;5;196mA\x1b[38;2;1;2;3;48;5;244mB\x1b[0;7;34mC");
+
+    assert_eq!(rendered[0].fg.as_deref(), Some("#ff0000"));
+    assert_eq!(rendered[1].fg.as_deref(), Some("#010203"));
+    assert_eq!(rendered[1].bg.as_deref(), Some("#808080"));
+    assert_eq!(rendered[2].fg, None);
+    assert_eq!(rendered[2].bg.as_deref(), Some("blue"));
+}
+
+#[test]
+fn test_sanitize_keeps_only_colours() {
+    let line = "\x1b]0;title\x07\x1b[2K\x1b[31mred\x1b[0m\rR";
+    let sanitized = sanitize(line);
+
+    assert_eq!(sanitized, "R\x1b[0;31med\x1b[0m");
+    assert_eq!(plain(&sanitized), plain(line));
+    assert_eq!(spans(&sanitized), spans(line));
+}
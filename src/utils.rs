@@ -1,6 +1,21 @@
 use rocket::http::Status;
 use serde_json::json;
 use std::io::Cursor;
+use std::path::PathBuf;
+
+/// Directory for files the server owns, such as generated keys. Set with `DATA_DIR`.
+pub fn data_dir() -> PathBuf {
+    std::env::var("DATA_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from("data"))
+}
+
+/// Whether the panel runs as root
+#[cfg(unix)]
+pub fn privileged() -> bool {
+    // SAFETY: geteuid cannot fail
+    unsafe { libc::geteuid() == 0 }
+}
 
 /// Helper function to create a JSON error response
 pub fn error_response<T: std::fmt::Display>(
@@ -18,3 +33,31 @@ pub fn error_response<T: std::fmt::Display>(
         .sized_body(body.len(), Cursor::new(body))
         .ok()
 }
+
+/// Scratch directory for tests, removed when dropped
+#[cfg(test)]
+pub struct TempDir(pub PathBuf);
+
+#[cfg(test)]
+impl TempDir {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        use rand::{distributions::Alphanumeric, thread_rng, Rng};
+
+        let suffix: String = thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(12)
+            .map(char::from)
+            .collect();
+        let path = std::env::temp_dir().join(format!("server_ui_{suffix}"));
+        std::fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+}
+
+#[cfg(test)]
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        _ = std::fs::remove_dir_all(&self.0);
+    }
+}